@@ -1,39 +1,244 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
 use core::panic;
-use std::{cmp::Ordering, fmt, sync::{Arc,Mutex}, thread::{self, JoinHandle}};
-use num_cpus;
+use std::{cmp::Ordering, fmt, ops::Range, sync::{atomic::Ordering as AtomicOrdering, mpsc::{self, Sender}, Arc}};
+
+use gene::Gene;
+
+pub mod allele;
+pub mod allele_history;
+pub mod alphabet;
+pub mod alps;
+pub mod approximation;
+pub mod archipelago;
+pub mod builder;
+#[cfg(feature = "serde")]
+pub mod checkpoint;
+pub mod chunked;
+pub mod cma_es;
+pub mod constraints;
+pub mod convergence;
+pub mod culling;
+pub mod decoding;
+pub mod delta;
+pub mod differential_evolution;
+pub mod distance;
+pub mod duplicates;
+pub mod error;
+pub mod evaluation;
+pub mod event_log;
+pub mod events;
+pub mod evolution_strategy;
+pub mod fitness_cache;
+pub mod gene;
+pub mod genetic_programming;
+pub mod global_archive;
+pub mod grammatical_evolution;
+pub mod hill_climbing;
+pub mod immigrants;
+pub mod import;
+pub mod initialization;
+pub mod integer;
+pub mod mating;
+pub mod memetic;
+pub mod metrics;
+pub mod multi_objective;
+pub mod multi_start;
+pub mod niching;
+pub mod mutation_rates;
+pub mod mutation_schedule;
+pub mod observers;
+pub mod packed;
+pub mod permutation;
+#[cfg(feature = "plotting")]
+pub mod plotting;
+pub(crate) mod pool;
+pub mod real;
+pub mod repair;
+pub mod replacement;
+pub mod report;
+pub mod restart;
+pub mod rng;
+pub mod runner;
+pub mod schema;
+pub mod selection;
+pub mod self_adaptation;
+pub mod simulated_annealing;
+pub mod sparse;
+pub mod speciation;
+pub mod stats;
+#[cfg(feature = "status-server")]
+pub mod status_server;
+pub mod steady_state;
+pub mod string_match;
+pub mod validation;
+pub mod variable_length;
+pub mod variation;
 
 const MAX_MUTATION_CHANCE: u8 = 100; //cant be higher than 100%
 
 /// Represents an individual in the population.
-/// Each individual has a set of genes (represented as a vector of booleans),
-/// a gene length, and a fitness score.
-#[derive(Eq, Debug, Clone)]
-pub struct Individual {
+/// Each individual has a set of genes (by default a vector of booleans, see `gene::Gene`
+/// for other gene types), a gene length, and a fitness score.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Individual<G: Gene = bool> {
     gene_length: usize,
-    genes: Vec<bool>,
+    genes: packed::Genes<G>,
     fitness: u64,
+    violation: f64,
+    objectives: Vec<f64>,
+    strategy: Vec<f64>,
+    age: u64,
 }
 
-impl Individual {
-    /// Randomizes the genes of the individual.
-    /// Each gene has a 50% chance of being `true` or `false`.
-    fn randomize(&mut self) {
-        self.genes = vec![true; self.gene_length];
-        for i in 0..self.gene_length {
-            let rand = rand::rng().random_range(0..=1);
-            if rand == 0 {
-                self.genes[i] = false
-            }
+impl<G: Gene> Individual<G> {
+    /// Randomizes the genes of the individual with `Gene::random`.
+    /// A bit has a 50% chance of being `true` or `false`.
+    fn randomize<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.genes = match self.genes {
+            packed::Genes::Packed(_) => packed::Genes::Packed(packed::PackedBits::random(self.gene_length, rng)),
+            packed::Genes::Dense(_) => packed::Genes::Dense((0..self.gene_length).map(|_| G::random(rng)).collect()),
+        };
+    }
+
+    /// An individual without genes, to be filled by an operator.
+    fn blank() -> Individual<G> {
+        Individual {
+            gene_length: 0,
+            genes: vec![].into(),
+            fitness: 0,
+            violation: 0.0,
+            objectives: vec![],
+            strategy: vec![],
+            age: 0,
+        }
+    }
+
+    /// Creates an individual with the given genes and a fitness of 0.
+    ///
+    /// # Arguments
+    /// * `genes` - The genes of the individual, at least one.
+    pub fn from_genes(genes: Vec<G>) -> Individual<G> {
+        if genes.is_empty() {
+            panic!("the gene length cannot be less than 1");
+        }
+        Individual {
+            gene_length: genes.len(),
+            genes: genes.into(),
+            fitness: 0,
+            violation: 0.0,
+            objectives: vec![],
+            strategy: vec![],
+            age: 0,
+        }
+    }
+
+    pub fn get_genes(&self) -> Vec<G>{
+        self.genes.to_vec()
+    }
+
+    pub fn get_gene(&self, index: usize) -> G {
+        self.check_gene_index(index);
+        self.genes[index].clone()
+    }
+
+    /// Sets a single gene.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the gene, must be within the gene length.
+    /// * `value` - The new value of the gene.
+    pub fn set_gene(&mut self, index: usize, value: G) {
+        self.check_gene_index(index);
+        self.genes.set(index, value);
+    }
+
+    /// Overwrites a range of genes. The gene length stays the same, so the replacement
+    /// must be exactly as long as the range.
+    ///
+    /// # Arguments
+    /// * `range` - The genes to overwrite, must be within the gene length.
+    /// * `genes` - The new genes.
+    pub fn splice(&mut self, range: Range<usize>, genes: &[G]) {
+        if range.start > range.end || range.end > self.gene_length {
+            panic!("gene range {}..{} is outside the gene length {}", range.start, range.end, self.gene_length);
+        }
+        if genes.len() != range.len() {
+            panic!("cant splice {} genes into a range of {}", genes.len(), range.len());
+        }
+        for (index, gene) in range.zip(genes) {
+            self.genes.set(index, gene.clone());
         }
     }
 
-    pub fn get_genes(&self) -> Vec<bool>{
-        return self.genes.clone();
+    fn check_gene_index(&self, index: usize) {
+        if index >= self.gene_length {
+            panic!("gene index {} is outside the gene length {}", index, self.gene_length);
+        }
     }
 
+    pub fn set_fitness(&mut self, fitness:u64) {
+        self.fitness = fitness;
+    }
+    
+    pub fn get_fitness(&self) -> u64{
+        self.fitness
+    }
+
+    /// Returns how many generations the genetic material of the individual has been in
+    /// the population: 0 for a random individual, the age of its oldest parent plus one
+    /// for a child. Survivors age by one per generation in ALPS mode, see `Population::set_alps`.
+    pub fn get_age(&self) -> u64 {
+        self.age
+    }
+
+    /// Mutates the gene at the specified index with a certain probability.
+    /// The mutation chance is determined by the `mutation_probability` parameter.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the gene to potentially mutate.
+    /// * `mutation_probability` - The probability of mutation (0.0-1.0).
+    /// * `rng` - The random number generator to draw from.
+    ///
+    /// # Returns
+    /// True if the gene was mutated.
+    fn mutate_at_index<R: Rng + ?Sized>(&mut self, index: usize, mutation_probability: f64, rng: &mut R) -> bool {
+        if rng.random_bool(mutation_probability) {
+            let mutated = self.genes[index].mutate(rng);
+            self.genes.set(index, mutated);
+            return true;
+        }
+        false
+    }
+}
+
+impl Individual {
+    /// Inverts a single gene.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the gene, must be within the gene length.
+    pub fn flip_gene(&mut self, index: usize) {
+        self.check_gene_index(index);
+        self.genes.set(index, !self.genes[index]);
+    }
+
+    /// Sets the 8 genes of a byte, the first gene of the byte being its most significant
+    /// bit as in `get_genes_as_decimal_bytes`.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the byte, i.e. genes `8 * index..8 * index + 8`.
+    /// * `value` - The new value of the byte.
+    pub fn set_byte(&mut self, index: usize, value: u8) {
+        if 8 * index + 8 > self.gene_length {
+            panic!("byte {} is outside the gene length {}", index, self.gene_length);
+        }
+        for bit in 0..8 {
+            self.genes.set(8 * index + bit, value & (0x80 >> bit) != 0);
+        }
+    }
+
+
     pub fn get_genes_as_bytes(&self) -> Vec<[u8;8]> {
-        if self.gene_length%8 != 0 {
+        if !self.gene_length.is_multiple_of(8) {
             panic!("gene length must be a multiple of 8 to convert to bytes");
         }
         let mut bytes :Vec<[u8;8]> = vec![];
@@ -50,57 +255,19 @@ impl Individual {
             }
             bytes.push(byte);
         }
-        return bytes;
+        bytes
     }
 
+    /// Converts the genes to bytes, reading the first gene of each byte as its most
+    /// significant bit. Use `get_genes_as_decimal_bytes_ordered` for other bit orders.
     pub fn get_genes_as_decimal_bytes(&self) -> Vec<u8>{
-        if self.gene_length%8 != 0 {
-            panic!("gene length must be a multiple of 8 to convert to bytes");
-        }
-        let mut bytes :Vec<u8> = vec![];
-        let chunks = self.gene_length/8;
-        // 0000 0000 c0
-        // 0000 0000 c1
-        // 0000 0000 c2
-        for i in 0..chunks{
-            let mut byte:u8 = 0;
-            for j in 0..8{
-                //              chunk location      digit location(reverse order)
-                if self.genes[(8*i) + (7 - j)]{
-                    let base: u32 = 2;
-                    let power: u32 = j as u32;
-                    byte += base.pow(power) as u8;
-                }
-            }
-            bytes.push(byte);
-        }
-        return bytes;
-    }
-
-    pub fn set_fitness(&mut self, fitness:u64) {
-        self.fitness = fitness;
-    }
-    
-    pub fn get_fitness(&self) -> u64{
-        self.fitness
-    }
-    /// Mutates the gene at the specified index with a certain probability.
-    /// The mutation chance is determined by the `mutation_chance` parameter.
-    ///
-    /// # Arguments
-    /// * `index` - The index of the gene to potentially mutate.
-    /// * `mutation_chance` - The probability of mutation (0-100).
-    fn mutate_at_index(&mut self, index: usize, mutation_chance: u8) {
-        let rand = rand::rng().random_range(0..100);
-        if rand < mutation_chance {
-            self.genes[index] = !self.genes[index];
-        }
+        self.get_genes_as_decimal_bytes_ordered(decoding::BitOrder::MsbFirst)
     }
 }
 
 /// Implements the `PartialOrd` trait for the `Individual` struct.
 /// This allows individuals to be compared based on their fitness scores.
-impl PartialOrd for Individual {
+impl<G: Gene> PartialOrd for Individual<G> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
@@ -108,15 +275,17 @@ impl PartialOrd for Individual {
 
 /// Implements the `PartialEq` trait for the `Individual` struct.
 /// This allows individuals to be compared for equality based on their fitness scores.
-impl PartialEq for Individual {
+impl<G: Gene> PartialEq for Individual<G> {
     fn eq(&self, other: &Self) -> bool {
         self.fitness == other.fitness
     }
 }
 
+impl<G: Gene> Eq for Individual<G> {}
+
 /// Implements the `Ord` trait for the `Individual` struct.
 /// This allows individuals to be ordered based on their fitness scores.
-impl Ord for Individual {
+impl<G: Gene> Ord for Individual<G> {
     fn cmp(&self, other: &Self) -> Ordering {
         self.fitness.cmp(&other.fitness)
     }
@@ -143,181 +312,711 @@ impl fmt::Display for Individual {
 /// The population contains a vector of individuals, the size of the population,
 /// the number of parents to select for reproduction, and the mutation chance.
 #[derive(Debug,Clone)]
-pub struct Population {
-    pub individuals: Vec<Individual>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "G: serde::Serialize", deserialize = "G: serde::Deserialize<'de>")))]
+pub struct Population<G: Gene = bool> {
+    pub individuals: Vec<Individual<G>>,
     population_size: usize,
     parent_count: usize,
-    mutation_chance: u8,
+    mutation_probability: f64,
+    mutation_schedule: Option<mutation_schedule::MutationSchedule>,
+    mutation_schedule_start: u64,
+    gene_mutation_rates: Option<Vec<f64>>,
+    self_adaptation: Option<self_adaptation::SelfAdaptation>,
     multi_threaded:bool,
-    crossover: CrossoverType
+    crossover: CrossoverType,
+    crossover_mask: Option<Vec<bool>>,
+    crossover_rate: f64,
+    variable_length: Option<variable_length::VariableLength>,
+    packed_storage: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    repair: Option<repair::RepairHook<G>>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "variation::serialize_hook", deserialize_with = "variation::deserialize_hook"))]
+    variation: Option<variation::VariationHook<G>>,
+    feasibility_first: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    constraints: Vec<constraints::ConstraintHook<G>>,
+    constraint_handling: constraints::ConstraintHandling,
+    penalty_scheduler: Option<constraints::PenaltyScheduler>,
+    multi_objective: bool,
+    selection_type: SelectionType,
+    elitism_count: Option<usize>,
+    steady_state: Option<usize>,
+    replace_oldest: bool,
+    fitness_sharing: Option<niching::FitnessSharing>,
+    crowding: bool,
+    crowding_pending: Option<usize>,
+    speciation: Option<speciation::Speciation>,
+    species: Vec<speciation::Species<G>>,
+    next_species_id: u64,
+    alps: Option<alps::Alps>,
+    mating_restriction: Option<mating::MatingRestriction>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "differential_evolution::serialize_hook", deserialize_with = "differential_evolution::deserialize_hook"))]
+    differential_evolution: Option<differential_evolution::DeHook<G>>,
+    de_pending: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "evolution_strategy::serialize_hook", deserialize_with = "evolution_strategy::deserialize_hook"))]
+    evolution_strategy: Option<evolution_strategy::EsHook<G>>,
+    es_pending: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "cma_es::serialize_hook", deserialize_with = "cma_es::deserialize_hook"))]
+    cma_es: Option<cma_es::CmaEsHook<G>>,
+    random_immigrants: Option<immigrants::RandomImmigrants>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    local_search: Option<memetic::LocalSearchHook<G>>,
+    // the number of individuals the last step appended at the end of the population
+    fresh_count: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    selection: Option<selection::SelectionPipeline<G>>,
+    generation: u64,
+    best_fitness_seen: Option<u64>,
+    best_ever: Option<Individual<G>>,
+    timeouts: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    subscribers: Vec<Sender<events::GaEvent<G>>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    observers: Vec<observers::ObserverHook<G>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fitness_cache: Option<fitness_cache::FitnessCache<G>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    duplicate_elimination: Option<duplicates::DuplicateElimination<G>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    operator_counters: Arc<events::OperatorCounters>,
+    rng: rng::GaRng
 }
-#[derive(Debug,Clone,PartialEq,Eq,Copy)]
+#[derive(Debug,Clone,PartialEq,Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CrossoverType{
     Bit,
-    Byte
+    Byte,
+    /// One random cut, the child takes the genes before it from one parent and the rest
+    /// from another, keeping blocks of neighbouring genes together.
+    SinglePoint,
+    /// Two random cuts, the middle segment comes from another parent.
+    TwoPoint,
+    /// The given number of random cuts, switching parents at every cut.
+    KPoint(usize),
+    /// Every gene comes from the fittest parent with probability `parent_bias` (0.0-1.0)
+    /// and from a random other parent otherwise. A bias of 0.5 with two parents is an
+    /// unbiased uniform crossover.
+    Uniform { parent_bias: f64 },
+    /// Single-point crossover on a random order of the loci: a random number of them comes
+    /// from a second parent, no matter where they are, so genes that belong together are
+    /// split equally often whether they are neighbours or far apart.
+    Shuffle,
+    /// Switches to another parent with probability `switch_probability` (0.0-1.0) at every
+    /// crossover point, copying segments of random count and length, 1 / probability
+    /// genes long on average. A small probability keeps co-adapted neighbours together.
+    Segment { switch_probability: f64 },
+    /// Eiben's diagonal crossover: one cut less than there are parents, every segment
+    /// from the next parent in turn, so all parents contribute a piece.
+    Diagonal,
+    /// Every gene takes the allele most parents carry there, ties are broken at random.
+    /// With many parents this keeps what they agree on and mutation explores the rest.
+    MajorityVote,
+    /// Every gene comes from a parent drawn proportionally to its fitness, uniformly if
+    /// all parents have a fitness of 0.
+    FitnessWeighted,
 }
 
-impl Population {
+/// How the parents of the next generation are chosen from the evaluated population.
+#[derive(Debug,Clone,PartialEq,Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelectionType{
+    /// The `parent_count` fittest individuals.
+    Truncation,
+    /// `parent_count` distinct individuals, each the fittest of `size` randomly drawn ones.
+    /// Smaller tournaments keep weaker individuals around longer.
+    Tournament { size: usize },
+    /// `parent_count` distinct individuals drawn with a probability that falls linearly
+    /// with their rank, ignoring how far apart the fitness values are. The best individual
+    /// is `pressure` (1.0-2.0) times as likely to be drawn as the average one, 1.0 draws
+    /// uniformly.
+    Rank { pressure: f64 },
+    /// `parent_count` individuals drawn proportionally to their fitness with stochastic
+    /// universal sampling: one spin with evenly spaced pointers, so the number of copies
+    /// of an individual stays close to its expected value, unlike with repeated roulette
+    /// spins. Fit individuals can be drawn more than once.
+    StochasticUniversal
+}
+
+impl<G: Gene> Population<G> {
     /// Randomizes the genes of all individuals in the population.
     fn randomize_population(&mut self) {
-        for i in 0..self.population_size {
-            self.individuals[i].randomize();
+        let mut individuals = std::mem::take(&mut self.individuals);
+        for individual in individuals.iter_mut() {
+            self.randomize_individual(individual);
         }
+        self.individuals = individuals;
     }
 
     /// Creates a new individual (child) from a set of parents.
     /// The child's genes are a combination of the parents' genes, with a chance of mutation.
+    /// The parent a gene is copied from can only change at a legal crossover point.
+    /// Variation operators, if set, replace the crossover and mutation, and so does cut and
+    /// splice crossover for variable-length chromosomes.
     ///
     /// # Arguments
-    /// * `parents` - A vector of parent individuals used to create the child.
+    /// * `parents` - The parent individuals used to create the child.
+    /// * `rng` - The random number generator to draw from.
+    /// * `buffer` - An individual that is no longer needed, its gene storage is reused for the child.
     ///
     /// # Returns
     /// A new `Individual` representing the child.
-    fn create_child(&self, parents: Vec<Individual>) -> Individual {
-        let mut individual: Individual = Individual {
-            gene_length: parents[0].gene_length,
-            genes: vec![false; parents[0].gene_length],
-            fitness: 0,
+    fn create_child(&self, parents: &[&Individual<G>], rng: &mut rng::GaRng, buffer: Individual<G>) -> Individual<G> {
+        // without crossover the child is a mutated copy of a single parent
+        let parents = if self.crossover_rate < 1.0 && !rng.random_bool(self.crossover_rate) {
+            std::slice::from_ref(&parents[rng.random_range(0..parents.len())])
+        } else {
+            parents
         };
-        let mut rand = rand::rng().random_range(0..parents.len());
+        let pair;
+        let parents = match self.mating_restriction {
+            Some(restriction) if parents.len() > 2 => {
+                let (a, b) = restriction.choose_pair(parents, rng);
+                pair = [parents[a], parents[b]];
+                &pair[..]
+            }
+            _ => parents,
+        };
+        let mut individual = buffer;
+        individual.gene_length = parents[0].gene_length;
+        individual.fitness = 0;
+        individual.violation = 0.0;
+        individual.objectives.clear();
+        individual.strategy.clear();
+        individual.age = parents.iter().map(|parent| parent.age).max().unwrap_or(0) + 1;
+        if let Some(variation::VariationHook(variation, _)) = &self.variation {
+            individual.genes.assign(variation.crossover(parents, rng));
+            if individual.genes.len() != individual.gene_length {
+                panic!("the variation operators made a child of {} genes instead of {}", individual.genes.len(), individual.gene_length);
+            }
+            let probability = self.inherit_strategy(&mut individual, parents, rng);
+            let mutations = variation.mutate(individual.genes.make_dense(), probability, rng);
+            self.operator_counters.offspring.fetch_add(1, AtomicOrdering::Relaxed);
+            self.operator_counters.mutations.fetch_add(mutations, AtomicOrdering::Relaxed);
+            self.repair_individual(&mut individual);
+            return individual;
+        }
+        if let Some(variable) = self.variable_length {
+            return self.splice_child(parents, rng, individual, variable);
+        }
+        individual.genes.clone_from(&parents[0].genes);
+        let mut mutations = 0;
+        if individual.genes.is_packed() && self.packed_child(&mut individual, parents, rng, &mut mutations) {
+            self.operator_counters.offspring.fetch_add(1, AtomicOrdering::Relaxed);
+            self.operator_counters.mutations.fetch_add(mutations, AtomicOrdering::Relaxed);
+            self.repair_individual(&mut individual);
+            return individual;
+        }
+        let mut rand = rng.random_range(0..parents.len());
+        let fittest = (0..parents.len()).max_by(|&a, &b| parents[a].cmp(parents[b])).unwrap_or(0);
+        let cuts = match self.crossover {
+            CrossoverType::SinglePoint => self.choose_cuts(individual.gene_length, 1, rng),
+            CrossoverType::TwoPoint => self.choose_cuts(individual.gene_length, 2, rng),
+            CrossoverType::KPoint(k) => self.choose_cuts(individual.gene_length, k, rng),
+            CrossoverType::Diagonal => self.choose_cuts(individual.gene_length, parents.len() - 1, rng),
+            CrossoverType::Bit
+            | CrossoverType::Byte
+            | CrossoverType::Uniform { .. }
+            | CrossoverType::Shuffle
+            | CrossoverType::Segment { .. }
+            | CrossoverType::MajorityVote
+            | CrossoverType::FitnessWeighted => vec![],
+        };
+        // shuffle crossover takes a random subset of the loci from a second parent
+        let first = rand;
+        // summed as u128 so a few parents near u64::MAX can't overflow
+        let fitness_total: u128 = match self.crossover {
+            CrossoverType::FitnessWeighted => parents.iter().map(|parent| parent.fitness as u128).sum(),
+            _ => 0,
+        };
+        let (second, shuffled) = match self.crossover {
+            CrossoverType::Shuffle if parents.len() > 1 => {
+                let second = (rand + rng.random_range(1..parents.len())) % parents.len();
+                let count = rng.random_range(1..individual.gene_length.max(2));
+                let mut shuffled = vec![false; individual.gene_length];
+                for i in rand::seq::index::sample(rng, individual.gene_length, count.min(individual.gene_length)) {
+                    shuffled[i] = true;
+                }
+                (second, shuffled)
+            }
+            _ => (rand, vec![]),
+        };
+        let adaptation = self.self_adaptation;
+        if let Some(adaptation) = adaptation.filter(|adaptation| !adaptation.is_per_gene()) {
+            // the one rate comes from the parent of the first gene
+            let rate = adaptation.mutate_rate(parents[rand].strategy.first().copied(), self.mutation_probability, rng);
+            individual.strategy.push(rate);
+        }
         for i in 0..individual.gene_length {
-            match self.crossover{
-                CrossoverType::Byte => {
-                    if i%8 == 0{
-                        rand = rand::rng().random_range(0..parents.len());
+            if self.is_crossover_point(i) {
+                match self.crossover{
+                    CrossoverType::Byte => {
+                        if i%8 == 0{
+                            rand = rng.random_range(0..parents.len());
+                        }
+                    },
+                    CrossoverType::Bit => {
+                        rand = rng.random_range(0..parents.len());
+                    }
+                    CrossoverType::SinglePoint | CrossoverType::TwoPoint | CrossoverType::KPoint(_) => {
+                        if cuts.binary_search(&i).is_ok() && parents.len() > 1 {
+                            // switch to another parent
+                            rand = (rand + rng.random_range(1..parents.len())) % parents.len();
+                        }
+                    }
+                    CrossoverType::Uniform { parent_bias } => {
+                        rand = if parents.len() < 2 || rng.random_bool(parent_bias) {
+                            fittest
+                        } else {
+                            // any parent except the fittest
+                            (fittest + rng.random_range(1..parents.len())) % parents.len()
+                        };
+                    }
+                    CrossoverType::Shuffle => {
+                        if shuffled.get(i) == Some(&true) {
+                            rand = second;
+                        } else if !shuffled.is_empty() {
+                            rand = first;
+                        }
+                    }
+                    CrossoverType::Segment { switch_probability } => {
+                        if i > 0 && parents.len() > 1 && rng.random_bool(switch_probability) {
+                            rand = (rand + rng.random_range(1..parents.len())) % parents.len();
+                        }
+                    }
+                    CrossoverType::Diagonal => {
+                        if cuts.binary_search(&i).is_ok() {
+                            rand = (rand + 1) % parents.len();
+                        }
+                    }
+                    CrossoverType::MajorityVote => {
+                        let votes: Vec<usize> = parents
+                            .iter()
+                            .map(|parent| parents.iter().filter(|other| other.genes[i] == parent.genes[i]).count())
+                            .collect();
+                        let most = votes.iter().copied().max().unwrap_or(0);
+                        let winners: Vec<usize> = (0..parents.len()).filter(|&p| votes[p] == most).collect();
+                        rand = winners[rng.random_range(0..winners.len())];
+                    }
+                    CrossoverType::FitnessWeighted => {
+                        rand = match fitness_total {
+                            0 => rng.random_range(0..parents.len()),
+                            total => {
+                                let point = rng.random_range(0..total);
+                                let mut cumulative = 0;
+                                parents
+                                    .iter()
+                                    .position(|parent| {
+                                        cumulative += parent.fitness as u128;
+                                        point < cumulative
+                                    })
+                                    .unwrap_or(0)
+                            }
+                        };
                     }
-                },
-                CrossoverType::Bit => {
-                    rand = rand::rng().random_range(0..parents.len());
                 }
             }
-            individual.genes[i] = parents[rand].genes[i];
-            individual.mutate_at_index(i, self.mutation_chance);
+            individual.genes.set(i, parents[rand].genes[i].clone());
+            let probability = match (adaptation, &self.gene_mutation_rates) {
+                (Some(adaptation), _) if adaptation.is_per_gene() => {
+                    // the rate of a gene comes from the same parent as the gene
+                    let rate = adaptation.mutate_rate(parents[rand].strategy.get(i).copied(), self.mutation_probability, rng);
+                    individual.strategy.push(rate);
+                    rate
+                }
+                (Some(_), _) => individual.strategy[0],
+                (None, Some(rates)) => rates[i],
+                (None, None) => self.mutation_probability,
+            };
+            if individual.mutate_at_index(i, probability, rng) {
+                mutations += 1;
+            }
         }
+        self.operator_counters.offspring.fetch_add(1, AtomicOrdering::Relaxed);
+        self.operator_counters.mutations.fetch_add(mutations, AtomicOrdering::Relaxed);
+        self.repair_individual(&mut individual);
 
-        return individual;
+        individual
+    }
+
+    /// Chooses up to `count` distinct cut positions for point crossover, in ascending
+    /// order. Only legal crossover points after the first gene are used.
+    fn choose_cuts<R: Rng + ?Sized>(&self, gene_length: usize, count: usize, rng: &mut R) -> Vec<usize> {
+        let legal: Vec<usize> = (1..gene_length).filter(|&i| self.is_crossover_point(i)).collect();
+        let mut cuts = rand::seq::index::sample(rng, legal.len(), count.min(legal.len()))
+            .into_iter()
+            .map(|i| legal[i])
+            .collect::<Vec<usize>>();
+        cuts.sort();
+        cuts
+    }
+
+    /// Sorts the individuals from best to worst, by fitness or, with feasibility-first
+    /// selection enabled, by `constraints::compare_feasibility_first`. In multi-objective
+    /// mode they are sorted by Pareto front and crowding distance instead.
+    pub(crate) fn sort_individuals(&mut self) {
+        if self.multi_objective {
+            self.sort_by_pareto_rank();
+        } else if self.feasibility_first {
+            self.individuals.sort_by(|a, b| constraints::compare_feasibility_first(b, a));
+        } else {
+            self.individuals.sort_by(|a, b| b.cmp(a));
+        }
+    }
+
+    /// Sets the probability that a child is recombined from its parents, 1.0 by default.
+    /// Otherwise it is a copy of a single random parent that is only mutated. Standard
+    /// genetic algorithms use 0.6-0.9. Variation operators and cut and splice crossover
+    /// get the single parent as their only parent.
+    ///
+    /// # Arguments
+    /// * `rate` - The crossover probability (0.0-1.0).
+    pub fn set_crossover_rate(&mut self, rate: f64) {
+        if !(0.0..=1.0).contains(&rate) {
+            panic!("crossover rate must be between 0 and 1");
+        }
+        self.crossover_rate = rate;
+    }
+
+    pub fn get_crossover_rate(&self) -> f64 {
+        self.crossover_rate
+    }
+
+    /// Restricts crossover to cut at the given gene indices only, e.g. the field
+    /// boundaries of an encoding, so that recombination never splits a multi-bit value.
+    /// A cut at index `i` means gene `i` may come from a different parent than gene `i - 1`.
+    /// Byte crossover additionally still only cuts at multiples of 8.
+    ///
+    /// # Arguments
+    /// * `points` - The legal cut positions, each must be smaller than the gene length.
+    pub fn set_crossover_points(&mut self, points: &[usize]) {
+        let gene_length = self.individuals[0].gene_length;
+        let mut mask = vec![false; gene_length];
+        for &point in points {
+            if point >= gene_length {
+                panic!("crossover point {} is outside the gene length {}", point, gene_length);
+            }
+            mask[point] = true;
+        }
+        self.crossover_mask = Some(mask);
+    }
+
+    /// Removes the crossover point restriction, every gene index is a legal cut again.
+    pub fn clear_crossover_points(&mut self) {
+        self.crossover_mask = None;
+    }
+
+    /// Returns true if crossover is allowed to switch parents at the given gene index.
+    fn is_crossover_point(&self, index: usize) -> bool {
+        match &self.crossover_mask {
+            Some(mask) => mask[index],
+            None => true,
+        }
     }
 
     /// Generates the next generation of individuals based on the current population.
     /// The top-performing individuals are selected as parents, and new individuals
     /// are created through recombination and mutation.
     pub fn next_generation(&mut self) {
-        self.individuals.sort_by(|a,b| b.cmp(a));
-        self.individuals.truncate(self.parent_count);
-        
-        // //Single threaded approch
-        if !self.multi_threaded{
-            let mut next_gen_individuals = vec![];
-            for _i in 0..self.population_size - self.individuals.len() {
-                next_gen_individuals.push(self.create_child(self.individuals.clone()));
-            }
-            self.individuals.append(&mut next_gen_individuals);
-        }
-
-        // //Multi threaded approach
-        else{
-            
-            let mut join_handles: Vec<JoinHandle<()>> = vec![];
-            let thread_count = if num_cpus::get() >= self.population_size{ self.population_size} else{num_cpus::get()};
-            let next_gen_individuals = Arc::new(Mutex::new(vec![]));
-            let mut ammount_left = self.population_size - self.individuals.len();
-            let chunk_size = self.population_size / thread_count;
-            let mutation_chance = self.mutation_chance;
-            let parents = Arc::new(self.individuals.clone());
-            let crossover = self.crossover.clone();
-            for i in 0..thread_count{
-                let next_gen_individuals = Arc::clone(&next_gen_individuals);
-                let mut end = if ammount_left < chunk_size{
-                    ammount_left
-                } else{
-                    chunk_size
-                };
-                ammount_left -= end;
-                if ammount_left > 0 && i+1 == thread_count{
-                    end += ammount_left;
-                }
-                let parents = Arc::clone(&parents);
-                join_handles.push(thread::spawn(move || {
-                        for _i in 0..end{
-                            let mut individual: Individual = Individual {
-                                gene_length: parents[0].gene_length,
-                                genes: vec![false; parents[0].gene_length],
-                                fitness: 0,
-                            };
-
-                            let mut rand = rand::rng().random_range(0..parents.len());
-                            for i in 0..individual.gene_length {
-                                match crossover{
-                                    CrossoverType::Byte => {
-                                        if i%8 == 0{
-                                            rand = rand::rng().random_range(0..parents.len());
-                                        }
-                                    },
-                                    CrossoverType::Bit => {
-                                        rand = rand::rng().random_range(0..parents.len());
-                                    }
-                                }
-
-                                individual.genes[i] = parents[rand].genes[i];
-                                individual.mutate_at_index(i, mutation_chance);
-                            }
-                            let mut next_gen_individuals = next_gen_individuals.lock().unwrap();
-                            next_gen_individuals.push(individual);
-                        }
-                    })
-                )
-
+        self.sort_individuals();
+        self.apply_mutation_schedule();
+        self.apply_penalty_schedule();
+        self.record_best();
+        self.emit_generation_completed();
+        self.notify_observers();
+        if let Some(replacement_count) = self.steady_state {
+            self.steady_state_step(replacement_count);
+            self.finish_generation(replacement_count);
+            return;
+        }
+        if self.crowding {
+            self.restore_population_size();
+            self.crowding_step();
+            self.finish_generation(self.individuals.len() / 2);
+            return;
+        }
+        if let Some(alps) = self.alps {
+            self.alps_step(alps);
+            self.finish_generation(self.individuals.len());
+            return;
+        }
+        if self.differential_evolution.is_some() {
+            self.restore_population_size();
+            self.de_step();
+            self.finish_generation(self.individuals.len() / 2);
+            return;
+        }
+        if let Some(offspring) = self.get_evolution_strategy().map(|strategy| strategy.get_offspring()) {
+            self.restore_population_size();
+            self.es_step();
+            self.finish_generation(offspring);
+            return;
+        }
+        if self.cma_es.is_some() {
+            self.restore_population_size();
+            self.cma_step();
+            self.finish_generation(self.individuals.len());
+            return;
+        }
+        let elites = self.elitism_count.map(|count| self.individuals[..count.min(self.individuals.len())].to_vec());
+        // the individuals that weren't selected are recycled into children
+        let spares = self.select_parents();
+        let parents = std::mem::take(&mut self.individuals);
+        // with elitism the parents only breed and the elites survive instead
+        let survivors = elites.as_ref().map_or(parents.len(), |elites| elites.len());
+        let count = self.population_size - survivors;
+        let (parents, mut children) = self.breed_children(parents, count, spares);
+        self.individuals = match elites {
+            Some(elites) => elites,
+            None => parents,
+        };
+        self.individuals.append(&mut children);
+        self.finish_generation(count);
+    }
 
-            }
+    /// Injects the immigrants that are due, replaces duplicates and counts the generation.
+    ///
+    /// # Arguments
+    /// * `fresh` - The number of new individuals the step appended at the end of the population.
+    fn finish_generation(&mut self, fresh: usize) {
+        self.inject_immigrants(fresh);
+        self.eliminate_duplicates(fresh);
+        self.apply_storage();
+        self.fresh_count = fresh.min(self.individuals.len());
+        self.generation += 1;
+    }
 
-            // Wait for all threads to finish
-            for handle in join_handles{
-                handle.join().unwrap();
-            }
-            let mut next_gen_individuals = Arc::try_unwrap(next_gen_individuals)
-                .unwrap()
-                .into_inner()
-                .unwrap();
-            self.individuals.append(&mut next_gen_individuals);
+    /// Panics if a replacement mode other than the given one is enabled, they each take
+    /// over `next_generation` and cant be combined.
+    ///
+    /// # Arguments
+    /// * `mode` - The name of the mode being enabled.
+    pub(crate) fn check_replacement_mode(&self, mode: &str) {
+        let modes = [
+            ("steady-state mode", self.steady_state.is_some()),
+            ("crowding", self.crowding),
+            ("alps", self.alps.is_some()),
+            ("differential evolution", self.differential_evolution.is_some()),
+            ("evolution strategies", self.evolution_strategy.is_some()),
+            ("cma-es", self.cma_es.is_some()),
+        ];
+        if let Some((other, _)) = modes.iter().find(|(name, enabled)| *enabled && *name != mode) {
+            panic!("{} cant be combined with {}", mode, other);
         }
     }
 
-    /// Prints the top `count` individuals in the population, sorted by fitness.
+    /// Creates children from the given parents. Every child gets its own random number
+    /// generator seeded from the population's, so a seeded population breeds the same
+    /// children whether it is multi threaded or not and however many threads there are.
+    /// The parents are moved to the breeding threads and handed back rather than cloned.
     ///
     /// # Arguments
-    /// * `count` - The number of individuals to print.
-    pub fn print(&mut self, mut count: usize) {
-        self.individuals.sort_by(|a,b| b.cmp(a));
-        if count > self.population_size {
-            count = self.population_size;
+    /// * `parents` - The parent individuals.
+    /// * `count` - The number of children to create.
+    /// * `spares` - Individuals that are no longer needed, their gene storage is reused for the children.
+    ///
+    /// # Returns
+    /// The parents, unchanged, and the children.
+    pub(crate) fn breed_children(
+        &mut self,
+        parents: Vec<Individual<G>>,
+        count: usize,
+        spares: Vec<Individual<G>>,
+    ) -> (Vec<Individual<G>>, Vec<Individual<G>>) {
+        let seeds: Vec<u64> = (0..count).map(|_| self.rng.random()).collect();
+        let mut spares = spares.into_iter();
+        let mut buffers: Vec<Individual<G>> = (0..count).map(|_| spares.next().unwrap_or_else(Individual::blank)).collect();
+
+        // //Single threaded approch
+        if !self.multi_threaded || count == 0{
+            let references: Vec<&Individual<G>> = parents.iter().collect();
+            let children = seeds
+                .into_iter()
+                .zip(buffers)
+                .map(|(seed, buffer)| self.create_child(&references, &mut rng::GaRng::seed_from_u64(seed), buffer))
+                .collect();
+            return (parents, children);
         }
-        println!("{}", self);
-        for i in 0..count {
-            println!("{}", self.individuals[i]);
+
+        // //Multi threaded approach, on the shared worker pool
+        let pool = pool::WorkerPool::global();
+        let thread_count = pool.get_size().min(count);
+        let chunk_size = count.div_ceil(thread_count);
+        let breeder = Arc::new(self.breeder());
+        let parents = Arc::new(parents);
+        let (sender, receiver) = mpsc::channel();
+        let chunks: Vec<Vec<u64>> = seeds.chunks(chunk_size).map(|seeds| seeds.to_vec()).collect();
+        let chunk_count = chunks.len();
+        for (index, seeds) in chunks.into_iter().enumerate() {
+            let breeder = Arc::clone(&breeder);
+            let parents = Arc::clone(&parents);
+            let buffers: Vec<Individual<G>> = buffers.drain(..seeds.len()).collect();
+            let sender = sender.clone();
+            pool.execute(move || {
+                let references: Vec<&Individual<G>> = parents.iter().collect();
+                let children: Vec<Individual<G>> = seeds
+                    .iter()
+                    .zip(buffers)
+                    .map(|(&seed, buffer)| breeder.create_child(&references, &mut rng::GaRng::seed_from_u64(seed), buffer))
+                    .collect();
+                // let go of the parents before reporting, so they can be handed back
+                drop(references);
+                drop(parents);
+                let _ = sender.send((index, children));
+            });
+        }
+        drop(sender);
+        // Wait for all chunks, keeping the children in seed order
+        let mut children: Vec<Vec<Individual<G>>> = vec![vec![]; chunk_count];
+        for _ in 0..chunk_count {
+            let (index, chunk) = receiver.recv().expect("a breeding thread panicked");
+            children[index] = chunk;
         }
+        let parents = Arc::try_unwrap(parents).unwrap_or_else(|parents| parents.as_ref().clone());
+        (parents, children.into_iter().flatten().collect())
+    }
+
+    /// Returns a copy of the population for the breeding threads. They need their own
+    /// handle on the operators, but not on the individuals, the fitness cache, the
+    /// species or the best individual, which are left out rather than copied.
+    fn breeder(&mut self) -> Population<G> {
+        let individuals = std::mem::take(&mut self.individuals);
+        let fitness_cache = self.fitness_cache.take();
+        let species = std::mem::take(&mut self.species);
+        let best_ever = self.best_ever.take();
+        let breeder = self.clone();
+        self.individuals = individuals;
+        self.fitness_cache = fitness_cache;
+        self.species = species;
+        self.best_ever = best_ever;
+        breeder
     }
 
     pub fn get_population_size(&self) -> usize{
         self.population_size
     }
 
-    pub fn read_fittest(&mut self) -> Individual{
-        self.individuals.sort_by(|a,b| b.cmp(a));
-        return self.individuals[0].clone();
+    /// Returns the current probability (0.0-1.0) of mutating each gene, which a mutation
+    /// schedule changes every generation.
+    pub fn get_mutation_probability(&self) -> f64{
+        self.mutation_probability
+    }
+
+    /// Sets the probability of mutating each gene, finer than the whole percents of a
+    /// mutation chance, e.g. `1.0 / gene_length as f64` for long chromosomes.
+    ///
+    /// # Arguments
+    /// * `mutation_probability` - The probability (0.0-1.0).
+    pub fn set_mutation_probability(&mut self, mutation_probability: f64) {
+        if !(0.0..=1.0).contains(&mutation_probability) {
+            panic!("mutation probability must be between 0 and 1");
+        }
+        self.mutation_probability = mutation_probability;
+    }
+
+    /// Returns how many times `next_generation` has been called.
+    pub fn get_generation(&self) -> u64{
+        self.generation
+    }
+
+    pub fn read_fittest(&mut self) -> Individual<G>{
+        self.sort_individuals();
+        self.record_best();
+        self.individuals[0].clone()
     }
 
+    /// Returns the `n`th fittest individual, 0 being the fittest, by the same ordering as
+    /// `next_generation` uses. Unlike `read_fittest` this leaves the order of the
+    /// population untouched and clones nothing.
+    ///
+    /// # Arguments
+    /// * `n` - The rank, smaller than the number of individuals.
+    pub fn read_nth_fittest(&self, n: usize) -> &Individual<G> {
+        if n >= self.individuals.len() {
+            panic!("rank {} is outside the population of {}", n, self.individuals.len());
+        }
+        &self.individuals[self.ranking()[n]]
+    }
+
+    /// Returns the `k` fittest individuals, fittest first, without reordering or cloning
+    /// the population. Fewer are returned if the population is smaller.
+    pub fn top_k(&self, k: usize) -> Vec<&Individual<G>> {
+        self.ranking().into_iter().take(k).map(|i| &self.individuals[i]).collect()
+    }
+
+    /// Returns the indices of the individuals from best to worst, the order
+    /// `sort_individuals` would put them in.
+    fn ranking(&self) -> Vec<usize> {
+        if self.multi_objective {
+            return self.pareto_order();
+        }
+        let mut order: Vec<usize> = (0..self.individuals.len()).collect();
+        let individuals = &self.individuals;
+        if self.feasibility_first {
+            order.sort_by(|&a, &b| constraints::compare_feasibility_first(&individuals[b], &individuals[a]));
+        } else {
+            order.sort_by(|&a, &b| individuals[b].cmp(&individuals[a]));
+        }
+        order
+    }
 
+    /// Returns the best individual ever observed in this population, with the fitness it
+    /// had when observed. Unlike `read_fittest` this never regresses when the best
+    /// individual is lost to replacement or a restart, or re-evaluated with a noisy
+    /// fitness function. Individuals are observed at every `next_generation`,
+    /// `read_fittest`, restart and culling, so evaluate the population before those.
+    pub fn get_best_ever(&self) -> Option<&Individual<G>> {
+        self.best_ever.as_ref()
+    }
+
+    /// Remembers the current best individual if it beats the best ever observed.
+    ///
+    /// # Returns
+    /// True if a new best was recorded.
+    pub(crate) fn record_best(&mut self) -> bool {
+        let compare = |a: &Individual<G>, b: &Individual<G>| {
+            if self.feasibility_first {
+                constraints::compare_feasibility_first(a, b)
+            } else {
+                a.cmp(b)
+            }
+        };
+        let best = match self.individuals.iter().max_by(|a, b| compare(a, b)) {
+            Some(best) => best,
+            None => return false,
+        };
+        let is_new_best = match &self.best_ever {
+            Some(best_ever) => compare(best, best_ever) == Ordering::Greater,
+            None => true,
+        };
+        if is_new_best {
+            self.best_ever = Some(best.clone());
+        }
+        is_new_best
+    }
+}
+
+impl Population {
+    /// Prints the top `count` individuals in the population, sorted by fitness. Use
+    /// `report` to print without sorting the population.
+    ///
+    /// # Arguments
+    /// * `count` - The number of individuals to print.
+    pub fn print(&mut self, count: usize) {
+        self.sort_individuals();
+        println!("{}", self.report(count));
+    }
 }
 
 /// Implements the `Display` trait for the `Population` struct.
 /// This allows the population to be printed in a human-readable format,
 /// showing the population size, parent count, and mutation chance.
-impl fmt::Display for Population {
+impl<G: Gene> fmt::Display for Population<G> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "population_size: {} parent_count: {} mutation_chance: {}",
-            self.population_size, self.parent_count, self.mutation_chance
+            "population_size: {} parent_count: {} mutation_probability: {}",
+            self.population_size, self.parent_count, self.mutation_probability
         )
     }
 }
@@ -336,37 +1035,284 @@ pub fn init_population(
     gene_length: usize,
     population_size: usize,
     parent_count: usize,
-    mut mutation_chance: u8,
+    mutation_chance: u8,
+    multi_threaded: bool,
+    crossover: CrossoverType
+) -> Population {
+    init_gene_population(gene_length, population_size, parent_count, mutation_chance, multi_threaded, crossover)
+}
+
+/// Initializes a new population with random individuals and a mutation probability
+/// finer than whole percents, e.g. `1.0 / gene_length as f64` for long chromosomes.
+/// Takes the same arguments as `init_population`, with the mutation chance as a
+/// probability.
+///
+/// # Arguments
+/// * `mutation_probability` - The probability of mutation for each gene (0.0-1.0).
+///
+/// # Returns
+/// A new `Population` with randomized individuals.
+pub fn init_population_with_probability(
+    gene_length: usize,
+    population_size: usize,
+    parent_count: usize,
+    mutation_probability: f64,
     multi_threaded: bool,
     crossover: CrossoverType
+) -> Population {
+    if let Err(error) = check_config(gene_length, population_size, parent_count, mutation_probability, crossover) {
+        panic!("{}", error);
+    }
+    let rng = rng::GaRng::from_rng(&mut rand::rng());
+    Population::new_unchecked(gene_length, population_size, parent_count, mutation_probability, multi_threaded, crossover, rng)
+}
+
+/// Initializes a new population with random individuals drawn from a seeded random number
+/// generator. Initialization, selection, crossover and mutation all draw from it, in the
+/// multi threaded path too, so the same seed and the same fitness function give the same
+/// run. Takes the same arguments as `init_population` plus the seed.
+///
+/// # Returns
+/// A new `Population` with randomized individuals.
+pub fn init_population_seeded(
+    gene_length: usize,
+    population_size: usize,
+    parent_count: usize,
+    mut mutation_chance: u8,
+    multi_threaded: bool,
+    crossover: CrossoverType,
+    seed: u64
 ) -> Population {
     if mutation_chance > MAX_MUTATION_CHANCE {
         mutation_chance = MAX_MUTATION_CHANCE;
     }
-    if gene_length <=0{
-        panic!("the gene length cannot be less than 1");
+    let mutation_probability = chance_to_probability(mutation_chance);
+    if let Err(error) = check_config(gene_length, population_size, parent_count, mutation_probability, crossover) {
+        panic!("{}", error);
     }
-    if parent_count < 1{
-        panic!("parent count must be 1 or more")
+    let rng = rng::GaRng::seed_from_u64(seed);
+    Population::new_unchecked(gene_length, population_size, parent_count, mutation_probability, multi_threaded, crossover, rng)
+}
+
+/// Initializes a new population of any gene type with random individuals, see `gene::Gene`.
+/// Takes the same arguments as `init_population`, e.g.
+/// `init_gene_population::<f64>(10, 50, 5, 10, false, CrossoverType::Bit)`.
+///
+/// # Returns
+/// A new `Population` with randomized individuals.
+pub fn init_gene_population<G: Gene>(
+    gene_length: usize,
+    population_size: usize,
+    parent_count: usize,
+    mut mutation_chance: u8,
+    multi_threaded: bool,
+    crossover: CrossoverType
+) -> Population<G> {
+    if mutation_chance > MAX_MUTATION_CHANCE {
+        mutation_chance = MAX_MUTATION_CHANCE;
     }
-    if parent_count > population_size{
-        panic!("parent count cant be larger than population")
+    match Population::try_new(gene_length, population_size, parent_count, mutation_chance, multi_threaded, crossover) {
+        Ok(population) => population,
+        Err(error) => panic!("{}", error),
     }
-    let mut temp = Population {
-        individuals: vec![
-            Individual {
-                gene_length,
-                genes: vec![true; gene_length],
-                fitness: 0,
-            };
-            population_size
-        ],
-        population_size,
-        parent_count,
-        mutation_chance,
-        multi_threaded,
-        crossover
-    };
-    temp.randomize_population();
-    return temp;
-}
\ No newline at end of file
+}
+
+/// Converts a mutation chance in whole percents (0-100) to a probability.
+fn chance_to_probability(mutation_chance: u8) -> f64 {
+    mutation_chance as f64 / 100.0
+}
+
+/// Checks a mutation chance in whole percents and converts it to a probability.
+fn check_mutation_chance(mutation_chance: u8) -> Result<f64, error::ConfigError> {
+    if mutation_chance > MAX_MUTATION_CHANCE {
+        return Err(error::ConfigError::MutationChance(mutation_chance));
+    }
+    Ok(chance_to_probability(mutation_chance))
+}
+
+/// Checks the settings shared by all ways of creating a population.
+fn check_config(
+    gene_length: usize,
+    population_size: usize,
+    parent_count: usize,
+    mutation_probability: f64,
+    crossover: CrossoverType
+) -> Result<(), error::ConfigError> {
+    if gene_length < 1 {
+        return Err(error::ConfigError::GeneLength);
+    }
+    if population_size < 1 {
+        return Err(error::ConfigError::PopulationSize);
+    }
+    if parent_count < 1 {
+        return Err(error::ConfigError::ParentCount);
+    }
+    if parent_count > population_size {
+        return Err(error::ConfigError::ParentsExceedPopulation { parent_count, population_size });
+    }
+    if !(0.0..=1.0).contains(&mutation_probability) {
+        return Err(error::ConfigError::MutationProbability(mutation_probability));
+    }
+    match crossover {
+        CrossoverType::KPoint(0) => return Err(error::ConfigError::Crossover(crossover)),
+        CrossoverType::Uniform { parent_bias } if !(0.0..=1.0).contains(&parent_bias) => {
+            return Err(error::ConfigError::Crossover(crossover))
+        }
+        CrossoverType::Segment { switch_probability } if !(0.0..=1.0).contains(&switch_probability) => {
+            return Err(error::ConfigError::Crossover(crossover))
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+impl<G: Gene> Population<G> {
+    /// Creates a population with random individuals like `init_gene_population`, but
+    /// returns an error instead of panicking on invalid settings, e.g. for servers and
+    /// GUIs that must not abort. Unlike `init_population` a mutation chance above 100 is
+    /// an error too.
+    ///
+    /// # Arguments
+    /// * `gene_length` - The length of the gene sequence for each individual.
+    /// * `population_size` - The number of individuals in the population.
+    /// * `parent_count` - The number of parents to select for reproduction.
+    /// * `mutation_chance` - The chance of mutation for each gene (0-100).
+    /// * `multi_threaded` - Whether children are bred on several threads.
+    /// * `crossover` - How the genes of the parents are recombined.
+    pub fn try_new(
+        gene_length: usize,
+        population_size: usize,
+        parent_count: usize,
+        mutation_chance: u8,
+        multi_threaded: bool,
+        crossover: CrossoverType
+    ) -> Result<Population<G>, error::GaError> {
+        let mutation_probability = check_mutation_chance(mutation_chance)?;
+        check_config(gene_length, population_size, parent_count, mutation_probability, crossover)?;
+        let rng = rng::GaRng::from_rng(&mut rand::rng());
+        Ok(Population::new_unchecked(gene_length, population_size, parent_count, mutation_probability, multi_threaded, crossover, rng))
+    }
+
+    /// Creates a population with random individuals from settings that were already checked.
+    /// All randomness of the population is drawn from `rng`.
+    fn new_unchecked(
+        gene_length: usize,
+        population_size: usize,
+        parent_count: usize,
+        mutation_probability: f64,
+        multi_threaded: bool,
+        crossover: CrossoverType,
+        rng: rng::GaRng
+    ) -> Population<G> {
+        let mut temp = Population::new_blank(gene_length, population_size, parent_count, mutation_probability, multi_threaded, crossover, rng);
+        temp.randomize_population();
+        temp
+    }
+
+    /// Creates a population like `new_unchecked` whose individuals have no genes yet.
+    fn new_blank(
+        gene_length: usize,
+        population_size: usize,
+        parent_count: usize,
+        mutation_probability: f64,
+        multi_threaded: bool,
+        crossover: CrossoverType,
+        rng: rng::GaRng
+    ) -> Population<G> {
+        Population {
+            individuals: vec![
+                Individual {
+                    gene_length,
+                    genes: vec![].into(),
+                    fitness: 0,
+                    violation: 0.0,
+                    objectives: vec![],
+                    strategy: vec![],
+                    age: 0,
+                };
+                population_size
+            ],
+            population_size,
+            parent_count,
+            mutation_probability,
+            mutation_schedule: None,
+            mutation_schedule_start: 0,
+            gene_mutation_rates: None,
+            self_adaptation: None,
+            multi_threaded,
+            crossover,
+            crossover_mask: None,
+            crossover_rate: 1.0,
+            variable_length: None,
+            packed_storage: false,
+            repair: None,
+            variation: None,
+            feasibility_first: false,
+            constraints: vec![],
+            constraint_handling: constraints::ConstraintHandling::Penalty(1.0),
+            penalty_scheduler: None,
+            multi_objective: false,
+            selection_type: SelectionType::Truncation,
+            elitism_count: None,
+            steady_state: None,
+            replace_oldest: false,
+            fitness_sharing: None,
+            crowding: false,
+            crowding_pending: None,
+            speciation: None,
+            species: vec![],
+            next_species_id: 0,
+            alps: None,
+            mating_restriction: None,
+            differential_evolution: None,
+            de_pending: None,
+            evolution_strategy: None,
+            es_pending: None,
+            cma_es: None,
+            random_immigrants: None,
+            local_search: None,
+            fresh_count: population_size,
+            selection: None,
+            generation: 0,
+            best_fitness_seen: None,
+            best_ever: None,
+            timeouts: 0,
+            subscribers: vec![],
+            observers: vec![],
+            fitness_cache: None,
+            duplicate_elimination: None,
+            operator_counters: Arc::new(events::OperatorCounters::default()),
+            rng
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.genes.iter().filter(|&&gene| gene).count() as u64
+    }
+
+    /// Evolves a seeded population and returns the genomes it ends with.
+    fn seeded_run(seed: u64, multi_threaded: bool) -> Vec<Vec<bool>> {
+        let mut population = init_population_seeded(64, 30, 6, 5, multi_threaded, CrossoverType::Bit, seed);
+        for _ in 0..20 {
+            population.evaluate(ones);
+            population.next_generation();
+        }
+        population.individuals.iter().map(|individual| individual.get_genes()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_run() {
+        assert_eq!(seeded_run(42, false), seeded_run(42, false));
+        assert_ne!(seeded_run(42, false), seeded_run(43, false));
+    }
+
+    #[test]
+    fn same_seed_gives_same_multi_threaded_run() {
+        assert_eq!(seeded_run(7, true), seeded_run(7, true));
+        assert_eq!(seeded_run(7, true), seeded_run(7, false));
+    }
+}
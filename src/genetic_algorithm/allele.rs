@@ -0,0 +1,123 @@
+use rand::Rng;
+
+use super::{rng::GaRng, variation::{BuiltInVariation, Variation}, Individual, Population};
+
+/// How a base-N gene changes when it mutates. Both keep the gene inside `0..allele_count`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlleleMutation {
+    /// The gene is replaced by a different, uniformly chosen allele.
+    Uniform,
+    /// The gene moves up or down by at most `step`, clamped to the allele range.
+    /// Suited to ordered categories where neighbouring values behave alike.
+    Creep { step: usize },
+}
+
+/// How two or more base-N parents are recombined. Both copy whole genes,
+/// so children never hold values outside the allele range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlleleCrossover {
+    /// Every gene is taken from a random parent.
+    Uniform,
+    /// Genes before a random cut come from one parent, the rest from another.
+    SinglePoint,
+}
+
+/// Variation operators for `Population<usize>` whose genes take one of `allele_count`
+/// values, `0..allele_count`, instead of a bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlleleOperators {
+    allele_count: usize,
+    mutation: AlleleMutation,
+    crossover: AlleleCrossover,
+}
+
+impl AlleleOperators {
+    /// Creates new operators.
+    ///
+    /// # Arguments
+    /// * `allele_count` - The number of values (N) each gene can take, genes are in `0..N`.
+    /// * `mutation` - How a mutating gene picks its new value.
+    /// * `crossover` - How parents are recombined.
+    pub fn new(allele_count: usize, mutation: AlleleMutation, crossover: AlleleCrossover) -> AlleleOperators {
+        if allele_count < 2 {
+            panic!("a gene needs at least 2 alleles");
+        }
+        AlleleOperators {
+            allele_count,
+            mutation,
+            crossover,
+        }
+    }
+
+    pub fn get_allele_count(&self) -> usize {
+        self.allele_count
+    }
+
+    pub fn get_mutation(&self) -> AlleleMutation {
+        self.mutation
+    }
+
+    pub fn get_crossover(&self) -> AlleleCrossover {
+        self.crossover
+    }
+}
+
+impl Variation<usize> for AlleleOperators {
+    fn random_genes(&self, gene_length: usize, rng: &mut GaRng) -> Vec<usize> {
+        (0..gene_length).map(|_| rng.random_range(0..self.allele_count)).collect()
+    }
+
+    fn crossover(&self, parents: &[&Individual<usize>], rng: &mut GaRng) -> Vec<usize> {
+        let gene_length = parents[0].gene_length;
+        match self.crossover {
+            AlleleCrossover::Uniform => {
+                (0..gene_length).map(|i| parents[rng.random_range(0..parents.len())].genes[i]).collect()
+            }
+            AlleleCrossover::SinglePoint => {
+                let first = parents[rng.random_range(0..parents.len())];
+                let second = parents[rng.random_range(0..parents.len())];
+                let cut = rng.random_range(0..=gene_length);
+                let mut genes = first.genes.as_slice()[..cut].to_vec();
+                genes.extend_from_slice(&second.genes.as_slice()[cut..]);
+                genes
+            }
+        }
+    }
+
+    fn mutate(&self, genes: &mut [usize], mutation_probability: f64, rng: &mut GaRng) -> u64 {
+        let mut mutations = 0;
+        for gene in genes.iter_mut() {
+            if !rng.random_bool(mutation_probability) {
+                continue;
+            }
+            *gene = match self.mutation {
+                AlleleMutation::Uniform => (*gene + rng.random_range(1..self.allele_count)) % self.allele_count,
+                AlleleMutation::Creep { step } => {
+                    let delta = rng.random_range(1..=step.max(1));
+                    if rng.random_bool(0.5) {
+                        gene.saturating_add(delta).min(self.allele_count - 1)
+                    } else {
+                        gene.saturating_sub(delta)
+                    }
+                }
+            };
+            mutations += 1;
+        }
+        mutations
+    }
+}
+
+impl Population<usize> {
+    /// Evolves the population with base-N operators and re-initializes every individual
+    /// with random alleles, resetting its fitness.
+    ///
+    /// # Arguments
+    /// * `operators` - The allele count, mutation and crossover.
+    pub fn set_allele_operators(&mut self, operators: AlleleOperators) {
+        self.set_built_in_variation(operators, BuiltInVariation::Allele(operators));
+        self.reinitialize_individuals();
+    }
+}
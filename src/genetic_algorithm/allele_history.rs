@@ -0,0 +1,122 @@
+use std::{fs, io, path::Path};
+
+use super::Population;
+
+impl Population {
+    /// Returns, for every locus, the fraction of individuals whose gene is `true`. With
+    /// variable-length chromosomes there is a locus for every gene of the longest genome.
+    pub fn allele_frequencies(&self) -> Vec<f64> {
+        let gene_length = self.individuals.iter().map(|individual| individual.gene_length).max().unwrap_or(0);
+        let mut counts = vec![0usize; gene_length];
+        for individual in self.individuals.iter() {
+            for (i, &gene) in individual.genes.iter().enumerate() {
+                if gene {
+                    counts[i] += 1;
+                }
+            }
+        }
+        let size = self.individuals.len() as f64;
+        counts.into_iter().map(|count| count as f64 / size).collect()
+    }
+}
+
+/// Records the per-locus allele frequencies of a population over a run, as a matrix with
+/// one row per recorded generation and one column per locus, for export to external
+/// heatmap and convergence plotting tools.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlleleFrequencyHistory {
+    interval: u64,
+    generation: u64,
+    generations: Vec<u64>,
+    rows: Vec<Vec<f64>>,
+}
+
+impl AlleleFrequencyHistory {
+    /// Creates an empty history.
+    ///
+    /// # Arguments
+    /// * `interval` - Only every `interval`-th generation is kept, 1 keeps all of them.
+    pub fn new(interval: u64) -> AlleleFrequencyHistory {
+        if interval < 1 {
+            panic!("the recording interval must be 1 or more");
+        }
+        AlleleFrequencyHistory {
+            interval,
+            generation: 0,
+            generations: vec![],
+            rows: vec![],
+        }
+    }
+
+    /// Records the current allele frequencies of the population, call once per generation.
+    /// Generations are counted from 1 and skipped ones are not stored.
+    ///
+    /// # Arguments
+    /// * `population` - The population to record.
+    pub fn record(&mut self, population: &Population) {
+        self.generation += 1;
+        if !(self.generation - 1).is_multiple_of(self.interval) {
+            return;
+        }
+        let row = population.allele_frequencies();
+        if let Some(previous) = self.rows.last() {
+            if previous.len() != row.len() {
+                panic!("gene length changed from {} to {} during recording", previous.len(), row.len());
+            }
+        }
+        self.generations.push(self.generation);
+        self.rows.push(row);
+    }
+
+    /// Returns the generation numbers of the recorded rows.
+    pub fn get_generations(&self) -> &[u64] {
+        &self.generations
+    }
+
+    /// Returns the recorded frequency matrix, one row per recorded generation.
+    pub fn get_rows(&self) -> &[Vec<f64>] {
+        &self.rows
+    }
+
+    /// Formats the history as CSV with a `generation,locus_0,locus_1,...` header.
+    pub fn to_csv(&self) -> String {
+        let gene_length = self.rows.first().map(|row| row.len()).unwrap_or(0);
+        let mut csv = String::from("generation");
+        for i in 0..gene_length {
+            csv.push_str(&format!(",locus_{}", i));
+        }
+        csv.push('\n');
+        for (generation, row) in self.generations.iter().zip(self.rows.iter()) {
+            csv.push_str(&generation.to_string());
+            for frequency in row {
+                csv.push_str(&format!(",{}", frequency));
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Formats the history as a JSON object `{"generations": [...], "frequencies": [[...], ...]}`.
+    pub fn to_json(&self) -> String {
+        let generations: Vec<String> = self.generations.iter().map(|g| g.to_string()).collect();
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let values: Vec<String> = row.iter().map(|f| f.to_string()).collect();
+                format!("[{}]", values.join(","))
+            })
+            .collect();
+        format!("{{\"generations\":[{}],\"frequencies\":[{}]}}", generations.join(","), rows.join(","))
+    }
+
+    /// Writes the history to a CSV file.
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    /// Writes the history to a JSON file.
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+}
@@ -0,0 +1,151 @@
+use rand::Rng;
+
+use super::{rng::GaRng, variation::{BuiltInVariation, Variation}, Individual, Population};
+
+/// The set of symbols the genes of a `Population<char>` can take, e.g. printable ASCII
+/// or DNA bases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alphabet {
+    symbols: Vec<char>,
+}
+
+impl Alphabet {
+    /// Creates an alphabet from the distinct characters of a string, in order.
+    pub fn new(symbols: &str) -> Alphabet {
+        let mut unique: Vec<char> = vec![];
+        for symbol in symbols.chars() {
+            if !unique.contains(&symbol) {
+                unique.push(symbol);
+            }
+        }
+        if unique.len() < 2 {
+            panic!("an alphabet needs at least 2 symbols");
+        }
+        Alphabet { symbols: unique }
+    }
+
+    /// The 95 printable ASCII characters, space to `~`.
+    pub fn printable_ascii() -> Alphabet {
+        Alphabet::new(&(' '..='~').collect::<String>())
+    }
+
+    /// The four DNA bases `ACGT`.
+    pub fn dna() -> Alphabet {
+        Alphabet::new("ACGT")
+    }
+
+    pub fn get_symbols(&self) -> &[char] {
+        &self.symbols
+    }
+
+    pub fn get_symbol_count(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn contains(&self, symbol: char) -> bool {
+        self.symbols.contains(&symbol)
+    }
+
+    /// Returns the position of a symbol in the alphabet.
+    pub fn index_of(&self, symbol: char) -> Option<usize> {
+        self.symbols.iter().position(|&s| s == symbol)
+    }
+
+    fn random_symbol(&self, rng: &mut GaRng) -> char {
+        self.symbols[rng.random_range(0..self.symbols.len())]
+    }
+}
+
+/// How a symbol gene changes when it mutates. Both keep the gene inside the alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SymbolMutation {
+    /// The gene is replaced by a different, uniformly chosen symbol.
+    Random,
+    /// The gene moves to the previous or next symbol of the alphabet (wrapping around),
+    /// useful when the alphabet order is meaningful such as ASCII codes.
+    Neighbor,
+}
+
+/// Variation operators for `Population<char>` whose genes are symbols of an `Alphabet`:
+/// every gene of a child is taken from a random parent and mutates into another symbol
+/// of the alphabet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymbolOperators {
+    alphabet: Alphabet,
+    mutation: SymbolMutation,
+}
+
+impl SymbolOperators {
+    /// Creates new operators.
+    ///
+    /// # Arguments
+    /// * `alphabet` - The symbols every gene is drawn from.
+    /// * `mutation` - How a mutating gene picks its new symbol.
+    pub fn new(alphabet: Alphabet, mutation: SymbolMutation) -> SymbolOperators {
+        SymbolOperators { alphabet, mutation }
+    }
+
+    pub fn get_alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    pub fn get_mutation(&self) -> SymbolMutation {
+        self.mutation
+    }
+}
+
+impl Variation<char> for SymbolOperators {
+    fn random_genes(&self, gene_length: usize, rng: &mut GaRng) -> Vec<char> {
+        (0..gene_length).map(|_| self.alphabet.random_symbol(rng)).collect()
+    }
+
+    fn crossover(&self, parents: &[&Individual<char>], rng: &mut GaRng) -> Vec<char> {
+        (0..parents[0].gene_length).map(|i| parents[rng.random_range(0..parents.len())].genes[i]).collect()
+    }
+
+    fn mutate(&self, genes: &mut [char], mutation_probability: f64, rng: &mut GaRng) -> u64 {
+        let length = self.alphabet.get_symbol_count();
+        let mut mutations = 0;
+        for gene in genes.iter_mut() {
+            if !rng.random_bool(mutation_probability) {
+                continue;
+            }
+            let current = self.alphabet.index_of(*gene).unwrap_or(0);
+            let next = match self.mutation {
+                SymbolMutation::Random => (current + rng.random_range(1..length)) % length,
+                SymbolMutation::Neighbor => {
+                    if rng.random_bool(0.5) {
+                        (current + 1) % length
+                    } else {
+                        (current + length - 1) % length
+                    }
+                }
+            };
+            *gene = self.alphabet.symbols[next];
+            mutations += 1;
+        }
+        mutations
+    }
+}
+
+impl Individual<char> {
+    /// Returns the genes as a string.
+    pub fn get_genes_as_string(&self) -> String {
+        self.genes.iter().collect()
+    }
+}
+
+impl Population<char> {
+    /// Evolves the population with symbol operators and re-initializes every individual
+    /// with random symbols of the alphabet, resetting its fitness.
+    ///
+    /// # Arguments
+    /// * `operators` - The alphabet and the mutation.
+    pub fn set_symbol_operators(&mut self, operators: SymbolOperators) {
+        self.set_built_in_variation(operators.clone(), BuiltInVariation::Symbol(operators));
+        self.reinitialize_individuals();
+    }
+}
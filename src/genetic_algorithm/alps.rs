@@ -0,0 +1,202 @@
+use super::{gene::Gene, Population};
+
+/// Age-layered population structure (Hornby's ALPS) settings. The population is split
+/// into `layers` layers of about equal size by the age of the individuals, with
+/// Fibonacci age limits: `age_gap`, `2 * age_gap`, `3 * age_gap`, `5 * age_gap` and so
+/// on, the last layer without a limit. Every `age_gap` generations the youngest layer is
+/// replaced by fresh random individuals, so new genetic material keeps entering the run
+/// and only has to compete with individuals of about its own age.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alps {
+    layers: usize,
+    age_gap: u64,
+}
+
+impl Alps {
+    /// Creates new ALPS settings.
+    ///
+    /// # Arguments
+    /// * `layers` - The number of age layers, 1 or more.
+    /// * `age_gap` - The generations between reseedings of the youngest layer and the unit of the age limits, 1 or more.
+    pub fn new(layers: usize, age_gap: u64) -> Alps {
+        if layers < 1 {
+            panic!("alps needs at least 1 layer");
+        }
+        if age_gap < 1 {
+            panic!("alps age gap must be 1 or more");
+        }
+        Alps { layers, age_gap }
+    }
+
+    pub fn get_layers(&self) -> usize {
+        self.layers
+    }
+
+    pub fn get_age_gap(&self) -> u64 {
+        self.age_gap
+    }
+
+    /// Returns the oldest age allowed in every layer but the last, youngest first.
+    pub fn get_age_limits(&self) -> Vec<u64> {
+        let (mut previous, mut current) = (1, 1);
+        let mut limits = vec![];
+        for _ in 1..self.layers {
+            limits.push(current * self.age_gap);
+            (previous, current) = (current, previous + current);
+        }
+        limits
+    }
+
+    /// Returns the layer an individual of the given age belongs to.
+    pub fn layer_of(&self, age: u64) -> usize {
+        self.get_age_limits().iter().position(|&limit| age <= limit).unwrap_or(self.layers - 1)
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Switches `next_generation` to ALPS. Each layer is bred from the selected parents
+    /// among its own members and those of the layer below, and keeps its best member,
+    /// which ages by one. The parent count is scaled down to the size of a layer. Layers
+    /// nobody is old enough for yet give their places to the layers below, so early on
+    /// the whole population is the youngest layer. Cant be combined with another
+    /// replacement mode, e.g. steady-state mode or crowding.
+    ///
+    /// # Arguments
+    /// * `alps` - The number of layers and the age gap.
+    pub fn set_alps(&mut self, alps: Alps) {
+        self.check_replacement_mode("alps");
+        self.alps = Some(alps);
+    }
+
+    /// Goes back to a single generational population.
+    pub fn clear_alps(&mut self) {
+        self.alps = None;
+    }
+
+    pub fn get_alps(&self) -> Option<Alps> {
+        self.alps
+    }
+
+    /// Returns the number of individuals in every age layer, youngest first, empty
+    /// without ALPS.
+    pub fn alps_layer_sizes(&self) -> Vec<usize> {
+        let alps = match self.alps {
+            Some(alps) => alps,
+            None => return vec![],
+        };
+        let mut sizes = vec![0; alps.layers];
+        for individual in &self.individuals {
+            sizes[alps.layer_of(individual.age)] += 1;
+        }
+        sizes
+    }
+
+    /// Replaces the population layer by layer, oldest first. The population is sorted
+    /// first so that the first member of every layer is its best.
+    pub(crate) fn alps_step(&mut self, alps: Alps) {
+        self.sort_individuals();
+        let mut members: Vec<Vec<usize>> = vec![vec![]; alps.layers];
+        for (index, individual) in self.individuals.iter().enumerate() {
+            members[alps.layer_of(individual.age)].push(index);
+        }
+        let reseed = self.generation > 0 && self.generation.is_multiple_of(alps.age_gap);
+        let template = self.individuals[0].clone();
+        let mut next = Vec::with_capacity(self.population_size);
+        // the places of empty layers are handed down to the next younger one
+        let mut carried = 0;
+        for layer in (0..alps.layers).rev() {
+            let capacity = self.population_size / alps.layers + usize::from(layer < self.population_size % alps.layers) + carried;
+            carried = 0;
+            let mut candidates = members[layer].clone();
+            if layer > 0 {
+                candidates.extend(&members[layer - 1]);
+                candidates.sort();
+            }
+            if layer == 0 && (reseed || candidates.is_empty()) {
+                for _ in 0..capacity {
+                    next.push(self.random_individual(&template));
+                }
+                continue;
+            }
+            if candidates.is_empty() || capacity == 0 {
+                carried = capacity;
+                continue;
+            }
+            let mut survivors = 0;
+            if let Some(&best) = members[layer].first() {
+                let mut elite = self.individuals[best].clone();
+                elite.age += 1;
+                next.push(elite);
+                survivors = 1;
+            }
+            let parent_count = (self.parent_count * capacity / self.population_size).clamp(1, candidates.len());
+            let parents = self
+                .select_parent_indices_from(candidates, parent_count)
+                .into_iter()
+                .map(|index| self.individuals[index].clone())
+                .collect();
+            let (_, mut children) = self.breed_children(parents, capacity - survivors, vec![]);
+            next.append(&mut children);
+        }
+        self.individuals = next;
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{init_population_seeded, CrossoverType, Individual};
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+    }
+
+    #[test]
+    fn age_limits_follow_the_fibonacci_sequence() {
+        let alps = Alps::new(5, 3);
+        assert_eq!(alps.get_age_limits(), vec![3, 6, 9, 15]);
+        let layers: Vec<usize> = [0, 3, 4, 6, 7, 9, 10, 15, 16, 1000].iter().map(|&age| alps.layer_of(age)).collect();
+        assert_eq!(layers, vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4]);
+        let single = Alps::new(1, 4);
+        assert!(single.get_age_limits().is_empty());
+        assert_eq!(single.layer_of(1000), 0);
+    }
+
+    #[test]
+    fn empty_layers_hand_their_places_down_and_the_elite_ages() {
+        let mut population = init_population_seeded(16, 12, 4, 5, false, CrossoverType::Bit, 1);
+        population.set_alps(Alps::new(3, 5));
+        population.individuals[0] = Individual::from_genes(vec![true; 16]);
+        population.evaluate(ones);
+        // the best individual is moved to the end, alps_step must still find it
+        population.individuals.sort_by_key(|individual| individual.get_fitness());
+        let best = population.individuals.last().unwrap().clone();
+        population.alps_step(Alps::new(3, 5));
+        assert_eq!(population.individuals.len(), 12);
+        assert_eq!(population.alps_layer_sizes(), vec![12, 0, 0]);
+        let elite = population.individuals.iter().find(|individual| individual.get_genes() == best.get_genes()).unwrap();
+        assert_eq!(elite.get_age(), 1);
+        assert_eq!(elite.get_fitness(), best.get_fitness());
+        assert!(population.individuals.iter().all(|individual| individual.get_age() == 1));
+    }
+
+    #[test]
+    fn the_youngest_layer_is_reseeded_every_age_gap() {
+        for (generation, reseeded) in [(3, true), (4, false)] {
+            let mut population = init_population_seeded(16, 12, 4, 5, false, CrossoverType::Bit, 2);
+            population.set_alps(Alps::new(2, 3));
+            population.evaluate(ones);
+            for (i, individual) in population.individuals.iter_mut().enumerate() {
+                individual.age = if i % 2 == 0 { 10 } else { 0 };
+            }
+            population.generation = generation;
+            population.sort_individuals();
+            let old_best = population.individuals.iter().find(|individual| individual.age == 10).unwrap().clone();
+            population.alps_step(Alps::new(2, 3));
+            assert_eq!(population.individuals.len(), 12);
+            let fresh = population.individuals.iter().filter(|individual| individual.age == 0).count();
+            assert_eq!(fresh, if reseeded { 6 } else { 0 });
+            assert!(population.individuals.iter().any(|individual| individual.age == 11 && individual.get_genes() == old_best.get_genes()));
+        }
+    }
+}
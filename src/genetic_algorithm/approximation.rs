@@ -0,0 +1,217 @@
+use std::{cmp::Reverse, collections::VecDeque};
+
+use super::{Individual, Population};
+
+/// Estimates offspring fitness from previously evaluated individuals so that only
+/// the most promising fraction of a generation has to go through the real
+/// (expensive) fitness function.
+///
+/// Every truly evaluated individual is kept in an archive. An unevaluated individual
+/// is scored with the mean fitness of its `neighbors` nearest archive entries
+/// (by Hamming distance); the best `evaluate_fraction` of the population according
+/// to that estimate is then evaluated for real and added to the archive.
+#[derive(Debug, Clone)]
+pub struct FitnessApproximator {
+    archive: VecDeque<Individual>,
+    archive_size: usize,
+    neighbors: usize,
+    evaluate_fraction: f64,
+    evaluations: u64,
+    approximations: u64,
+}
+
+impl FitnessApproximator {
+    /// Creates a new approximator with an empty archive.
+    ///
+    /// # Arguments
+    /// * `neighbors` - How many nearest archived individuals are averaged for an estimate.
+    /// * `evaluate_fraction` - The fraction (0.0-1.0) of each generation that is truly evaluated.
+    /// * `archive_size` - The maximum number of evaluated individuals kept, oldest are dropped first.
+    pub fn new(neighbors: usize, evaluate_fraction: f64, archive_size: usize) -> FitnessApproximator {
+        if neighbors < 1 {
+            panic!("neighbor count must be 1 or more");
+        }
+        if !(0.0..=1.0).contains(&evaluate_fraction) {
+            panic!("evaluate fraction must be between 0 and 1");
+        }
+        if archive_size < neighbors {
+            panic!("archive size cant be smaller than the neighbor count");
+        }
+        FitnessApproximator {
+            archive: VecDeque::new(),
+            archive_size,
+            neighbors,
+            evaluate_fraction,
+            evaluations: 0,
+            approximations: 0,
+        }
+    }
+
+    /// Assigns a fitness to every individual of the population.
+    /// Until the archive holds at least `neighbors` entries every individual is evaluated
+    /// with `fitness`, afterwards only the top `evaluate_fraction` by estimated fitness is.
+    /// Like `Population::evaluate` it takes fitness values from the fitness cache, and the
+    /// evaluated and the estimated fitness both go through the constraints, fitness
+    /// sharing, speciation and pending replacement steps; only real evaluations are cached.
+    ///
+    /// # Arguments
+    /// * `population` - The population to score.
+    /// * `fitness` - The real fitness function.
+    pub fn evaluate<F: Fn(&Individual) -> u64>(&mut self, population: &mut Population, fitness: F) {
+        let count = population.individuals.len();
+        let pending = population.apply_fitness_cache(0..count);
+        let evaluated = if self.archive.len() < self.neighbors {
+            pending
+        } else {
+            let mut estimates: Vec<(usize, u64)> = pending
+                .into_iter()
+                .map(|i| (i, self.estimate(&population.individuals[i])))
+                .collect();
+            estimates.sort_by_key(|&(_, estimate)| Reverse(estimate));
+
+            let evaluate_count = (estimates.len() as f64 * self.evaluate_fraction).ceil() as usize;
+            let mut evaluated = vec![];
+            for (rank, (i, estimate)) in estimates.into_iter().enumerate() {
+                if rank < evaluate_count {
+                    evaluated.push(i);
+                } else {
+                    population.individuals[i].set_fitness(estimate);
+                    self.approximations += 1;
+                }
+            }
+            evaluated.sort_unstable();
+            evaluated
+        };
+        for &i in &evaluated {
+            let individual = &mut population.individuals[i];
+            individual.set_fitness(fitness(individual));
+            self.evaluations += 1;
+            // archived before the constraints, so estimates are raw fitness like the evaluations
+            self.archive_individual(individual.clone());
+        }
+        population.after_evaluation(0..count, &evaluated, &fitness);
+    }
+
+    /// Estimates the fitness of an individual from its nearest archived neighbors.
+    /// An exact genome match returns the archived fitness as is.
+    ///
+    /// # Arguments
+    /// * `individual` - The individual to estimate.
+    ///
+    /// # Returns
+    /// The estimated fitness, 0 if the archive is empty.
+    pub fn estimate(&self, individual: &Individual) -> u64 {
+        let mut distances: Vec<(usize, u64)> = self
+            .archive
+            .iter()
+            .map(|archived| (archived.hamming_distance(individual), archived.fitness))
+            .collect();
+        if distances.is_empty() {
+            return 0;
+        }
+        distances.sort_by_key(|a| a.0);
+        if distances[0].0 == 0 {
+            return distances[0].1;
+        }
+        let nearest = &distances[..self.neighbors.min(distances.len())];
+        let sum: u128 = nearest.iter().map(|&(_, fitness)| fitness as u128).sum();
+        (sum / nearest.len() as u128) as u64
+    }
+
+    /// Returns how many times the real fitness function was called.
+    pub fn get_evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Returns how many fitness values were estimated instead of evaluated.
+    pub fn get_approximations(&self) -> u64 {
+        self.approximations
+    }
+
+    fn archive_individual(&mut self, individual: Individual) {
+        if self.archive.len() >= self.archive_size {
+            self.archive.pop_front();
+        }
+        self.archive.push_back(individual);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{init_population_seeded, CrossoverType};
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+    }
+
+    fn archived(genes: Vec<bool>, fitness: u64) -> Individual {
+        let mut individual = Individual::from_genes(genes);
+        individual.set_fitness(fitness);
+        individual
+    }
+
+    #[test]
+    fn warm_up_evaluates_everything() {
+        let mut population = init_population_seeded(16, 10, 2, 5, false, CrossoverType::Bit, 1);
+        let mut approximator = FitnessApproximator::new(3, 0.5, 50);
+        approximator.evaluate(&mut population, ones);
+        assert_eq!(approximator.get_evaluations(), 10);
+        assert_eq!(approximator.get_approximations(), 0);
+        for individual in &population.individuals {
+            assert_eq!(individual.get_fitness(), ones(individual));
+        }
+    }
+
+    #[test]
+    fn only_the_evaluate_fraction_is_evaluated_after_warm_up() {
+        let mut population = init_population_seeded(16, 10, 2, 5, false, CrossoverType::Bit, 2);
+        let mut approximator = FitnessApproximator::new(3, 0.3, 50);
+        approximator.evaluate(&mut population, ones);
+        population.next_generation();
+        approximator.evaluate(&mut population, ones);
+        assert_eq!(approximator.get_evaluations(), 13);
+        assert_eq!(approximator.get_approximations(), 7);
+    }
+
+    #[test]
+    fn estimated_fitness_goes_through_the_constraints() {
+        let mut population = init_population_seeded(16, 10, 2, 5, false, CrossoverType::Bit, 3);
+        population.add_constraint(|_: &Individual| 1.0);
+        let mut approximator = FitnessApproximator::new(3, 0.0, 50);
+        approximator.evaluate(&mut population, ones);
+        population.next_generation();
+        approximator.evaluate(&mut population, ones);
+        assert_eq!(approximator.get_approximations(), 10);
+        for individual in &population.individuals {
+            assert_eq!(individual.get_fitness(), approximator.estimate(individual).saturating_sub(1));
+            assert!(!individual.is_feasible());
+        }
+    }
+
+    #[test]
+    fn exact_match_returns_the_archived_fitness() {
+        let mut approximator = FitnessApproximator::new(2, 0.5, 10);
+        approximator.archive_individual(archived(vec![true, true, false, false], 9));
+        approximator.archive_individual(archived(vec![true, true, true, false], 1));
+        approximator.archive_individual(archived(vec![true, true, false, true], 3));
+        assert_eq!(approximator.estimate(&Individual::from_genes(vec![true, true, false, false])), 9);
+        assert_eq!(approximator.estimate(&Individual::from_genes(vec![true, true, true, true])), 2);
+    }
+
+    #[test]
+    fn estimate_does_not_overflow() {
+        let mut approximator = FitnessApproximator::new(2, 0.5, 10);
+        approximator.archive_individual(archived(vec![true, false], u64::MAX));
+        approximator.archive_individual(archived(vec![false, true], u64::MAX - 2));
+        assert_eq!(approximator.estimate(&Individual::from_genes(vec![true, true])), u64::MAX - 1);
+    }
+
+    #[test]
+    fn full_archive_drops_the_oldest() {
+        let mut approximator = FitnessApproximator::new(1, 0.5, 2);
+        approximator.archive_individual(archived(vec![true, true], 5));
+        approximator.archive_individual(archived(vec![true, false], 6));
+        approximator.archive_individual(archived(vec![false, false], 7));
+        assert_eq!(approximator.estimate(&Individual::from_genes(vec![true, true])), 6);
+    }
+}
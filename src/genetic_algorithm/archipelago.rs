@@ -0,0 +1,168 @@
+use std::thread;
+
+use super::{events::GaEvent, gene::Gene, Individual, Population};
+
+/// Which islands of an `Archipelago` send migrants to which.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// Every island sends its migrants to the next one, the last to the first.
+    Ring,
+    /// Every island sends its migrants to all other islands.
+    FullyConnected,
+}
+
+/// Several populations (islands) evolving in parallel, one thread each, that
+/// periodically exchange their best individuals. The islands drift apart between
+/// migrations, which keeps far more diversity than one large population.
+///
+/// Islands may be configured differently, e.g. with different mutation chances, but
+/// must share the gene length.
+#[derive(Debug)]
+pub struct Archipelago<G: Gene = bool> {
+    islands: Vec<Population<G>>,
+    topology: Topology,
+    migration_interval: u64,
+    migrant_count: usize,
+    migrations: u64,
+}
+
+impl<G: Gene> Archipelago<G> {
+    /// Creates an archipelago that migrates the best individual of every island every
+    /// 10 generations.
+    ///
+    /// # Arguments
+    /// * `islands` - The populations, at least one.
+    /// * `topology` - Which islands send migrants to which.
+    pub fn new(islands: Vec<Population<G>>, topology: Topology) -> Archipelago<G> {
+        if islands.is_empty() {
+            panic!("an archipelago needs at least 1 island");
+        }
+        Archipelago {
+            islands,
+            topology,
+            migration_interval: 10,
+            migrant_count: 1,
+            migrations: 0,
+        }
+    }
+
+    /// Sets the number of generations between migrations.
+    pub fn migration_interval(mut self, generations: u64) -> Archipelago<G> {
+        if generations < 1 {
+            panic!("migration interval must be 1 or more");
+        }
+        self.migration_interval = generations;
+        self
+    }
+
+    /// Sets how many of its best individuals an island sends to each of its targets.
+    /// They replace the worst individuals of the target.
+    pub fn migrant_count(mut self, count: usize) -> Archipelago<G> {
+        self.migrant_count = count;
+        self
+    }
+
+    /// Evolves every island for the given number of generations, migrating every
+    /// `migration_interval` generations. The islands are left evaluated.
+    ///
+    /// # Arguments
+    /// * `fitness` - The fitness function shared by all islands, called from several threads at once.
+    /// * `generations` - The number of generations every island evolves.
+    pub fn evolve<F: Fn(&Individual<G>) -> u64 + Sync>(&mut self, fitness: F, generations: u64) {
+        let fitness = &fitness;
+        self.run_islands(|island| island.evaluate(fitness));
+        let mut done = 0;
+        while done < generations {
+            let epoch = self.migration_interval.min(generations - done);
+            self.run_islands(|island| {
+                for _ in 0..epoch {
+                    island.next_generation();
+                    island.evaluate(fitness);
+                }
+            });
+            done += epoch;
+            if epoch == self.migration_interval {
+                self.migrate();
+            }
+        }
+    }
+
+    /// Runs a closure on every island, each on its own thread.
+    fn run_islands<R: Fn(&mut Population<G>) + Sync>(&mut self, run: R) {
+        let run = &run;
+        thread::scope(|scope| {
+            for island in self.islands.iter_mut() {
+                scope.spawn(move || run(island));
+            }
+        });
+    }
+
+    /// Sends copies of the best individuals of every island to its targets, replacing
+    /// their worst individuals. An island always keeps at least its best individual.
+    pub fn migrate(&mut self) {
+        let count = self.islands.len();
+        if count < 2 {
+            return;
+        }
+        let emigrants: Vec<Vec<Individual<G>>> = self
+            .islands
+            .iter_mut()
+            .map(|island| {
+                island.sort_individuals();
+                island.individuals.iter().take(self.migrant_count).cloned().collect()
+            })
+            .collect();
+        let mut arrivals: Vec<Vec<Individual<G>>> = vec![vec![]; count];
+        for (source, migrants) in emigrants.into_iter().enumerate() {
+            match self.topology {
+                Topology::Ring => arrivals[(source + 1) % count].extend(migrants),
+                Topology::FullyConnected => {
+                    for (target, arrival) in arrivals.iter_mut().enumerate() {
+                        if target != source {
+                            arrival.extend(migrants.iter().cloned());
+                        }
+                    }
+                }
+            }
+        }
+        for (island, migrants) in self.islands.iter_mut().zip(arrivals) {
+            let size = island.individuals.len();
+            let accepted = migrants.len().min(size.saturating_sub(1));
+            for (i, migrant) in migrants.into_iter().take(accepted).enumerate() {
+                island.individuals[size - 1 - i] = migrant;
+            }
+            if accepted > 0 {
+                let generation = island.generation;
+                island.emit(GaEvent::Migration { generation, count: accepted });
+            }
+        }
+        self.migrations += 1;
+    }
+
+    /// Returns the fittest individual of all islands.
+    pub fn read_fittest(&mut self) -> Individual<G> {
+        self.islands
+            .iter_mut()
+            .map(|island| island.read_fittest())
+            .max_by(|a, b| a.cmp(b))
+            .unwrap()
+    }
+
+    pub fn get_islands(&self) -> &[Population<G>] {
+        &self.islands
+    }
+
+    /// Returns the islands, in the order they were given.
+    pub fn into_islands(self) -> Vec<Population<G>> {
+        self.islands
+    }
+
+    pub fn get_topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Returns how many migrations have taken place.
+    pub fn get_migration_count(&self) -> u64 {
+        self.migrations
+    }
+}
@@ -0,0 +1,196 @@
+use rand::SeedableRng;
+use std::{marker::PhantomData, sync::Arc};
+
+use super::{
+    check_config, check_mutation_chance,
+    error::ConfigError,
+    gene::Gene,
+    initialization::{Initializer, InitializerHook},
+    rng::GaRng,
+    CrossoverType, Population, SelectionType,
+};
+
+/// Configures a population step by step instead of through the positional arguments of
+/// `init_population`, e.g.
+/// `PopulationBuilder::new().gene_length(88).size(10).parents(4).mutation_chance(15).crossover(CrossoverType::Byte).build()`.
+/// Settings that aren't given keep the defaults of `new`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct PopulationBuilder<G: Gene = bool> {
+    gene_length: usize,
+    size: usize,
+    parents: usize,
+    // a mutation chance is kept as given until `build`, so it is checked as a chance
+    mutation_chance: Option<u8>,
+    mutation_probability: f64,
+    multi_threaded: bool,
+    crossover: CrossoverType,
+    crossover_rate: f64,
+    selection: SelectionType,
+    elitism_count: Option<usize>,
+    seed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    initializer: Option<InitializerHook<G>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    gene: PhantomData<G>,
+}
+
+impl<G: Gene> Default for PopulationBuilder<G> {
+    fn default() -> PopulationBuilder<G> {
+        PopulationBuilder {
+            gene_length: 0,
+            size: 100,
+            parents: 10,
+            mutation_chance: None,
+            mutation_probability: 0.01,
+            multi_threaded: false,
+            crossover: CrossoverType::Bit,
+            crossover_rate: 1.0,
+            selection: SelectionType::Truncation,
+            elitism_count: None,
+            seed: None,
+            initializer: None,
+            gene: PhantomData,
+        }
+    }
+}
+
+impl<G: Gene> PopulationBuilder<G> {
+    /// Starts with 100 individuals, 10 parents, a mutation chance of 1, single threaded
+    /// bit crossover and truncation selection. The gene length has no default.
+    pub fn new() -> PopulationBuilder<G> {
+        PopulationBuilder::default()
+    }
+
+    pub fn gene_length(mut self, gene_length: usize) -> PopulationBuilder<G> {
+        self.gene_length = gene_length;
+        self
+    }
+
+    pub fn size(mut self, size: usize) -> PopulationBuilder<G> {
+        self.size = size;
+        self
+    }
+
+    pub fn parents(mut self, parents: usize) -> PopulationBuilder<G> {
+        self.parents = parents;
+        self
+    }
+
+    /// The chance of mutation for each gene (0-100).
+    pub fn mutation_chance(mut self, mutation_chance: u8) -> PopulationBuilder<G> {
+        self.mutation_chance = Some(mutation_chance);
+        self
+    }
+
+    /// The probability of mutation for each gene (0.0-1.0), for rates finer than whole
+    /// percents. Replaces the mutation chance.
+    pub fn mutation_probability(mut self, mutation_probability: f64) -> PopulationBuilder<G> {
+        self.mutation_chance = None;
+        self.mutation_probability = mutation_probability;
+        self
+    }
+
+    pub fn multi_threaded(mut self, multi_threaded: bool) -> PopulationBuilder<G> {
+        self.multi_threaded = multi_threaded;
+        self
+    }
+
+    pub fn crossover(mut self, crossover: CrossoverType) -> PopulationBuilder<G> {
+        self.crossover = crossover;
+        self
+    }
+
+    /// See `Population::set_crossover_rate`.
+    pub fn crossover_rate(mut self, rate: f64) -> PopulationBuilder<G> {
+        self.crossover_rate = rate;
+        self
+    }
+
+    pub fn selection(mut self, selection: SelectionType) -> PopulationBuilder<G> {
+        self.selection = selection;
+        self
+    }
+
+    /// See `Population::set_elitism_count`.
+    pub fn elitism(mut self, count: usize) -> PopulationBuilder<G> {
+        self.elitism_count = Some(count);
+        self
+    }
+
+    /// Seeds the population's random number generator, see `init_population_seeded`.
+    pub fn seed(mut self, seed: u64) -> PopulationBuilder<G> {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Creates the starting individuals with an initializer instead of at random. It
+    /// draws from the population's random number generator, so a seeded population
+    /// still starts the same.
+    pub fn initializer<I: Initializer<G> + 'static>(mut self, initializer: I) -> PopulationBuilder<G> {
+        self.initializer = Some(InitializerHook(Arc::new(initializer)));
+        self
+    }
+
+    /// Checks the settings and creates a population with random individuals, or those
+    /// of the initializer.
+    pub fn build(mut self) -> Result<Population<G>, ConfigError> {
+        if let Some(mutation_chance) = self.mutation_chance {
+            self.mutation_probability = check_mutation_chance(mutation_chance)?;
+        }
+        check_config(self.gene_length, self.size, self.parents, self.mutation_probability, self.crossover)?;
+        match self.selection {
+            SelectionType::Tournament { size } if size < 1 => return Err(ConfigError::Selection(self.selection)),
+            SelectionType::Rank { pressure } if !(1.0..=2.0).contains(&pressure) => {
+                return Err(ConfigError::Selection(self.selection))
+            }
+            _ => {}
+        }
+        if !(0.0..=1.0).contains(&self.crossover_rate) {
+            return Err(ConfigError::CrossoverRate(self.crossover_rate));
+        }
+        if let Some(elitism_count) = self.elitism_count {
+            if elitism_count > self.size {
+                return Err(ConfigError::ElitismExceedsPopulation {
+                    elitism_count,
+                    population_size: self.size,
+                });
+            }
+        }
+        let rng = match self.seed {
+            Some(seed) => GaRng::seed_from_u64(seed),
+            None => GaRng::from_rng(&mut rand::rng()),
+        };
+        let mut population = match &self.initializer {
+            Some(InitializerHook(initializer)) => {
+                let mut population = Population::new_blank(
+                    self.gene_length,
+                    self.size,
+                    self.parents,
+                    self.mutation_probability,
+                    self.multi_threaded,
+                    self.crossover,
+                    rng,
+                );
+                population.initialize_with(initializer.as_ref());
+                population
+            }
+            None => Population::new_unchecked(
+                self.gene_length,
+                self.size,
+                self.parents,
+                self.mutation_probability,
+                self.multi_threaded,
+                self.crossover,
+                rng,
+            ),
+        };
+        population.set_selection_type(self.selection);
+        population.set_crossover_rate(self.crossover_rate);
+        if let Some(elitism_count) = self.elitism_count {
+            population.set_elitism_count(elitism_count);
+        }
+        Ok(population)
+    }
+}
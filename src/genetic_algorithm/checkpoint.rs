@@ -0,0 +1,510 @@
+use std::{
+    fs,
+    io,
+    path::Path,
+    sync::{atomic::Ordering, Arc},
+};
+
+use super::{
+    check_config, events::{OperatorCounters, OperatorCounts}, fitness_cache::{FitnessCache, HashableGene, SavedCache},
+    mutation_schedule::MutationSchedule, Individual, Population, SelectionType,
+};
+
+const HEADER: &[u8] = b"genetic_algorithm checkpoint 2\n";
+
+/// Everything a checkpoint stores: the population in its serde representation and the
+/// state kept outside of it.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "P: serde::Serialize, G: serde::Serialize", deserialize = "P: serde::Deserialize<'de>, G: serde::Deserialize<'de>"))]
+struct Checkpoint<P, G> {
+    population: P,
+    operator_counts: OperatorCounts,
+    fitness_cache: Option<SavedCache<G>>,
+    duplicate_elimination: Option<(usize, u64)>,
+    local_search_evaluations: Option<u64>,
+    closures: Closures,
+}
+
+/// The closures set on a population when it was saved. They can't be saved, so a loaded
+/// population is only resumed once they are set again.
+#[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Closures {
+    repair: bool,
+    variation: bool,
+    constraints: usize,
+    selection: bool,
+    local_search: bool,
+    observers: usize,
+}
+
+impl Closures {
+    fn of<G: HashableGene>(population: &Population<G>) -> Closures {
+        Closures {
+            repair: population.repair.is_some(),
+            variation: population.has_custom_variation(),
+            constraints: population.constraints.len(),
+            selection: population.selection.is_some(),
+            local_search: population.local_search.is_some(),
+            observers: population.observers.len(),
+        }
+    }
+
+    /// Returns the names of the closures that are set on one population but not the other.
+    fn missing(&self, restored: &Closures) -> Vec<&'static str> {
+        let mut missing = vec![];
+        if self.repair != restored.repair {
+            missing.push("repair");
+        }
+        if self.variation != restored.variation {
+            missing.push("variation operators");
+        }
+        if self.constraints != restored.constraints {
+            missing.push("constraints");
+        }
+        if self.selection != restored.selection {
+            missing.push("selection pipeline");
+        }
+        if self.local_search != restored.local_search {
+            missing.push("local search");
+        }
+        if self.observers != restored.observers {
+            missing.push("observers");
+        }
+        missing
+    }
+}
+
+impl<G: HashableGene + serde::Serialize + serde::de::DeserializeOwned> Population<G> {
+    /// Writes the complete evolutionary state to a file: the serde representation of the
+    /// population, which includes the configuration, the individuals in their current
+    /// order, the built-in variation operators, differential evolution, evolution
+    /// strategies and the CMA-ES distribution, along with the operator counts, the fitness
+    /// cache, duplicate elimination and the state of the random number generator. A
+    /// population loaded from it continues exactly like this one would have.
+    ///
+    /// Closures can't be saved: repair, custom variation operators, constraints, selection
+    /// pipelines, local search and observers have to be set again with
+    /// `load_checkpoint_with`. Event subscribers aren't saved either, subscribe again
+    /// after loading.
+    ///
+    /// # Arguments
+    /// * `path` - The checkpoint file, created or truncated.
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let checkpoint = Checkpoint {
+            population: self,
+            operator_counts: self.get_operator_counts(),
+            fitness_cache: self.fitness_cache.as_ref().map(FitnessCache::save),
+            duplicate_elimination: self.get_duplicate_elimination().map(|attempts| (attempts, self.get_duplicates_replaced())),
+            local_search_evaluations: self.local_search.as_ref().map(|_| self.get_local_search_evaluations()),
+            closures: Closures::of(self),
+        };
+        let mut bytes = HEADER.to_vec();
+        bytes.extend(postcard::to_stdvec(&checkpoint).map_err(|error| invalid(&error.to_string()))?);
+        fs::write(path, bytes)
+    }
+
+    /// Reads a population written by `save_checkpoint` that had no closures set.
+    ///
+    /// # Arguments
+    /// * `path` - The checkpoint file.
+    ///
+    /// # Returns
+    /// The restored population, or an `InvalidData` error if the file isn't a valid
+    /// checkpoint or the population had closures set.
+    pub fn load_checkpoint<P: AsRef<Path>>(path: P) -> io::Result<Population<G>> {
+        Population::load_checkpoint_with(path, |_| {})
+    }
+
+    /// Reads a population written by `save_checkpoint` and sets the closures it had
+    /// again, e.g. with `set_repair` and `add_constraint`. A local search set again
+    /// continues with the saved evaluation count.
+    ///
+    /// # Arguments
+    /// * `path` - The checkpoint file.
+    /// * `restore` - Sets the closures on the loaded population.
+    ///
+    /// # Returns
+    /// The restored population, or an `InvalidData` error if the file isn't a valid
+    /// checkpoint or `restore` didn't set the same closures as the saved population had,
+    /// which would resume a different run.
+    pub fn load_checkpoint_with<P: AsRef<Path>, F: FnOnce(&mut Population<G>)>(path: P, restore: F) -> io::Result<Population<G>> {
+        let bytes = fs::read(path)?;
+        let body = bytes.strip_prefix(HEADER).ok_or_else(|| invalid("not a checkpoint file"))?;
+        let checkpoint: Checkpoint<Population<G>, G> = postcard::from_bytes(body).map_err(|error| invalid(&format!("invalid checkpoint: {}", error)))?;
+        let mut population = checkpoint.population;
+        check_population(&population)?;
+        let counts = checkpoint.operator_counts;
+        let counters = OperatorCounters::default();
+        counters.offspring.store(counts.offspring, Ordering::Relaxed);
+        counters.mutations.store(counts.mutations, Ordering::Relaxed);
+        counters.repairs.store(counts.repairs, Ordering::Relaxed);
+        counters.restarts.store(counts.restarts, Ordering::Relaxed);
+        counters.migrations.store(counts.migrations, Ordering::Relaxed);
+        population.operator_counters = Arc::new(counters);
+        population.fitness_cache = checkpoint.fitness_cache.map(FitnessCache::restore);
+        if let Some((attempts, replaced)) = checkpoint.duplicate_elimination {
+            population.restore_duplicate_elimination(attempts, replaced);
+        }
+        population.apply_storage();
+
+        restore(&mut population);
+        if let Some(evaluations) = checkpoint.local_search_evaluations {
+            population.restore_local_search_evaluations(evaluations);
+        }
+        let missing = checkpoint.closures.missing(&Closures::of(&population));
+        if !missing.is_empty() {
+            return Err(invalid(&format!("the checkpoint needs the same {} as the saved population", missing.join(", "))));
+        }
+        Ok(population)
+    }
+}
+
+/// Checks a loaded population like the setters check a new one, breeding would panic on
+/// invalid settings later.
+fn check_population<G: HashableGene>(population: &Population<G>) -> io::Result<()> {
+    let gene_length = match population.individuals.first() {
+        Some(individual) => individual.gene_length,
+        None => return Err(invalid("checkpoint has no individuals")),
+    };
+    let fits = |individual: &Individual<G>| {
+        let length = match population.variable_length {
+            Some(variable) => variable.allows(individual.gene_length),
+            None => individual.gene_length == gene_length,
+        };
+        length && individual.genes.len() == individual.gene_length
+    };
+    if !population.individuals.iter().all(fits) {
+        return Err(invalid("individuals of different gene lengths"));
+    }
+    check_config(gene_length, population.population_size, population.parent_count, population.mutation_probability, population.crossover)
+        .map_err(|error| invalid(&error.to_string()))?;
+    if let Some(rates) = &population.gene_mutation_rates {
+        if rates.len() != gene_length || !rates.iter().all(|rate| (0.0..=1.0).contains(rate)) {
+            return Err(invalid("invalid gene mutation rates"));
+        }
+    }
+    if population.mutation_schedule.is_some_and(|schedule| !mutation_schedule_is_valid(schedule)) {
+        return Err(invalid("invalid mutation schedule"));
+    }
+    if !(0.0..=1.0).contains(&population.crossover_rate) {
+        return Err(invalid("invalid crossover rate"));
+    }
+    match population.selection_type {
+        SelectionType::Tournament { size: 0 } => return Err(invalid("tournament size must be 1 or more")),
+        SelectionType::Rank { pressure } if !(1.0..=2.0).contains(&pressure) => {
+            return Err(invalid("rank selection pressure must be between 1 and 2"))
+        }
+        _ => {}
+    }
+    if population.elitism_count.is_some_and(|count| count > population.population_size) {
+        return Err(invalid("elitism count larger than the population"));
+    }
+    if population.steady_state.is_some_and(|count| count < 1 || count > population.population_size) {
+        return Err(invalid("invalid steady-state replacement count"));
+    }
+    // only the children of a pending step may exceed the population size
+    let pending = population.crowding_pending.or(population.de_pending).or(population.es_pending).unwrap_or(0);
+    if population.individuals.len() > population.population_size + pending {
+        return Err(invalid("more individuals than the population size"));
+    }
+    if population.fresh_count > population.individuals.len() {
+        return Err(invalid("more fresh individuals than individuals"));
+    }
+    if let Some(adaptation) = population.self_adaptation {
+        let (min, max) = adaptation.get_bounds();
+        let learning_rate = adaptation.get_learning_rate();
+        if learning_rate.is_nan() || learning_rate < 0.0 || !(min > 0.0 && min <= max && max <= 1.0) {
+            return Err(invalid("invalid self-adaptation"));
+        }
+    }
+    if let Some(variable) = population.variable_length {
+        let (min, max) = variable.get_bounds();
+        let block = variable.get_block_size();
+        let valid = min >= 1
+            && min <= max
+            && block >= 1
+            && min % block == 0
+            && max % block == 0
+            && (0.0..=1.0).contains(&variable.get_insertion())
+            && (0.0..=1.0).contains(&variable.get_deletion());
+        if !valid {
+            return Err(invalid("invalid variable-length settings"));
+        }
+    }
+    if let Some(scheduler) = population.penalty_scheduler {
+        scheduler.check().map_err(invalid)?;
+    }
+    if population.random_immigrants.is_some_and(|immigrants| !(0.0..=1.0).contains(&immigrants.get_fraction()) || immigrants.get_interval() < 1) {
+        return Err(invalid("invalid random immigrants"));
+    }
+    if population.alps.is_some_and(|alps| alps.get_layers() < 1 || alps.get_age_gap() < 1) {
+        return Err(invalid("invalid alps settings"));
+    }
+    if population.mating_restriction.is_some_and(|restriction| restriction.get_attempts() < 1) {
+        return Err(invalid("invalid mating restriction"));
+    }
+    if population.speciation.is_some_and(|speciation| speciation.get_threshold() < 1) {
+        return Err(invalid("invalid speciation"));
+    }
+    if population.fitness_sharing.is_some_and(|sharing| sharing.get_radius() < 1 || sharing.get_alpha().is_nan() || sharing.get_alpha() <= 0.0) {
+        return Err(invalid("invalid fitness sharing"));
+    }
+    let bounds = [
+        population.get_differential_evolution().map(|de| de.get_bounds().len()),
+        population.get_evolution_strategy().map(|strategy| strategy.get_bounds().len()),
+        population.get_cma_es().map(|cma| cma.get_bounds().len()),
+    ];
+    if bounds.iter().flatten().any(|&length| length != gene_length) {
+        return Err(invalid("bounds dont match the gene length"));
+    }
+    Ok(())
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Returns true if the schedule passes the checks of `Population::set_mutation_schedule`.
+fn mutation_schedule_is_valid(schedule: MutationSchedule) -> bool {
+    let valid = |probability: f64| (0.0..=1.0).contains(&probability);
+    match schedule {
+        MutationSchedule::LinearDecay { start, end, .. } => valid(start) && valid(end),
+        MutationSchedule::ExponentialDecay { start, factor, min } => valid(start) && valid(factor) && valid(min),
+        MutationSchedule::Adaptive { min, max, step, min_diversity } => {
+            valid(min) && valid(max) && valid(step) && valid(min_diversity) && min <= max
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::super::{
+        builder::PopulationBuilder, cma_es::CmaEs, constraints::PenaltySchedule, differential_evolution::{DeStrategy, DifferentialEvolution},
+        evolution_strategy::{EsSelection, EvolutionStrategy}, init_population_seeded, CrossoverType,
+    };
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.genes.iter().filter(|&&gene| gene).count() as u64
+    }
+
+    fn sphere(individual: &Individual<f64>) -> u64 {
+        let sum: f64 = individual.genes.iter().map(|gene| gene * gene).sum();
+        (1e6 / (1.0 + sum)) as u64
+    }
+
+    /// Saves a population to a fresh checkpoint file and loads it with `restore`.
+    fn round_trip<G, F>(name: &str, population: &Population<G>, restore: F) -> io::Result<Population<G>>
+    where
+        G: HashableGene + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut Population<G>),
+    {
+        let path = env::temp_dir().join(format!("ga_checkpoint_{}_{}.bin", name, std::process::id()));
+        population.save_checkpoint(&path).unwrap();
+        let result = Population::load_checkpoint_with(&path, restore);
+        fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn resumed_run_matches_uninterrupted_run() {
+        let mut population = init_population_seeded(48, 24, 6, 4, false, CrossoverType::Bit, 11);
+        population.set_elitism_count(2);
+        for _ in 0..5 {
+            population.evaluate(ones);
+            population.next_generation();
+        }
+        let mut resumed = round_trip("resume", &population, |_| {}).unwrap();
+        assert_eq!(resumed.get_generation(), population.get_generation());
+        assert_eq!(resumed.get_rng_state(), population.get_rng_state());
+        for _ in 0..10 {
+            population.evaluate(ones);
+            population.next_generation();
+            resumed.evaluate(ones);
+            resumed.next_generation();
+        }
+        let genes = |population: &Population| population.individuals.iter().map(|individual| individual.get_genes()).collect::<Vec<_>>();
+        assert_eq!(genes(&resumed), genes(&population));
+        assert_eq!(resumed.get_best_ever().map(Individual::get_genes), population.get_best_ever().map(Individual::get_genes));
+        assert_eq!(resumed.get_operator_counts(), population.get_operator_counts());
+    }
+
+    #[test]
+    fn penalty_schedule_survives_a_checkpoint() {
+        let mut population = init_population_seeded(16, 10, 2, 4, false, CrossoverType::Bit, 12);
+        population.set_penalty_schedule(PenaltySchedule::Adaptive { initial: 2.0, target_feasible: 0.5, increase: 1.5 });
+        for _ in 0..3 {
+            population.evaluate(ones);
+            population.next_generation();
+        }
+        let resumed = round_trip("penalty", &population, |_| {}).unwrap();
+        assert_eq!(resumed.get_penalty_scheduler(), population.get_penalty_scheduler());
+        assert_eq!(resumed.get_constraint_handling(), population.get_constraint_handling());
+    }
+
+    /// Corrupts a population the way an edited file could and loads it from a checkpoint.
+    fn load_modified(name: &str, modify: impl FnOnce(&mut Population)) -> io::Result<Population> {
+        let mut population = init_population_seeded(16, 10, 2, 4, false, CrossoverType::Bit, 13);
+        population.evaluate(ones);
+        population.next_generation();
+        modify(&mut population);
+        round_trip(name, &population, |_| {})
+    }
+
+    #[test]
+    fn load_rejects_invalid_settings() {
+        let rejects = |name: &str, modify: fn(&mut Population)| {
+            let error = load_modified(name, modify).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", name);
+        };
+        rejects("no_parents", |population| population.parent_count = 0);
+        rejects("too_many_parents", |population| population.parent_count = 11);
+        rejects("small_population", |population| population.population_size = 4);
+        rejects("mutation", |population| population.mutation_probability = 1.5);
+        rejects("crossover", |population| population.crossover = CrossoverType::KPoint(0));
+        rejects("tournament", |population| population.selection_type = SelectionType::Tournament { size: 0 });
+        rejects("rank", |population| population.selection_type = SelectionType::Rank { pressure: 3.0 });
+        rejects("elitism", |population| population.elitism_count = Some(11));
+        rejects("steady_state", |population| population.steady_state = Some(0));
+        rejects("fresh_count", |population| population.fresh_count = 11);
+        rejects("gene_length", |population| population.individuals[0].gene_length = 3);
+        assert!(load_modified("valid", |population| population.elitism_count = Some(2)).is_ok());
+    }
+
+    #[test]
+    fn fresh_count_survives_a_checkpoint() {
+        let mut population = init_population_seeded(16, 10, 2, 4, false, CrossoverType::Bit, 14);
+        population.set_steady_state(3);
+        population.evaluate(ones);
+        population.next_generation();
+        let resumed = round_trip("fresh", &population, |_| {}).unwrap();
+        assert_eq!(population.fresh_count, 3);
+        assert_eq!(resumed.fresh_count, 3);
+    }
+
+    #[test]
+    fn load_rejects_other_files() {
+        let path = env::temp_dir().join(format!("ga_not_a_checkpoint_{}.txt", std::process::id()));
+        fs::write(&path, "not a checkpoint\n").unwrap();
+        let error = Population::<bool>::load_checkpoint(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    fn real_population(seed: u64) -> Population<f64> {
+        PopulationBuilder::<f64>::new().gene_length(3).size(8).parents(2).seed(seed).build().unwrap()
+    }
+
+    /// Checkpoints a real-valued population part way through a step and checks that the
+    /// resumed run stays identical.
+    fn assert_real_resume(name: &str, mut population: Population<f64>, step: fn(&mut Population<f64>)) {
+        for _ in 0..4 {
+            step(&mut population);
+        }
+        population.next_generation();
+        let mut resumed = round_trip(name, &population, |_| {}).unwrap();
+        for _ in 0..6 {
+            step(&mut population);
+            step(&mut resumed);
+        }
+        let genes = |population: &Population<f64>| population.individuals.iter().map(|individual| individual.get_genes()).collect::<Vec<_>>();
+        assert_eq!(genes(&resumed), genes(&population), "{}", name);
+    }
+
+    #[test]
+    fn cma_es_distribution_survives_a_checkpoint() {
+        let mut population = real_population(15);
+        population.set_cma_es(CmaEs::new(vec![(-5.0, 5.0); 3]));
+        for _ in 0..3 {
+            population.evaluate(sphere);
+            population.next_generation();
+        }
+        let resumed = round_trip("cma_state", &population, |_| {}).unwrap();
+        assert_eq!(resumed.get_cma_es(), population.get_cma_es());
+        population.evaluate(sphere);
+        assert_real_resume("cma", population, |population| {
+            population.next_generation();
+            population.evaluate(sphere);
+        });
+    }
+
+    #[test]
+    fn pending_differential_evolution_survives_a_checkpoint() {
+        let mut population = real_population(16);
+        population.set_differential_evolution(DifferentialEvolution::new(vec![(-5.0, 5.0); 3], DeStrategy::Rand1Bin, 0.6, 0.9));
+        population.evaluate(sphere);
+        let resumed = round_trip("de_state", &population, |_| {}).unwrap();
+        assert_eq!(resumed.get_differential_evolution(), population.get_differential_evolution());
+        assert_real_resume("de", population, |population| {
+            population.evaluate_last(8, sphere);
+            population.next_generation();
+        });
+    }
+
+    #[test]
+    fn pending_evolution_strategy_survives_a_checkpoint() {
+        let mut population = real_population(17);
+        population.set_evolution_strategy(EvolutionStrategy::new(vec![(-5.0, 5.0); 3], 16, EsSelection::Plus));
+        population.evaluate(sphere);
+        let resumed = round_trip("es_state", &population, |_| {}).unwrap();
+        assert_eq!(resumed.get_evolution_strategy(), population.get_evolution_strategy());
+        assert_real_resume("es", population, |population| {
+            population.evaluate_last(16, sphere);
+            population.next_generation();
+        });
+    }
+
+    #[test]
+    fn fitness_cache_and_duplicate_elimination_survive_a_checkpoint() {
+        let mut population = init_population_seeded(16, 10, 2, 4, false, CrossoverType::Bit, 18);
+        population.set_fitness_cache(100);
+        population.set_duplicate_elimination(3);
+        for _ in 0..3 {
+            population.evaluate(ones);
+            population.next_generation();
+        }
+        let mut resumed = round_trip("cache", &population, |_| {}).unwrap();
+        assert_eq!(resumed.get_fitness_cache_counts(), population.get_fitness_cache_counts());
+        assert_eq!(resumed.get_duplicate_elimination(), Some(3));
+        assert_eq!(resumed.get_duplicates_replaced(), population.get_duplicates_replaced());
+        // the cached genomes are restored, so both runs hit the cache equally often
+        for _ in 0..3 {
+            population.evaluate(ones);
+            population.next_generation();
+            resumed.evaluate(ones);
+            resumed.next_generation();
+        }
+        assert_eq!(resumed.get_fitness_cache_counts(), population.get_fitness_cache_counts());
+    }
+
+    fn clear_first(individual: &mut Individual) {
+        individual.set_gene(0, false);
+    }
+
+    #[test]
+    fn load_fails_until_closures_are_set_again() {
+        let mut population = init_population_seeded(16, 10, 2, 4, false, CrossoverType::Bit, 19);
+        population.set_repair(clear_first);
+        population.evaluate(ones);
+        population.next_generation();
+        let error = round_trip("closures", &population, |_| {}).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("repair"), "{}", error);
+        let resumed = round_trip("closures_set", &population, |population| population.set_repair(clear_first)).unwrap();
+        assert_eq!(resumed.get_generation(), population.get_generation());
+    }
+
+    #[test]
+    fn packed_storage_survives_a_checkpoint() {
+        let mut population = init_population_seeded(70, 10, 2, 4, false, CrossoverType::Bit, 20);
+        population.set_packed_storage(true);
+        population.evaluate(ones);
+        population.next_generation();
+        let resumed = round_trip("packed", &population, |_| {}).unwrap();
+        assert!(resumed.is_packed_storage());
+        assert!(resumed.individuals.iter().all(|individual| individual.genes.is_packed()));
+        let genes = |population: &Population| population.individuals.iter().map(|individual| individual.get_genes()).collect::<Vec<_>>();
+        assert_eq!(genes(&resumed), genes(&population));
+    }
+}
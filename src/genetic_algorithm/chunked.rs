@@ -0,0 +1,192 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use super::{rng::GaRng, Individual, Population};
+
+/// A population stored in a file instead of in memory, for populations too large for
+/// RAM. Evaluation and breeding stream over chunks of individuals, so only one chunk and
+/// the parents are in memory at a time.
+///
+/// Every individual is a fixed-size record: its fitness as a little endian `u64`
+/// followed by its genes packed into bytes, first gene in the most significant bit.
+#[derive(Debug)]
+pub struct ChunkedPopulation {
+    file: File,
+    gene_length: usize,
+    population_size: usize,
+}
+
+impl ChunkedPopulation {
+    /// Creates (or truncates) a population file with random individuals.
+    ///
+    /// # Arguments
+    /// * `path` - The file backing the population.
+    /// * `gene_length` - The length of the gene sequence for each individual.
+    /// * `population_size` - The number of individuals in the population.
+    /// * `chunk_size` - How many individuals are generated in memory at a time.
+    /// * `rng` - The random number generator to draw the genes from.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        gene_length: usize,
+        population_size: usize,
+        chunk_size: usize,
+        rng: &mut GaRng,
+    ) -> io::Result<ChunkedPopulation> {
+        if gene_length < 1 {
+            panic!("the gene length cannot be less than 1");
+        }
+        if population_size < 1 {
+            panic!("population size must be 1 or more");
+        }
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+        let mut population = ChunkedPopulation {
+            file,
+            gene_length,
+            population_size,
+        };
+        let chunk_size = chunk_size.max(1);
+        for start in (0..population_size).step_by(chunk_size) {
+            let chunk: Vec<Individual> = (start..(start + chunk_size).min(population_size))
+                .map(|_| {
+                    let mut individual = Individual::from_genes(vec![false; gene_length]);
+                    individual.randomize(rng);
+                    individual
+                })
+                .collect();
+            population.write_chunk(start, &chunk)?;
+        }
+        Ok(population)
+    }
+
+    /// Opens an existing population file.
+    ///
+    /// # Arguments
+    /// * `path` - The file backing the population.
+    /// * `gene_length` - The gene length the file was created with.
+    pub fn open<P: AsRef<Path>>(path: P, gene_length: usize) -> io::Result<ChunkedPopulation> {
+        if gene_length < 1 {
+            panic!("the gene length cannot be less than 1");
+        }
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let record_size = record_size(gene_length) as u64;
+        let length = file.metadata()?.len();
+        if length == 0 || length % record_size != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("file size {} is not a multiple of the record size {}", length, record_size),
+            ));
+        }
+        Ok(ChunkedPopulation {
+            file,
+            gene_length,
+            population_size: (length / record_size) as usize,
+        })
+    }
+
+    pub fn get_gene_length(&self) -> usize {
+        self.gene_length
+    }
+
+    pub fn get_population_size(&self) -> usize {
+        self.population_size
+    }
+
+    /// Reads `count` individuals starting at index `start`, fewer at the end of the population.
+    pub fn read_chunk(&mut self, start: usize, count: usize) -> io::Result<Vec<Individual>> {
+        let count = count.min(self.population_size.saturating_sub(start));
+        let record_size = record_size(self.gene_length);
+        let mut bytes = vec![0u8; count * record_size];
+        self.file.seek(SeekFrom::Start((start * record_size) as u64))?;
+        self.file.read_exact(&mut bytes)?;
+        Ok(bytes.chunks(record_size).map(|record| self.decode(record)).collect())
+    }
+
+    /// Writes individuals over the ones starting at index `start`.
+    pub fn write_chunk(&mut self, start: usize, individuals: &[Individual]) -> io::Result<()> {
+        if start + individuals.len() > self.population_size {
+            panic!("chunk {}..{} is outside the population size {}", start, start + individuals.len(), self.population_size);
+        }
+        let mut bytes = Vec::with_capacity(individuals.len() * record_size(self.gene_length));
+        for individual in individuals {
+            if individual.gene_length != self.gene_length {
+                panic!("individual has gene length {} instead of {}", individual.gene_length, self.gene_length);
+            }
+            bytes.extend_from_slice(&individual.fitness.to_le_bytes());
+            for byte in individual.genes.as_slice().chunks(8) {
+                bytes.push(byte.iter().enumerate().fold(0u8, |acc, (bit, &gene)| acc | ((gene as u8) << (7 - bit))));
+            }
+        }
+        self.file.seek(SeekFrom::Start((start * record_size(self.gene_length)) as u64))?;
+        self.file.write_all(&bytes)
+    }
+
+    /// Evaluates every individual, one chunk at a time.
+    ///
+    /// # Arguments
+    /// * `chunk_size` - How many individuals are in memory at a time.
+    /// * `fitness` - The fitness function.
+    pub fn evaluate<F: Fn(&Individual) -> u64>(&mut self, chunk_size: usize, fitness: F) -> io::Result<()> {
+        let chunk_size = chunk_size.max(1);
+        for start in (0..self.population_size).step_by(chunk_size) {
+            let mut chunk = self.read_chunk(start, chunk_size)?;
+            for individual in chunk.iter_mut() {
+                individual.set_fitness(fitness(individual));
+            }
+            self.write_chunk(start, &chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the `count` fittest individuals, best first, keeping only those in memory.
+    pub fn fittest(&mut self, count: usize, chunk_size: usize) -> io::Result<Vec<Individual>> {
+        let chunk_size = chunk_size.max(1);
+        let mut best: Vec<Individual> = vec![];
+        for start in (0..self.population_size).step_by(chunk_size) {
+            best.extend(self.read_chunk(start, chunk_size)?);
+            best.sort_by(|a, b| b.cmp(a));
+            best.truncate(count);
+        }
+        Ok(best)
+    }
+
+    /// Replaces the population with the next generation. The fittest individuals become
+    /// the parents and survive at the start of the file, the rest is overwritten with
+    /// children bred with the operators of `breeder`: its parent count, crossover,
+    /// mutation chance, repair and random number generator. The individuals of `breeder`
+    /// are not used.
+    ///
+    /// # Arguments
+    /// * `breeder` - The population whose settings and operators breed the children.
+    /// * `chunk_size` - How many children are in memory at a time.
+    pub fn next_generation(&mut self, breeder: &mut Population, chunk_size: usize) -> io::Result<()> {
+        let mut parents = self.fittest(breeder.parent_count, chunk_size)?;
+        self.write_chunk(0, &parents)?;
+        let chunk_size = chunk_size.max(1);
+        let mut spares = vec![];
+        for start in (parents.len()..self.population_size).step_by(chunk_size) {
+            let count = chunk_size.min(self.population_size - start);
+            let (returned, children) = breeder.breed_children(parents, count, spares);
+            self.write_chunk(start, &children)?;
+            parents = returned;
+            // the written children are recycled into the next chunk
+            spares = children;
+        }
+        self.file.flush()
+    }
+
+    fn decode(&self, record: &[u8]) -> Individual {
+        let mut fitness = [0u8; 8];
+        fitness.copy_from_slice(&record[..8]);
+        let genes = (0..self.gene_length).map(|i| record[8 + i / 8] & (0x80 >> (i % 8)) != 0).collect();
+        let mut individual = Individual::from_genes(genes);
+        individual.set_fitness(u64::from_le_bytes(fitness));
+        individual
+    }
+}
+
+fn record_size(gene_length: usize) -> usize {
+    8 + gene_length.div_ceil(8)
+}
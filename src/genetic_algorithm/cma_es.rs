@@ -0,0 +1,413 @@
+use std::fmt;
+
+use super::{
+    gene::Gene,
+    real::{standard_normal, RealCrossover, RealMutation, RealOperators},
+    rng::GaRng,
+    Population,
+};
+
+/// Covariance matrix adaptation evolution strategy (Hansen's CMA-ES) for real-valued
+/// genes, the method of choice for non-separable, badly conditioned continuous problems.
+/// Every generation the whole population is sampled from a multivariate normal
+/// distribution; its mean moves to the weighted mean of the fitter half, its covariance
+/// learns the shape of the landscape from successful steps and its overall step size is
+/// controlled by the length of the evolution path.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CmaEs {
+    bounds: Vec<(f64, f64)>,
+    mean: Vec<f64>,
+    sigma: f64,
+    covariance: Vec<Vec<f64>>,
+    // the eigenvectors (columns) and the square roots of the eigenvalues of the covariance
+    basis: Vec<Vec<f64>>,
+    scales: Vec<f64>,
+    sigma_path: Vec<f64>,
+    covariance_path: Vec<f64>,
+    updates: u64,
+}
+
+impl CmaEs {
+    /// Creates a new distribution centered in the bounds with a step size of 0.3 times
+    /// the width of the widest gene range.
+    ///
+    /// # Arguments
+    /// * `bounds` - The inclusive `(min, max)` range of every gene, one per gene.
+    pub fn new(bounds: Vec<(f64, f64)>) -> CmaEs {
+        if bounds.is_empty() {
+            panic!("the gene length cannot be less than 1");
+        }
+        if let Some((min, max)) = bounds.iter().find(|(min, max)| min >= max || !min.is_finite() || !max.is_finite()) {
+            panic!("invalid gene bounds {}..={}", min, max);
+        }
+        let n = bounds.len();
+        let mean = bounds.iter().map(|(min, max)| (min + max) / 2.0).collect();
+        let sigma = 0.3 * bounds.iter().map(|(min, max)| max - min).fold(0.0, f64::max);
+        CmaEs {
+            bounds,
+            mean,
+            sigma,
+            covariance: identity(n),
+            basis: identity(n),
+            scales: vec![1.0; n],
+            sigma_path: vec![0.0; n],
+            covariance_path: vec![0.0; n],
+            updates: 0,
+        }
+    }
+
+    /// Starts the distribution at the given mean instead of the center of the bounds.
+    pub fn initial_mean(mut self, mean: Vec<f64>) -> CmaEs {
+        if mean.len() != self.bounds.len() {
+            panic!("{} mean values for a gene length of {}", mean.len(), self.bounds.len());
+        }
+        self.mean = mean.iter().zip(&self.bounds).map(|(value, (min, max))| value.clamp(*min, *max)).collect();
+        self
+    }
+
+    /// Starts with the given overall step size, in gene units.
+    pub fn initial_sigma(mut self, sigma: f64) -> CmaEs {
+        if !(sigma > 0.0 && sigma.is_finite()) {
+            panic!("cma-es sigma must be larger than 0");
+        }
+        self.sigma = sigma;
+        self
+    }
+
+    pub fn get_bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    /// Returns the current mean of the distribution, the best guess of the optimum.
+    pub fn get_mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    /// Returns the current overall step size.
+    pub fn get_sigma(&self) -> f64 {
+        self.sigma
+    }
+
+    pub fn get_covariance(&self) -> &[Vec<f64>] {
+        &self.covariance
+    }
+
+    /// Returns the ratio of the largest to the smallest axis of the distribution, large
+    /// for badly conditioned problems.
+    pub fn get_axis_ratio(&self) -> f64 {
+        let largest = self.scales.iter().copied().fold(0.0, f64::max);
+        let smallest = self.scales.iter().copied().fold(f64::INFINITY, f64::min);
+        largest / smallest
+    }
+
+    /// Returns the number of times the distribution was updated.
+    pub fn get_updates(&self) -> u64 {
+        self.updates
+    }
+
+    /// Draws a point from the distribution, clamped to the bounds.
+    fn sample(&self, rng: &mut GaRng) -> Vec<f64> {
+        let n = self.mean.len();
+        let scaled: Vec<f64> = (0..n).map(|i| self.scales[i] * standard_normal(rng)).collect();
+        (0..n)
+            .map(|i| {
+                let step: f64 = (0..n).map(|j| self.basis[i][j] * scaled[j]).sum();
+                let (min, max) = self.bounds[i];
+                (self.mean[i] + self.sigma * step).clamp(min, max)
+            })
+            .collect()
+    }
+
+    /// Moves the distribution towards the given points, fittest first.
+    fn update(&mut self, ranked: &[Vec<f64>]) {
+        let n = self.mean.len() as f64;
+        let mu = (ranked.len() / 2).max(1);
+        let raw: Vec<f64> = (0..mu).map(|i| (mu as f64 + 0.5).ln() - ((i + 1) as f64).ln()).collect();
+        let total: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|weight| weight / total).collect();
+        let mueff = 1.0 / weights.iter().map(|weight| weight * weight).sum::<f64>();
+        let cc = (4.0 + mueff / n) / (n + 4.0 + 2.0 * mueff / n);
+        let cs = (mueff + 2.0) / (n + mueff + 5.0);
+        let c1 = 2.0 / ((n + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((n + 2.0).powi(2) + mueff));
+        let damps = 1.0 + 2.0 * (((mueff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let chi_n = n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n));
+
+        let old_mean = self.mean.clone();
+        let steps: Vec<Vec<f64>> = ranked[..mu]
+            .iter()
+            .map(|point| point.iter().zip(&old_mean).map(|(x, m)| (x - m) / self.sigma).collect())
+            .collect();
+        let dimensions = old_mean.len();
+        let mean_step: Vec<f64> = (0..dimensions).map(|i| (0..mu).map(|k| weights[k] * steps[k][i]).sum()).collect();
+        self.mean = (0..dimensions).map(|i| old_mean[i] + self.sigma * mean_step[i]).collect();
+
+        // the step whitened by the current covariance: B * D^-1 * B^T * step
+        let rotated: Vec<f64> = (0..dimensions)
+            .map(|j| (0..dimensions).map(|i| self.basis[i][j] * mean_step[i]).sum::<f64>() / self.scales[j])
+            .collect();
+        let whitened: Vec<f64> = (0..dimensions).map(|i| (0..dimensions).map(|j| self.basis[i][j] * rotated[j]).sum()).collect();
+        let sigma_rate = (cs * (2.0 - cs) * mueff).sqrt();
+        for (path, step) in self.sigma_path.iter_mut().zip(&whitened) {
+            *path = (1.0 - cs) * *path + sigma_rate * step;
+        }
+        self.updates += 1;
+        let path_length = self.sigma_path.iter().map(|value| value * value).sum::<f64>().sqrt();
+        let stalled = path_length / (1.0 - (1.0 - cs).powf(2.0 * self.updates as f64)).sqrt() / chi_n >= 1.4 + 2.0 / (n + 1.0);
+        let h_sigma = if stalled { 0.0 } else { 1.0 };
+        let covariance_rate = (cc * (2.0 - cc) * mueff).sqrt();
+        for (path, step) in self.covariance_path.iter_mut().zip(&mean_step) {
+            *path = (1.0 - cc) * *path + h_sigma * covariance_rate * step;
+        }
+
+        let correction = (1.0 - h_sigma) * cc * (2.0 - cc);
+        for i in 0..dimensions {
+            for j in 0..=i {
+                let rank_one = self.covariance_path[i] * self.covariance_path[j] + correction * self.covariance[i][j];
+                let rank_mu: f64 = (0..mu).map(|k| weights[k] * steps[k][i] * steps[k][j]).sum();
+                let value = (1.0 - c1 - cmu) * self.covariance[i][j] + c1 * rank_one + cmu * rank_mu;
+                self.covariance[i][j] = value;
+                self.covariance[j][i] = value;
+            }
+        }
+        self.sigma *= ((cs / damps) * (path_length / chi_n - 1.0)).exp();
+        let widest = self.bounds.iter().map(|(min, max)| max - min).fold(0.0, f64::max);
+        self.sigma = self.sigma.clamp(f64::MIN_POSITIVE, widest);
+
+        let (basis, eigenvalues) = symmetric_eigen(&self.covariance);
+        self.basis = basis;
+        self.scales = eigenvalues.iter().map(|value| value.max(1e-20).sqrt()).collect();
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect()
+}
+
+/// Decomposes a symmetric matrix with cyclic Jacobi rotations.
+///
+/// # Returns
+/// The eigenvectors as columns and the eigenvalues.
+fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut vectors = identity(n);
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n).flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j))).map(|(i, j)| a[i][j] * a[i][j]).sum();
+        if off_diagonal < 1e-30 {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (head, tail) = a.split_at_mut(q);
+                for (apk, aqk) in head[p].iter_mut().zip(tail[0].iter_mut()) {
+                    let (x, y) = (*apk, *aqk);
+                    *apk = c * x - s * y;
+                    *aqk = s * x + c * y;
+                }
+                for row in vectors.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    (vectors, (0..n).map(|i| a[i][i]).collect())
+}
+
+/// CMA-ES stored on a population, with the conversions between its points and genes.
+#[derive(Clone)]
+pub(crate) struct CmaEsHook<G: Gene> {
+    cma: CmaEs,
+    to_genes: fn(Vec<f64>) -> Vec<G>,
+    from_genes: fn(&[G]) -> Vec<f64>,
+}
+
+impl<G: Gene> fmt::Debug for CmaEsHook<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CmaEsHook({:?})", self.cma)
+    }
+}
+
+/// Saves the distribution with the population.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_hook<G: Gene, S: serde::Serializer>(hook: &Option<CmaEsHook<G>>, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    hook.as_ref().map(|hook| &hook.cma).serialize(serializer)
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_hook<'de, G: Gene, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<CmaEsHook<G>>, D::Error> {
+    use serde::{de::Error, Deserialize};
+    match Option::<CmaEs>::deserialize(deserializer)? {
+        Some(cma) => {
+            let hook: Box<dyn std::any::Any> = Box::new(CmaEsHook::<f64> {
+                cma,
+                to_genes: |point| point,
+                from_genes: |genes| genes.to_vec(),
+            });
+            hook.downcast::<CmaEsHook<G>>().map(|hook| Some(*hook)).map_err(|_| D::Error::custom("cma-es needs f64 genes"))
+        }
+        None => Ok(None),
+    }
+}
+
+impl Population<f64> {
+    /// Switches `next_generation` to CMA-ES and samples every individual from the initial
+    /// distribution. The population size is λ, the number of samples per generation, of
+    /// which the fitter half updates the distribution; 4 + 3·ln(n) is the usual choice for
+    /// n genes. The whole population is replaced every generation, the selection type,
+    /// parent count, crossover, mutation and elitism are not used. Also sets real-valued
+    /// operators with the same bounds, so restarts and immigrants respect them; restarted
+    /// individuals are replaced by samples of the current distribution in the next
+    /// generation. Cant be combined with another replacement mode. The distribution is
+    /// saved with the population, e.g. in a checkpoint.
+    ///
+    /// # Arguments
+    /// * `cma` - The bounds and the initial distribution.
+    pub fn set_cma_es(&mut self, cma: CmaEs) {
+        self.check_replacement_mode("cma-es");
+        if self.population_size < 4 {
+            panic!("cma-es needs a population of at least 4");
+        }
+        let operators = RealOperators::new(cma.bounds.clone(), RealMutation::Gaussian { sigma: 0.1 }, RealCrossover::Arithmetic);
+        self.set_real_operators(operators);
+        self.cma_es = Some(CmaEsHook {
+            cma,
+            to_genes: |point| point,
+            from_genes: |genes| genes.to_vec(),
+        });
+        self.cma_sample_all();
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Goes back to the genetic algorithm, keeping the real-valued operators.
+    pub fn clear_cma_es(&mut self) {
+        self.cma_es = None;
+    }
+
+    /// Returns the current CMA-ES distribution.
+    pub fn get_cma_es(&self) -> Option<&CmaEs> {
+        self.cma_es.as_ref().map(|hook| &hook.cma)
+    }
+
+    /// Updates the distribution from the sorted, evaluated population and replaces every
+    /// individual with a new sample.
+    pub(crate) fn cma_step(&mut self) {
+        let ranked: Vec<Vec<f64>> = match &self.cma_es {
+            Some(hook) => self.individuals.iter().map(|individual| (hook.from_genes)(&individual.genes.as_slice())).collect(),
+            None => return,
+        };
+        if let Some(hook) = self.cma_es.as_mut() {
+            hook.cma.update(&ranked);
+        }
+        self.cma_sample_all();
+    }
+
+    /// Replaces every individual with a repaired sample of the distribution.
+    fn cma_sample_all(&mut self) {
+        let hook = match self.cma_es.take() {
+            Some(hook) => hook,
+            None => return,
+        };
+        let mut individuals = std::mem::take(&mut self.individuals);
+        for individual in individuals.iter_mut() {
+            individual.genes.assign((hook.to_genes)(hook.cma.sample(&mut self.rng)));
+            individual.fitness = 0;
+            individual.violation = 0.0;
+            individual.objectives.clear();
+            individual.age += 1;
+            self.repair_individual(individual);
+        }
+        self.individuals = individuals;
+        self.cma_es = Some(hook);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{builder::PopulationBuilder, Individual};
+    use super::*;
+
+    const OPTIMUM: [f64; 3] = [1.0, -2.0, 0.5];
+
+    fn shifted_sphere(individual: &Individual<f64>) -> u64 {
+        let distance: f64 = individual.get_genes().iter().zip(OPTIMUM).map(|(gene, optimum)| (gene - optimum).powi(2)).sum();
+        ((100.0 - distance) * 1e6) as u64
+    }
+
+    /// A quadratic 100 times steeper along the second gene than along the first.
+    fn ellipsoid(individual: &Individual<f64>) -> u64 {
+        let genes = individual.get_genes();
+        ((100.0 - genes[0] * genes[0] - 100.0 * genes[1] * genes[1]).max(0.0) * 1e6) as u64
+    }
+
+    fn run(cma: CmaEs, fitness: fn(&Individual<f64>) -> u64, generations: usize) -> Population<f64> {
+        let gene_length = cma.get_bounds().len();
+        let mut population = PopulationBuilder::<f64>::new().gene_length(gene_length).size(12).parents(2).seed(7).build().unwrap();
+        population.set_cma_es(cma);
+        for _ in 0..generations {
+            population.evaluate(fitness);
+            population.next_generation();
+            assert_eq!(population.individuals.len(), 12);
+        }
+        population
+    }
+
+    #[test]
+    fn eigendecomposition_reconstructs_the_matrix() {
+        let matrix = vec![vec![4.0, 1.0, 0.5], vec![1.0, 3.0, -0.2], vec![0.5, -0.2, 2.0]];
+        let (vectors, values) = symmetric_eigen(&matrix);
+        for i in 0..3 {
+            for j in 0..3 {
+                // V * diag(values) * V^T gives the matrix back
+                let rebuilt: f64 = (0..3).map(|k| vectors[i][k] * values[k] * vectors[j][k]).sum();
+                assert!((rebuilt - matrix[i][j]).abs() < 1e-9, "entry {} {} is {}", i, j, rebuilt);
+                // the eigenvectors are orthonormal
+                let dot: f64 = (0..3).map(|k| vectors[k][i] * vectors[k][j]).sum();
+                assert!((dot - if i == j { 1.0 } else { 0.0 }).abs() < 1e-9);
+            }
+        }
+        let (_, mut values) = symmetric_eigen(&[vec![2.0, 1.0], vec![1.0, 2.0]]);
+        values.sort_by(f64::total_cmp);
+        assert!((values[0] - 1.0).abs() < 1e-12 && (values[1] - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn converges_on_a_shifted_sphere() {
+        let population = run(CmaEs::new(vec![(-5.0, 5.0); 3]), shifted_sphere, 150);
+        let cma = population.get_cma_es().unwrap();
+        assert_eq!(cma.get_updates(), 150);
+        for (mean, optimum) in cma.get_mean().iter().zip(OPTIMUM) {
+            assert!((mean - optimum).abs() < 1e-3, "mean {:?}", cma.get_mean());
+        }
+        assert!(cma.get_sigma() < 0.01, "sigma {}", cma.get_sigma());
+    }
+
+    #[test]
+    fn covariance_learns_a_badly_conditioned_quadratic() {
+        let cma = CmaEs::new(vec![(-5.0, 5.0); 2]).initial_mean(vec![3.0, 0.5]).initial_sigma(1.0);
+        let population = run(cma, ellipsoid, 60);
+        let cma = population.get_cma_es().unwrap();
+        let covariance = cma.get_covariance();
+        assert_eq!(covariance[0][1], covariance[1][0]);
+        // the flat first gene gets the wider axis
+        assert!(covariance[0][0] > covariance[1][1], "covariance {:?}", covariance);
+        assert!(cma.get_axis_ratio() > 3.0, "axis ratio {}", cma.get_axis_ratio());
+    }
+}
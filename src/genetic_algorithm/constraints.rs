@@ -0,0 +1,389 @@
+use std::{cmp::Ordering, fmt, ops::Range, sync::Arc};
+
+use super::{gene::Gene, Individual, Population};
+
+/// How the fitness of an individual that violates a registered constraint is treated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConstraintHandling {
+    /// Lowers the fitness by `weight` times the total violation, never below 0.
+    /// Change the weight over a run with `Population::set_penalty_schedule`.
+    Penalty(f64),
+    /// Sets the fitness of every infeasible individual to 0.
+    DeathPenalty,
+    /// Keeps the fitness and only records the violation, for feasibility-first selection
+    /// or constrained domination in multi-objective mode.
+    ViolationOnly,
+}
+
+/// A constraint function stored on a population.
+#[derive(Clone)]
+pub(crate) struct ConstraintHook<G: Gene>(Arc<Constraint<G>>);
+
+type Constraint<G> = dyn Fn(&Individual<G>) -> f64 + Send + Sync;
+
+impl<G: Gene> fmt::Debug for ConstraintHook<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ConstraintHook")
+    }
+}
+
+/// How the weight of a constraint violation penalty changes over a run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PenaltySchedule {
+    /// The same weight every generation.
+    Constant(f64),
+    /// Ramps linearly from `start` to `end` over `generations`, then stays at `end`.
+    Linear { start: f64, end: f64, generations: u64 },
+    /// Multiplies `start` by `factor` every generation, capped at `max`.
+    Exponential { start: f64, factor: f64, max: f64 },
+    /// Adapts to the population: when fewer than `target_feasible` (0.0-1.0) of the
+    /// individuals are feasible the weight is multiplied by `increase`, otherwise divided
+    /// by it, so early exploration isn't strangled but the run still ends feasible.
+    Adaptive {
+        initial: f64,
+        target_feasible: f64,
+        increase: f64,
+    },
+}
+
+/// Tracks the penalty weight of a `PenaltySchedule` over a run and applies it.
+/// A population advances its own with every generation, see `Population::set_penalty_schedule`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PenaltyScheduler {
+    schedule: PenaltySchedule,
+    generation: u64,
+    weight: f64,
+}
+
+impl PenaltyScheduler {
+    /// Creates a scheduler at generation 0.
+    /// Panics on a negative or non-finite weight or factor.
+    pub fn new(schedule: PenaltySchedule) -> PenaltyScheduler {
+        let weight = match check_schedule(schedule) {
+            Ok(weight) => weight,
+            Err(message) => panic!("{}", message),
+        };
+        PenaltyScheduler {
+            schedule,
+            generation: 0,
+            weight,
+        }
+    }
+
+    /// Checks a scheduler part way through its schedule, e.g. one loaded from a checkpoint.
+    #[cfg(feature = "serde")]
+    pub(crate) fn check(&self) -> Result<(), &'static str> {
+        check_schedule(self.schedule)?;
+        check_weight(self.weight)?;
+        Ok(())
+    }
+
+    pub fn get_schedule(&self) -> PenaltySchedule {
+        self.schedule
+    }
+
+    /// Returns the current penalty weight.
+    pub fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn get_generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Advances the schedule by one generation, call once per generation.
+    ///
+    /// # Arguments
+    /// * `feasible_fraction` - The fraction (0.0-1.0) of the population without violations, only used by `Adaptive`.
+    pub fn next_generation(&mut self, feasible_fraction: f64) {
+        self.generation += 1;
+        self.weight = match self.schedule {
+            PenaltySchedule::Constant(weight) => weight,
+            PenaltySchedule::Linear { start, end, generations } => {
+                let progress = (self.generation as f64 / generations.max(1) as f64).min(1.0);
+                start + (end - start) * progress
+            }
+            PenaltySchedule::Exponential { start, factor, max } => (start * factor.powf(self.generation as f64)).min(max),
+            PenaltySchedule::Adaptive { target_feasible, increase, .. } => {
+                if feasible_fraction < target_feasible {
+                    self.weight * increase
+                } else {
+                    self.weight / increase
+                }
+            }
+        };
+    }
+
+    /// Lowers a fitness by the weighted violation, never going below 0.
+    ///
+    /// # Arguments
+    /// * `fitness` - The unconstrained fitness.
+    /// * `violation` - The total constraint violation, 0 for a feasible individual.
+    pub fn penalize(&self, fitness: u64, violation: f64) -> u64 {
+        penalize(self.weight, fitness, violation)
+    }
+}
+
+/// Checks the settings of a schedule and returns its initial weight.
+fn check_schedule(schedule: PenaltySchedule) -> Result<f64, &'static str> {
+    match schedule {
+        PenaltySchedule::Constant(weight) => check_weight(weight),
+        PenaltySchedule::Linear { start, end, .. } => {
+            check_weight(end)?;
+            check_weight(start)
+        }
+        PenaltySchedule::Exponential { start, factor, max } => {
+            if !factor.is_finite() || factor <= 0.0 {
+                return Err("penalty growth factor must be finite and positive");
+            }
+            check_weight(max)?;
+            check_weight(start)
+        }
+        PenaltySchedule::Adaptive { initial, target_feasible, increase } => {
+            if !(0.0..=1.0).contains(&target_feasible) {
+                return Err("target feasible fraction must be between 0 and 1");
+            }
+            if !increase.is_finite() || increase < 1.0 {
+                return Err("adaptive increase factor must be finite and 1 or more");
+            }
+            check_weight(initial)
+        }
+    }
+}
+
+fn check_weight(weight: f64) -> Result<f64, &'static str> {
+    if !weight.is_finite() || weight < 0.0 {
+        return Err("penalty weight must be finite and cant be negative");
+    }
+    Ok(weight)
+}
+
+fn penalize(weight: f64, fitness: u64, violation: f64) -> u64 {
+    let penalty = (weight * violation.max(0.0)).round();
+    if penalty >= fitness as f64 {
+        return 0;
+    }
+    fitness - penalty as u64
+}
+
+impl<G: Gene> Individual<G> {
+    /// Sets the total constraint violation of the individual, 0 when it is feasible.
+    pub fn set_violation(&mut self, violation: f64) {
+        if violation.is_nan() || violation < 0.0 {
+            panic!("constraint violation cant be negative");
+        }
+        self.violation = violation;
+    }
+
+    pub fn get_violation(&self) -> f64 {
+        self.violation
+    }
+
+    /// Returns true if the individual violates no constraint.
+    pub fn is_feasible(&self) -> bool {
+        self.violation == 0.0
+    }
+}
+
+/// Compares two individuals feasibility first: any feasible individual beats any
+/// infeasible one, feasible individuals are compared by fitness and infeasible ones
+/// by violation, the smaller the better. `Ordering::Greater` means `a` is better.
+pub fn compare_feasibility_first<G: Gene>(a: &Individual<G>, b: &Individual<G>) -> Ordering {
+    match (a.is_feasible(), b.is_feasible()) {
+        (true, true) => a.fitness.cmp(&b.fitness),
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => b.violation.total_cmp(&a.violation),
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Enables or disables feasibility-first selection, a penalty-free way of handling
+    /// constraints: parents and the fittest individual are chosen with
+    /// `compare_feasibility_first` instead of by fitness alone. Set each individual's
+    /// violation with `Individual::set_violation` when evaluating it.
+    pub fn set_feasibility_first(&mut self, enabled: bool) {
+        self.feasibility_first = enabled;
+    }
+
+    /// Registers a constraint. After every evaluation each individual's violation is set
+    /// to the sum of what the constraints return and its fitness is adjusted according
+    /// to the constraint handling, a penalty with weight 1 by default. To fix infeasible
+    /// children instead of penalizing them, set a repair operator with `set_repair`.
+    ///
+    /// # Arguments
+    /// * `constraint` - Returns by how much an individual violates the constraint, 0 (or less) if it doesn't.
+    pub fn add_constraint<C: Fn(&Individual<G>) -> f64 + Send + Sync + 'static>(&mut self, constraint: C) {
+        self.constraints.push(ConstraintHook(Arc::new(constraint)));
+    }
+
+    /// Removes all registered constraints.
+    pub fn clear_constraints(&mut self) {
+        self.constraints.clear();
+    }
+
+    /// Sets how individuals that violate a registered constraint are treated.
+    /// Removes a penalty schedule.
+    pub fn set_constraint_handling(&mut self, handling: ConstraintHandling) {
+        if let ConstraintHandling::Penalty(weight) = handling {
+            if weight.is_nan() || weight < 0.0 {
+                panic!("penalty weight cant be negative");
+            }
+        }
+        self.constraint_handling = handling;
+        self.penalty_scheduler = None;
+    }
+
+    pub fn get_constraint_handling(&self) -> ConstraintHandling {
+        self.constraint_handling
+    }
+
+    /// Changes the penalty weight every generation by the given schedule, switching the
+    /// constraint handling to `ConstraintHandling::Penalty` with the schedule's initial
+    /// weight right away. `next_generation` advances it once the population is evaluated,
+    /// so the new weight applies from the next evaluation on.
+    ///
+    /// # Arguments
+    /// * `schedule` - How the penalty weight changes.
+    pub fn set_penalty_schedule(&mut self, schedule: PenaltySchedule) {
+        let scheduler = PenaltyScheduler::new(schedule);
+        self.constraint_handling = ConstraintHandling::Penalty(scheduler.get_weight());
+        self.penalty_scheduler = Some(scheduler);
+    }
+
+    /// Goes back to a fixed penalty weight, the current one.
+    pub fn clear_penalty_schedule(&mut self) {
+        self.penalty_scheduler = None;
+    }
+
+    pub fn get_penalty_scheduler(&self) -> Option<&PenaltyScheduler> {
+        self.penalty_scheduler.as_ref()
+    }
+
+    /// Moves the penalty weight one generation along the schedule, if one is set.
+    /// Called with the evaluated population, before the next generation is bred.
+    pub(crate) fn apply_penalty_schedule(&mut self) {
+        let feasible_fraction = self.feasible_fraction();
+        if let Some(scheduler) = &mut self.penalty_scheduler {
+            scheduler.next_generation(feasible_fraction);
+            self.constraint_handling = ConstraintHandling::Penalty(scheduler.get_weight());
+        }
+    }
+
+    /// Sets the violation of a range of freshly evaluated individuals from the registered
+    /// constraints and adjusts their fitness. Does nothing without constraints, so
+    /// violations set by hand are kept.
+    pub(crate) fn apply_constraints_to(&mut self, range: Range<usize>) {
+        if self.constraints.is_empty() {
+            return;
+        }
+        for individual in self.individuals[range].iter_mut() {
+            let violation: f64 = self
+                .constraints
+                .iter()
+                .map(|ConstraintHook(constraint)| constraint(individual).max(0.0))
+                .sum();
+            individual.set_violation(violation);
+            individual.fitness = match self.constraint_handling {
+                ConstraintHandling::Penalty(weight) => penalize(weight, individual.fitness, violation),
+                ConstraintHandling::DeathPenalty if violation > 0.0 => 0,
+                ConstraintHandling::DeathPenalty | ConstraintHandling::ViolationOnly => individual.fitness,
+            };
+        }
+    }
+
+    /// Returns the fraction of the population that violates no constraint.
+    pub fn feasible_fraction(&self) -> f64 {
+        let feasible = self.individuals.iter().filter(|individual| individual.is_feasible()).count();
+        feasible as f64 / self.individuals.len() as f64
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{init_population_seeded, CrossoverType};
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+    }
+
+    #[test]
+    fn linear_schedule_ramps_and_holds() {
+        let mut scheduler = PenaltyScheduler::new(PenaltySchedule::Linear { start: 1.0, end: 5.0, generations: 4 });
+        let mut weights = vec![scheduler.get_weight()];
+        for _ in 0..5 {
+            scheduler.next_generation(1.0);
+            weights.push(scheduler.get_weight());
+        }
+        assert_eq!(weights, vec![1.0, 2.0, 3.0, 4.0, 5.0, 5.0]);
+    }
+
+    #[test]
+    fn exponential_schedule_is_capped() {
+        let mut scheduler = PenaltyScheduler::new(PenaltySchedule::Exponential { start: 1.0, factor: 2.0, max: 5.0 });
+        scheduler.next_generation(1.0);
+        scheduler.next_generation(1.0);
+        assert_eq!(scheduler.get_weight(), 4.0);
+        scheduler.next_generation(1.0);
+        assert_eq!(scheduler.get_weight(), 5.0);
+    }
+
+    #[test]
+    fn adaptive_schedule_follows_feasibility() {
+        let mut scheduler = PenaltyScheduler::new(PenaltySchedule::Adaptive { initial: 4.0, target_feasible: 0.5, increase: 2.0 });
+        scheduler.next_generation(0.2);
+        assert_eq!(scheduler.get_weight(), 8.0);
+        scheduler.next_generation(0.8);
+        assert_eq!(scheduler.get_weight(), 4.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "penalty growth factor must be finite and positive")]
+    fn zero_growth_factor_is_rejected() {
+        PenaltyScheduler::new(PenaltySchedule::Exponential { start: 1.0, factor: 0.0, max: 5.0 });
+    }
+
+    #[test]
+    #[should_panic(expected = "penalty weight must be finite and cant be negative")]
+    fn negative_end_weight_is_rejected() {
+        PenaltyScheduler::new(PenaltySchedule::Linear { start: 1.0, end: -1.0, generations: 4 });
+    }
+
+    #[test]
+    #[should_panic(expected = "penalty weight must be finite and cant be negative")]
+    fn infinite_max_weight_is_rejected() {
+        PenaltyScheduler::new(PenaltySchedule::Exponential { start: 1.0, factor: 2.0, max: f64::INFINITY });
+    }
+
+    #[test]
+    fn population_advances_the_penalty_schedule() {
+        let mut population = init_population_seeded(16, 10, 2, 5, false, CrossoverType::Bit, 6);
+        population.add_constraint(|_: &Individual| 1.0);
+        population.set_penalty_schedule(PenaltySchedule::Linear { start: 0.0, end: 3.0, generations: 3 });
+        assert_eq!(population.get_constraint_handling(), ConstraintHandling::Penalty(0.0));
+        for generation in 1..=3 {
+            population.evaluate(ones);
+            population.next_generation();
+            assert_eq!(population.get_penalty_scheduler().map(PenaltyScheduler::get_generation), Some(generation));
+            assert_eq!(population.get_constraint_handling(), ConstraintHandling::Penalty(generation as f64));
+        }
+        population.evaluate(ones);
+        for individual in &population.individuals {
+            assert_eq!(individual.get_fitness(), ones(individual).saturating_sub(3));
+        }
+    }
+
+    #[test]
+    fn setting_the_handling_removes_the_schedule() {
+        let mut population = init_population_seeded(16, 10, 2, 5, false, CrossoverType::Bit, 7);
+        population.set_penalty_schedule(PenaltySchedule::Constant(2.0));
+        population.set_constraint_handling(ConstraintHandling::DeathPenalty);
+        assert!(population.get_penalty_scheduler().is_none());
+        population.evaluate(ones);
+        population.next_generation();
+        assert_eq!(population.get_constraint_handling(), ConstraintHandling::DeathPenalty);
+    }
+}
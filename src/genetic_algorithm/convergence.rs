@@ -0,0 +1,224 @@
+use super::{events::GaEvent, Individual, Population};
+
+impl Population {
+    /// Returns the mean Shannon entropy (in bits, 0.0-1.0) of the allele distribution
+    /// over all loci. 1.0 means every locus is split evenly, 0.0 that all individuals are
+    /// identical or that there are no loci at all.
+    pub fn entropy(&self) -> f64 {
+        let frequencies = self.allele_frequencies();
+        if frequencies.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = frequencies
+            .iter()
+            .map(|&p| {
+                if p <= 0.0 || p >= 1.0 {
+                    0.0
+                } else {
+                    -(p * p.log2() + (1.0 - p) * (1.0 - p).log2())
+                }
+            })
+            .sum();
+        total / frequencies.len() as f64
+    }
+
+    /// CHC-style cataclysmic mutation: keeps the fittest individual and replaces everyone
+    /// else with a heavily mutated copy of it. Unlike a full restart this keeps the
+    /// structure learned so far while still spreading the population out again.
+    /// Fitness of the replaced individuals is reset to 0.
+    ///
+    /// # Arguments
+    /// * `mutation_chance` - The chance (0-100) of flipping each gene of the copies, e.g. 35.
+    pub fn cataclysm(&mut self, mutation_chance: u8) {
+        if mutation_chance > super::MAX_MUTATION_CHANCE {
+            panic!("cataclysm mutation chance cant be higher than 100");
+        }
+        self.sort_individuals();
+        self.record_best();
+        let best = self.individuals[0].clone();
+        for i in 1..self.individuals.len() {
+            let mut individual = best.clone();
+            for j in 0..individual.gene_length {
+                individual.mutate_at_index(j, mutation_chance as f64 / 100.0, &mut self.rng);
+            }
+            individual.fitness = 0;
+            self.repair_individual(&mut individual);
+            self.individuals[i] = individual;
+        }
+        self.emit(GaEvent::Restart {
+            generation: self.generation,
+        });
+    }
+
+    /// Returns the fraction of loci that are fixed, meaning at least `fixation_threshold`
+    /// of the population carries the same allele there.
+    ///
+    /// # Arguments
+    /// * `fixation_threshold` - The share of the majority allele (0.5-1.0) from which a locus counts as fixed.
+    pub fn fixed_locus_fraction(&self, fixation_threshold: f64) -> f64 {
+        let frequencies = self.allele_frequencies();
+        let fixed = frequencies
+            .iter()
+            .filter(|&&frequency| frequency >= fixation_threshold || 1.0 - frequency >= fixation_threshold)
+            .count();
+        fixed as f64 / frequencies.len() as f64
+    }
+}
+
+/// Signals that a population has converged once enough of its loci are fixed.
+/// Usable as a termination criterion or as the trigger for a diversity response.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceDetector {
+    fixation_threshold: f64,
+    fixed_fraction: f64,
+}
+
+impl ConvergenceDetector {
+    /// Creates a new detector.
+    ///
+    /// # Arguments
+    /// * `fixation_threshold` - The share of the majority allele (0.5-1.0) from which a locus counts as fixed, e.g. 0.95.
+    /// * `fixed_fraction` - The fraction of fixed loci (0.0-1.0) from which the population counts as converged.
+    pub fn new(fixation_threshold: f64, fixed_fraction: f64) -> ConvergenceDetector {
+        if !(0.5..=1.0).contains(&fixation_threshold) {
+            panic!("fixation threshold must be between 0.5 and 1");
+        }
+        if !(0.0..=1.0).contains(&fixed_fraction) {
+            panic!("fixed fraction must be between 0 and 1");
+        }
+        ConvergenceDetector {
+            fixation_threshold,
+            fixed_fraction,
+        }
+    }
+
+    /// Returns true if the population has converged.
+    pub fn has_converged(&self, population: &Population) -> bool {
+        population.fixed_locus_fraction(self.fixation_threshold) >= self.fixed_fraction
+    }
+
+    pub fn get_fixation_threshold(&self) -> f64 {
+        self.fixation_threshold
+    }
+
+    pub fn get_fixed_fraction(&self) -> f64 {
+        self.fixed_fraction
+    }
+}
+
+/// What to do when a population's diversity collapses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiversityResponse {
+    /// Replaces the worst `fraction` (0.0-1.0) of the population with random individuals.
+    RandomImmigrants { fraction: f64 },
+    /// Mutates every gene of all but the fittest individual with `mutation_chance` (0-100).
+    Hypermutation { mutation_chance: u8 },
+    /// Re-randomizes all but the `keep` fittest individuals.
+    PartialRestart { keep: usize },
+    /// Replaces all but the fittest individual with copies of it mutated with
+    /// `mutation_chance` (0-100), see `Population::cataclysm`.
+    Cataclysm { mutation_chance: u8 },
+}
+
+/// Injects diversity into a population whenever its entropy falls below a threshold.
+/// Apply it by hand between generations, or let a `GaRunner` apply it with
+/// `GaRunner::diversity_policy`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiversityPolicy {
+    entropy_threshold: f64,
+    response: DiversityResponse,
+}
+
+impl DiversityPolicy {
+    /// Creates a new policy.
+    ///
+    /// # Arguments
+    /// * `entropy_threshold` - The population entropy (0.0-1.0) below which the response is applied.
+    /// * `response` - How diversity is injected.
+    pub fn new(entropy_threshold: f64, response: DiversityResponse) -> DiversityPolicy {
+        if !(0.0..=1.0).contains(&entropy_threshold) {
+            panic!("entropy threshold must be between 0 and 1");
+        }
+        match response {
+            DiversityResponse::RandomImmigrants { fraction } if !(0.0..=1.0).contains(&fraction) => {
+                panic!("immigrant fraction must be between 0 and 1")
+            }
+            DiversityResponse::Hypermutation { mutation_chance } | DiversityResponse::Cataclysm { mutation_chance }
+                if mutation_chance > super::MAX_MUTATION_CHANCE =>
+            {
+                panic!("mutation chance cant be higher than 100")
+            }
+            _ => {}
+        }
+        DiversityPolicy {
+            entropy_threshold,
+            response,
+        }
+    }
+
+    /// Applies the response if the population's entropy is below the threshold.
+    /// Fitness of the affected individuals is reset to 0, so re-evaluate before selecting.
+    ///
+    /// # Arguments
+    /// * `population` - The population to check.
+    ///
+    /// # Returns
+    /// True if diversity was injected.
+    pub fn apply(&self, population: &mut Population) -> bool {
+        if population.entropy() >= self.entropy_threshold {
+            return false;
+        }
+        population.sort_individuals();
+        population.record_best();
+        let size = population.individuals.len();
+        match self.response {
+            DiversityResponse::RandomImmigrants { fraction } => {
+                let count = ((size as f64 * fraction).round() as usize).min(size);
+                for i in size - count..size {
+                    reset(population, i);
+                }
+            }
+            DiversityResponse::Hypermutation { mutation_chance } => {
+                for i in 1..size {
+                    let mut individual = population.individuals[i].clone();
+                    for j in 0..individual.gene_length {
+                        individual.mutate_at_index(j, mutation_chance as f64 / 100.0, &mut population.rng);
+                    }
+                    individual.fitness = 0;
+                    population.repair_individual(&mut individual);
+                    population.individuals[i] = individual;
+                }
+            }
+            DiversityResponse::PartialRestart { keep } => {
+                for i in keep.min(size)..size {
+                    reset(population, i);
+                }
+            }
+            DiversityResponse::Cataclysm { mutation_chance } => {
+                population.cataclysm(mutation_chance);
+                return true;
+            }
+        }
+        population.emit(GaEvent::Restart {
+            generation: population.generation,
+        });
+        true
+    }
+
+    pub fn get_entropy_threshold(&self) -> f64 {
+        self.entropy_threshold
+    }
+
+    pub fn get_response(&self) -> DiversityResponse {
+        self.response
+    }
+}
+
+/// Re-randomizes the individual at `index` and resets its fitness.
+fn reset(population: &mut Population, index: usize) {
+    let mut individual: Individual = population.individuals[index].clone();
+    individual.randomize(&mut population.rng);
+    individual.fitness = 0;
+    population.repair_individual(&mut individual);
+    population.individuals[index] = individual;
+}
@@ -0,0 +1,141 @@
+use super::{gene::Gene, Individual, Population};
+
+/// How `Population::retain` replaces the removed individuals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refill {
+    /// New random individuals.
+    Random,
+    /// Children of the fittest remaining individuals, bred like in `next_generation`.
+    /// Falls back to random individuals when nobody is left.
+    Bred,
+}
+
+impl<G: Gene> Population<G> {
+    /// Removes every individual the predicate rejects and refills the population back to
+    /// `population_size`, e.g. to purge infeasible or degenerate individuals mid-run. New
+    /// individuals are repaired if a repair operator is set and have a fitness of 0.
+    ///
+    /// # Arguments
+    /// * `keep` - Returns true for the individuals to keep.
+    /// * `refill` - How the removed individuals are replaced.
+    ///
+    /// # Returns
+    /// The number of individuals removed.
+    pub fn retain<F: FnMut(&Individual<G>) -> bool>(&mut self, keep: F, refill: Refill) -> usize {
+        let template = self.individuals[0].clone();
+        self.record_best();
+        let before = self.individuals.len();
+        self.individuals.retain(keep);
+        let removed = before - self.individuals.len();
+        if self.individuals.is_empty() || refill == Refill::Random {
+            while self.individuals.len() < self.population_size {
+                let mut individual = template.clone();
+                self.randomize_individual(&mut individual);
+                individual.fitness = 0;
+                individual.violation = 0.0;
+                self.repair_individual(&mut individual);
+                self.individuals.push(individual);
+            }
+        } else {
+            self.sort_individuals();
+            let count = self.population_size.saturating_sub(self.individuals.len());
+            let others = self.individuals.split_off(self.parent_count.min(self.individuals.len()));
+            let parents = std::mem::take(&mut self.individuals);
+            let (parents, mut children) = self.breed_children(parents, count, vec![]);
+            self.individuals = parents;
+            self.individuals.extend(others);
+            self.individuals.append(&mut children);
+        }
+        removed
+    }
+
+    /// Adds an individual to the running population, e.g. a candidate from a user
+    /// interface or another optimizer. The population is one larger until the next
+    /// generation brings it back to `population_size`; in steady-state mode it stays
+    /// larger, make room with `remove_worst` first. The individual is repaired and keeps
+    /// its fitness, so set it or evaluate before selecting.
+    ///
+    /// # Arguments
+    /// * `individual` - The individual, with the population's gene length or one the variable-length settings allow.
+    pub fn insert(&mut self, mut individual: Individual<G>) {
+        match self.variable_length {
+            Some(variable) if !variable.allows(individual.gene_length) => {
+                panic!("gene length {} doesnt fit the variable-length settings", individual.gene_length);
+            }
+            Some(_) => {}
+            None => {
+                let gene_length = self.individuals[0].gene_length;
+                if individual.gene_length != gene_length {
+                    panic!("cant insert an individual of {} genes into a gene length of {}", individual.gene_length, gene_length);
+                }
+            }
+        }
+        if individual.strategy.is_empty() {
+            self.initialize_strategy(&mut individual);
+        }
+        self.repair_individual(&mut individual);
+        self.individuals.push(individual);
+    }
+
+    /// Brings the sorted population back to `population_size` before a replacement mode
+    /// that only works on the individuals present steps, e.g. after `remove_worst` or
+    /// `insert`: the worst individuals are dropped and missing ones are random
+    /// individuals with a fitness of 0.
+    pub(crate) fn restore_population_size(&mut self) {
+        self.individuals.truncate(self.population_size);
+        let template = self.individuals[0].clone();
+        while self.individuals.len() < self.population_size {
+            let individual = self.random_individual(&template);
+            self.individuals.push(individual);
+        }
+    }
+
+    /// Removes the least fit individuals, by the population's ordering. At least one
+    /// individual is always kept. The next generation refills the population, with
+    /// children or, in the replacement modes, random individuals. Only steady-state mode
+    /// keeps it smaller, refill it with `insert`.
+    ///
+    /// # Arguments
+    /// * `count` - The number of individuals to remove.
+    ///
+    /// # Returns
+    /// The removed individuals, best first.
+    pub fn remove_worst(&mut self, count: usize) -> Vec<Individual<G>> {
+        self.sort_individuals();
+        self.record_best();
+        let keep = self.individuals.len().saturating_sub(count).max(1);
+        self.individuals.split_off(keep)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{builder::PopulationBuilder, cma_es::CmaEs};
+    use super::*;
+
+    fn refills(mut population: Population<f64>) {
+        let fitness = |individual: &Individual<f64>| (100.0 - individual.get_genes()[0].abs()) as u64;
+        population.evaluate(fitness);
+        population.remove_worst(7);
+        assert_eq!(population.individuals.len(), 3);
+        for _ in 0..3 {
+            population.next_generation();
+            population.evaluate(fitness);
+            assert_eq!(population.individuals.len(), 10);
+        }
+    }
+
+    fn population() -> Population<f64> {
+        PopulationBuilder::<f64>::new().gene_length(2).size(10).parents(2).seed(6).build().unwrap()
+    }
+
+    #[test]
+    fn next_generation_refills_after_remove_worst() {
+        refills(population());
+        let mut crowding = population();
+        crowding.set_crowding(true);
+        refills(crowding);
+        let mut cma = population();
+        cma.set_cma_es(CmaEs::new(vec![(-5.0, 5.0); 2]));
+        refills(cma);
+    }
+}
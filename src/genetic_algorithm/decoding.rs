@@ -0,0 +1,342 @@
+use super::Individual;
+
+/// The order in which the 8 genes of a byte are read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOrder {
+    /// The first gene of a byte is its most significant bit (the default).
+    MsbFirst,
+    /// The first gene of a byte is its least significant bit.
+    LsbFirst,
+}
+
+/// The order in which the bytes of a multi-byte word are read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// The first byte of a word is its most significant byte.
+    BigEndian,
+    /// The first byte of a word is its least significant byte.
+    LittleEndian,
+}
+
+/// How the bits of a numeric gene group encode its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryEncoding {
+    /// Plain base 2, most significant bit first.
+    Standard,
+    /// Reflected binary Gray code, most significant bit first. Neighbouring values
+    /// differ in a single bit, so a one bit mutation can always reach them.
+    Gray,
+}
+
+/// Converts a Gray coded value to plain binary.
+pub fn gray_to_binary(gray: u64) -> u64 {
+    let mut binary = gray;
+    let mut shift = 1;
+    while shift < 64 {
+        binary ^= binary >> shift;
+        shift <<= 1;
+    }
+    binary
+}
+
+/// Converts a plain binary value to Gray code.
+pub fn binary_to_gray(binary: u64) -> u64 {
+    binary ^ (binary >> 1)
+}
+
+/// An unsigned integer type that whole-genome Gray decoding and encoding work with,
+/// one value per `BITS` genes.
+pub trait GrayValue: Copy {
+    const BITS: usize;
+
+    fn from_u64(value: u64) -> Self;
+
+    fn to_u64(self) -> u64;
+}
+
+impl GrayValue for u8 {
+    const BITS: usize = 8;
+
+    fn from_u64(value: u64) -> u8 {
+        value as u8
+    }
+
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+}
+
+impl GrayValue for u16 {
+    const BITS: usize = 16;
+
+    fn from_u64(value: u64) -> u16 {
+        value as u16
+    }
+
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+}
+
+impl GrayValue for u32 {
+    const BITS: usize = 32;
+
+    fn from_u64(value: u64) -> u32 {
+        value as u32
+    }
+
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+}
+
+impl GrayValue for u64 {
+    const BITS: usize = 64;
+
+    fn from_u64(value: u64) -> u64 {
+        value
+    }
+
+    fn to_u64(self) -> u64 {
+        self
+    }
+}
+
+/// Returns the largest value `bits` bits can hold.
+pub(crate) fn max_value(bits: usize) -> u64 {
+    if bits == 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+impl Individual {
+    /// Converts the genes to bytes, reading the bits of each byte in the given order.
+    ///
+    /// # Arguments
+    /// * `bit_order` - Whether the first gene of a byte is its most or least significant bit.
+    pub fn get_genes_as_decimal_bytes_ordered(&self, bit_order: BitOrder) -> Vec<u8> {
+        if !self.gene_length.is_multiple_of(8) {
+            panic!("gene length must be a multiple of 8 to convert to bytes");
+        }
+        let mut bytes: Vec<u8> = vec![];
+        for chunk in self.genes.as_slice().chunks(8) {
+            let mut byte: u8 = 0;
+            for (j, &gene) in chunk.iter().enumerate() {
+                if gene {
+                    byte |= match bit_order {
+                        BitOrder::MsbFirst => 1 << (7 - j),
+                        BitOrder::LsbFirst => 1 << j,
+                    };
+                }
+            }
+            bytes.push(byte);
+        }
+        bytes
+    }
+
+    /// Converts the genes to bytes like `get_genes_as_decimal_bytes`, but reads every byte
+    /// as Gray code, so neighbouring values are always one bit flip apart instead of up
+    /// to 8 (the Hamming cliff between 127 and 128).
+    pub fn get_genes_as_gray_decoded_bytes(&self) -> Vec<u8> {
+        self.decode_gray::<u8>()
+    }
+
+    /// Interprets the whole genome as consecutive Gray coded unsigned values of
+    /// `T::BITS` genes each, most significant bit first, e.g. `decode_gray::<u16>()`.
+    pub fn decode_gray<T: GrayValue>(&self) -> Vec<T> {
+        if !self.gene_length.is_multiple_of(T::BITS) {
+            panic!("gene length must be a multiple of {} to decode {} bit values", T::BITS, T::BITS);
+        }
+        (0..self.gene_length / T::BITS)
+            .map(|i| T::from_u64(self.read_unsigned(i * T::BITS, T::BITS, BinaryEncoding::Gray)))
+            .collect()
+    }
+
+    /// Creates an individual that `decode_gray` decodes to the given values, to seed a
+    /// population with known solutions.
+    ///
+    /// # Arguments
+    /// * `values` - The values, at least one.
+    pub fn from_gray_values<T: GrayValue>(values: &[T]) -> Individual {
+        let mut individual = Individual::from_genes(vec![false; values.len() * T::BITS]);
+        for (i, &value) in values.iter().enumerate() {
+            individual.write_unsigned(i * T::BITS, T::BITS, BinaryEncoding::Gray, value.to_u64());
+        }
+        individual
+    }
+
+    /// Converts the genes to unsigned words of `word_bytes` bytes each,
+    /// so genomes can be read with the layout of an external binary format.
+    ///
+    /// # Arguments
+    /// * `word_bytes` - The number of bytes per word (1-8).
+    /// * `bit_order` - Whether the first gene of a byte is its most or least significant bit.
+    /// * `byte_order` - Whether the first byte of a word is its most or least significant byte.
+    pub fn get_genes_as_words(&self, word_bytes: usize, bit_order: BitOrder, byte_order: ByteOrder) -> Vec<u64> {
+        if !(1..=8).contains(&word_bytes) {
+            panic!("word size must be between 1 and 8 bytes");
+        }
+        if !self.gene_length.is_multiple_of(8 * word_bytes) {
+            panic!("gene length must be a multiple of {} to convert to {} byte words", 8 * word_bytes, word_bytes);
+        }
+        let mut words: Vec<u64> = vec![];
+        for chunk in self.get_genes_as_decimal_bytes_ordered(bit_order).chunks(word_bytes) {
+            let mut word: u64 = 0;
+            for (j, &byte) in chunk.iter().enumerate() {
+                let shift = match byte_order {
+                    ByteOrder::BigEndian => 8 * (word_bytes - 1 - j),
+                    ByteOrder::LittleEndian => 8 * j,
+                };
+                word |= (byte as u64) << shift;
+            }
+            words.push(word);
+        }
+        words
+    }
+
+    /// Interprets a group of genes as a fixed-point value spread evenly over `[min, max]`,
+    /// all zero bits decode to `min` and all one bits to `max`.
+    ///
+    /// # Arguments
+    /// * `start` - The index of the first gene of the group.
+    /// * `bits` - The number of genes in the group (1-64).
+    /// * `min` - The lower bound of the value.
+    /// * `max` - The upper bound of the value.
+    /// * `encoding` - Whether the group is plain binary or Gray coded.
+    pub fn decode_fixed_point(&self, start: usize, bits: usize, min: f64, max: f64, encoding: BinaryEncoding) -> f64 {
+        let raw = self.read_unsigned(start, bits, encoding);
+        min + (max - min) * raw as f64 / max_value(bits) as f64
+    }
+
+    /// Interprets the whole genome as consecutive fixed-point values of `bits` genes each.
+    ///
+    /// # Arguments
+    /// * `bits` - The number of genes per value (1-64), must divide the gene length.
+    /// * `min` - The lower bound of every value.
+    /// * `max` - The upper bound of every value.
+    /// * `encoding` - Whether the groups are plain binary or Gray coded.
+    pub fn decode_fixed_points(&self, bits: usize, min: f64, max: f64, encoding: BinaryEncoding) -> Vec<f64> {
+        if bits < 1 || !self.gene_length.is_multiple_of(bits) {
+            panic!("gene length must be a multiple of {} to decode fixed-point values", bits);
+        }
+        (0..self.gene_length / bits)
+            .map(|i| self.decode_fixed_point(i * bits, bits, min, max, encoding))
+            .collect()
+    }
+
+    /// Writes the fixed-point representation closest to `value` into a group of genes.
+    /// Values outside `[min, max]` are clamped.
+    ///
+    /// # Arguments
+    /// * `start` - The index of the first gene of the group.
+    /// * `bits` - The number of genes in the group (1-64).
+    /// * `min` - The lower bound of the value.
+    /// * `max` - The upper bound of the value.
+    /// * `encoding` - Whether the group is plain binary or Gray coded.
+    /// * `value` - The value to encode.
+    pub fn encode_fixed_point(&mut self, start: usize, bits: usize, min: f64, max: f64, encoding: BinaryEncoding, value: f64) {
+        if min >= max {
+            panic!("min must be smaller than max");
+        }
+        let raw = ((value.clamp(min, max) - min) / (max - min) * max_value(bits) as f64).round() as u64;
+        self.write_unsigned(start, bits, encoding, raw);
+    }
+
+    /// Reads `bits` genes starting at `start` as an unsigned integer, most significant bit first.
+    pub(crate) fn read_unsigned(&self, start: usize, bits: usize, encoding: BinaryEncoding) -> u64 {
+        if !(1..=64).contains(&bits) {
+            panic!("a gene group must be between 1 and 64 bits");
+        }
+        if start + bits > self.gene_length {
+            panic!("gene group {}..{} is outside the gene length {}", start, start + bits, self.gene_length);
+        }
+        let mut raw: u64 = 0;
+        for i in start..start + bits {
+            raw = (raw << 1) | self.genes[i] as u64;
+        }
+        match encoding {
+            BinaryEncoding::Standard => raw,
+            BinaryEncoding::Gray => gray_to_binary(raw),
+        }
+    }
+
+    /// Writes an unsigned integer into `bits` genes starting at `start`, most significant bit first.
+    pub(crate) fn write_unsigned(&mut self, start: usize, bits: usize, encoding: BinaryEncoding, value: u64) {
+        if !(1..=64).contains(&bits) {
+            panic!("a gene group must be between 1 and 64 bits");
+        }
+        if start + bits > self.gene_length {
+            panic!("gene group {}..{} is outside the gene length {}", start, start + bits, self.gene_length);
+        }
+        if value > max_value(bits) {
+            panic!("value {} does not fit in {} bits", value, bits);
+        }
+        let raw = match encoding {
+            BinaryEncoding::Standard => value,
+            BinaryEncoding::Gray => binary_to_gray(value),
+        };
+        for j in 0..bits {
+            self.genes.set(start + j, (raw >> (bits - 1 - j)) & 1 == 1);
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_bytes(bytes: &[u8]) -> Individual {
+        Individual::from_genes(bytes.iter().flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1)).collect())
+    }
+
+    #[test]
+    fn words_follow_bit_and_byte_order() {
+        let individual = from_bytes(&[0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(individual.get_genes_as_decimal_bytes_ordered(BitOrder::MsbFirst), vec![0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(individual.get_genes_as_decimal_bytes_ordered(BitOrder::LsbFirst), vec![0x48, 0x2C, 0x6A, 0x1E]);
+        assert_eq!(individual.get_genes_as_words(2, BitOrder::MsbFirst, ByteOrder::BigEndian), vec![0x1234, 0x5678]);
+        assert_eq!(individual.get_genes_as_words(2, BitOrder::MsbFirst, ByteOrder::LittleEndian), vec![0x3412, 0x7856]);
+        assert_eq!(individual.get_genes_as_words(4, BitOrder::LsbFirst, ByteOrder::BigEndian), vec![0x482C_6A1E]);
+        assert_eq!(individual.get_genes_as_words(4, BitOrder::LsbFirst, ByteOrder::LittleEndian), vec![0x1E6A_2C48]);
+    }
+
+    #[test]
+    fn fixed_points_round_trip() {
+        for encoding in [BinaryEncoding::Standard, BinaryEncoding::Gray] {
+            let mut individual = Individual::from_genes(vec![false; 30]);
+            let step = 5.0 / max_value(10) as f64;
+            for i in 0..=100 {
+                let value = -2.0 + i as f64 * 0.05;
+                individual.encode_fixed_point(10, 10, -2.0, 3.0, encoding, value);
+                let decoded = individual.decode_fixed_point(10, 10, -2.0, 3.0, encoding);
+                assert!((decoded - value).abs() <= step / 2.0, "{:?} decoded {} as {}", encoding, value, decoded);
+            }
+            individual.encode_fixed_point(0, 10, -2.0, 3.0, encoding, -2.0);
+            individual.encode_fixed_point(20, 10, -2.0, 3.0, encoding, 10.0);
+            assert_eq!(individual.decode_fixed_points(10, -2.0, 3.0, encoding)[0], -2.0);
+            assert_eq!(individual.decode_fixed_points(10, -2.0, 3.0, encoding)[2], 3.0);
+        }
+    }
+
+    #[test]
+    fn gray_values_round_trip() {
+        let values: Vec<u16> = vec![0, 1, 127, 128, 12345, u16::MAX];
+        assert_eq!(Individual::from_gray_values(&values).decode_gray::<u16>(), values);
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(Individual::from_gray_values(&bytes).get_genes_as_gray_decoded_bytes(), bytes);
+        for value in 0..1000u64 {
+            assert_eq!(gray_to_binary(binary_to_gray(value)), value);
+        }
+    }
+
+    #[test]
+    fn neighbouring_gray_values_differ_in_one_gene() {
+        for value in 0..255u8 {
+            let low = Individual::from_gray_values(&[value]);
+            let high = Individual::from_gray_values(&[value + 1]);
+            let differences = low.get_genes().iter().zip(high.get_genes().iter()).filter(|(a, b)| a != b).count();
+            assert_eq!(differences, 1);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use super::{rng::GaRng, Individual};
+
+/// A fitness function that can update a fitness from the loci that changed, for
+/// problems where a few flipped genes only change the fitness locally (e.g. conflict
+/// counts), instead of recomputing it from scratch.
+pub trait DeltaEvaluator {
+    /// Computes the fitness from scratch.
+    fn evaluate(&self, individual: &Individual) -> u64;
+
+    /// Computes the fitness of an individual whose genes at `changed` were just flipped,
+    /// given its fitness before the change.
+    ///
+    /// # Arguments
+    /// * `individual` - The individual after the change.
+    /// * `previous_fitness` - The fitness before the change.
+    /// * `changed` - The flipped loci, in ascending order.
+    fn evaluate_delta(&self, individual: &Individual, previous_fitness: u64, changed: &[usize]) -> u64;
+}
+
+impl Individual {
+    /// Mutates every gene with the given chance and updates the fitness incrementally.
+    /// The current fitness must be up to date.
+    ///
+    /// # Arguments
+    /// * `mutation_chance` - The probability of mutation (0-100).
+    /// * `evaluator` - Updates the fitness from the flipped loci.
+    /// * `rng` - The random number generator to draw from.
+    ///
+    /// # Returns
+    /// The flipped loci.
+    pub fn mutate_with_delta<D: DeltaEvaluator + ?Sized>(&mut self, mutation_chance: u8, evaluator: &D, rng: &mut GaRng) -> Vec<usize> {
+        if mutation_chance > super::MAX_MUTATION_CHANCE {
+            panic!("mutation chance cant be higher than 100");
+        }
+        let changed: Vec<usize> = (0..self.gene_length)
+            .filter(|&i| self.mutate_at_index(i, mutation_chance as f64 / 100.0, rng))
+            .collect();
+        if !changed.is_empty() {
+            self.fitness = evaluator.evaluate_delta(self, self.fitness, &changed);
+        }
+        changed
+    }
+
+    /// Flips the given genes and updates the fitness incrementally. The current fitness
+    /// must be up to date.
+    ///
+    /// # Arguments
+    /// * `loci` - The genes to flip, each at most once.
+    /// * `evaluator` - Updates the fitness from the flipped loci.
+    pub fn flip_genes_with_delta<D: DeltaEvaluator + ?Sized>(&mut self, loci: &[usize], evaluator: &D) {
+        let mut changed = loci.to_vec();
+        changed.sort();
+        changed.dedup();
+        if changed.len() != loci.len() {
+            panic!("every locus can only be flipped once");
+        }
+        for &index in &changed {
+            self.flip_gene(index);
+        }
+        self.fitness = evaluator.evaluate_delta(self, self.fitness, &changed);
+    }
+
+    /// Computes the fitness from scratch with a delta evaluator and stores it.
+    pub fn evaluate_with<D: DeltaEvaluator + ?Sized>(&mut self, evaluator: &D) {
+        self.fitness = evaluator.evaluate(self);
+    }
+}
@@ -0,0 +1,311 @@
+use std::{cmp::Ordering, fmt, sync::Arc};
+
+use rand::Rng;
+
+use super::{
+    constraints,
+    gene::Gene,
+    real::{RealCrossover, RealMutation, RealOperators},
+    rng::GaRng,
+    Individual, Population,
+};
+
+/// How a differential evolution mutant is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeStrategy {
+    /// DE/rand/1/bin: a random individual plus the weighted difference of two others.
+    Rand1Bin,
+    /// DE/best/1/bin: the fittest individual plus the weighted difference of two others.
+    /// Converges faster, but more readily onto a local optimum.
+    Best1Bin,
+}
+
+/// Differential evolution settings for real-valued genes, the usual choice for
+/// continuous problems. Every individual is the target of one trial per generation: a
+/// mutant built by the strategy, crossed over gene by gene with the target (binomial
+/// crossover), then clamped to the bounds. The trial replaces its target if it is at
+/// least as fit.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DifferentialEvolution {
+    bounds: Vec<(f64, f64)>,
+    strategy: DeStrategy,
+    weight: f64,
+    crossover_rate: f64,
+}
+
+impl DifferentialEvolution {
+    /// Creates new settings.
+    ///
+    /// # Arguments
+    /// * `bounds` - The inclusive `(min, max)` range of every gene, one per gene.
+    /// * `strategy` - How mutants are built.
+    /// * `weight` - The differential weight F, usually 0.4-1.0.
+    /// * `crossover_rate` - The probability CR (0.0-1.0) that a trial gene comes from the mutant.
+    pub fn new(bounds: Vec<(f64, f64)>, strategy: DeStrategy, weight: f64, crossover_rate: f64) -> DifferentialEvolution {
+        if bounds.is_empty() {
+            panic!("the gene length cannot be less than 1");
+        }
+        if let Some((min, max)) = bounds.iter().find(|(min, max)| min > max || !min.is_finite() || !max.is_finite()) {
+            panic!("invalid gene bounds {}..={}", min, max);
+        }
+        if !(weight > 0.0 && weight <= 2.0) {
+            panic!("differential weight must be larger than 0 and at most 2");
+        }
+        if !(0.0..=1.0).contains(&crossover_rate) {
+            panic!("crossover rate must be between 0 and 1");
+        }
+        DifferentialEvolution {
+            bounds,
+            strategy,
+            weight,
+            crossover_rate,
+        }
+    }
+
+    pub fn get_bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    pub fn get_strategy(&self) -> DeStrategy {
+        self.strategy
+    }
+
+    pub fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn get_crossover_rate(&self) -> f64 {
+        self.crossover_rate
+    }
+}
+
+/// Builds trials for a gene type, so the generic evolution loop can run differential
+/// evolution, which only exists for `f64` genes.
+pub(crate) trait TrialOperator<G: Gene>: Send + Sync {
+    fn settings(&self) -> &DifferentialEvolution;
+
+    /// Returns the genes of the trial for the target at `target` of the sorted population.
+    fn trial(&self, individuals: &[Individual<G>], target: usize, rng: &mut GaRng) -> Vec<G>;
+}
+
+impl TrialOperator<f64> for DifferentialEvolution {
+    fn settings(&self) -> &DifferentialEvolution {
+        self
+    }
+
+    fn trial(&self, individuals: &[Individual<f64>], target: usize, rng: &mut GaRng) -> Vec<f64> {
+        // three distinct individuals other than the target
+        let mut picks: Vec<usize> = vec![];
+        while picks.len() < 3 {
+            let pick = rng.random_range(0..individuals.len());
+            if pick != target && !picks.contains(&pick) {
+                picks.push(pick);
+            }
+        }
+        let (base, a, b) = match self.strategy {
+            DeStrategy::Rand1Bin => (picks[0], picks[1], picks[2]),
+            DeStrategy::Best1Bin => (0, picks[0], picks[1]),
+        };
+        let (base, a, b) = (&individuals[base].genes, &individuals[a].genes, &individuals[b].genes);
+        let target = &individuals[target].genes;
+        let forced = rng.random_range(0..target.len());
+        (0..target.len())
+            .map(|i| {
+                let value = if i == forced || rng.random_bool(self.crossover_rate) {
+                    base[i] + self.weight * (a[i] - b[i])
+                } else {
+                    target[i]
+                };
+                let (min, max) = self.bounds[i];
+                value.clamp(min, max)
+            })
+            .collect()
+    }
+}
+
+/// Differential evolution stored on a population.
+#[derive(Clone)]
+pub(crate) struct DeHook<G: Gene>(Arc<dyn TrialOperator<G>>);
+
+impl<G: Gene> fmt::Debug for DeHook<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DeHook({:?})", self.0.settings())
+    }
+}
+
+/// Saves the settings of differential evolution with the population.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_hook<G: Gene, S: serde::Serializer>(hook: &Option<DeHook<G>>, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    hook.as_ref().map(|DeHook(de)| de.settings()).serialize(serializer)
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_hook<'de, G: Gene, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<DeHook<G>>, D::Error> {
+    use serde::{de::Error, Deserialize};
+    match Option::<DifferentialEvolution>::deserialize(deserializer)? {
+        Some(de) => {
+            let hook: Box<dyn std::any::Any> = Box::new(DeHook::<f64>(Arc::new(de)));
+            hook.downcast::<DeHook<G>>().map(|hook| Some(*hook)).map_err(|_| D::Error::custom("differential evolution needs f64 genes"))
+        }
+        None => Ok(None),
+    }
+}
+
+impl Population<f64> {
+    /// Switches `next_generation` to differential evolution. Every step appends one trial
+    /// per individual; as soon as they are evaluated, best with
+    /// `evaluate_last(population_size, ..)`, each trial competes with its target and the
+    /// population is back to `population_size`. The selection type, parent count,
+    /// crossover and mutation are not used. Also sets real-valued operators with the
+    /// same bounds and re-initializes every individual uniformly within them, so restarts
+    /// and immigrants respect the bounds too. Needs at least 4 individuals and cant be
+    /// combined with another replacement mode, e.g. steady-state mode. The settings are
+    /// saved with the population, e.g. in a checkpoint.
+    ///
+    /// # Arguments
+    /// * `de` - The bounds, strategy and parameters.
+    pub fn set_differential_evolution(&mut self, de: DifferentialEvolution) {
+        self.check_replacement_mode("differential evolution");
+        if self.population_size < 4 {
+            panic!("differential evolution needs a population of at least 4");
+        }
+        let operators = RealOperators::new(de.bounds.clone(), RealMutation::Gaussian { sigma: 0.1 }, RealCrossover::Arithmetic);
+        self.set_real_operators(operators);
+        self.differential_evolution = Some(DeHook(Arc::new(de)));
+        self.de_pending = None;
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Goes back to the genetic algorithm, keeping the real-valued operators.
+    pub fn clear_differential_evolution(&mut self) {
+        self.differential_evolution = None;
+        self.de_pending = None;
+    }
+
+    pub fn get_differential_evolution(&self) -> Option<&DifferentialEvolution> {
+        self.differential_evolution.as_ref().map(|DeHook(de)| de.settings())
+    }
+
+    /// Returns the number of trials waiting for their evaluation to compete against their
+    /// targets, `None` when no step is pending.
+    pub fn get_de_pending(&self) -> Option<usize> {
+        self.de_pending
+    }
+
+    /// Appends a repaired trial for every individual of the sorted population. The trials
+    /// are only built from the current individuals, never from other trials, so the
+    /// population has to be back at `population_size` first.
+    pub(crate) fn de_step(&mut self) {
+        let de = match &self.differential_evolution {
+            Some(DeHook(de)) => de.clone(),
+            None => return,
+        };
+        let count = self.individuals.len();
+        for target in 0..count {
+            let mut trial = self.individuals[target].clone();
+            trial.genes.assign(de.trial(&self.individuals[..count], target, &mut self.rng));
+            trial.fitness = 0;
+            trial.violation = 0.0;
+            trial.objectives.clear();
+            trial.age += 1;
+            self.repair_individual(&mut trial);
+            self.individuals.push(trial);
+        }
+        self.de_pending = Some(count);
+    }
+
+    /// Replaces every target whose evaluated trial is at least as fit, by feasibility
+    /// first if enabled. A pending step is dropped if the population changed size since.
+    pub(crate) fn resolve_differential_evolution(&mut self) {
+        let count = match self.de_pending.take() {
+            Some(count) if self.individuals.len() == 2 * count => count,
+            _ => return,
+        };
+        let trials = self.individuals.split_off(count);
+        for (target, trial) in trials.into_iter().enumerate() {
+            let incumbent = &self.individuals[target];
+            let accepted = if self.feasibility_first {
+                constraints::compare_feasibility_first(&trial, incumbent) != Ordering::Less
+            } else {
+                trial.fitness >= incumbent.fitness
+            };
+            if accepted {
+                self.individuals[target] = trial;
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::builder::PopulationBuilder;
+    use super::*;
+
+    fn sphere(individual: &Individual<f64>) -> u64 {
+        let distance: f64 = individual.get_genes().iter().map(|gene| gene * gene).sum();
+        ((100.0 - distance) * 1e6) as u64
+    }
+
+    fn population(strategy: DeStrategy, seed: u64) -> Population<f64> {
+        let mut population = PopulationBuilder::<f64>::new().gene_length(3).size(20).parents(2).seed(seed).build().unwrap();
+        population.set_differential_evolution(DifferentialEvolution::new(vec![(-5.0, 5.0); 3], strategy, 0.6, 0.9));
+        population.evaluate(sphere);
+        population
+    }
+
+    #[test]
+    fn both_strategies_converge_on_the_sphere() {
+        for strategy in [DeStrategy::Rand1Bin, DeStrategy::Best1Bin] {
+            let mut population = population(strategy, 4);
+            let mut best = 0;
+            for _ in 0..100 {
+                population.next_generation();
+                population.evaluate_last(20, sphere);
+                assert_eq!(population.individuals.len(), 20);
+                let fittest = population.individuals.iter().map(|individual| individual.get_fitness()).max().unwrap();
+                assert!(fittest >= best, "{:?} lost its best individual", strategy);
+                best = fittest;
+            }
+            assert!(best > 99_990_000, "{:?} only reached {}", strategy, best);
+        }
+    }
+
+    #[test]
+    fn trial_replaces_target_only_when_at_least_as_fit() {
+        let mut population = population(DeStrategy::Rand1Bin, 9);
+        population.sort_individuals();
+        population.de_step();
+        let targets: Vec<Individual<f64>> = population.individuals[..20].to_vec();
+        let trials: Vec<Individual<f64>> = population.individuals[20..].to_vec();
+        for (i, target) in targets.iter().enumerate() {
+            let fitness = target.get_fitness();
+            let trial_fitness = match i % 3 {
+                0 => fitness + 1,
+                1 => fitness,
+                _ => fitness - 1,
+            };
+            population.individuals[20 + i].set_fitness(trial_fitness);
+        }
+        population.resolve_differential_evolution();
+        assert_eq!(population.individuals.len(), 20);
+        for i in 0..20 {
+            let expected = if i % 3 == 2 { &targets[i] } else { &trials[i] };
+            assert_eq!(population.individuals[i].get_genes(), expected.get_genes());
+        }
+    }
+
+    #[test]
+    fn refills_after_remove_worst() {
+        let mut population = population(DeStrategy::Rand1Bin, 2);
+        population.remove_worst(18);
+        for _ in 0..5 {
+            population.next_generation();
+            population.evaluate_last(20, sphere);
+            assert_eq!(population.individuals.len(), 20);
+            assert_eq!(population.get_de_pending(), None);
+        }
+    }
+}
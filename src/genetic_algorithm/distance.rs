@@ -0,0 +1,76 @@
+use super::{decoding::BinaryEncoding, gene::Gene, Individual};
+
+impl<G: Gene> Individual<G> {
+    /// Returns the number of loci at which the two genomes differ. Genomes of different
+    /// lengths also differ at every locus only the longer one has.
+    pub fn hamming_distance(&self, other: &Individual<G>) -> usize {
+        self.genes.count_differences(&other.genes)
+    }
+
+    /// Returns the indices of the loci at which the two genomes differ, in ascending order.
+    pub fn changed_loci(&self, other: &Individual<G>) -> Vec<usize> {
+        self.check_same_length(other);
+        (0..self.gene_length).filter(|&i| self.genes[i] != other.genes[i]).collect()
+    }
+
+    fn check_same_length(&self, other: &Individual<G>) {
+        if self.gene_length != other.gene_length {
+            panic!("cant compare gene lengths {} and {}", self.gene_length, other.gene_length);
+        }
+    }
+}
+
+impl Individual {
+    /// Returns the Euclidean distance between the real-valued decodings of two genomes,
+    /// see `decode_fixed_points`.
+    ///
+    /// # Arguments
+    /// * `other` - The individual to compare with.
+    /// * `bits` - The number of genes per value.
+    /// * `min` - The lower bound of every value.
+    /// * `max` - The upper bound of every value.
+    /// * `encoding` - How the gene groups are encoded.
+    pub fn euclidean_distance(&self, other: &Individual, bits: usize, min: f64, max: f64, encoding: BinaryEncoding) -> f64 {
+        self.check_same_length(other);
+        let a = self.decode_fixed_points(bits, min, max, encoding);
+        let b = other.decode_fixed_points(bits, min, max, encoding);
+        a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum::<f64>().sqrt()
+    }
+}
+
+impl Individual<f64> {
+    /// Returns the Euclidean distance between two real-valued genomes.
+    ///
+    /// # Arguments
+    /// * `other` - The individual to compare with, of the same gene length.
+    pub fn euclidean_distance(&self, other: &Individual<f64>) -> f64 {
+        self.check_same_length(other);
+        self.genes.iter().zip(other.genes.iter()).map(|(x, y)| (x - y) * (x - y)).sum::<f64>().sqrt()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn real_genomes_have_a_euclidean_distance() {
+        let a = Individual::from_genes(vec![0.0, 1.0, -2.0]);
+        let b = Individual::from_genes(vec![3.0, 5.0, -2.0]);
+        assert_eq!(a.euclidean_distance(&b), 5.0);
+        assert_eq!(b.euclidean_distance(&a), 5.0);
+        assert_eq!(a.euclidean_distance(&a), 0.0);
+    }
+
+    #[test]
+    fn fixed_point_genomes_have_a_euclidean_distance() {
+        let a = Individual::from_genes(vec![false; 16]);
+        let b = Individual::from_genes(vec![true; 16]);
+        assert_eq!(a.euclidean_distance(&b, 8, -1.0, 2.0, BinaryEncoding::Standard), 18.0f64.sqrt());
+    }
+
+    #[test]
+    #[should_panic(expected = "cant compare gene lengths 2 and 3")]
+    fn real_genomes_must_have_the_same_length() {
+        Individual::from_genes(vec![0.0, 1.0]).euclidean_distance(&Individual::from_genes(vec![0.0, 1.0, 2.0]));
+    }
+}
@@ -0,0 +1,118 @@
+use std::{collections::HashMap, fmt};
+
+use rand::{Rng, SeedableRng};
+
+use super::{
+    fitness_cache::{hash_genes, HashableGene},
+    gene::Gene,
+    rng::GaRng,
+    Population,
+};
+
+/// Duplicate elimination settings stored on a population.
+#[derive(Clone)]
+pub(crate) struct DuplicateElimination<G: Gene> {
+    attempts: usize,
+    hash: fn(&[G]) -> u64,
+    replaced: u64,
+}
+
+impl<G: Gene> fmt::Debug for DuplicateElimination<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DuplicateElimination {{ attempts: {}, replaced: {} }}", self.attempts, self.replaced)
+    }
+}
+
+impl<G: HashableGene> Population<G> {
+    /// Replaces every new individual of a generation that is genetically identical to
+    /// another one, by genome hash, with a mutant of itself, so the population doesn't
+    /// fill up with clones of the elite. Only the individuals a step created are
+    /// replaced, the survivors are never touched. A mutant is bred like a child of the
+    /// clone alone; if it is still a duplicate after `attempts` tries, a random
+    /// individual takes its place. Checkpoints save the settings and the number of
+    /// replaced duplicates.
+    ///
+    /// # Arguments
+    /// * `attempts` - How many mutants are tried before falling back to a random individual.
+    pub fn set_duplicate_elimination(&mut self, attempts: usize) {
+        self.duplicate_elimination = Some(DuplicateElimination {
+            attempts,
+            hash: hash_genes::<G>,
+            replaced: 0,
+        });
+    }
+
+    /// Sets duplicate elimination as saved in a checkpoint.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore_duplicate_elimination(&mut self, attempts: usize, replaced: u64) {
+        self.set_duplicate_elimination(attempts);
+        if let Some(elimination) = self.duplicate_elimination.as_mut() {
+            elimination.replaced = replaced;
+        }
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Stops replacing duplicates.
+    pub fn clear_duplicate_elimination(&mut self) {
+        self.duplicate_elimination = None;
+    }
+
+    /// Returns the number of mutants tried per duplicate, `None` without duplicate elimination.
+    pub fn get_duplicate_elimination(&self) -> Option<usize> {
+        self.duplicate_elimination.as_ref().map(|elimination| elimination.attempts)
+    }
+
+    /// Returns the number of duplicates replaced since duplicate elimination was set.
+    pub fn get_duplicates_replaced(&self) -> u64 {
+        self.duplicate_elimination.as_ref().map_or(0, |elimination| elimination.replaced)
+    }
+
+    /// Replaces the individuals among the last `fresh` whose genome already occurs
+    /// earlier in the population.
+    ///
+    /// # Arguments
+    /// * `fresh` - The number of individuals the step appended, the only ones that can be replaced.
+    pub(crate) fn eliminate_duplicates(&mut self, fresh: usize) {
+        let (attempts, hash) = match &self.duplicate_elimination {
+            Some(elimination) => (elimination.attempts, elimination.hash),
+            None => return,
+        };
+        let start = self.individuals.len() - fresh.min(self.individuals.len());
+        // the indices of the genomes seen so far by their hash, to tell collisions apart
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut replaced = 0;
+        for index in 0..self.individuals.len() {
+            let mut key = hash(&self.individuals[index].genes.as_slice());
+            if index >= start && self.is_seen(&seen, key, index) {
+                let clone = self.individuals[index].clone();
+                let mut unique = false;
+                for _ in 0..attempts {
+                    let mut rng = GaRng::seed_from_u64(self.rng.random());
+                    let mutant = self.create_child(&[&clone], &mut rng, clone.clone());
+                    key = hash(&mutant.genes.as_slice());
+                    self.individuals[index] = mutant;
+                    if !self.is_seen(&seen, key, index) {
+                        unique = true;
+                        break;
+                    }
+                }
+                if !unique {
+                    self.individuals[index] = self.random_individual(&clone);
+                    key = hash(&self.individuals[index].genes.as_slice());
+                }
+                replaced += 1;
+            }
+            seen.entry(key).or_default().push(index);
+        }
+        if let Some(elimination) = &mut self.duplicate_elimination {
+            elimination.replaced += replaced;
+        }
+    }
+
+    /// Returns whether the genome at `index` equals one of those seen with the same hash.
+    fn is_seen(&self, seen: &HashMap<u64, Vec<usize>>, key: u64, index: usize) -> bool {
+        let genes = &self.individuals[index].genes;
+        seen.get(&key).is_some_and(|indices| indices.iter().any(|&other| self.individuals[other].genes == *genes))
+    }
+}
@@ -0,0 +1,94 @@
+use std::{error::Error, fmt};
+
+use super::{CrossoverType, SelectionType};
+
+/// A population setting that can't work.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// The gene length is 0.
+    GeneLength,
+    /// The population size is 0.
+    PopulationSize,
+    /// The parent count is 0.
+    ParentCount,
+    /// There are more parents than individuals.
+    ParentsExceedPopulation { parent_count: usize, population_size: usize },
+    /// The mutation chance is above 100.
+    MutationChance(u8),
+    /// The mutation probability is outside 0.0-1.0.
+    MutationProbability(f64),
+    /// The crossover settings are invalid, e.g. a k-point crossover without cuts.
+    Crossover(CrossoverType),
+    /// The crossover rate is outside 0.0-1.0.
+    CrossoverRate(f64),
+    /// The selection settings are invalid, e.g. a tournament of size 0.
+    Selection(SelectionType),
+    /// More elites than individuals.
+    ElitismExceedsPopulation { elitism_count: usize, population_size: usize },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::GeneLength => write!(f, "the gene length cannot be less than 1"),
+            ConfigError::PopulationSize => write!(f, "population size must be 1 or more"),
+            ConfigError::ParentCount => write!(f, "parent count must be 1 or more"),
+            ConfigError::ParentsExceedPopulation { parent_count, population_size } => write!(
+                f,
+                "parent count cant be larger than population ({} > {})",
+                parent_count, population_size
+            ),
+            ConfigError::MutationChance(chance) => write!(f, "mutation chance cant be higher than 100 ({})", chance),
+            ConfigError::MutationProbability(probability) => {
+                write!(f, "mutation probability must be between 0 and 1 ({})", probability)
+            }
+            ConfigError::Crossover(CrossoverType::KPoint(_)) => write!(f, "k-point crossover needs at least 1 cut"),
+            ConfigError::Crossover(CrossoverType::Uniform { .. }) => {
+                write!(f, "uniform crossover parent bias must be between 0 and 1")
+            }
+            ConfigError::Crossover(CrossoverType::Segment { .. }) => {
+                write!(f, "segment crossover switch probability must be between 0 and 1")
+            }
+            ConfigError::Crossover(crossover) => write!(f, "invalid crossover {:?}", crossover),
+            ConfigError::CrossoverRate(rate) => write!(f, "crossover rate must be between 0 and 1 ({})", rate),
+            ConfigError::Selection(selection) => write!(f, "invalid selection {:?}", selection),
+            ConfigError::ElitismExceedsPopulation { elitism_count, population_size } => write!(
+                f,
+                "elitism count cant be larger than population ({} > {})",
+                elitism_count, population_size
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// An error of the genetic algorithm, for library users that need to handle failures
+/// instead of aborting the process.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GaError {
+    /// The population settings can't work.
+    Config(ConfigError),
+}
+
+impl fmt::Display for GaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GaError::Config(error) => write!(f, "invalid configuration: {}", error),
+        }
+    }
+}
+
+impl Error for GaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GaError::Config(error) => Some(error),
+        }
+    }
+}
+
+impl From<ConfigError> for GaError {
+    fn from(error: ConfigError) -> GaError {
+        GaError::Config(error)
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    cell::Cell,
+    error::Error,
+    fmt,
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use super::{
+    gene::Gene,
+    pool::{self, WorkerPool},
+    Individual, Population,
+};
+
+// the states of a timed evaluation
+const RUNNING: u8 = 0;
+const FINISHED: u8 = 1;
+const ABANDONED: u8 = 2;
+
+/// What to do when a fallible fitness function fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitnessErrorPolicy {
+    /// Stop evaluating and return the error.
+    Abort,
+    /// Give the individual this fitness and continue.
+    Penalize(u64),
+}
+
+/// A fitness function failed for the individual at `index`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluationError<E> {
+    pub index: usize,
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for EvaluationError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fitness evaluation of individual {} failed: {}", self.index, self.error)
+    }
+}
+
+impl<E: Error + 'static> Error for EvaluationError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Evaluates every individual with a fitness function. All evaluation methods apply
+    /// the registered constraints afterwards, see `Population::add_constraint`.
+    ///
+    /// # Arguments
+    /// * `fitness` - Returns the fitness of an individual.
+    pub fn evaluate<F: Fn(&Individual<G>) -> u64>(&mut self, fitness: F) {
+        let pending = self.apply_fitness_cache(0..self.individuals.len());
+        for &index in &pending {
+            let individual = &mut self.individuals[index];
+            individual.set_fitness(fitness(individual));
+        }
+        self.after_evaluation(0..self.individuals.len(), &pending, &fitness);
+    }
+
+    /// Evaluates only the last `count` individuals, the children of a steady-state step,
+    /// see `Population::set_steady_state`. The others keep their fitness, so an expensive
+    /// fitness function is only called for the new individuals.
+    ///
+    /// # Arguments
+    /// * `count` - The number of individuals at the end of the population to evaluate.
+    /// * `fitness` - Returns the fitness of an individual.
+    pub fn evaluate_last<F: Fn(&Individual<G>) -> u64>(&mut self, count: usize, fitness: F) {
+        let start = self.individuals.len().saturating_sub(count);
+        let pending = self.apply_fitness_cache(start..self.individuals.len());
+        for &index in &pending {
+            let individual = &mut self.individuals[index];
+            individual.set_fitness(fitness(individual));
+        }
+        self.after_evaluation(start..self.individuals.len(), &pending, &fitness);
+    }
+
+    /// Evaluates every individual with a fitness function, spreading the individuals
+    /// over one scoped thread per CPU, so the fitness function can borrow local data.
+    /// Worth it when evaluations are expensive.
+    ///
+    /// # Arguments
+    /// * `fitness` - Returns the fitness of an individual, called from several threads at once.
+    pub fn par_evaluate<F: Fn(&Individual<G>) -> u64 + Sync>(&mut self, fitness: F) {
+        let pending = self.apply_fitness_cache(0..self.individuals.len());
+        let mut targets: Vec<&mut Individual<G>> = self
+            .individuals
+            .iter_mut()
+            .enumerate()
+            .filter(|(index, _)| pending.binary_search(index).is_ok())
+            .map(|(_, individual)| individual)
+            .collect();
+        let thread_count = num_cpus::get().clamp(1, targets.len().max(1));
+        let chunk_size = targets.len().div_ceil(thread_count).max(1);
+        let fitness = &fitness;
+        thread::scope(|scope| {
+            for chunk in targets.chunks_mut(chunk_size) {
+                scope.spawn(move || {
+                    for individual in chunk.iter_mut() {
+                        individual.set_fitness(fitness(individual));
+                    }
+                });
+            }
+        });
+        self.after_evaluation(0..self.individuals.len(), &pending, fitness);
+    }
+
+    /// Evaluates every individual with a fitness function that can fail, e.g. one that
+    /// reads files or talks to a simulator, instead of having to panic inside it.
+    ///
+    /// # Arguments
+    /// * `fitness` - Returns the fitness of an individual or an error.
+    /// * `policy` - Whether an error aborts the evaluation or is penalized.
+    ///
+    /// # Returns
+    /// The number of failed evaluations, or the first error with `FitnessErrorPolicy::Abort`.
+    /// Individuals evaluated before an abort keep their new fitness.
+    pub fn try_evaluate<F, E>(&mut self, fitness: F, policy: FitnessErrorPolicy) -> Result<usize, EvaluationError<E>>
+    where
+        F: Fn(&Individual<G>) -> Result<u64, E>,
+    {
+        self.try_evaluate_range(0..self.individuals.len(), fitness, policy)
+    }
+
+    /// Evaluates only the last `count` individuals with a fitness function that can fail,
+    /// the fallible counterpart of `evaluate_last`.
+    ///
+    /// # Arguments
+    /// * `count` - The number of individuals at the end of the population to evaluate.
+    /// * `fitness` - Returns the fitness of an individual or an error.
+    /// * `policy` - Whether an error aborts the evaluation or is penalized.
+    ///
+    /// # Returns
+    /// The number of failed evaluations, or the first error with `FitnessErrorPolicy::Abort`.
+    pub fn try_evaluate_last<F, E>(
+        &mut self,
+        count: usize,
+        fitness: F,
+        policy: FitnessErrorPolicy,
+    ) -> Result<usize, EvaluationError<E>>
+    where
+        F: Fn(&Individual<G>) -> Result<u64, E>,
+    {
+        let start = self.individuals.len().saturating_sub(count);
+        self.try_evaluate_range(start..self.individuals.len(), fitness, policy)
+    }
+
+    fn try_evaluate_range<F, E>(
+        &mut self,
+        range: Range<usize>,
+        fitness: F,
+        policy: FitnessErrorPolicy,
+    ) -> Result<usize, EvaluationError<E>>
+    where
+        F: Fn(&Individual<G>) -> Result<u64, E>,
+    {
+        let mut failures = 0;
+        let mut evaluated = vec![];
+        for index in self.apply_fitness_cache(range.clone()) {
+            let individual = &mut self.individuals[index];
+            match fitness(individual) {
+                Ok(value) => {
+                    individual.set_fitness(value);
+                    evaluated.push(index);
+                }
+                Err(error) => match policy {
+                    FitnessErrorPolicy::Abort => {
+                        self.fill_fitness_cache(&evaluated);
+                        return Err(EvaluationError { index, error });
+                    }
+                    FitnessErrorPolicy::Penalize(penalty) => {
+                        individual.set_fitness(penalty);
+                        failures += 1;
+                    }
+                },
+            }
+        }
+        // failed evaluations aren't cached, they are retried next time; a failed local search
+        // candidate gets the penalty, or the lowest fitness with Abort, so it is never kept
+        let penalty = match policy {
+            FitnessErrorPolicy::Abort => 0,
+            FitnessErrorPolicy::Penalize(penalty) => penalty,
+        };
+        let refine = |individual: &Individual<G>| fitness(individual).unwrap_or(penalty);
+        self.after_evaluation(range, &evaluated, &refine);
+        Ok(failures)
+    }
+
+    /// Evaluates every individual with a time limit per evaluation, for fitness functions
+    /// backed by a simulator that may hang. Each evaluation runs on the shared worker pool
+    /// and an individual whose evaluation takes longer than `timeout` gets the penalty
+    /// fitness, as does a local search candidate. The time limit starts when a worker
+    /// picks the evaluation up, not while it waits in the pool's queue. A panic of the
+    /// fitness function is not a timeout, it is passed on to the caller.
+    /// A timed-out evaluation can't be cancelled, it finishes in the background and its
+    /// result is discarded. The pool starts another worker in its place meanwhile, so a
+    /// hanging evaluation doesn't hold up the others.
+    ///
+    /// # Arguments
+    /// * `fitness` - The fitness function.
+    /// * `timeout` - The wall-clock limit of one evaluation.
+    /// * `penalty` - The fitness of individuals whose evaluation timed out.
+    ///
+    /// # Returns
+    /// The number of timed-out evaluations, also added to `get_timeout_count`.
+    pub fn evaluate_with_timeout<F>(&mut self, fitness: F, timeout: Duration, penalty: u64) -> usize
+    where
+        F: Fn(&Individual<G>) -> u64 + Send + Sync + 'static,
+    {
+        let fitness = Arc::new(fitness);
+        let mut timeouts = 0;
+        let mut evaluated = vec![];
+        for index in self.apply_fitness_cache(0..self.individuals.len()) {
+            let individual = &mut self.individuals[index];
+            match evaluate_timed(&fitness, individual, timeout) {
+                Some(value) => {
+                    individual.set_fitness(value);
+                    evaluated.push(index);
+                }
+                None => {
+                    individual.set_fitness(penalty);
+                    timeouts += 1;
+                }
+            }
+        }
+        // local search candidates are held to the same time limit
+        let search_timeouts = Cell::new(0);
+        let refine = |individual: &Individual<G>| {
+            evaluate_timed(&fitness, individual, timeout).unwrap_or_else(|| {
+                search_timeouts.set(search_timeouts.get() + 1);
+                penalty
+            })
+        };
+        self.after_evaluation(0..self.individuals.len(), &evaluated, &refine);
+        self.timeouts += (timeouts + search_timeouts.get()) as u64;
+        timeouts
+    }
+
+    /// Returns how many fitness evaluations timed out over the whole run.
+    pub fn get_timeout_count(&self) -> u64 {
+        self.timeouts
+    }
+
+    /// Everything that follows the fitness function, shared by all evaluation methods:
+    /// caches the new fitness values, refines individuals with the local search, applies
+    /// the constraints, fitness sharing and speciation to the evaluated range and settles
+    /// a pending crowding, differential evolution or evolution strategy step.
+    ///
+    /// # Arguments
+    /// * `range` - The individuals that were evaluated.
+    /// * `evaluated` - The indices whose fitness came from the fitness function, the ones cached.
+    /// * `fitness` - Evaluates the local search candidates.
+    pub(crate) fn after_evaluation<F: Fn(&Individual<G>) -> u64>(&mut self, range: Range<usize>, evaluated: &[usize], fitness: &F) {
+        self.fill_fitness_cache(evaluated);
+        self.apply_local_search(range.clone(), fitness);
+        self.apply_constraints_to(range.clone());
+        self.apply_fitness_sharing_to(range.clone());
+        self.apply_speciation_to(range);
+        self.resolve_crowding();
+        self.resolve_differential_evolution();
+        self.resolve_evolution_strategy();
+    }
+}
+
+/// Evaluates one individual on the worker pool, `None` if it takes longer than `timeout`
+/// once started. Resumes the panic of a fitness function that panicked.
+fn evaluate_timed<G: Gene, F>(fitness: &Arc<F>, individual: &Individual<G>, timeout: Duration) -> Option<u64>
+where
+    F: Fn(&Individual<G>) -> u64 + Send + Sync + 'static,
+{
+    let pool = WorkerPool::global();
+    let (started_sender, started) = mpsc::channel();
+    let (sender, receiver) = mpsc::channel();
+    let fitness = Arc::clone(fitness);
+    let candidate = individual.clone();
+    // RUNNING until the evaluation either finishes or is given up on, whichever is first
+    let state = Arc::new(AtomicU8::new(RUNNING));
+    let job_state = Arc::clone(&state);
+    pool.execute(move || {
+        let _ = started_sender.send(());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| fitness(&candidate)));
+        if job_state.compare_exchange(RUNNING, FINISHED, Ordering::AcqRel, Ordering::Acquire).is_err() {
+            // given up on, a stand-in worker already took this one's place
+            pool::retire_worker();
+        }
+        let _ = sender.send(result);
+    });
+    // the clock only starts once a worker runs the evaluation
+    started.recv().expect("worker pool dropped a timed evaluation");
+    let result = match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            if state.compare_exchange(RUNNING, ABANDONED, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+                pool.replace_worker();
+                return None;
+            }
+            // finished right at the limit
+            receiver.recv().expect("worker pool dropped a timed evaluation")
+        }
+        Err(RecvTimeoutError::Disconnected) => panic!("worker pool dropped a timed evaluation"),
+    };
+    match result {
+        Ok(value) => Some(value),
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::super::{init_population_seeded, CrossoverType};
+    use super::*;
+
+    #[test]
+    fn par_evaluate_borrows_local_data() {
+        let target: Vec<bool> = (0..32).map(|i| i % 3 == 0).collect();
+        let fitness = |individual: &Individual| individual.get_genes().iter().zip(&target).filter(|(a, b)| a == b).count() as u64;
+        let mut parallel = init_population_seeded(32, 20, 4, 15, true, CrossoverType::Bit, 5);
+        let mut sequential = parallel.clone();
+        parallel.par_evaluate(fitness);
+        sequential.evaluate(fitness);
+        let fitnesses = |population: &Population| population.individuals.iter().map(Individual::get_fitness).collect::<Vec<_>>();
+        assert_eq!(fitnesses(&parallel), fitnesses(&sequential));
+    }
+
+    #[test]
+    fn slow_evaluations_get_the_penalty() {
+        let mut population = init_population_seeded(8, 6, 2, 15, false, CrossoverType::Bit, 3);
+        let slow = population.individuals.iter().filter(|individual| individual.get_genes()[0]).count();
+        assert!(slow > 0 && slow < 6);
+        let timeouts = population.evaluate_with_timeout(
+            |individual: &Individual| {
+                if individual.get_genes()[0] {
+                    thread::sleep(Duration::from_millis(300));
+                }
+                1
+            },
+            Duration::from_millis(50),
+            7,
+        );
+        assert_eq!(timeouts, slow);
+        for individual in &population.individuals {
+            assert_eq!(individual.get_fitness(), if individual.get_genes()[0] { 7 } else { 1 });
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "simulator crashed")]
+    fn timed_evaluation_passes_panics_on() {
+        let mut population = init_population_seeded(8, 4, 2, 15, false, CrossoverType::Bit, 3);
+        population.evaluate_with_timeout(|_: &Individual| panic!("simulator crashed"), Duration::from_secs(5), 0);
+    }
+}
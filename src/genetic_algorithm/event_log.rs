@@ -0,0 +1,88 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use super::{events::GaEvent, Population};
+
+/// Writes a run as JSON lines, one self-contained JSON object per generation or event,
+/// so analysis tools can tail the log during a run or ingest it afterwards.
+pub struct EventLog<W: Write> {
+    writer: W,
+}
+
+impl EventLog<BufWriter<File>> {
+    /// Creates (or truncates) a log file.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<EventLog<BufWriter<File>>> {
+        Ok(EventLog::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> EventLog<W> {
+    /// Creates a log that writes to any writer.
+    pub fn new(writer: W) -> EventLog<W> {
+        EventLog { writer }
+    }
+
+    /// Logs the state of an evaluated population, including the operator usage counts.
+    ///
+    /// # Arguments
+    /// * `population` - The evaluated population.
+    pub fn log_generation(&mut self, population: &Population) -> io::Result<()> {
+        let individuals = &population.individuals;
+        let best_fitness = individuals.iter().map(|individual| individual.fitness).max().unwrap_or(0);
+        let mean_fitness =
+            individuals.iter().map(|individual| individual.fitness as f64).sum::<f64>() / individuals.len() as f64;
+        let counts = population.get_operator_counts();
+        writeln!(
+            self.writer,
+            "{{\"type\":\"generation\",\"generation\":{},\"best_fitness\":{},\"mean_fitness\":{},\"diversity\":{},\
+\"population_size\":{},\"operators\":{{\"offspring\":{},\"mutations\":{},\"repairs\":{},\"restarts\":{},\"migrations\":{}}}}}",
+            population.generation,
+            best_fitness,
+            mean_fitness,
+            population.entropy(),
+            individuals.len(),
+            counts.offspring,
+            counts.mutations,
+            counts.repairs,
+            counts.restarts,
+            counts.migrations
+        )?;
+        self.writer.flush()
+    }
+
+    /// Logs an event, e.g. one received from `Population::subscribe`.
+    pub fn log_event(&mut self, event: &GaEvent) -> io::Result<()> {
+        match event {
+            GaEvent::GenerationCompleted { generation, best_fitness } => writeln!(
+                self.writer,
+                "{{\"type\":\"generation_completed\",\"generation\":{},\"best_fitness\":{}}}",
+                generation, best_fitness
+            )?,
+            GaEvent::NewBest { generation, individual } => {
+                let genes: String = individual.genes.iter().map(|&gene| if gene { '1' } else { '0' }).collect();
+                writeln!(
+                    self.writer,
+                    "{{\"type\":\"new_best\",\"generation\":{},\"individual\":{{\"fitness\":{},\"genes\":\"{}\"}}}}",
+                    generation, individual.fitness, genes
+                )?
+            }
+            GaEvent::Restart { generation } => {
+                writeln!(self.writer, "{{\"type\":\"restart\",\"generation\":{}}}", generation)?
+            }
+            GaEvent::Migration { generation, count } => writeln!(
+                self.writer,
+                "{{\"type\":\"migration\",\"generation\":{},\"count\":{}}}",
+                generation, count
+            )?,
+        }
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::{self, Receiver},
+};
+
+use super::{gene::Gene, Individual, Population};
+
+/// Something that happened during a run, sent to every subscriber of a population.
+#[derive(Debug, Clone)]
+pub enum GaEvent<G: Gene = bool> {
+    /// A generation was evaluated and is about to be replaced by its offspring.
+    GenerationCompleted { generation: u64, best_fitness: u64 },
+    /// A fitter individual than ever before was found.
+    NewBest { generation: u64, individual: Individual<G> },
+    /// Part of the population was re-initialized to restore diversity.
+    Restart { generation: u64 },
+    /// Individuals from elsewhere were inserted into the population.
+    Migration { generation: u64, count: usize },
+}
+
+/// How often each operator has been applied to a population.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OperatorCounts {
+    /// Children created by crossover.
+    pub offspring: u64,
+    /// Genes flipped while creating children.
+    pub mutations: u64,
+    /// Individuals passed through the repair operator.
+    pub repairs: u64,
+    /// Diversity restarts and cataclysms.
+    pub restarts: u64,
+    /// Individuals inserted by migration.
+    pub migrations: u64,
+}
+
+/// The live counters behind `OperatorCounts`, shared with the breeding threads.
+#[derive(Debug, Default)]
+pub(crate) struct OperatorCounters {
+    pub(crate) offspring: AtomicU64,
+    pub(crate) mutations: AtomicU64,
+    pub(crate) repairs: AtomicU64,
+    pub(crate) restarts: AtomicU64,
+    pub(crate) migrations: AtomicU64,
+}
+
+impl<G: Gene> Population<G> {
+    /// Returns how often each operator has been applied so far.
+    pub fn get_operator_counts(&self) -> OperatorCounts {
+        let counters = &self.operator_counters;
+        OperatorCounts {
+            offspring: counters.offspring.load(Ordering::Relaxed),
+            mutations: counters.mutations.load(Ordering::Relaxed),
+            repairs: counters.repairs.load(Ordering::Relaxed),
+            restarts: counters.restarts.load(Ordering::Relaxed),
+            migrations: counters.migrations.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Subscribes to the events of this population. Events are queued until received,
+    /// so a GUI or dashboard can observe a run from another thread without polling the
+    /// population itself. Dropping the receiver unsubscribes.
+    ///
+    /// # Returns
+    /// The receiving end of the event channel.
+    pub fn subscribe(&mut self) -> Receiver<GaEvent<G>> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Sends an event to every subscriber, forgetting subscribers that hung up.
+    pub(crate) fn emit(&mut self, event: GaEvent<G>) {
+        match event {
+            GaEvent::Restart { .. } => {
+                self.operator_counters.restarts.fetch_add(1, Ordering::Relaxed);
+            }
+            GaEvent::Migration { count, .. } => {
+                self.operator_counters.migrations.fetch_add(count as u64, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+        self.subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    /// Emits the events of a finished generation, the individuals must be sorted best first.
+    pub(crate) fn emit_generation_completed(&mut self) {
+        let best = &self.individuals[0];
+        let is_new_best = self.best_fitness_seen.is_none_or(|seen| best.fitness > seen);
+        let best_fitness = best.fitness;
+        if is_new_best {
+            self.best_fitness_seen = Some(best_fitness);
+            let individual = best.clone();
+            self.emit(GaEvent::NewBest {
+                generation: self.generation,
+                individual,
+            });
+        }
+        self.emit(GaEvent::GenerationCompleted {
+            generation: self.generation,
+            best_fitness,
+        });
+    }
+}
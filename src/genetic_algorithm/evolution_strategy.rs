@@ -0,0 +1,331 @@
+use std::{fmt, sync::Arc};
+
+use rand::Rng;
+
+use super::{
+    gene::Gene,
+    real::{standard_normal, RealCrossover, RealMutation, RealOperators},
+    rng::GaRng,
+    Individual, Population,
+};
+
+/// Which individuals an evolution strategy keeps after each step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EsSelection {
+    /// (μ, λ): the μ fittest offspring replace all parents, so a parent lives for one
+    /// generation only. Forgets lucky step sizes, needs λ ≥ μ.
+    Comma,
+    /// (μ + λ): the μ fittest of parents and offspring survive, elitist.
+    Plus,
+}
+
+/// Classic evolution strategy settings for real-valued genes. The population size is μ.
+/// Every step creates λ offspring, each a copy of a random parent mutated by adding
+/// normally distributed noise to every gene. Every individual carries one step size per
+/// gene, its strategy, which is mutated log-normally before the genes, so the step sizes
+/// adapt themselves to the landscape.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvolutionStrategy {
+    bounds: Vec<(f64, f64)>,
+    offspring: usize,
+    selection: EsSelection,
+    initial_step: f64,
+}
+
+impl EvolutionStrategy {
+    /// Creates new settings with initial step sizes of a tenth of the width of every
+    /// gene's bounds.
+    ///
+    /// # Arguments
+    /// * `bounds` - The inclusive `(min, max)` range of every gene, one per gene.
+    /// * `offspring` - λ, the number of offspring per step, 1 or more.
+    /// * `selection` - Whether the parents compete with their offspring.
+    pub fn new(bounds: Vec<(f64, f64)>, offspring: usize, selection: EsSelection) -> EvolutionStrategy {
+        if bounds.is_empty() {
+            panic!("the gene length cannot be less than 1");
+        }
+        if let Some((min, max)) = bounds.iter().find(|(min, max)| min > max || !min.is_finite() || !max.is_finite()) {
+            panic!("invalid gene bounds {}..={}", min, max);
+        }
+        if offspring < 1 {
+            panic!("an evolution strategy needs at least 1 offspring");
+        }
+        EvolutionStrategy {
+            bounds,
+            offspring,
+            selection,
+            initial_step: 0.1,
+        }
+    }
+
+    /// Sets the step size new individuals start with, as a fraction of the width of
+    /// every gene's bounds.
+    pub fn initial_step(mut self, fraction: f64) -> EvolutionStrategy {
+        if fraction.is_nan() || fraction <= 0.0 {
+            panic!("initial step size must be larger than 0");
+        }
+        self.initial_step = fraction;
+        self
+    }
+
+    pub fn get_bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    pub fn get_offspring(&self) -> usize {
+        self.offspring
+    }
+
+    pub fn get_selection(&self) -> EsSelection {
+        self.selection
+    }
+
+    pub fn get_initial_step(&self) -> f64 {
+        self.initial_step
+    }
+}
+
+/// Mutates offspring for a gene type, so the generic evolution loop can run evolution
+/// strategies, which only exist for `f64` genes.
+pub(crate) trait OffspringOperator<G: Gene>: Send + Sync {
+    fn settings(&self) -> &EvolutionStrategy;
+
+    /// Mutates a copy of a parent, step sizes first, into an offspring.
+    fn mutate(&self, offspring: &mut Individual<G>, rng: &mut GaRng);
+}
+
+impl OffspringOperator<f64> for EvolutionStrategy {
+    fn settings(&self) -> &EvolutionStrategy {
+        self
+    }
+
+    fn mutate(&self, offspring: &mut Individual<f64>, rng: &mut GaRng) {
+        let length = offspring.genes.len() as f64;
+        // Schwefel's learning rates for one step size per gene
+        let global_rate = 1.0 / (2.0 * length).sqrt();
+        let local_rate = 1.0 / (2.0 * length.sqrt()).sqrt();
+        if offspring.strategy.len() != offspring.genes.len() {
+            offspring.strategy = self.bounds.iter().map(|(min, max)| self.initial_step * (max - min)).collect();
+        }
+        let global = global_rate * standard_normal(rng);
+        for (i, gene) in offspring.genes.make_dense().iter_mut().enumerate() {
+            let (min, max) = self.bounds[i];
+            let width = max - min;
+            let step = (offspring.strategy[i] * (global + local_rate * standard_normal(rng)).exp()).clamp(width * 1e-12, width);
+            offspring.strategy[i] = step;
+            *gene = (*gene + step * standard_normal(rng)).clamp(min, max);
+        }
+    }
+}
+
+/// An evolution strategy stored on a population.
+#[derive(Clone)]
+pub(crate) struct EsHook<G: Gene>(Arc<dyn OffspringOperator<G>>);
+
+impl<G: Gene> fmt::Debug for EsHook<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EsHook({:?})", self.0.settings())
+    }
+}
+
+/// Saves the settings of the evolution strategy with the population.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_hook<G: Gene, S: serde::Serializer>(hook: &Option<EsHook<G>>, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    hook.as_ref().map(|EsHook(strategy)| strategy.settings()).serialize(serializer)
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_hook<'de, G: Gene, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<EsHook<G>>, D::Error> {
+    use serde::{de::Error, Deserialize};
+    match Option::<EvolutionStrategy>::deserialize(deserializer)? {
+        Some(strategy) => {
+            let hook: Box<dyn std::any::Any> = Box::new(EsHook::<f64>(Arc::new(strategy)));
+            hook.downcast::<EsHook<G>>().map(|hook| Some(*hook)).map_err(|_| D::Error::custom("evolution strategies need f64 genes"))
+        }
+        None => Ok(None),
+    }
+}
+
+impl Population<f64> {
+    /// Switches `next_generation` to an evolution strategy with the population size as μ.
+    /// Every step appends λ offspring; as soon as they are evaluated, best with
+    /// `evaluate_last(offspring, ..)`, the population is cut back to the μ survivors. The
+    /// selection type, parent count, crossover, mutation probability and elitism are not
+    /// used. Also sets real-valued operators with the same bounds and re-initializes every
+    /// individual uniformly within them, so restarts and immigrants respect the bounds
+    /// too. Cant be combined with another replacement mode, e.g. steady-state mode, or
+    /// with self-adaptation. The settings are saved with the population, e.g. in a
+    /// checkpoint.
+    ///
+    /// # Arguments
+    /// * `strategy` - The bounds, λ and the selection.
+    pub fn set_evolution_strategy(&mut self, strategy: EvolutionStrategy) {
+        self.check_replacement_mode("evolution strategies");
+        if self.self_adaptation.is_some() {
+            panic!("evolution strategies adapt their own step sizes and cant be combined with self-adaptation");
+        }
+        if strategy.selection == EsSelection::Comma && strategy.offspring < self.population_size {
+            panic!("(μ, λ) selection needs at least as many offspring as the population size");
+        }
+        let operators = RealOperators::new(strategy.bounds.clone(), RealMutation::Gaussian { sigma: 0.1 }, RealCrossover::Arithmetic);
+        self.set_real_operators(operators);
+        self.evolution_strategy = Some(EsHook(Arc::new(strategy)));
+        self.es_pending = None;
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Goes back to the genetic algorithm, keeping the real-valued operators. The step
+    /// sizes individuals carry are dropped.
+    pub fn clear_evolution_strategy(&mut self) {
+        self.evolution_strategy = None;
+        self.es_pending = None;
+        for individual in self.individuals.iter_mut() {
+            individual.strategy.clear();
+        }
+    }
+
+    pub fn get_evolution_strategy(&self) -> Option<&EvolutionStrategy> {
+        self.evolution_strategy.as_ref().map(|EsHook(strategy)| strategy.settings())
+    }
+
+    /// Returns the number of offspring waiting for their evaluation before survivors are
+    /// selected, `None` when no step is pending.
+    pub fn get_es_pending(&self) -> Option<usize> {
+        self.es_pending
+    }
+
+    /// Returns the mean step size of every gene over the population, empty unless the
+    /// individuals carry step sizes of an evolution strategy.
+    pub fn mean_step_sizes(&self) -> Vec<f64> {
+        if self.evolution_strategy.is_none() {
+            return vec![];
+        }
+        let carriers: Vec<&Vec<f64>> = self
+            .individuals
+            .iter()
+            .map(|individual| &individual.strategy)
+            .filter(|strategy| strategy.len() == self.individuals[0].gene_length)
+            .collect();
+        if carriers.is_empty() {
+            return vec![];
+        }
+        (0..carriers[0].len())
+            .map(|i| carriers.iter().map(|strategy| strategy[i]).sum::<f64>() / carriers.len() as f64)
+            .collect()
+    }
+
+    /// Appends λ repaired offspring of random parents.
+    pub(crate) fn es_step(&mut self) {
+        let strategy = match &self.evolution_strategy {
+            Some(EsHook(strategy)) => strategy.clone(),
+            None => return,
+        };
+        let count = strategy.settings().offspring;
+        let parents = self.individuals.len();
+        for _ in 0..count {
+            let mut offspring = self.individuals[self.rng.random_range(0..parents)].clone();
+            strategy.mutate(&mut offspring, &mut self.rng);
+            offspring.fitness = 0;
+            offspring.violation = 0.0;
+            offspring.objectives.clear();
+            offspring.age += 1;
+            self.repair_individual(&mut offspring);
+            self.individuals.push(offspring);
+        }
+        self.es_pending = Some(count);
+    }
+
+    /// Cuts the population back to the μ survivors of the evaluated offspring, and the
+    /// parents with (μ + λ) selection. Every individual before the offspring counts as a
+    /// parent, even if the population changed size since the step.
+    pub(crate) fn resolve_evolution_strategy(&mut self) {
+        let count = match self.es_pending.take() {
+            Some(count) if count <= self.individuals.len() => count,
+            _ => return,
+        };
+        let selection = match &self.evolution_strategy {
+            Some(EsHook(strategy)) => strategy.settings().selection,
+            None => return,
+        };
+        if selection == EsSelection::Comma {
+            self.individuals.drain(..self.individuals.len() - count);
+        }
+        self.sort_individuals();
+        self.individuals.truncate(self.population_size);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::builder::PopulationBuilder;
+    use super::*;
+
+    fn sphere(individual: &Individual<f64>) -> u64 {
+        let distance: f64 = individual.get_genes().iter().map(|gene| gene * gene).sum();
+        ((100.0 - distance) * 1e6) as u64
+    }
+
+    fn population(size: usize, offspring: usize, selection: EsSelection) -> Population<f64> {
+        let mut population = PopulationBuilder::<f64>::new().gene_length(3).size(size).parents(2).seed(8).build().unwrap();
+        population.set_evolution_strategy(EvolutionStrategy::new(vec![(-5.0, 5.0); 3], offspring, selection));
+        population.evaluate(sphere);
+        population
+    }
+
+    /// Runs one step in which every parent is fitter than any offspring.
+    fn step_with_fit_parents(population: &mut Population<f64>, offspring: usize) {
+        population.next_generation();
+        let parents = population.individuals.len() - offspring;
+        for individual in population.individuals[..parents].iter_mut() {
+            individual.set_fitness(u64::MAX);
+        }
+        population.evaluate_last(offspring, sphere);
+    }
+
+    #[test]
+    fn comma_selection_replaces_every_parent() {
+        let mut population = population(5, 10, EsSelection::Comma);
+        step_with_fit_parents(&mut population, 10);
+        assert_eq!(population.individuals.len(), 5);
+        assert!(population.individuals.iter().all(|individual| individual.get_fitness() < u64::MAX));
+    }
+
+    #[test]
+    fn plus_selection_keeps_fitter_parents() {
+        let mut population = population(5, 10, EsSelection::Plus);
+        step_with_fit_parents(&mut population, 10);
+        assert_eq!(population.individuals.len(), 5);
+        assert!(population.individuals.iter().all(|individual| individual.get_fitness() == u64::MAX));
+    }
+
+    #[test]
+    fn step_sizes_shrink_near_the_optimum() {
+        let mut population = population(10, 20, EsSelection::Plus);
+        let initial = population.mean_step_sizes();
+        assert!(initial.iter().all(|&step| (step - 1.0).abs() < 1e-9));
+        for _ in 0..200 {
+            population.next_generation();
+            population.evaluate_last(20, sphere);
+        }
+        assert!(population.individuals[0].get_fitness() > 99_990_000);
+        assert!(population.mean_step_sizes().iter().all(|&step| step < 0.1), "{:?}", population.mean_step_sizes());
+    }
+
+    #[test]
+    fn keeps_population_size_after_remove_worst() {
+        let mut population = population(10, 5, EsSelection::Plus);
+        population.remove_worst(3);
+        for _ in 0..5 {
+            population.next_generation();
+            population.evaluate_last(5, sphere);
+            assert_eq!(population.individuals.len(), 10);
+        }
+        population.next_generation();
+        population.remove_worst(3);
+        population.evaluate_last(5, sphere);
+        assert_eq!(population.individuals.len(), 10);
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+use super::{gene::Gene, Population};
+
+/// A gene type whose genomes can be used as fitness cache keys. Every gene feeds its
+/// exact value to the hasher, so genomes that differ in any gene hash differently.
+pub trait HashableGene: Gene {
+    fn hash_gene<H: Hasher>(&self, state: &mut H);
+}
+
+impl HashableGene for bool {
+    fn hash_gene<H: Hasher>(&self, state: &mut H) {
+        self.hash(state);
+    }
+}
+
+impl HashableGene for u8 {
+    fn hash_gene<H: Hasher>(&self, state: &mut H) {
+        self.hash(state);
+    }
+}
+
+impl HashableGene for char {
+    fn hash_gene<H: Hasher>(&self, state: &mut H) {
+        self.hash(state);
+    }
+}
+
+/// Hashes the bit pattern, so 0.0 and -0.0 are different keys.
+impl HashableGene for f64 {
+    fn hash_gene<H: Hasher>(&self, state: &mut H) {
+        self.to_bits().hash(state);
+    }
+}
+
+impl HashableGene for i64 {
+    fn hash_gene<H: Hasher>(&self, state: &mut H) {
+        self.hash(state);
+    }
+}
+
+impl HashableGene for usize {
+    fn hash_gene<H: Hasher>(&self, state: &mut H) {
+        self.hash(state);
+    }
+}
+
+pub(crate) fn hash_genes<G: HashableGene>(genes: &[G]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for gene in genes {
+        gene.hash_gene(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// A cached fitness with the genome it belongs to, to tell hash collisions apart.
+#[derive(Clone)]
+struct Entry<G: Gene> {
+    genes: Vec<G>,
+    fitness: u64,
+    last_used: u64,
+}
+
+/// Remembers the fitness of recently evaluated genomes, evicting the least recently
+/// used one when full.
+#[derive(Clone)]
+pub(crate) struct FitnessCache<G: Gene> {
+    capacity: usize,
+    hash: fn(&[G]) -> u64,
+    entries: HashMap<u64, Entry<G>>,
+    // the hash of every entry by the tick it was last used at, oldest first
+    recency: BTreeMap<u64, u64>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl<G: Gene> fmt::Debug for FitnessCache<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "FitnessCache {{ capacity: {}, len: {}, hits: {}, misses: {} }}",
+            self.capacity,
+            self.entries.len(),
+            self.hits,
+            self.misses
+        )
+    }
+}
+
+impl<G: Gene> FitnessCache<G> {
+    /// Returns the cached fitness of a genome and counts the hit or miss.
+    fn get(&mut self, genes: &[G]) -> Option<u64> {
+        let key = (self.hash)(genes);
+        match self.entries.get_mut(&key) {
+            Some(entry) if entry.genes == genes => {
+                self.recency.remove(&entry.last_used);
+                self.tick += 1;
+                entry.last_used = self.tick;
+                self.recency.insert(self.tick, key);
+                self.hits += 1;
+                Some(entry.fitness)
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Caches the fitness of a genome, evicting the least recently used entry if full.
+    /// A colliding entry of another genome is replaced.
+    fn insert(&mut self, genes: &[G], fitness: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = (self.hash)(genes);
+        if let Some(old) = self.entries.remove(&key) {
+            self.recency.remove(&old.last_used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.recency.insert(self.tick, key);
+        self.entries.insert(
+            key,
+            Entry {
+                genes: genes.to_vec(),
+                fitness,
+                last_used: self.tick,
+            },
+        );
+    }
+}
+
+/// A fitness cache as saved in a checkpoint, every entry with the tick it was last used
+/// at. The keys aren't saved, they are hashed again on load.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "G: serde::Serialize", deserialize = "G: serde::Deserialize<'de>"))]
+pub(crate) struct SavedCache<G> {
+    capacity: usize,
+    entries: Vec<(u64, Vec<G>, u64)>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+#[cfg(feature = "serde")]
+impl<G: HashableGene> FitnessCache<G> {
+    pub(crate) fn save(&self) -> SavedCache<G> {
+        SavedCache {
+            capacity: self.capacity,
+            entries: self.recency.values().map(|key| &self.entries[key]).map(|entry| (entry.last_used, entry.genes.clone(), entry.fitness)).collect(),
+            tick: self.tick,
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+
+    pub(crate) fn restore(saved: SavedCache<G>) -> FitnessCache<G> {
+        let mut cache = FitnessCache {
+            capacity: saved.capacity,
+            hash: hash_genes::<G>,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: saved.tick,
+            hits: saved.hits,
+            misses: saved.misses,
+        };
+        for (last_used, genes, fitness) in saved.entries {
+            let key = hash_genes(&genes);
+            cache.recency.insert(last_used, key);
+            cache.entries.insert(key, Entry { genes, fitness, last_used });
+        }
+        cache
+    }
+}
+
+impl<G: HashableGene> Population<G> {
+    /// Caches the fitness of evaluated genomes so identical individuals, common late in
+    /// a run, aren't evaluated again. Only for deterministic fitness functions: a cached
+    /// genome keeps the fitness of its first evaluation. The constraints are still applied
+    /// to cached individuals. Replaces an existing cache, the hits and misses start at 0.
+    /// Checkpoints save the cached genomes along with the counts.
+    ///
+    /// # Arguments
+    /// * `capacity` - The number of genomes to remember, the least recently used is evicted first.
+    pub fn set_fitness_cache(&mut self, capacity: usize) {
+        self.fitness_cache = Some(FitnessCache {
+            capacity,
+            hash: hash_genes::<G>,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        });
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Removes the fitness cache, every individual is evaluated again.
+    pub fn clear_fitness_cache(&mut self) {
+        self.fitness_cache = None;
+    }
+
+    /// Returns the number of cache hits and misses since the cache was set, both 0 without one.
+    pub fn get_fitness_cache_counts(&self) -> (u64, u64) {
+        match &self.fitness_cache {
+            Some(cache) => (cache.hits, cache.misses),
+            None => (0, 0),
+        }
+    }
+
+    /// Gives every individual of the range whose genome is cached its cached fitness.
+    ///
+    /// # Returns
+    /// The indices of the individuals that still have to be evaluated, the whole range without a cache.
+    pub(crate) fn apply_fitness_cache(&mut self, range: Range<usize>) -> Vec<usize> {
+        let cache = match &mut self.fitness_cache {
+            Some(cache) => cache,
+            None => return range.collect(),
+        };
+        let mut pending = vec![];
+        for index in range {
+            let individual = &mut self.individuals[index];
+            match cache.get(&individual.genes.as_slice()) {
+                Some(fitness) => individual.fitness = fitness,
+                None => pending.push(index),
+            }
+        }
+        pending
+    }
+
+    /// Caches the fitness of the given, freshly evaluated individuals.
+    pub(crate) fn fill_fitness_cache(&mut self, evaluated: &[usize]) {
+        if let Some(cache) = &mut self.fitness_cache {
+            for &index in evaluated {
+                let individual = &self.individuals[index];
+                cache.insert(&individual.genes.as_slice(), individual.fitness);
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(capacity: usize, hash: fn(&[u8]) -> u64) -> FitnessCache<u8> {
+        FitnessCache {
+            capacity,
+            hash,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used_genome() {
+        let mut cache = cache(2, hash_genes::<u8>);
+        cache.insert(&[1], 10);
+        cache.insert(&[2], 20);
+        assert_eq!(cache.get(&[1]), Some(10));
+        cache.insert(&[3], 30);
+        assert_eq!(cache.get(&[2]), None);
+        assert_eq!(cache.get(&[1]), Some(10));
+        assert_eq!(cache.get(&[3]), Some(30));
+        cache.insert(&[3], 31);
+        cache.insert(&[4], 40);
+        assert_eq!(cache.get(&[1]), None);
+        assert_eq!(cache.get(&[3]), Some(31));
+        assert_eq!(cache.get(&[4]), Some(40));
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.recency.len(), 2);
+        assert_eq!((cache.hits, cache.misses), (5, 2));
+    }
+
+    #[test]
+    fn colliding_genomes_are_told_apart() {
+        let mut cache = cache(4, |_| 0);
+        cache.insert(&[1], 10);
+        assert_eq!(cache.get(&[2]), None);
+        cache.insert(&[2], 20);
+        assert_eq!(cache.get(&[1]), None);
+        assert_eq!(cache.get(&[2]), Some(20));
+    }
+
+    #[test]
+    fn zero_capacity_caches_nothing() {
+        let mut cache = cache(0, hash_genes::<u8>);
+        cache.insert(&[1], 10);
+        assert_eq!(cache.get(&[1]), None);
+    }
+}
@@ -0,0 +1,83 @@
+use rand::Rng;
+use std::fmt;
+
+/// A single locus of a genome. `Individual<G>` and `Population<G>` work with any gene
+/// type that knows how to create a random value of itself and how to mutate itself, so
+/// selection, crossover and the generation loop are shared by all gene types. Without a
+/// type parameter they default to `bool`, the bit strings all other modules work with.
+pub trait Gene: Clone + PartialEq + fmt::Debug + Send + Sync + 'static {
+    /// Returns a random gene, used to initialize individuals.
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self;
+
+    /// Returns a mutated copy of the gene.
+    fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> Self;
+}
+
+/// A bit, mutation flips it.
+impl Gene for bool {
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> bool {
+        rng.random_bool(0.5)
+    }
+
+    fn mutate<R: Rng + ?Sized>(&self, _rng: &mut R) -> bool {
+        !self
+    }
+}
+
+/// Any byte, mutation replaces it with another random byte.
+impl Gene for u8 {
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> u8 {
+        rng.random()
+    }
+
+    fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> u8 {
+        rng.random()
+    }
+}
+
+/// A printable ASCII character, mutation replaces it with another one.
+impl Gene for char {
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> char {
+        rng.random_range(' '..='~')
+    }
+
+    fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> char {
+        rng.random_range(' '..='~')
+    }
+}
+
+/// A real value in `[0, 1]`, mutation moves it by up to 0.1 in either direction.
+/// Scale it to the problem's range in the fitness function.
+impl Gene for f64 {
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+        rng.random_range(0.0..=1.0)
+    }
+
+    fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        (self + rng.random_range(-0.1..=0.1)).clamp(0.0, 1.0)
+    }
+}
+
+/// Any integer, mutation replaces it with another random one. Restrict it to a range
+/// with `integer::IntegerOperators`.
+impl Gene for i64 {
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> i64 {
+        rng.random()
+    }
+
+    fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> i64 {
+        rng.random()
+    }
+}
+
+/// An index, mutation replaces it with another random one. Evolve orderings of
+/// `0..gene_length` with `permutation::PermutationOperators`.
+impl Gene for usize {
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> usize {
+        rng.random::<u64>() as usize
+    }
+
+    fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        rng.random::<u64>() as usize
+    }
+}
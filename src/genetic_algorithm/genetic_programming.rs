@@ -0,0 +1,491 @@
+use std::{fmt, sync::Arc};
+
+use rand::Rng;
+
+use super::{rng::GaRng, variation::Variation, Individual, Population};
+
+type Function<T> = dyn Fn(&[T]) -> T + Send + Sync;
+
+/// What a node of an expression tree does.
+#[derive(Clone)]
+enum Primitive<T> {
+    Function(usize, Arc<Function<T>>),
+    Variable(usize),
+    Constant(T),
+}
+
+/// The functions and terminals expression trees are built from. A tree is stored in
+/// prefix order, every gene the id of a node, a function followed by its arguments, in
+/// a `Population<usize>` whose gene length is the largest tree size. Genes after the end
+/// of the tree are unused. Every gene vector decodes to a valid tree: ids wrap around
+/// the number of primitives and arguments missing at the end read as the first terminal.
+#[derive(Clone)]
+pub struct PrimitiveSet<T> {
+    names: Vec<String>,
+    primitives: Vec<Primitive<T>>,
+    variables: usize,
+}
+
+impl<T: Clone + Send + Sync + 'static> Default for PrimitiveSet<T> {
+    fn default() -> PrimitiveSet<T> {
+        PrimitiveSet::new()
+    }
+}
+
+impl<T> fmt::Debug for PrimitiveSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PrimitiveSet({})", self.names.join(", "))
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> PrimitiveSet<T> {
+    /// Creates an empty set, add at least one terminal before building trees.
+    pub fn new() -> PrimitiveSet<T> {
+        PrimitiveSet {
+            names: vec![],
+            primitives: vec![],
+            variables: 0,
+        }
+    }
+
+    /// Adds a function node.
+    ///
+    /// # Arguments
+    /// * `name` - The name trees are printed with.
+    /// * `arity` - The number of arguments, 1 or more.
+    /// * `function` - Computes the node's value from the values of its arguments, in order.
+    pub fn add_function<F: Fn(&[T]) -> T + Send + Sync + 'static>(mut self, name: &str, arity: usize, function: F) -> PrimitiveSet<T> {
+        if arity < 1 {
+            panic!("a function needs at least 1 argument, add a terminal instead");
+        }
+        self.names.push(name.to_string());
+        self.primitives.push(Primitive::Function(arity, Arc::new(function)));
+        self
+    }
+
+    /// Adds a terminal that reads the next input, the first variable added reads
+    /// `inputs[0]` when evaluating.
+    pub fn add_variable(mut self, name: &str) -> PrimitiveSet<T> {
+        self.names.push(name.to_string());
+        self.primitives.push(Primitive::Variable(self.variables));
+        self.variables += 1;
+        self
+    }
+
+    /// Adds a terminal with a fixed value.
+    pub fn add_constant(mut self, name: &str, value: T) -> PrimitiveSet<T> {
+        self.names.push(name.to_string());
+        self.primitives.push(Primitive::Constant(value));
+        self
+    }
+
+    /// Returns the number of inputs `evaluate` expects.
+    pub fn get_variable_count(&self) -> usize {
+        self.variables
+    }
+
+    /// Returns the name of every node id.
+    pub fn get_names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Computes the value of a tree.
+    ///
+    /// # Arguments
+    /// * `genes` - The tree in prefix order.
+    /// * `inputs` - The values of the variables, in the order they were added.
+    pub fn evaluate(&self, genes: &[usize], inputs: &[T]) -> T {
+        if inputs.len() < self.variables {
+            panic!("{} inputs for {} variables", inputs.len(), self.variables);
+        }
+        self.evaluate_at(genes, &mut 0, inputs)
+    }
+
+    fn evaluate_at(&self, genes: &[usize], position: &mut usize, inputs: &[T]) -> T {
+        let node = self.node(genes, *position);
+        *position += 1;
+        match &self.primitives[node] {
+            Primitive::Function(arity, function) => {
+                let arguments: Vec<T> = (0..*arity).map(|_| self.evaluate_at(genes, position, inputs)).collect();
+                function(&arguments)
+            }
+            Primitive::Variable(index) => inputs[*index].clone(),
+            Primitive::Constant(value) => value.clone(),
+        }
+    }
+
+    /// Returns a tree as a one-line expression, e.g. `add(x, mul(x, 2))`.
+    pub fn format(&self, genes: &[usize]) -> String {
+        let mut text = String::new();
+        self.format_at(genes, &mut 0, &mut text);
+        text
+    }
+
+    fn format_at(&self, genes: &[usize], position: &mut usize, text: &mut String) {
+        let node = self.node(genes, *position);
+        *position += 1;
+        text.push_str(&self.names[node]);
+        let arity = self.arity(node);
+        if arity > 0 {
+            text.push('(');
+            for i in 0..arity {
+                if i > 0 {
+                    text.push_str(", ");
+                }
+                self.format_at(genes, position, text);
+            }
+            text.push(')');
+        }
+    }
+
+    /// Returns a tree with one node per line, arguments indented below their function.
+    pub fn format_tree(&self, genes: &[usize]) -> String {
+        let mut text = String::new();
+        self.format_tree_at(genes, &mut 0, 0, &mut text);
+        text
+    }
+
+    fn format_tree_at(&self, genes: &[usize], position: &mut usize, depth: usize, text: &mut String) {
+        let node = self.node(genes, *position);
+        *position += 1;
+        text.push_str(&format!("{}{}\n", "  ".repeat(depth), self.names[node]));
+        for _ in 0..self.arity(node) {
+            self.format_tree_at(genes, position, depth + 1, text);
+        }
+    }
+
+    /// Returns the number of nodes of a tree, at most the number of genes.
+    pub fn tree_size(&self, genes: &[usize]) -> usize {
+        self.subtree_end(genes, 0)
+    }
+
+    /// Returns the depth of a tree, 0 for a single terminal.
+    pub fn tree_depth(&self, genes: &[usize]) -> usize {
+        let mut depth = 0;
+        // the number of arguments still missing on every level of the current path
+        let mut missing: Vec<usize> = vec![];
+        for position in 0..self.tree_size(genes) {
+            depth = depth.max(missing.len());
+            if let Some(last) = missing.last_mut() {
+                *last -= 1;
+            }
+            let arity = self.arity(self.node(genes, position));
+            if arity > 0 {
+                missing.push(arity);
+            }
+            while missing.last() == Some(&0) {
+                missing.pop();
+            }
+        }
+        depth
+    }
+
+    /// Returns the node id at a position, wrapped around the number of primitives and
+    /// the first terminal past the end of the genes.
+    fn node(&self, genes: &[usize], position: usize) -> usize {
+        match genes.get(position) {
+            Some(gene) => gene % self.primitives.len(),
+            None => self.terminals()[0],
+        }
+    }
+
+    fn arity(&self, node: usize) -> usize {
+        match &self.primitives[node] {
+            Primitive::Function(arity, _) => *arity,
+            _ => 0,
+        }
+    }
+
+    fn terminals(&self) -> Vec<usize> {
+        (0..self.primitives.len()).filter(|&node| self.arity(node) == 0).collect()
+    }
+
+    fn functions(&self) -> Vec<usize> {
+        (0..self.primitives.len()).filter(|&node| self.arity(node) > 0).collect()
+    }
+
+    /// Returns the end (exclusive) of the subtree starting at a position, capped at the
+    /// number of genes.
+    fn subtree_end(&self, genes: &[usize], start: usize) -> usize {
+        let mut missing = 1;
+        let mut position = start;
+        while missing > 0 && position < genes.len() {
+            missing += self.arity(self.node(genes, position));
+            missing -= 1;
+            position += 1;
+        }
+        position
+    }
+
+    /// Appends a random tree of at most the given depth: every branch reaches it with
+    /// `full`, branches end at random with grow.
+    fn random_tree(&self, depth: usize, full: bool, rng: &mut GaRng, genes: &mut Vec<usize>) {
+        let (functions, terminals) = (self.functions(), self.terminals());
+        let total = functions.len() + terminals.len();
+        let function = depth > 0 && !functions.is_empty() && (full || rng.random_range(0..total) < functions.len());
+        if !function {
+            genes.push(terminals[rng.random_range(0..terminals.len())]);
+            return;
+        }
+        let node = functions[rng.random_range(0..functions.len())];
+        genes.push(node);
+        for _ in 0..self.arity(node) {
+            self.random_tree(depth - 1, full, rng, genes);
+        }
+    }
+}
+
+/// Genetic programming operators for a `Population<usize>` of expression trees over a
+/// `PrimitiveSet`: ramped half-and-half initialization, subtree crossover, and point and
+/// subtree mutation, all within a depth limit and the gene length as size limit.
+#[derive(Debug, Clone)]
+pub struct GpOperators<T> {
+    primitives: PrimitiveSet<T>,
+    max_depth: usize,
+    init_depth: (usize, usize),
+    subtree_mutation: f64,
+}
+
+impl<T: Clone + Send + Sync + 'static> GpOperators<T> {
+    /// Creates operators that initialize trees of depth 2 up to 6 (or the limit) and
+    /// replace a random subtree of one child in ten.
+    ///
+    /// # Arguments
+    /// * `primitives` - The functions and terminals, with at least one terminal.
+    /// * `max_depth` - The largest depth a tree may grow to, 0 allows single terminals only.
+    pub fn new(primitives: PrimitiveSet<T>, max_depth: usize) -> GpOperators<T> {
+        if primitives.terminals().is_empty() {
+            panic!("a primitive set needs at least 1 terminal");
+        }
+        GpOperators {
+            primitives,
+            max_depth,
+            init_depth: (2.min(max_depth), 6.min(max_depth)),
+            subtree_mutation: 0.1,
+        }
+    }
+
+    /// Sets the range of depths new trees are ramped over.
+    pub fn init_depth(mut self, min: usize, max: usize) -> GpOperators<T> {
+        if min > max || max > self.max_depth {
+            panic!("initial depths must satisfy min <= max <= max depth");
+        }
+        self.init_depth = (min, max);
+        self
+    }
+
+    /// Sets the probability (0.0-1.0) that a child gets a random subtree replaced by a new one.
+    pub fn subtree_mutation(mut self, probability: f64) -> GpOperators<T> {
+        if !(0.0..=1.0).contains(&probability) {
+            panic!("subtree mutation probability must be between 0 and 1");
+        }
+        self.subtree_mutation = probability;
+        self
+    }
+
+    pub fn get_primitives(&self) -> &PrimitiveSet<T> {
+        &self.primitives
+    }
+
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn get_init_depth(&self) -> (usize, usize) {
+        self.init_depth
+    }
+
+    pub fn get_subtree_mutation(&self) -> f64 {
+        self.subtree_mutation
+    }
+
+    /// Returns true if a tree fits the depth limit and the gene length.
+    fn fits(&self, tree: &[usize], gene_length: usize) -> bool {
+        tree.len() <= gene_length && self.primitives.tree_depth(tree) <= self.max_depth
+    }
+
+    /// Pads a tree with the first terminal up to the gene length.
+    fn pad(&self, mut tree: Vec<usize>, gene_length: usize) -> Vec<usize> {
+        tree.resize(gene_length, self.primitives.terminals()[0]);
+        tree
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Variation<usize> for GpOperators<T> {
+    fn random_genes(&self, gene_length: usize, rng: &mut GaRng) -> Vec<usize> {
+        let (min, max) = self.init_depth;
+        for _ in 0..10 {
+            let mut tree = vec![];
+            self.primitives.random_tree(rng.random_range(min..=max), rng.random_bool(0.5), rng, &mut tree);
+            if tree.len() <= gene_length {
+                return self.pad(tree, gene_length);
+            }
+        }
+        let terminals = self.primitives.terminals();
+        self.pad(vec![terminals[rng.random_range(0..terminals.len())]], gene_length)
+    }
+
+    /// Replaces a random subtree of one parent with a random subtree of another, trying
+    /// again a few times if the child would exceed a limit and copying the first parent
+    /// if it keeps doing so.
+    fn crossover(&self, parents: &[&Individual<usize>], rng: &mut GaRng) -> Vec<usize> {
+        let first = parents[rng.random_range(0..parents.len())].genes.as_slice();
+        let second = parents[rng.random_range(0..parents.len())].genes.as_slice();
+        let primitives = &self.primitives;
+        let (first_size, second_size) = (primitives.tree_size(&first), primitives.tree_size(&second));
+        for _ in 0..5 {
+            let cut = rng.random_range(0..first_size);
+            let cut_end = primitives.subtree_end(&first, cut);
+            let graft = rng.random_range(0..second_size);
+            let graft_end = primitives.subtree_end(&second, graft);
+            let mut child = first[..cut].to_vec();
+            child.extend_from_slice(&second[graft..graft_end]);
+            child.extend_from_slice(&first[cut_end..first_size]);
+            if self.fits(&child, first.len()) {
+                return self.pad(child, first.len());
+            }
+        }
+        first.into_owned()
+    }
+
+    /// Point mutation replaces every node with the mutation probability by another of the
+    /// same arity, then subtree mutation may replace a random subtree by a grown one.
+    fn mutate(&self, genes: &mut [usize], mutation_probability: f64, rng: &mut GaRng) -> u64 {
+        let primitives = &self.primitives;
+        let mut mutations = 0;
+        let size = primitives.tree_size(genes);
+        for position in 0..size {
+            if !rng.random_bool(mutation_probability) {
+                continue;
+            }
+            let arity = primitives.arity(primitives.node(genes, position));
+            let peers: Vec<usize> = (0..primitives.primitives.len()).filter(|&node| primitives.arity(node) == arity).collect();
+            genes[position] = peers[rng.random_range(0..peers.len())];
+            mutations += 1;
+        }
+        if rng.random_bool(self.subtree_mutation) {
+            let cut = rng.random_range(0..size);
+            let cut_end = primitives.subtree_end(genes, cut);
+            let mut replacement = vec![];
+            primitives.random_tree(rng.random_range(0..=self.init_depth.1), false, rng, &mut replacement);
+            let mut child = genes[..cut].to_vec();
+            child.append(&mut replacement);
+            child.extend_from_slice(&genes[cut_end..size]);
+            if self.fits(&child, genes.len()) {
+                genes.copy_from_slice(&self.pad(child, genes.len()));
+                mutations += 1;
+            }
+        }
+        mutations
+    }
+}
+
+impl Population<usize> {
+    /// Evolves the population as expression trees with genetic programming operators and
+    /// re-initializes every individual with ramped half-and-half, resetting its fitness.
+    /// The gene length is the largest tree size. Evaluate trees with
+    /// `PrimitiveSet::evaluate` on the genes and print them with `PrimitiveSet::format`.
+    ///
+    /// # Arguments
+    /// * `operators` - The primitives and limits.
+    pub fn set_gp_operators<T: Clone + Send + Sync + 'static>(&mut self, operators: GpOperators<T>) {
+        self.set_variation(operators);
+        self.reinitialize_individuals();
+    }
+}
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    // node ids: add 0, mul 1, x 2, two 3
+    fn primitives() -> PrimitiveSet<f64> {
+        PrimitiveSet::new()
+            .add_function("add", 2, |args: &[f64]| args[0] + args[1])
+            .add_function("mul", 2, |args: &[f64]| args[0] * args[1])
+            .add_variable("x")
+            .add_constant("2", 2.0)
+    }
+
+    #[test]
+    fn evaluates_and_formats_trees() {
+        let primitives = primitives();
+        let genes = [0, 2, 1, 2, 3, 2, 2];
+        assert_eq!(primitives.evaluate(&genes, &[3.0]), 9.0);
+        assert_eq!(primitives.format(&genes), "add(x, mul(x, 2))");
+        assert_eq!(primitives.format_tree(&genes), "add\n  x\n  mul\n    x\n    2\n");
+        assert_eq!(primitives.tree_size(&genes), 5);
+        assert_eq!(primitives.tree_depth(&genes), 2);
+        assert_eq!(primitives.tree_depth(&[3]), 0);
+    }
+
+    #[test]
+    fn any_genes_decode_to_a_tree() {
+        let primitives = primitives();
+        // 5 wraps around to mul, its missing second argument reads as x
+        assert_eq!(primitives.format(&[5, 7]), "mul(2, x)");
+        assert_eq!(primitives.evaluate(&[5, 7], &[4.0]), 8.0);
+        assert_eq!(primitives.tree_size(&[5, 7]), 2);
+    }
+
+    #[test]
+    fn random_trees_respect_the_depth_limits() {
+        let operators = GpOperators::new(primitives(), 5).init_depth(1, 3);
+        let mut rng = GaRng::seed_from_u64(1);
+        for _ in 0..200 {
+            let genes = operators.random_genes(32, &mut rng);
+            assert_eq!(genes.len(), 32);
+            assert!(operators.primitives.tree_depth(&genes) <= 3);
+        }
+        for depth in 0..4 {
+            let mut full = vec![];
+            operators.primitives.random_tree(depth, true, &mut rng, &mut full);
+            assert_eq!(operators.primitives.tree_depth(&full), depth);
+            assert_eq!(full.len(), 2usize.pow(depth as u32 + 1) - 1);
+        }
+    }
+
+    #[test]
+    fn subtree_crossover_grafts_within_the_limits() {
+        let operators = GpOperators::new(primitives(), 3);
+        let mut rng = GaRng::seed_from_u64(2);
+        // add(x, x) and mul(2, mul(2, 2))
+        let first = Individual::from_genes(vec![0, 2, 2, 2, 2, 2, 2]);
+        let second = Individual::from_genes(vec![1, 3, 1, 3, 3, 2, 2]);
+        let mut grafted = false;
+        for _ in 0..100 {
+            let child = operators.crossover(&[&first, &second], &mut rng);
+            assert_eq!(child.len(), 7);
+            assert!(operators.primitives.tree_depth(&child) <= 3);
+            grafted |= child != first.get_genes() && child != second.get_genes();
+        }
+        assert!(grafted);
+    }
+
+    #[test]
+    fn crossover_copies_a_parent_when_no_graft_fits() {
+        let operators = GpOperators::new(primitives(), 1);
+        let mut rng = GaRng::seed_from_u64(3);
+        // mul(mul(x, x), x) is deeper than the limit, so grafts of its inner mul never fit
+        let shallow = Individual::from_genes(vec![0, 2, 3, 2, 2]);
+        let deep = Individual::from_genes(vec![1, 1, 2, 2, 2]);
+        for _ in 0..50 {
+            let child = operators.crossover(&[&shallow, &deep], &mut rng);
+            assert!(operators.primitives.tree_depth(&child) <= 1 || child == deep.get_genes(), "{:?}", child);
+        }
+    }
+
+    #[test]
+    fn mutation_respects_the_limits() {
+        let operators = GpOperators::new(primitives(), 4).subtree_mutation(1.0);
+        let mut rng = GaRng::seed_from_u64(4);
+        for _ in 0..100 {
+            let mut genes = operators.random_genes(20, &mut rng);
+            operators.mutate(&mut genes, 0.3, &mut rng);
+            assert_eq!(genes.len(), 20);
+            assert!(operators.primitives.tree_size(&genes) <= 20);
+            assert!(operators.primitives.tree_depth(&genes) <= 4);
+        }
+    }
+}
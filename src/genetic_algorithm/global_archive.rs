@@ -0,0 +1,161 @@
+use rand::Rng;
+use std::{
+    sync::{Arc, RwLock},
+    thread,
+};
+
+use super::{events::GaEvent, gene::Gene, rng::GaRng, Individual, Population};
+
+/// A bounded archive of the best individuals found by several concurrently evolving
+/// populations. Populations publish their elites into it and occasionally draw migrants
+/// from it, without any fixed migration topology or synchronisation between them.
+///
+/// Cloning the archive gives another handle to the same shared storage.
+#[derive(Debug, Clone)]
+pub struct GlobalArchive<G: Gene = bool> {
+    members: Arc<RwLock<Vec<Individual<G>>>>,
+    capacity: usize,
+}
+
+impl<G: Gene> GlobalArchive<G> {
+    /// Creates an empty archive.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of individuals kept, the fittest win.
+    pub fn new(capacity: usize) -> GlobalArchive<G> {
+        if capacity < 1 {
+            panic!("archive capacity must be 1 or more");
+        }
+        GlobalArchive {
+            members: Arc::new(RwLock::new(vec![])),
+            capacity,
+        }
+    }
+
+    /// Adds individuals to the archive, skipping genomes it already holds. If another
+    /// population is writing at the same time nothing is published and false is
+    /// returned, so publishing never blocks evolution.
+    ///
+    /// # Arguments
+    /// * `elites` - The individuals to publish.
+    pub fn try_publish(&self, elites: &[Individual<G>]) -> bool {
+        let mut members = match self.members.try_write() {
+            Ok(members) => members,
+            Err(_) => return false,
+        };
+        for elite in elites {
+            if !members.iter().any(|member| member.genes == elite.genes) {
+                members.push(elite.clone());
+            }
+        }
+        members.sort_by(|a, b| b.cmp(a));
+        members.truncate(self.capacity);
+        true
+    }
+
+    /// Returns up to `count` randomly chosen archive members.
+    ///
+    /// # Arguments
+    /// * `count` - The number of members drawn, with repetition.
+    /// * `rng` - The random number generator to draw from.
+    pub fn draw(&self, count: usize, rng: &mut GaRng) -> Vec<Individual<G>> {
+        let members = self.members.read().unwrap();
+        if members.is_empty() {
+            return vec![];
+        }
+        (0..count)
+            .map(|_| members[rng.random_range(0..members.len())].clone())
+            .collect()
+    }
+
+    /// Returns a copy of the archive, fittest first.
+    pub fn get_members(&self) -> Vec<Individual<G>> {
+        self.members.read().unwrap().clone()
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// Settings for populations evolving around a shared `GlobalArchive`.
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveSettings {
+    /// The number of generations every population runs.
+    pub generations: u64,
+    /// The capacity of the shared archive.
+    pub archive_capacity: usize,
+    /// How many of its best individuals a population publishes at a time.
+    pub publish_count: usize,
+    /// A population publishes every `publish_interval` generations.
+    pub publish_interval: u64,
+    /// The chance (0.0-1.0) per generation that a population draws migrants.
+    pub migration_chance: f64,
+    /// How many migrants replace the worst individuals when a population draws.
+    pub migrant_count: usize,
+}
+
+/// Evolves several populations concurrently, one thread each, exchanging individuals
+/// only through a shared global archive. Every population draws its migrations from its
+/// own random number generator, but what the archive holds at a draw depends on the
+/// timing of the threads.
+///
+/// # Arguments
+/// * `populations` - The populations to evolve.
+/// * `fitness` - The fitness function shared by all populations.
+/// * `settings` - The run settings.
+///
+/// # Returns
+/// The final populations, in the given order, and the archive.
+pub fn run_with_global_archive<G, F>(
+    populations: Vec<Population<G>>,
+    fitness: F,
+    settings: ArchiveSettings,
+) -> (Vec<Population<G>>, GlobalArchive<G>)
+where
+    G: Gene,
+    F: Fn(&Individual<G>) -> u64 + Sync,
+{
+    if !(0.0..=1.0).contains(&settings.migration_chance) {
+        panic!("migration chance must be between 0 and 1");
+    }
+    let archive = GlobalArchive::new(settings.archive_capacity);
+    let populations = thread::scope(|scope| {
+        let handles: Vec<_> = populations
+            .into_iter()
+            .map(|mut population| {
+                let archive = archive.clone();
+                let fitness = &fitness;
+                scope.spawn(move || {
+                    for generation in 0..settings.generations {
+                        population.evaluate(fitness);
+                        population.sort_individuals();
+                        if generation % settings.publish_interval.max(1) == 0 {
+                            let count = settings.publish_count.min(population.individuals.len());
+                            archive.try_publish(&population.individuals[..count]);
+                        }
+                        if population.rng.random_bool(settings.migration_chance) {
+                            let count = settings.migrant_count.min(population.individuals.len());
+                            let migrants = archive.draw(count, &mut population.rng);
+                            let size = population.individuals.len();
+                            let count = migrants.len();
+                            for (i, migrant) in migrants.into_iter().enumerate() {
+                                population.individuals[size - 1 - i] = migrant;
+                            }
+                            if count > 0 {
+                                let generation = population.get_generation();
+                                population.emit(GaEvent::Migration { generation, count });
+                            }
+                        }
+                        population.next_generation();
+                    }
+                    population.evaluate(fitness);
+                    population.sort_individuals();
+                    population
+                })
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+    (populations, archive)
+}
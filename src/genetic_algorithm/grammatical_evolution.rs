@@ -0,0 +1,512 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use super::{gene::Gene, Individual};
+
+/// One symbol of a grammar alternative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Symbol {
+    NonTerminal(usize),
+    Terminal(String),
+}
+
+/// A grammar that can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarError {
+    /// The text holds no rule.
+    Empty,
+    /// A line is neither a rule nor a continuation of one.
+    Syntax { line: usize, message: String },
+    /// A non-terminal is used but never defined.
+    Undefined(String),
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrammarError::Empty => write!(f, "the grammar has no rules"),
+            GrammarError::Syntax { line, message } => write!(f, "grammar line {}: {}", line, message),
+            GrammarError::Undefined(name) => write!(f, "non-terminal <{}> is used but never defined", name),
+        }
+    }
+}
+
+impl Error for GrammarError {}
+
+/// Why a genome maps to no program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MappingError {
+    /// The genome has no codons.
+    NoCodons,
+    /// The derivation was still incomplete after wrapping around the genome the
+    /// allowed number of times.
+    TooManyWraps,
+}
+
+impl fmt::Display for MappingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MappingError::NoCodons => write!(f, "the genome has no codons"),
+            MappingError::TooManyWraps => write!(f, "the derivation is incomplete after the maximum number of wraps"),
+        }
+    }
+}
+
+impl Error for MappingError {}
+
+/// A context-free grammar in BNF, e.g.
+///
+/// ```text
+/// <expr> ::= <expr> <op> <expr> | (<expr>) | <var>
+/// <op>   ::= + | - | *
+/// <var>  ::= x | 1.0
+/// ```
+///
+/// Every rule starts a line with a non-terminal and `::=`; lines starting with `|` add
+/// alternatives to the rule above, blank lines and lines starting with `#` are skipped.
+/// Text between non-terminals is copied verbatim, trimmed at the ends of an alternative;
+/// quote it with `"` to include `|`, `<` or surrounding spaces. The first rule's
+/// non-terminal is the start symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    names: Vec<String>,
+    rules: Vec<Vec<Vec<Symbol>>>,
+}
+
+impl Grammar {
+    /// Parses a grammar.
+    pub fn parse(text: &str) -> Result<Grammar, GrammarError> {
+        let mut names: Vec<String> = vec![];
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        let mut rules: Vec<Vec<Vec<Symbol>>> = vec![];
+        let mut defined: Vec<bool> = vec![];
+        let mut current: Option<usize> = None;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let syntax = |message: &str| GrammarError::Syntax {
+                line: number + 1,
+                message: message.to_string(),
+            };
+            let body = if let Some(rest) = line.strip_prefix('|') {
+                if current.is_none() {
+                    return Err(syntax("alternatives before the first rule"));
+                }
+                rest
+            } else {
+                let (head, body) = line.split_once("::=").ok_or_else(|| syntax("expected <name> ::= alternatives"))?;
+                let name = head
+                    .trim()
+                    .strip_prefix('<')
+                    .and_then(|head| head.strip_suffix('>'))
+                    .ok_or_else(|| syntax("a rule has to start with a <non-terminal>"))?;
+                let index = intern(name, &mut names, &mut indices, &mut rules, &mut defined);
+                if defined[index] {
+                    return Err(syntax(&format!("<{}> is defined twice", name)));
+                }
+                defined[index] = true;
+                current = Some(index);
+                body
+            };
+            for alternative in split_alternatives(body).map_err(|message| syntax(&message))? {
+                let mut symbols = vec![];
+                for symbol in alternative {
+                    symbols.push(match symbol {
+                        Token::Name(name) => Symbol::NonTerminal(intern(&name, &mut names, &mut indices, &mut rules, &mut defined)),
+                        Token::Text(text) | Token::Quoted(text) => Symbol::Terminal(text),
+                    });
+                }
+                rules[current.unwrap()].push(symbols);
+            }
+        }
+        if names.is_empty() {
+            return Err(GrammarError::Empty);
+        }
+        if let Some(index) = defined.iter().position(|defined| !defined) {
+            return Err(GrammarError::Undefined(names[index].clone()));
+        }
+        if let Some(index) = rules.iter().position(|alternatives| alternatives.is_empty()) {
+            return Err(GrammarError::Syntax {
+                line: 0,
+                message: format!("<{}> has no alternatives", names[index]),
+            });
+        }
+        Ok(Grammar { names, rules })
+    }
+
+    /// Returns the non-terminals in the order they first appear, the start symbol first.
+    pub fn get_non_terminals(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Maps codons to a program with a leftmost derivation: every non-terminal with more
+    /// than one alternative is replaced by alternative `codon % alternatives`, consuming
+    /// one codon, rules with a single alternative consume none. When the codons run out
+    /// the mapping wraps around to the first one.
+    ///
+    /// # Arguments
+    /// * `codons` - The codons, e.g. from `codons` of an individual.
+    /// * `max_wraps` - How many times the mapping may wrap around.
+    pub fn map(&self, codons: &[u64], max_wraps: usize) -> Result<Mapping, MappingError> {
+        if codons.is_empty() {
+            return Err(MappingError::NoCodons);
+        }
+        let mut program = String::new();
+        let mut stack = vec![Symbol::NonTerminal(0)];
+        let mut used = 0;
+        let limit = codons.len() * (max_wraps + 1);
+        // rules with one alternative consume no codon, so bound the expansions in case
+        // they recurse
+        let mut expansions = 0;
+        while let Some(symbol) = stack.pop() {
+            let rule = match symbol {
+                Symbol::Terminal(text) => {
+                    program.push_str(&text);
+                    continue;
+                }
+                Symbol::NonTerminal(rule) => rule,
+            };
+            let alternatives = &self.rules[rule];
+            let choice = if alternatives.len() == 1 {
+                expansions += 1;
+                if expansions > limit * 64 {
+                    return Err(MappingError::TooManyWraps);
+                }
+                0
+            } else {
+                if used == limit {
+                    return Err(MappingError::TooManyWraps);
+                }
+                used += 1;
+                (codons[(used - 1) % codons.len()] % alternatives.len() as u64) as usize
+            };
+            stack.extend(alternatives[choice].iter().rev().cloned());
+        }
+        Ok(Mapping {
+            program,
+            used_codons: used,
+            wraps: used.saturating_sub(1) / codons.len(),
+        })
+    }
+}
+
+/// A program a genome maps to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    pub program: String,
+    /// The number of codons consumed, counting wrapped ones again.
+    pub used_codons: usize,
+    /// How many times the mapping wrapped around the genome.
+    pub wraps: usize,
+}
+
+enum Token {
+    Name(String),
+    Text(String),
+    Quoted(String),
+}
+
+fn intern(
+    name: &str,
+    names: &mut Vec<String>,
+    indices: &mut HashMap<String, usize>,
+    rules: &mut Vec<Vec<Vec<Symbol>>>,
+    defined: &mut Vec<bool>,
+) -> usize {
+    if let Some(&index) = indices.get(name) {
+        return index;
+    }
+    names.push(name.to_string());
+    rules.push(vec![]);
+    defined.push(false);
+    indices.insert(name.to_string(), names.len() - 1);
+    names.len() - 1
+}
+
+/// Splits the right-hand side of a rule into alternatives of non-terminals and text.
+fn split_alternatives(body: &str) -> Result<Vec<Vec<Token>>, String> {
+    let mut alternatives = vec![];
+    let mut tokens: Vec<Token> = vec![];
+    let mut text = String::new();
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => {
+                let name: String = chars.by_ref().take_while(|&c| c != '>').collect();
+                if name.is_empty() {
+                    return Err("empty or unclosed <non-terminal>".to_string());
+                }
+                push_text(&mut tokens, &mut text);
+                tokens.push(Token::Name(name));
+            }
+            '"' => {
+                // the unquoted text before is trimmed on its own
+                push_text(&mut tokens, &mut text);
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        closed = true;
+                        break;
+                    }
+                    text.push(c);
+                }
+                if !closed {
+                    return Err("unclosed quote".to_string());
+                }
+                // quoted spaces are kept at the ends of an alternative
+                if !text.is_empty() {
+                    tokens.push(Token::Quoted(std::mem::take(&mut text)));
+                }
+            }
+            '|' => {
+                push_text(&mut tokens, &mut text);
+                alternatives.push(trim(std::mem::take(&mut tokens)));
+            }
+            c => text.push(c),
+        }
+    }
+    push_text(&mut tokens, &mut text);
+    alternatives.push(trim(tokens));
+    Ok(alternatives)
+}
+
+fn push_text(tokens: &mut Vec<Token>, text: &mut String) {
+    if !text.is_empty() {
+        tokens.push(Token::Text(std::mem::take(text)));
+    }
+}
+
+/// Trims the unquoted whitespace at the ends of an alternative.
+fn trim(mut tokens: Vec<Token>) -> Vec<Token> {
+    if let Some(Token::Text(text)) = tokens.first_mut() {
+        *text = text.trim_start().to_string();
+    }
+    if let Some(Token::Text(text)) = tokens.last_mut() {
+        *text = text.trim_end().to_string();
+    }
+    tokens.retain(|token| !matches!(token, Token::Text(text) if text.is_empty()));
+    tokens
+}
+
+/// A gene type whose genomes can be read as grammatical evolution codons.
+pub trait CodonGene: Gene {
+    /// Returns the codons of a genome.
+    ///
+    /// # Arguments
+    /// * `genes` - The genome.
+    /// * `codon_bits` - The number of bits per codon, only used by bit genomes.
+    fn codons(genes: &[Self], codon_bits: usize) -> Vec<u64>;
+}
+
+/// Every `codon_bits` bits are a codon, most significant bit first, the bits left over
+/// at the end are ignored.
+impl CodonGene for bool {
+    fn codons(genes: &[bool], codon_bits: usize) -> Vec<u64> {
+        genes
+            .chunks_exact(codon_bits)
+            .map(|bits| bits.iter().fold(0, |codon, &bit| (codon << 1) | bit as u64))
+            .collect()
+    }
+}
+
+/// Every byte is a codon.
+impl CodonGene for u8 {
+    fn codons(genes: &[u8], _codon_bits: usize) -> Vec<u64> {
+        genes.iter().map(|&gene| gene as u64).collect()
+    }
+}
+
+/// Every integer is a codon, negative ones by their two's complement.
+impl CodonGene for i64 {
+    fn codons(genes: &[i64], _codon_bits: usize) -> Vec<u64> {
+        genes.iter().map(|&gene| gene as u64).collect()
+    }
+}
+
+/// Every index is a codon.
+impl CodonGene for usize {
+    fn codons(genes: &[usize], _codon_bits: usize) -> Vec<u64> {
+        genes.iter().map(|&gene| gene as u64).collect()
+    }
+}
+
+/// Grammatical evolution: genomes of any `CodonGene` type are mapped through a grammar
+/// to programs or strings, which the fitness function scores. The search itself is the
+/// usual genetic algorithm, e.g. `GaRunner::new(population, ge.fitness(score))`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrammaticalEvolution {
+    grammar: Grammar,
+    codon_bits: usize,
+    max_wraps: usize,
+    invalid_fitness: u64,
+}
+
+impl GrammaticalEvolution {
+    /// Creates a mapping with 8-bit codons for bit genomes, 2 wraps and a fitness of 0
+    /// for genomes that map to no program.
+    pub fn new(grammar: Grammar) -> GrammaticalEvolution {
+        GrammaticalEvolution {
+            grammar,
+            codon_bits: 8,
+            max_wraps: 2,
+            invalid_fitness: 0,
+        }
+    }
+
+    /// Sets the number of bits per codon of bit genomes, 1 to 64.
+    pub fn codon_bits(mut self, bits: usize) -> GrammaticalEvolution {
+        if !(1..=64).contains(&bits) {
+            panic!("codon bits must be between 1 and 64");
+        }
+        self.codon_bits = bits;
+        self
+    }
+
+    /// Sets how many times a mapping may wrap around the genome, 0 disables wrapping.
+    pub fn max_wraps(mut self, wraps: usize) -> GrammaticalEvolution {
+        self.max_wraps = wraps;
+        self
+    }
+
+    /// Sets the fitness of genomes that map to no program.
+    pub fn invalid_fitness(mut self, fitness: u64) -> GrammaticalEvolution {
+        self.invalid_fitness = fitness;
+        self
+    }
+
+    pub fn get_grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+
+    pub fn get_codon_bits(&self) -> usize {
+        self.codon_bits
+    }
+
+    pub fn get_max_wraps(&self) -> usize {
+        self.max_wraps
+    }
+
+    pub fn get_invalid_fitness(&self) -> u64 {
+        self.invalid_fitness
+    }
+
+    /// Returns the codons of an individual.
+    pub fn codons<G: CodonGene>(&self, individual: &Individual<G>) -> Vec<u64> {
+        G::codons(&individual.genes.as_slice(), self.codon_bits)
+    }
+
+    /// Maps an individual to its program.
+    pub fn map<G: CodonGene>(&self, individual: &Individual<G>) -> Result<Mapping, MappingError> {
+        self.grammar.map(&self.codons(individual), self.max_wraps)
+    }
+
+    /// Turns a fitness function of programs into one of individuals, which gives genomes
+    /// that map to no program the invalid fitness.
+    ///
+    /// # Arguments
+    /// * `fitness` - Returns the fitness of a program.
+    pub fn fitness<'a, G, F>(&'a self, fitness: F) -> impl Fn(&Individual<G>) -> u64 + Sync + 'a
+    where
+        G: CodonGene,
+        F: Fn(&str) -> u64 + Sync + 'a,
+    {
+        move |individual| match self.map(individual) {
+            Ok(mapping) => fitness(&mapping.program),
+            Err(_) => self.invalid_fitness,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARITHMETIC: &str = "
+        # sums and products
+        <e>  ::= <e><op><e> | <v>
+        <op> ::= + | *
+        <v>  ::= x
+               | 1
+    ";
+
+    fn program(grammar: &str, codons: &[u64]) -> String {
+        Grammar::parse(grammar).unwrap().map(codons, 0).unwrap().program
+    }
+
+    #[test]
+    fn maps_a_leftmost_derivation() {
+        let grammar = Grammar::parse(ARITHMETIC).unwrap();
+        assert_eq!(grammar.get_non_terminals(), ["e", "op", "v"]);
+        let mapping = grammar.map(&[0, 1, 0, 1, 1, 1], 0).unwrap();
+        assert_eq!(
+            mapping,
+            Mapping {
+                program: String::from("x*1"),
+                used_codons: 6,
+                wraps: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn quoted_text_keeps_special_characters() {
+        let grammar = "<s> ::= \" | \" | \"<\" | a\" b \"";
+        assert_eq!(program(grammar, &[0]), " | ");
+        assert_eq!(program(grammar, &[1]), "<");
+        assert_eq!(program(grammar, &[2]), "a b ");
+    }
+
+    #[test]
+    fn empty_alternatives_derive_nothing() {
+        let grammar = "<s> ::= a<s> |";
+        assert_eq!(program(grammar, &[0, 0, 1]), "aa");
+        assert_eq!(program(grammar, &[1]), "");
+    }
+
+    #[test]
+    fn mapping_wraps_around_the_codons() {
+        let grammar = Grammar::parse("<s> ::= <b><b><b>\n<b> ::= x | y").unwrap();
+        let mapping = grammar.map(&[0, 1], 1).unwrap();
+        assert_eq!((mapping.program.as_str(), mapping.used_codons, mapping.wraps), ("xyx", 3, 1));
+        assert_eq!(grammar.map(&[0, 1], 0), Err(MappingError::TooManyWraps));
+        assert_eq!(grammar.map(&[], 1), Err(MappingError::NoCodons));
+    }
+
+    #[test]
+    fn endless_derivations_are_cut_off() {
+        // recursing through codons never finishes
+        let grammar = Grammar::parse(ARITHMETIC).unwrap();
+        assert_eq!(grammar.map(&[0], 3), Err(MappingError::TooManyWraps));
+        // nor does a rule with one alternative, which consumes no codons
+        let grammar = Grammar::parse("<s> ::= <s>x").unwrap();
+        assert_eq!(grammar.map(&[0], 3), Err(MappingError::TooManyWraps));
+    }
+
+    #[test]
+    fn rejects_invalid_grammars() {
+        assert_eq!(Grammar::parse("# nothing\n\n"), Err(GrammarError::Empty));
+        assert_eq!(Grammar::parse("<s> ::= <t>"), Err(GrammarError::Undefined(String::from("t"))));
+        let syntax_line = |text: &str| match Grammar::parse(text) {
+            Err(GrammarError::Syntax { line, .. }) => line,
+            other => panic!("expected a syntax error, got {:?}", other),
+        };
+        assert_eq!(syntax_line("| a"), 1);
+        assert_eq!(syntax_line("<s> ::= a\n<s> ::= b"), 2);
+        assert_eq!(syntax_line("<s> = a"), 1);
+        assert_eq!(syntax_line("s ::= a"), 1);
+        assert_eq!(syntax_line("<s> ::= \"a"), 1);
+        assert_eq!(syntax_line("<s> ::= <>"), 1);
+    }
+
+    #[test]
+    fn bit_genomes_are_read_as_codons() {
+        let bits: Vec<bool> = [0, 0, 0, 0, 0, 1, 0, 1, 1, 1].iter().map(|&bit| bit == 1).collect();
+        assert_eq!(bool::codons(&bits, 4), vec![0, 5]);
+        let ge = GrammaticalEvolution::new(Grammar::parse(ARITHMETIC).unwrap()).codon_bits(4).invalid_fitness(7);
+        let fitness = ge.fitness(|program: &str| program.len() as u64);
+        // x: codons 1 and 0
+        assert_eq!(fitness(&Individual::from_genes(vec![false, false, false, true, false, false, false, false])), 1);
+        // 0 only grows the expression
+        assert_eq!(fitness(&Individual::from_genes(vec![false; 8])), 7);
+    }
+}
@@ -0,0 +1,209 @@
+use rand::{seq::SliceRandom, SeedableRng};
+
+use super::{gene::Gene, rng::GaRng, Individual};
+
+/// The outcome of a hill climb.
+#[derive(Debug, Clone)]
+pub struct ClimbResult<G: Gene = bool> {
+    /// The fittest individual found, with its fitness.
+    pub best: Individual<G>,
+    /// The number of fitness evaluations, the starting individuals included.
+    pub evaluations: u64,
+    /// The number of climbs, 1 without random restarts.
+    pub climbs: u64,
+    /// Whether the last climb reached a local optimum rather than the evaluation budget.
+    pub local_optimum: bool,
+}
+
+/// A first-improvement hill climber on the `Individual` encoding: it mutates one gene
+/// at a time with `Gene::mutate`, trying the loci in random order, and keeps a change
+/// only if it raises the fitness. For bits that is a bit-flip climber, which stops at a
+/// local optimum once no single flip helps. A baseline for benchmarks and the local
+/// search of memetic algorithms.
+#[derive(Debug, Clone)]
+pub struct HillClimber {
+    max_evaluations: u64,
+    rng: GaRng,
+}
+
+impl HillClimber {
+    /// Creates a climber with a random seed.
+    ///
+    /// # Arguments
+    /// * `max_evaluations` - The evaluation budget of a call, restarts included.
+    pub fn new(max_evaluations: u64) -> HillClimber {
+        HillClimber {
+            max_evaluations,
+            rng: GaRng::from_rng(&mut rand::rng()),
+        }
+    }
+
+    /// Creates a climber whose climbs can be reproduced.
+    ///
+    /// # Arguments
+    /// * `max_evaluations` - The evaluation budget of a call, restarts included.
+    /// * `seed` - The seed of the random number generator.
+    pub fn seeded(max_evaluations: u64, seed: u64) -> HillClimber {
+        HillClimber {
+            max_evaluations,
+            rng: GaRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn get_max_evaluations(&self) -> u64 {
+        self.max_evaluations
+    }
+
+    /// Climbs from the given individual until no single-gene change improves it or the
+    /// budget is used up.
+    ///
+    /// # Arguments
+    /// * `initial` - The starting point, its fitness is evaluated again.
+    /// * `fitness` - Returns the fitness of an individual.
+    pub fn climb<G, F>(&mut self, initial: Individual<G>, fitness: F) -> ClimbResult<G>
+    where
+        G: Gene,
+        F: Fn(&Individual<G>) -> u64,
+    {
+        let mut best = initial;
+        best.fitness = fitness(&best);
+        let budget = self.max_evaluations.saturating_sub(1);
+        let (used, local_optimum) = climb_evaluated(&mut best, &fitness, budget, &mut self.rng);
+        ClimbResult {
+            best,
+            evaluations: used + 1,
+            climbs: 1,
+            local_optimum,
+        }
+    }
+
+    /// Climbs from random individuals again and again until the budget is used up and
+    /// returns the best local optimum found.
+    ///
+    /// # Arguments
+    /// * `gene_length` - The number of genes of the random starting points.
+    /// * `fitness` - Returns the fitness of an individual.
+    pub fn climb_with_restarts<G, F>(&mut self, gene_length: usize, fitness: F) -> ClimbResult<G>
+    where
+        G: Gene,
+        F: Fn(&Individual<G>) -> u64,
+    {
+        if gene_length < 1 {
+            panic!("the gene length cannot be less than 1");
+        }
+        let mut best: Option<Individual<G>> = None;
+        let mut evaluations = 0;
+        let mut climbs = 0;
+        let mut local_optimum = false;
+        while evaluations < self.max_evaluations.max(1) {
+            let mut found = Individual::from_genes((0..gene_length).map(|_| G::random(&mut self.rng)).collect());
+            found.fitness = fitness(&found);
+            let budget = self.max_evaluations.max(1) - evaluations - 1;
+            let (used, optimum) = climb_evaluated(&mut found, &fitness, budget, &mut self.rng);
+            evaluations += used + 1;
+            climbs += 1;
+            local_optimum = optimum;
+            if best.as_ref().is_none_or(|best| found.fitness > best.fitness) {
+                best = Some(found);
+            }
+        }
+        ClimbResult {
+            best: best.unwrap(),
+            evaluations,
+            climbs,
+            local_optimum,
+        }
+    }
+}
+
+/// Climbs from an evaluated individual in place with at most `budget` more evaluations.
+///
+/// # Returns
+/// The evaluations used and whether the individual is now a local optimum.
+pub(crate) fn climb_evaluated<G, F>(current: &mut Individual<G>, fitness: &F, budget: u64, rng: &mut GaRng) -> (u64, bool)
+where
+    G: Gene,
+    F: Fn(&Individual<G>) -> u64 + ?Sized,
+{
+    let mut evaluations = 0;
+    let mut loci: Vec<usize> = (0..current.gene_length).collect();
+    loop {
+        let mut improved = false;
+        loci.shuffle(rng);
+        for &index in &loci {
+            if evaluations >= budget {
+                return (evaluations, false);
+            }
+            let original = current.genes[index].clone();
+            let original_fitness = current.fitness;
+            current.genes.set(index, original.mutate(rng));
+            current.fitness = fitness(current);
+            evaluations += 1;
+            if current.fitness > original_fitness {
+                improved = true;
+            } else {
+                current.genes.set(index, original);
+                current.fitness = original_fitness;
+            }
+        }
+        if !improved {
+            return (evaluations, true);
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+    }
+
+    // all ones is the global optimum, every other genome is drawn towards all zeros
+    fn trap(individual: &Individual) -> u64 {
+        match ones(individual) {
+            16 => 100,
+            count => 16 - count,
+        }
+    }
+
+    #[test]
+    fn climbs_to_the_optimum_of_one_max() {
+        let result = HillClimber::seeded(1000, 1).climb(Individual::from_genes(vec![false; 16]), ones);
+        assert_eq!(result.best.get_fitness(), 16);
+        assert!(result.local_optimum);
+        assert_eq!(result.climbs, 1);
+        // the last pass over all loci finds no improvement
+        assert!(result.evaluations >= 1 + 16 + 16);
+    }
+
+    #[test]
+    fn stops_at_a_local_optimum() {
+        let result = HillClimber::seeded(1000, 2).climb(Individual::from_genes(vec![false; 16]), trap);
+        assert_eq!(result.best.get_genes(), vec![false; 16]);
+        assert!(result.local_optimum);
+        assert_eq!(result.evaluations, 1 + 16);
+    }
+
+    #[test]
+    fn stops_when_the_budget_is_used_up() {
+        let result = HillClimber::seeded(5, 3).climb(Individual::from_genes(vec![false; 16]), ones);
+        assert_eq!(result.evaluations, 5);
+        assert!(!result.local_optimum);
+        assert_eq!(result.best.get_fitness(), 4);
+    }
+
+    #[test]
+    fn restarts_use_the_whole_budget_and_keep_the_best_climb() {
+        let result = HillClimber::seeded(300, 4).climb_with_restarts(8, ones);
+        assert_eq!(result.evaluations, 300);
+        assert!(result.climbs > 1);
+        assert_eq!(result.best.get_fitness(), 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "the gene length cannot be less than 1")]
+    fn restarts_need_genes() {
+        HillClimber::seeded(10, 5).climb_with_restarts::<bool, _>(0, ones);
+    }
+}
@@ -0,0 +1,78 @@
+use super::{gene::Gene, Population};
+
+/// Random-immigrant settings: every `interval` generations a `fraction` of the population
+/// is replaced by brand-new random individuals, so exploration never stops entirely.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RandomImmigrants {
+    fraction: f64,
+    interval: u64,
+}
+
+impl RandomImmigrants {
+    /// Creates new random-immigrant settings.
+    ///
+    /// # Arguments
+    /// * `fraction` - The share of the population replaced, 0.0-1.0, rounded up to whole individuals.
+    /// * `interval` - The generations between injections, 1 injects every generation.
+    pub fn new(fraction: f64, interval: u64) -> RandomImmigrants {
+        if !(0.0..=1.0).contains(&fraction) {
+            panic!("immigrant fraction must be between 0 and 1");
+        }
+        if interval < 1 {
+            panic!("immigrant interval must be 1 or more");
+        }
+        RandomImmigrants { fraction, interval }
+    }
+
+    pub fn get_fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    pub fn get_interval(&self) -> u64 {
+        self.interval
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Injects random immigrants in `next_generation`. They take the places of the worst
+    /// individuals: the newest, not yet evaluated ones of the step, i.e. the children of a
+    /// generation or steady-state step, so the survivors are never touched. Immigrants
+    /// are repaired and have a fitness of 0 until evaluated.
+    ///
+    /// # Arguments
+    /// * `immigrants` - How many individuals are replaced and how often.
+    pub fn set_random_immigrants(&mut self, immigrants: RandomImmigrants) {
+        self.random_immigrants = Some(immigrants);
+    }
+
+    /// Stops injecting random immigrants.
+    pub fn clear_random_immigrants(&mut self) {
+        self.random_immigrants = None;
+    }
+
+    pub fn get_random_immigrants(&self) -> Option<RandomImmigrants> {
+        self.random_immigrants
+    }
+
+    /// Replaces up to `fresh` individuals at the end of the population with random ones
+    /// if the generation being created is due for immigrants.
+    ///
+    /// # Arguments
+    /// * `fresh` - The number of individuals the step appended, the only ones that can be replaced.
+    pub(crate) fn inject_immigrants(&mut self, fresh: usize) {
+        let immigrants = match self.random_immigrants {
+            Some(immigrants) if (self.generation + 1).is_multiple_of(immigrants.interval) => immigrants,
+            _ => return,
+        };
+        let count = ((immigrants.fraction * self.individuals.len() as f64).ceil() as usize).min(fresh);
+        if count == 0 {
+            return;
+        }
+        let template = self.individuals[0].clone();
+        let start = self.individuals.len() - count;
+        for index in start..self.individuals.len() {
+            self.individuals[index] = self.random_individual(&template);
+        }
+    }
+}
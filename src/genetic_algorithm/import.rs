@@ -0,0 +1,311 @@
+use std::{fs, io, path::Path};
+
+use super::{decoding::BinaryEncoding, Individual, Population};
+
+/// How each genome in an imported file is written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GenomeFormat {
+    /// A string of `0`s and `1`s, one character per gene.
+    Bits,
+    /// Byte values (0-255), each expanded to 8 genes most significant bit first.
+    Bytes,
+    /// Real numbers, each encoded as a fixed-point group of `bits` genes in `[min, max]`.
+    Reals {
+        bits: usize,
+        min: f64,
+        max: f64,
+        encoding: BinaryEncoding,
+    },
+}
+
+/// A parsed value of the JSON subset genome files use.
+enum JsonValue {
+    String(String),
+    Number(f64),
+    Array(Vec<JsonValue>),
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Parses JSON made of arrays, strings and numbers.
+struct JsonParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> JsonParser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn parse_value(&mut self) -> io::Result<JsonValue> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('[') => {
+                self.chars.next();
+                let mut values = vec![];
+                self.skip_whitespace();
+                if self.chars.peek() == Some(&']') {
+                    self.chars.next();
+                    return Ok(JsonValue::Array(values));
+                }
+                loop {
+                    values.push(self.parse_value()?);
+                    self.skip_whitespace();
+                    match self.chars.next() {
+                        Some(',') => continue,
+                        Some(']') => return Ok(JsonValue::Array(values)),
+                        other => return Err(invalid_data(format!("expected , or ] but found {:?}", other))),
+                    }
+                }
+            }
+            Some('"') => {
+                self.chars.next();
+                let mut string = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some('"') => return Ok(JsonValue::String(string)),
+                        Some(c) => string.push(c),
+                        None => return Err(invalid_data("unterminated string".to_string())),
+                    }
+                }
+            }
+            Some(_) => {
+                let mut number = String::new();
+                while self.chars.peek().is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+                    number.push(self.chars.next().unwrap());
+                }
+                number
+                    .parse()
+                    .map(JsonValue::Number)
+                    .map_err(|_| invalid_data(format!("invalid json value near {:?}", number)))
+            }
+            None => Err(invalid_data("unexpected end of json".to_string())),
+        }
+    }
+}
+
+/// Builds an individual from the textual values of one genome.
+fn parse_genome(values: &[String], format: GenomeFormat, gene_length: usize) -> io::Result<Individual> {
+    let mut individual = Individual {
+        gene_length,
+        genes: vec![false; gene_length].into(),
+        fitness: 0,
+        violation: 0.0,
+        objectives: vec![],
+        strategy: vec![],
+        age: 0,
+    };
+    let genes: Vec<bool> = match format {
+        GenomeFormat::Bits => {
+            let mut genes = vec![];
+            for c in values.concat().chars() {
+                match c {
+                    '0' => genes.push(false),
+                    '1' => genes.push(true),
+                    _ => return Err(invalid_data(format!("invalid bit {:?}", c))),
+                }
+            }
+            genes
+        }
+        GenomeFormat::Bytes => {
+            let mut genes = vec![];
+            for value in values {
+                let byte: u8 = value
+                    .parse()
+                    .map_err(|_| invalid_data(format!("invalid byte {:?}", value)))?;
+                genes.extend((0..8).map(|j| (byte >> (7 - j)) & 1 == 1));
+            }
+            genes
+        }
+        GenomeFormat::Reals { bits, min, max, encoding } => {
+            if values.len() * bits != gene_length {
+                return Err(invalid_data(format!(
+                    "{} values of {} bits dont match the gene length {}",
+                    values.len(),
+                    bits,
+                    gene_length
+                )));
+            }
+            for (i, value) in values.iter().enumerate() {
+                let real: f64 = value
+                    .parse()
+                    .map_err(|_| invalid_data(format!("invalid number {:?}", value)))?;
+                individual.encode_fixed_point(i * bits, bits, min, max, encoding, real);
+            }
+            return Ok(individual);
+        }
+    };
+    if genes.len() != gene_length {
+        return Err(invalid_data(format!(
+            "genome has {} genes but the gene length is {}",
+            genes.len(),
+            gene_length
+        )));
+    }
+    individual.genes = genes.into();
+    Ok(individual)
+}
+
+/// Reads genomes from a file. Files ending in `.json` must hold an array with one entry
+/// per genome, either a bit string or an array of numbers. Any other file is read as CSV
+/// with one genome per line; empty lines and lines starting with `#` are skipped.
+///
+/// # Arguments
+/// * `path` - The file to read.
+/// * `format` - How each genome is written.
+/// * `gene_length` - The gene length every genome must decode to.
+///
+/// # Returns
+/// The genomes as individuals with fitness 0.
+pub fn read_genomes<P: AsRef<Path>>(path: P, format: GenomeFormat, gene_length: usize) -> io::Result<Vec<Individual>> {
+    let is_json = path.as_ref().extension().is_some_and(|extension| extension == "json");
+    let text = fs::read_to_string(path)?;
+    let genomes: Vec<Vec<String>> = if is_json {
+        let mut parser = JsonParser {
+            chars: text.chars().peekable(),
+        };
+        let entries = match parser.parse_value()? {
+            JsonValue::Array(entries) => entries,
+            _ => return Err(invalid_data("the json file must hold an array of genomes".to_string())),
+        };
+        parser.skip_whitespace();
+        if let Some(c) = parser.chars.peek() {
+            return Err(invalid_data(format!("unexpected {:?} after the array of genomes", c)));
+        }
+        let mut genomes = vec![];
+        for entry in entries {
+            genomes.push(match entry {
+                JsonValue::String(bits) => vec![bits],
+                JsonValue::Array(values) => {
+                    let mut genome = vec![];
+                    for value in values {
+                        match value {
+                            JsonValue::Number(number) => genome.push(number.to_string()),
+                            _ => return Err(invalid_data("genome arrays can only hold numbers".to_string())),
+                        }
+                    }
+                    genome
+                }
+                JsonValue::Number(_) => return Err(invalid_data("a genome cant be a single number".to_string())),
+            });
+        }
+        genomes
+    } else {
+        text.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.split(',').map(|value| value.trim().to_string()).collect())
+            .collect()
+    };
+    genomes
+        .iter()
+        .map(|genome| parse_genome(genome, format, gene_length))
+        .collect()
+}
+
+impl Population {
+    /// Seeds the population with genomes read from a CSV or JSON file (see `read_genomes`),
+    /// e.g. historical solutions produced outside Rust. The imported genomes replace the
+    /// first individuals of the population, the rest stay as they are.
+    ///
+    /// # Arguments
+    /// * `path` - The file to read.
+    /// * `format` - How each genome is written.
+    ///
+    /// # Returns
+    /// The number of imported genomes.
+    pub fn import_genomes<P: AsRef<Path>>(&mut self, path: P, format: GenomeFormat) -> io::Result<usize> {
+        let genomes = read_genomes(path, format, self.individuals[0].gene_length)?;
+        if genomes.len() > self.population_size {
+            return Err(invalid_data(format!(
+                "{} genomes dont fit in a population of {}",
+                genomes.len(),
+                self.population_size
+            )));
+        }
+        let count = genomes.len();
+        self.seed_with(genomes);
+        Ok(count)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    /// Writes `contents` to a temporary file with the given extension and reads it back.
+    fn read(name: &str, extension: &str, contents: &str, format: GenomeFormat, gene_length: usize) -> io::Result<Vec<Individual>> {
+        let path = env::temp_dir().join(format!("ga_import_{}_{}.{}", name, std::process::id(), extension));
+        fs::write(&path, contents).unwrap();
+        let result = read_genomes(&path, format, gene_length);
+        fs::remove_file(&path).unwrap();
+        result
+    }
+
+    fn genes(individuals: &[Individual]) -> Vec<Vec<bool>> {
+        individuals.iter().map(Individual::get_genes).collect()
+    }
+
+    #[test]
+    fn reads_csv_bits_and_bytes() {
+        let bits = read("csv_bits", "csv", "# header\n0110\n\n 1001 \n", GenomeFormat::Bits, 4).unwrap();
+        assert_eq!(genes(&bits), vec![vec![false, true, true, false], vec![true, false, false, true]]);
+        let bytes = read("csv_bytes", "csv", "1, 128\n", GenomeFormat::Bytes, 16).unwrap();
+        let mut expected = vec![false; 16];
+        expected[7] = true;
+        expected[8] = true;
+        assert_eq!(genes(&bytes), vec![expected]);
+    }
+
+    #[test]
+    fn reads_csv_reals() {
+        let format = GenomeFormat::Reals {
+            bits: 8,
+            min: 0.0,
+            max: 255.0,
+            encoding: BinaryEncoding::Standard,
+        };
+        let reals = read("csv_reals", "csv", "3,200\n", format, 16).unwrap();
+        assert_eq!(reals[0].decode_fixed_points(8, 0.0, 255.0, BinaryEncoding::Standard), vec![3.0, 200.0]);
+    }
+
+    #[test]
+    fn reads_json_strings_and_arrays() {
+        let bits = read("json_bits", "json", " [\"01\", \"10\"] \n", GenomeFormat::Bits, 2).unwrap();
+        assert_eq!(genes(&bits), vec![vec![false, true], vec![true, false]]);
+        let bytes = read("json_bytes", "json", "[[255], [0]]", GenomeFormat::Bytes, 8).unwrap();
+        assert_eq!(genes(&bytes), vec![vec![true; 8], vec![false; 8]]);
+        assert!(read("json_empty", "json", "[]", GenomeFormat::Bits, 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_the_wrong_gene_length() {
+        let error = read("length_csv", "csv", "0110\n", GenomeFormat::Bits, 5).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error = read("length_json", "json", "[[1, 2]]", GenomeFormat::Bytes, 8).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let cases = [
+            ("bit", "csv", "01x1\n", GenomeFormat::Bits),
+            ("byte", "csv", "256\n", GenomeFormat::Bytes),
+            ("unterminated", "json", "[\"0101", GenomeFormat::Bits),
+            ("unclosed", "json", "[\"0101\"", GenomeFormat::Bits),
+            ("not_array", "json", "\"0101\"", GenomeFormat::Bits),
+            ("number", "json", "[5]", GenomeFormat::Bits),
+            ("nested", "json", "[[\"1\"]]", GenomeFormat::Bytes),
+            ("trailing", "json", "[\"0101\"] garbage", GenomeFormat::Bits),
+        ];
+        for (name, extension, contents, format) in cases {
+            let error = read(name, extension, contents, format, 4).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", contents);
+        }
+    }
+}
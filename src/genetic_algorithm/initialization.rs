@@ -0,0 +1,405 @@
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use std::{fmt, sync::Arc};
+
+use super::{
+    check_config, check_mutation_chance, decoding::BinaryEncoding, gene::Gene, rng::GaRng, variation::VariationHook, CrossoverType, Individual,
+    Population, MAX_MUTATION_CHANCE,
+};
+
+/// Creates the individuals of a starting population, so problem specific construction
+/// heuristics (greedy solutions, biased bit probabilities, ...) can replace the uniform
+/// random start. Closures `Fn(usize, &mut GaRng) -> Individual<G>` implement it too.
+pub trait Initializer<G: Gene = bool>: Send + Sync {
+    /// Creates one individual.
+    ///
+    /// # Arguments
+    /// * `gene_length` - The gene length the individual must have.
+    /// * `rng` - The population's random number generator, so seeded populations start the same.
+    fn init(&self, gene_length: usize, rng: &mut GaRng) -> Individual<G>;
+}
+
+impl<G: Gene, F: Fn(usize, &mut GaRng) -> Individual<G> + Send + Sync> Initializer<G> for F {
+    fn init(&self, gene_length: usize, rng: &mut GaRng) -> Individual<G> {
+        self(gene_length, rng)
+    }
+}
+
+/// Sets every gene to `true` with the given probability, 0.5 gives the default random start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiasedInitializer {
+    pub probability: f64,
+}
+
+impl Initializer for BiasedInitializer {
+    fn init(&self, gene_length: usize, rng: &mut GaRng) -> Individual {
+        Individual::from_genes((0..gene_length).map(|_| rng.random_bool(self.probability)).collect())
+    }
+}
+
+/// A shared initializer stored on a population builder.
+#[derive(Clone)]
+pub(crate) struct InitializerHook<G: Gene>(pub(crate) Arc<dyn Initializer<G>>);
+
+impl<G: Gene> fmt::Debug for InitializerHook<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "InitializerHook")
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Replaces every individual with one created by the initializer from the
+    /// population's random number generator, then repaired.
+    pub(crate) fn initialize_with<I: Initializer<G> + ?Sized>(&mut self, initializer: &I) {
+        let gene_length = self.individuals[0].gene_length;
+        let mut individuals = std::mem::take(&mut self.individuals);
+        for individual in individuals.iter_mut() {
+            let mut created = initializer.init(gene_length, &mut self.rng);
+            if created.gene_length != gene_length || created.genes.len() != gene_length {
+                panic!("the initializer created {} genes instead of {}", created.genes.len(), gene_length);
+            }
+            created.fitness = 0;
+            created.violation = 0.0;
+            created.objectives.clear();
+            created.age = 0;
+            self.initialize_strategy(&mut created);
+            self.repair_individual(&mut created);
+            *individual = created;
+        }
+        self.individuals = individuals;
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Injects known individuals, e.g. a heuristic solution or the output of a previous
+    /// run (see `Individual::from_genes`), in place of the first individuals so the run
+    /// doesn't start purely at random. The rest of the population stays as it is. The
+    /// seeded individuals are repaired and their fitness reset, so evaluate before
+    /// selecting.
+    ///
+    /// # Arguments
+    /// * `individuals` - The individuals, at most the population size, each with the population's gene length.
+    pub fn seed_with(&mut self, individuals: Vec<Individual<G>>) {
+        if individuals.len() > self.individuals.len() {
+            panic!("{} individuals dont fit in a population of {}", individuals.len(), self.individuals.len());
+        }
+        let gene_length = self.individuals[0].gene_length;
+        for (i, mut individual) in individuals.into_iter().enumerate() {
+            if individual.gene_length != gene_length {
+                panic!("cant seed an individual of {} genes into a gene length of {}", individual.gene_length, gene_length);
+            }
+            individual.fitness = 0;
+            individual.violation = 0.0;
+            individual.objectives.clear();
+            self.initialize_strategy(&mut individual);
+            self.repair_individual(&mut individual);
+            self.individuals[i] = individual;
+        }
+    }
+}
+
+/// Initializes a new population whose individuals are created by an `Initializer`
+/// instead of at random, of any gene type. Use `PopulationBuilder::initializer` for a
+/// seeded population.
+///
+/// # Arguments
+/// * `gene_length` - The length of the gene sequence for each individual.
+/// * `population_size` - The number of individuals in the population.
+/// * `parent_count` - The number of parents to select for reproduction.
+/// * `mutation_chance` - The chance of mutation for each gene (0-100).
+/// * `multi_threaded` - Whether children are created on multiple threads.
+/// * `crossover` - How parent genes are recombined.
+/// * `initializer` - Creates every individual of the starting population.
+///
+/// # Returns
+/// A new `Population` made of the initializer's individuals.
+pub fn init_population_with<G: Gene, I: Initializer<G> + ?Sized>(
+    gene_length: usize,
+    population_size: usize,
+    parent_count: usize,
+    mutation_chance: u8,
+    multi_threaded: bool,
+    crossover: CrossoverType,
+    initializer: &I,
+) -> Population<G> {
+    let mutation_probability = match check_mutation_chance(mutation_chance.min(MAX_MUTATION_CHANCE)) {
+        Ok(probability) => probability,
+        Err(error) => panic!("{}", error),
+    };
+    if let Err(error) = check_config(gene_length, population_size, parent_count, mutation_probability, crossover) {
+        panic!("{}", error);
+    }
+    let rng = GaRng::from_rng(&mut rand::rng());
+    let mut population = Population::new_blank(gene_length, population_size, parent_count, mutation_probability, multi_threaded, crossover, rng);
+    population.initialize_with(initializer);
+    population
+}
+
+/// Draws a Latin hypercube sample of the unit cube: each of the `dimensions` axes is cut
+/// into `samples` equal strata and every stratum of every axis holds exactly one point,
+/// so the points cover the space evenly instead of clumping.
+///
+/// # Arguments
+/// * `samples` - The number of points.
+/// * `dimensions` - The number of coordinates of each point.
+/// * `rng` - The random number generator to draw from.
+///
+/// # Returns
+/// `samples` points with coordinates in `[0, 1)`.
+pub fn latin_hypercube(samples: usize, dimensions: usize, rng: &mut GaRng) -> Vec<Vec<f64>> {
+    let mut points = vec![vec![0.0; dimensions]; samples];
+    for d in 0..dimensions {
+        let mut strata: Vec<usize> = (0..samples).collect();
+        strata.shuffle(rng);
+        for (point, stratum) in points.iter_mut().zip(strata) {
+            point[d] = (stratum as f64 + rng.random::<f64>()) / samples as f64;
+        }
+    }
+    points
+}
+
+/// Returns the first `count` prime numbers.
+fn primes(count: usize) -> Vec<u64> {
+    let mut primes: Vec<u64> = vec![];
+    let mut candidate = 2;
+    while primes.len() < count {
+        if primes.iter().all(|p| candidate % p != 0) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+/// Returns the `index`-th element of the van der Corput sequence in the given base.
+fn radical_inverse(mut index: u64, base: u64) -> f64 {
+    let mut result = 0.0;
+    let mut fraction = 1.0 / base as f64;
+    while index > 0 {
+        result += (index % base) as f64 * fraction;
+        index /= base;
+        fraction /= base as f64;
+    }
+    result
+}
+
+/// Generates points of the Halton low-discrepancy sequence, which fills the unit cube
+/// progressively more evenly than random points. Each dimension uses the next prime as
+/// its base; the first few points of the sequence are skipped since they are strongly
+/// correlated across dimensions.
+///
+/// # Arguments
+/// * `samples` - The number of points.
+/// * `dimensions` - The number of coordinates of each point.
+///
+/// # Returns
+/// `samples` points with coordinates in `[0, 1)`.
+pub fn halton(samples: usize, dimensions: usize) -> Vec<Vec<f64>> {
+    let bases = primes(dimensions);
+    let skip = bases.last().copied().unwrap_or(0);
+    (0..samples as u64)
+        .map(|i| bases.iter().map(|&base| radical_inverse(i + 1 + skip, base)).collect())
+        .collect()
+}
+
+impl Individual {
+    /// Returns the opposite of this individual: every gene flipped, fitness reset to 0.
+    pub fn opposite(&self) -> Individual {
+        Individual {
+            gene_length: self.gene_length,
+            genes: self.genes.iter().map(|gene| !gene).collect(),
+            fitness: 0,
+            violation: 0.0,
+            objectives: vec![],
+            strategy: vec![],
+            age: 0,
+        }
+    }
+}
+
+impl Population {
+    /// Opposition-based initialization: evaluates every individual and its bitwise
+    /// complement and keeps the fitter of the two. Meant to be called right after
+    /// `init_population`; it costs one extra evaluation per individual and usually
+    /// gives a noticeably better starting population.
+    ///
+    /// # Arguments
+    /// * `fitness` - The fitness function, every kept individual has its fitness set.
+    pub fn apply_opposition<F: Fn(&Individual) -> u64>(&mut self, fitness: F) {
+        self.keep_fitter_opposites(fitness, Individual::opposite);
+    }
+
+    /// Re-initializes the population with a Latin hypercube sample for genomes that encode
+    /// real-valued parameters as consecutive fixed-point groups (see `Individual::decode_fixed_points`).
+    /// Fitness of every individual is reset to 0.
+    ///
+    /// # Arguments
+    /// * `bits` - The number of genes per parameter (1-64), must divide the gene length.
+    /// * `min` - The lower bound of every parameter.
+    /// * `max` - The upper bound of every parameter.
+    /// * `encoding` - Whether the groups are plain binary or Gray coded.
+    pub fn latin_hypercube_init(&mut self, bits: usize, min: f64, max: f64, encoding: BinaryEncoding) {
+        let gene_length = self.individuals[0].gene_length;
+        if bits < 1 || !gene_length.is_multiple_of(bits) {
+            panic!("gene length must be a multiple of {} to encode fixed-point values", bits);
+        }
+        let dimensions = gene_length / bits;
+        let points = latin_hypercube(self.individuals.len(), dimensions, &mut self.rng);
+        for (individual, point) in self.individuals.iter_mut().zip(points) {
+            for (d, coordinate) in point.into_iter().enumerate() {
+                individual.encode_fixed_point(d * bits, bits, min, max, encoding, min + (max - min) * coordinate);
+            }
+            individual.set_fitness(0);
+        }
+        let mut individuals = std::mem::take(&mut self.individuals);
+        for individual in individuals.iter_mut() {
+            self.repair_individual(individual);
+        }
+        self.individuals = individuals;
+    }
+
+    /// Re-initializes the population with points of the Halton sequence for genomes that
+    /// encode real-valued parameters as consecutive fixed-point groups.
+    /// Fitness of every individual is reset to 0.
+    ///
+    /// # Arguments
+    /// * `bits` - The number of genes per parameter (1-64), must divide the gene length.
+    /// * `min` - The lower bound of every parameter.
+    /// * `max` - The upper bound of every parameter.
+    /// * `encoding` - Whether the groups are plain binary or Gray coded.
+    pub fn halton_init(&mut self, bits: usize, min: f64, max: f64, encoding: BinaryEncoding) {
+        let gene_length = self.individuals[0].gene_length;
+        if bits < 1 || !gene_length.is_multiple_of(bits) {
+            panic!("gene length must be a multiple of {} to encode fixed-point values", bits);
+        }
+        let points = halton(self.individuals.len(), gene_length / bits);
+        for (individual, point) in self.individuals.iter_mut().zip(points) {
+            for (d, coordinate) in point.into_iter().enumerate() {
+                individual.encode_fixed_point(d * bits, bits, min, max, encoding, min + (max - min) * coordinate);
+            }
+            individual.set_fitness(0);
+        }
+        let mut individuals = std::mem::take(&mut self.individuals);
+        for individual in individuals.iter_mut() {
+            self.repair_individual(individual);
+        }
+        self.individuals = individuals;
+    }
+}
+
+impl Population<f64> {
+    /// Opposition-based initialization for real-valued genomes: evaluates every individual
+    /// and its reflection in the bounds, `min + max - x` per gene, and keeps the fitter of
+    /// the two. The bounds are those of the `RealOperators` set with `set_real_operators`,
+    /// or `[0, 1]` without variation operators.
+    ///
+    /// # Arguments
+    /// * `fitness` - The fitness function, every kept individual has its fitness set.
+    pub fn apply_opposition<F: Fn(&Individual<f64>) -> u64>(&mut self, fitness: F) {
+        let variation = self.variation.clone();
+        self.keep_fitter_opposites(fitness, |individual| {
+            let genes = match &variation {
+                Some(VariationHook(variation, _)) => match variation.opposite_genes(&individual.genes.as_slice()) {
+                    Some(genes) => genes,
+                    None => panic!("the variation operators have no opposite, use RealOperators for opposition"),
+                },
+                None => individual.genes.iter().map(|gene| 1.0 - gene).collect(),
+            };
+            Individual::from_genes(genes)
+        });
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Evaluates every individual and its opposite and keeps the fitter of the two.
+    fn keep_fitter_opposites<F: Fn(&Individual<G>) -> u64, O: Fn(&Individual<G>) -> Individual<G>>(&mut self, fitness: F, opposite: O) {
+        let mut individuals = std::mem::take(&mut self.individuals);
+        for individual in individuals.iter_mut() {
+            individual.set_fitness(fitness(individual));
+            let mut opposite = opposite(individual);
+            self.repair_individual(&mut opposite);
+            opposite.set_fitness(fitness(&opposite));
+            if opposite.fitness > individual.fitness {
+                *individual = opposite;
+            }
+        }
+        self.individuals = individuals;
+    }
+}
+
+impl Population<usize> {
+    /// Re-initializes the population with points of the Halton sequence, so the base-N
+    /// genes of the starting population, see `AlleleOperators`, cover `0..allele_count`
+    /// evenly. Fitness of every individual is reset to 0.
+    ///
+    /// # Arguments
+    /// * `allele_count` - The number of values (N) each gene can take.
+    pub fn halton_init_alleles(&mut self, allele_count: usize) {
+        if allele_count < 2 {
+            panic!("a gene needs at least 2 alleles");
+        }
+        let gene_length = self.individuals[0].gene_length;
+        let points = halton(self.individuals.len(), gene_length);
+        let mut individuals = std::mem::take(&mut self.individuals);
+        for (individual, point) in individuals.iter_mut().zip(points) {
+            individual.genes = point
+                .into_iter()
+                .map(|coordinate| ((coordinate * allele_count as f64) as usize).min(allele_count - 1))
+                .collect();
+            individual.set_fitness(0);
+            self.repair_individual(individual);
+        }
+        self.individuals = individuals;
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        builder::PopulationBuilder,
+        init_population_seeded,
+        real::{RealCrossover, RealMutation, RealOperators},
+    };
+    use super::*;
+
+    #[test]
+    fn opposition_keeps_the_fitter_complement() {
+        let mut population = init_population_seeded(8, 10, 2, 5, false, CrossoverType::Bit, 3);
+        let before: Vec<Vec<bool>> = population.individuals.iter().map(|individual| individual.get_genes()).collect();
+        let ones = |individual: &Individual| individual.get_genes().iter().filter(|&&gene| gene).count() as u64;
+        population.apply_opposition(ones);
+        for (individual, genes) in population.individuals.iter().zip(before) {
+            let complement: Vec<bool> = genes.iter().map(|gene| !gene).collect();
+            let best = if ones(&Individual::from_genes(complement.clone())) > ones(&Individual::from_genes(genes.clone())) { complement } else { genes };
+            assert_eq!(individual.get_genes(), best);
+            assert_eq!(individual.get_fitness(), ones(individual));
+        }
+    }
+
+    #[test]
+    fn real_opposition_reflects_genes_in_their_bounds() {
+        let bounds = vec![(-2.0, 4.0), (10.0, 20.0)];
+        let mut population = PopulationBuilder::<f64>::new().gene_length(2).size(10).parents(2).seed(5).build().unwrap();
+        population.set_real_operators(RealOperators::new(bounds.clone(), RealMutation::Gaussian { sigma: 0.1 }, RealCrossover::Arithmetic));
+        let before: Vec<Vec<f64>> = population.individuals.iter().map(|individual| individual.get_genes()).collect();
+        // rewards small first genes, so the reflection wins whenever the gene is above the center
+        population.apply_opposition(|individual| (1000.0 * (4.0 - individual.get_gene(0))) as u64);
+        for (individual, genes) in population.individuals.iter().zip(before) {
+            let reflected = vec![2.0 - genes[0], 30.0 - genes[1]];
+            let expected = if reflected[0] < genes[0] { reflected } else { genes };
+            assert_eq!(individual.get_genes(), expected);
+            for (gene, (min, max)) in individual.get_genes().iter().zip(&bounds) {
+                assert!((min..=max).contains(&gene));
+            }
+        }
+    }
+
+    #[test]
+    fn real_opposition_without_operators_reflects_in_the_unit_interval() {
+        let mut population = PopulationBuilder::<f64>::new().gene_length(3).size(6).parents(2).seed(9).build().unwrap();
+        let before: Vec<Vec<f64>> = population.individuals.iter().map(|individual| individual.get_genes()).collect();
+        population.apply_opposition(|individual| (1000.0 * individual.get_genes().iter().sum::<f64>()) as u64);
+        for (individual, genes) in population.individuals.iter().zip(before) {
+            let reflected: Vec<f64> = genes.iter().map(|gene| 1.0 - gene).collect();
+            assert!(individual.get_genes() == genes || individual.get_genes() == reflected);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use rand::Rng;
+
+use super::{rng::GaRng, variation::{BuiltInVariation, Variation}, Individual, Population};
+
+/// How an integer gene changes when it mutates. Both keep the gene inside its bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntegerMutation {
+    /// The gene is replaced by a different, uniformly chosen value from its range.
+    Uniform,
+    /// The gene moves up or down by at most `step`, clamped to its range.
+    /// Suited to parameters where neighbouring values behave alike.
+    Creep { step: i64 },
+}
+
+/// How integer parents are recombined. Both copy whole genes, so children never hold
+/// values outside the bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntegerCrossover {
+    /// Every gene is taken from a random parent.
+    Uniform,
+    /// Genes before a random cut come from one parent, the rest from another.
+    SinglePoint,
+}
+
+/// Variation operators for `Population<i64>` with an inclusive `[min, max]` range per
+/// gene, so no fitness evaluations are wasted on values a bit field could hold but the
+/// problem doesn't allow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegerOperators {
+    bounds: Vec<(i64, i64)>,
+    mutation: IntegerMutation,
+    crossover: IntegerCrossover,
+}
+
+impl IntegerOperators {
+    /// Creates new operators.
+    ///
+    /// # Arguments
+    /// * `bounds` - The inclusive `(min, max)` range of every gene, one per gene.
+    /// * `mutation` - How a mutating gene picks its new value.
+    /// * `crossover` - How parents are recombined.
+    pub fn new(bounds: Vec<(i64, i64)>, mutation: IntegerMutation, crossover: IntegerCrossover) -> IntegerOperators {
+        if bounds.is_empty() {
+            panic!("the gene length cannot be less than 1");
+        }
+        if let Some((min, max)) = bounds.iter().find(|(min, max)| min > max) {
+            panic!("invalid gene bounds {}..={}", min, max);
+        }
+        if let IntegerMutation::Creep { step } = mutation {
+            if step < 1 {
+                panic!("creep step must be 1 or more");
+            }
+        }
+        IntegerOperators {
+            bounds,
+            mutation,
+            crossover,
+        }
+    }
+
+    pub fn get_bounds(&self) -> &[(i64, i64)] {
+        &self.bounds
+    }
+
+    pub fn get_mutation(&self) -> IntegerMutation {
+        self.mutation
+    }
+
+    pub fn get_crossover(&self) -> IntegerCrossover {
+        self.crossover
+    }
+}
+
+impl Variation<i64> for IntegerOperators {
+    fn random_genes(&self, gene_length: usize, rng: &mut GaRng) -> Vec<i64> {
+        if gene_length != self.bounds.len() {
+            panic!("{} gene bounds for a gene length of {}", self.bounds.len(), gene_length);
+        }
+        self.bounds.iter().map(|&(min, max)| rng.random_range(min..=max)).collect()
+    }
+
+    fn crossover(&self, parents: &[&Individual<i64>], rng: &mut GaRng) -> Vec<i64> {
+        let gene_length = parents[0].gene_length;
+        match self.crossover {
+            IntegerCrossover::Uniform => {
+                (0..gene_length).map(|i| parents[rng.random_range(0..parents.len())].genes[i]).collect()
+            }
+            IntegerCrossover::SinglePoint => {
+                let first = &parents[rng.random_range(0..parents.len())];
+                let second = &parents[rng.random_range(0..parents.len())];
+                let cut = rng.random_range(0..=gene_length);
+                let mut genes = first.genes.as_slice()[..cut].to_vec();
+                genes.extend_from_slice(&second.genes.as_slice()[cut..]);
+                genes
+            }
+        }
+    }
+
+    fn mutate(&self, genes: &mut [i64], mutation_probability: f64, rng: &mut GaRng) -> u64 {
+        let mut mutations = 0;
+        for (i, gene) in genes.iter_mut().enumerate() {
+            let (min, max) = self.bounds[i];
+            if min == max || !rng.random_bool(mutation_probability) {
+                continue;
+            }
+            *gene = match self.mutation {
+                IntegerMutation::Uniform => loop {
+                    let value = rng.random_range(min..=max);
+                    if value != *gene {
+                        break value;
+                    }
+                },
+                IntegerMutation::Creep { step } => {
+                    let delta = rng.random_range(1..=step);
+                    if rng.random_bool(0.5) {
+                        gene.saturating_add(delta).min(max)
+                    } else {
+                        gene.saturating_sub(delta).max(min)
+                    }
+                }
+            };
+            mutations += 1;
+        }
+        mutations
+    }
+}
+
+impl Population<i64> {
+    /// Evolves the population with bounded integer operators and re-initializes every
+    /// individual uniformly within the bounds, resetting its fitness.
+    ///
+    /// # Arguments
+    /// * `operators` - The operators, with one bound per gene.
+    pub fn set_integer_operators(&mut self, operators: IntegerOperators) {
+        let gene_length = self.individuals[0].gene_length;
+        if operators.bounds.len() != gene_length {
+            panic!("{} gene bounds for a gene length of {}", operators.bounds.len(), gene_length);
+        }
+        self.set_built_in_variation(operators.clone(), BuiltInVariation::Integer(operators));
+        self.reinitialize_individuals();
+    }
+}
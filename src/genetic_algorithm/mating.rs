@@ -0,0 +1,79 @@
+use rand::Rng;
+
+use super::{gene::Gene, rng::GaRng, Individual, Population};
+
+/// Incest prevention: a child is bred from a pair of the selected parents that differ
+/// in at least `min_distance` loci, so near-identical parents don't produce yet another
+/// copy of themselves and small populations take longer to collapse onto one genome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatingRestriction {
+    min_distance: usize,
+    attempts: usize,
+}
+
+impl MatingRestriction {
+    /// Creates a new mating restriction.
+    ///
+    /// # Arguments
+    /// * `min_distance` - The smallest Hamming distance between two parents.
+    /// * `attempts` - The random pairs drawn per child, 1 or more. If none is far enough apart the most distant one is used.
+    pub fn new(min_distance: usize, attempts: usize) -> MatingRestriction {
+        if attempts < 1 {
+            panic!("mating restriction needs at least 1 attempt");
+        }
+        MatingRestriction { min_distance, attempts }
+    }
+
+    pub fn get_min_distance(&self) -> usize {
+        self.min_distance
+    }
+
+    pub fn get_attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Draws random pairs of distinct parents until one is at least `min_distance` apart.
+    ///
+    /// # Returns
+    /// The indices of the pair, the most distant one drawn if none is far enough apart.
+    pub(crate) fn choose_pair<G: Gene>(&self, parents: &[&Individual<G>], rng: &mut GaRng) -> (usize, usize) {
+        let mut best = (0, 1);
+        let mut best_distance = None;
+        for _ in 0..self.attempts {
+            let a = rng.random_range(0..parents.len());
+            let b = (a + rng.random_range(1..parents.len())) % parents.len();
+            let distance = parents[a].hamming_distance(parents[b]);
+            if distance >= self.min_distance {
+                return (a, b);
+            }
+            if best_distance.is_none_or(|best_distance| distance > best_distance) {
+                best = (a, b);
+                best_distance = Some(distance);
+            }
+        }
+        best
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Breeds every child from two of the selected parents that are at least a minimum
+    /// Hamming distance apart instead of from all of them. Applies to every mode that
+    /// breeds children from selected parents, i.e. not to differential evolution,
+    /// evolution strategies or CMA-ES.
+    ///
+    /// # Arguments
+    /// * `restriction` - The minimum distance and the attempts before falling back.
+    pub fn set_mating_restriction(&mut self, restriction: MatingRestriction) {
+        self.mating_restriction = Some(restriction);
+    }
+
+    /// Breeds children from all selected parents again.
+    pub fn clear_mating_restriction(&mut self) {
+        self.mating_restriction = None;
+    }
+
+    pub fn get_mating_restriction(&self) -> Option<MatingRestriction> {
+        self.mating_restriction
+    }
+}
@@ -0,0 +1,204 @@
+use std::{fmt, ops::Range, sync::Arc};
+
+use super::{gene::Gene, hill_climbing, rng::GaRng, Individual, Population};
+
+/// Refines an evaluated individual, turning the genetic algorithm into a memetic
+/// algorithm. Improvements are written back into the population (Lamarckian learning).
+/// Closures `Fn(&mut Individual<G>, &dyn Fn(&Individual<G>) -> u64, u64, &mut GaRng) -> u64`
+/// implement it too.
+pub trait LocalSearch<G: Gene = bool>: Send + Sync {
+    /// Improves the individual in place and keeps its fitness up to date.
+    ///
+    /// # Arguments
+    /// * `individual` - The individual, with its fitness evaluated.
+    /// * `fitness` - The fitness function of the evaluation.
+    /// * `budget` - The most fitness evaluations to use.
+    /// * `rng` - The population's random number generator.
+    ///
+    /// # Returns
+    /// The number of fitness evaluations used.
+    fn improve(
+        &self,
+        individual: &mut Individual<G>,
+        fitness: &dyn Fn(&Individual<G>) -> u64,
+        budget: u64,
+        rng: &mut GaRng,
+    ) -> u64;
+}
+
+impl<G, F> LocalSearch<G> for F
+where
+    G: Gene,
+    F: Fn(&mut Individual<G>, &dyn Fn(&Individual<G>) -> u64, u64, &mut GaRng) -> u64 + Send + Sync,
+{
+    fn improve(
+        &self,
+        individual: &mut Individual<G>,
+        fitness: &dyn Fn(&Individual<G>) -> u64,
+        budget: u64,
+        rng: &mut GaRng,
+    ) -> u64 {
+        self(individual, fitness, budget, rng)
+    }
+}
+
+/// The built-in local search: a first-improvement hill climb changing one gene at a
+/// time, bit flips for bits, see `hill_climbing::HillClimber`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HillClimb;
+
+impl<G: Gene> LocalSearch<G> for HillClimb {
+    fn improve(
+        &self,
+        individual: &mut Individual<G>,
+        fitness: &dyn Fn(&Individual<G>) -> u64,
+        budget: u64,
+        rng: &mut GaRng,
+    ) -> u64 {
+        hill_climbing::climb_evaluated(individual, fitness, budget, rng).0
+    }
+}
+
+/// Which of the just evaluated individuals the local search refines, e.g. only the
+/// last ones after `evaluate_last`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalSearchTarget {
+    /// The individuals the last `next_generation` created, at the end of the population.
+    Children,
+    /// The given number of fittest evaluated individuals.
+    Fittest(usize),
+    /// Every evaluated individual.
+    All,
+}
+
+/// A local search stored on a population.
+#[derive(Clone)]
+pub(crate) struct LocalSearchHook<G: Gene> {
+    search: Arc<dyn LocalSearch<G>>,
+    target: LocalSearchTarget,
+    budget: u64,
+    evaluations: u64,
+}
+
+impl<G: Gene> fmt::Debug for LocalSearchHook<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "LocalSearchHook {{ target: {:?}, budget: {}, evaluations: {} }}",
+            self.target, self.budget, self.evaluations
+        )
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Sets a local search that every evaluation method runs on the targeted individuals
+    /// after evaluating them, before the constraints are applied. The refined genes and
+    /// fitness replace the originals. `try_evaluate` and `evaluate_with_timeout` give a
+    /// candidate whose evaluation fails or times out their penalty fitness.
+    ///
+    /// # Arguments
+    /// * `search` - The local search, e.g. `HillClimb`.
+    /// * `target` - Which individuals are refined.
+    /// * `budget` - The most fitness evaluations per refined individual.
+    pub fn set_local_search<L>(&mut self, search: L, target: LocalSearchTarget, budget: u64)
+    where
+        L: LocalSearch<G> + 'static,
+    {
+        self.local_search = Some(LocalSearchHook {
+            search: Arc::new(search),
+            target,
+            budget,
+            evaluations: 0,
+        });
+    }
+
+    /// Removes the local search.
+    pub fn clear_local_search(&mut self) {
+        self.local_search = None;
+    }
+
+    /// Returns the number of fitness evaluations the local search used since it was set,
+    /// 0 without one.
+    pub fn get_local_search_evaluations(&self) -> u64 {
+        self.local_search.as_ref().map_or(0, |hook| hook.evaluations)
+    }
+
+    /// Sets the evaluation count of a local search set again after loading a checkpoint.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore_local_search_evaluations(&mut self, evaluations: u64) {
+        if let Some(hook) = self.local_search.as_mut() {
+            hook.evaluations = evaluations;
+        }
+    }
+
+    /// Refines the targeted individuals of a freshly evaluated range with the local search,
+    /// if one is set. The rest already carry constrained or shared fitness, which the raw
+    /// fitness of the candidates can't be compared against.
+    pub(crate) fn apply_local_search<F: Fn(&Individual<G>) -> u64>(&mut self, range: Range<usize>, fitness: &F) {
+        let hook = match &self.local_search {
+            Some(hook) => hook.clone(),
+            None => return,
+        };
+        let count = self.individuals.len();
+        let targets: Vec<usize> = match hook.target {
+            LocalSearchTarget::Children => (range.start.max(count - self.fresh_count.min(count))..range.end).collect(),
+            LocalSearchTarget::Fittest(fittest) => self
+                .ranking()
+                .into_iter()
+                .filter(|index| range.contains(index))
+                .take(fittest)
+                .collect(),
+            LocalSearchTarget::All => range.collect(),
+        };
+        let mut evaluations = 0;
+        for index in targets {
+            evaluations += hook.search.improve(&mut self.individuals[index], fitness, hook.budget, &mut self.rng);
+        }
+        if let Some(hook) = &mut self.local_search {
+            hook.evaluations += evaluations;
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{init_population_seeded, CrossoverType};
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+    }
+
+    #[test]
+    fn only_evaluated_individuals_are_refined_and_constrained() {
+        let mut population = init_population_seeded(16, 8, 2, 5, false, CrossoverType::Bit, 4);
+        population.add_constraint(|_: &Individual| 1.0);
+        population.evaluate(ones);
+        let before = population.individuals.clone();
+        population.set_local_search(HillClimb, LocalSearchTarget::All, 32);
+        population.evaluate_last(2, ones);
+        assert!(population.get_local_search_evaluations() > 0);
+        for (individual, old) in population.individuals[..6].iter().zip(&before) {
+            assert_eq!(individual.get_genes(), old.get_genes());
+            assert_eq!(individual.get_fitness(), old.get_fitness());
+        }
+        for individual in &population.individuals[6..] {
+            assert!(ones(individual) > 0);
+            assert_eq!(individual.get_fitness(), ones(individual) - 1);
+        }
+    }
+
+    #[test]
+    fn fittest_target_ranks_within_the_evaluated_range() {
+        let mut population = init_population_seeded(16, 8, 2, 5, false, CrossoverType::Bit, 5);
+        population.evaluate(ones);
+        population.individuals[0].set_fitness(1000);
+        let before = population.individuals.clone();
+        population.set_local_search(HillClimb, LocalSearchTarget::Fittest(1), 32);
+        population.evaluate_last(3, ones);
+        assert!(population.get_local_search_evaluations() > 0);
+        for (individual, old) in population.individuals[..5].iter().zip(&before) {
+            assert_eq!(individual.get_genes(), old.get_genes());
+            assert_eq!(individual.get_fitness(), old.get_fitness());
+        }
+    }
+}
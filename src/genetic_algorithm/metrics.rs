@@ -0,0 +1,140 @@
+use std::{
+    fmt::Write,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use super::Population;
+
+/// Run metrics of one generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunMetrics {
+    pub generation: u64,
+    pub best_fitness: u64,
+    pub mean_fitness: f64,
+    /// The population entropy, see `Population::entropy`.
+    pub diversity: f64,
+    /// Wall-clock seconds since the previous observation, 0 for the first one.
+    pub generation_seconds: f64,
+    /// Fitness evaluations per second since the previous observation.
+    pub evaluations_per_second: f64,
+    pub evaluations_total: u64,
+    /// Fitness evaluations that timed out since the start of the run, see
+    /// `Population::evaluate_with_timeout`.
+    pub timeouts_total: u64,
+}
+
+impl RunMetrics {
+    /// Formats the metrics in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut text = String::new();
+        let metrics: [(&str, &str, &str, f64); 8] = [
+            ("ga_generation", "gauge", "Current generation.", self.generation as f64),
+            ("ga_best_fitness", "gauge", "Best fitness of the current generation.", self.best_fitness as f64),
+            ("ga_mean_fitness", "gauge", "Mean fitness of the current generation.", self.mean_fitness),
+            ("ga_diversity", "gauge", "Mean per-locus allele entropy of the population.", self.diversity),
+            ("ga_generation_duration_seconds", "gauge", "Duration of the last generation.", self.generation_seconds),
+            ("ga_evaluations_per_second", "gauge", "Fitness evaluations per second during the last generation.", self.evaluations_per_second),
+            ("ga_evaluations_total", "counter", "Fitness evaluations since the start of the run.", self.evaluations_total as f64),
+            ("ga_evaluation_timeouts_total", "counter", "Fitness evaluations that timed out since the start of the run.", self.timeouts_total as f64),
+        ];
+        for (name, kind, help, value) in metrics {
+            let _ = writeln!(text, "# HELP {} {}", name, help);
+            let _ = writeln!(text, "# TYPE {} {}", name, kind);
+            let _ = writeln!(text, "{} {}", name, value);
+        }
+        text
+    }
+}
+
+/// Receives the metrics of every observed generation, e.g. to forward them to a
+/// monitoring system.
+pub trait MetricsRecorder: Send {
+    fn record(&mut self, metrics: &RunMetrics);
+}
+
+/// Keeps the latest metrics and renders them for a Prometheus scrape.
+/// Cloning gives another handle to the same metrics, so one clone can be handed to a
+/// `MetricsCollector` while another serves scrapes.
+#[derive(Debug, Clone, Default)]
+pub struct PrometheusRecorder {
+    latest: Arc<Mutex<Option<RunMetrics>>>,
+}
+
+impl PrometheusRecorder {
+    pub fn new() -> PrometheusRecorder {
+        PrometheusRecorder::default()
+    }
+
+    /// Returns the latest metrics in the Prometheus text format, empty before the first generation.
+    pub fn render(&self) -> String {
+        match *self.latest.lock().unwrap() {
+            Some(metrics) => metrics.to_prometheus(),
+            None => String::new(),
+        }
+    }
+}
+
+impl MetricsRecorder for PrometheusRecorder {
+    fn record(&mut self, metrics: &RunMetrics) {
+        *self.latest.lock().unwrap() = Some(*metrics);
+    }
+}
+
+/// Measures run metrics once per generation and hands them to the registered recorders.
+#[derive(Default)]
+pub struct MetricsCollector {
+    last_observation: Option<Instant>,
+    evaluations_total: u64,
+    recorders: Vec<Box<dyn MetricsRecorder>>,
+}
+
+impl MetricsCollector {
+    pub fn new() -> MetricsCollector {
+        MetricsCollector::default()
+    }
+
+    /// Adds a recorder that receives every observation.
+    pub fn add_recorder<R: MetricsRecorder + 'static>(&mut self, recorder: R) {
+        self.recorders.push(Box::new(recorder));
+    }
+
+    /// Measures an evaluated population, call once per generation.
+    ///
+    /// # Arguments
+    /// * `population` - The evaluated population.
+    /// * `evaluations` - The fitness evaluations done since the previous observation.
+    ///
+    /// # Returns
+    /// The measured metrics, which were also passed to every recorder.
+    pub fn observe(&mut self, population: &Population, evaluations: u64) -> RunMetrics {
+        let now = Instant::now();
+        let generation_seconds = match self.last_observation {
+            Some(last) => now.duration_since(last).as_secs_f64(),
+            None => 0.0,
+        };
+        self.last_observation = Some(now);
+        self.evaluations_total += evaluations;
+
+        let individuals = &population.individuals;
+        let metrics = RunMetrics {
+            generation: population.generation,
+            best_fitness: individuals.iter().map(|individual| individual.fitness).max().unwrap_or(0),
+            mean_fitness: individuals.iter().map(|individual| individual.fitness as f64).sum::<f64>()
+                / individuals.len() as f64,
+            diversity: population.entropy(),
+            generation_seconds,
+            evaluations_per_second: if generation_seconds > 0.0 {
+                evaluations as f64 / generation_seconds
+            } else {
+                0.0
+            },
+            evaluations_total: self.evaluations_total,
+            timeouts_total: population.get_timeout_count(),
+        };
+        for recorder in self.recorders.iter_mut() {
+            recorder.record(&metrics);
+        }
+        metrics
+    }
+}
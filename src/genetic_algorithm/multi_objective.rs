@@ -0,0 +1,196 @@
+use super::{gene::Gene, Individual, Population};
+
+impl<G: Gene> Individual<G> {
+    /// Sets the objective values of the individual for multi-objective optimization,
+    /// every objective is maximized like the fitness. Negate objectives to minimize them.
+    pub fn set_objectives(&mut self, objectives: Vec<f64>) {
+        if objectives.iter().any(|objective| objective.is_nan()) {
+            panic!("objectives cant be NaN");
+        }
+        self.objectives = objectives;
+    }
+
+    pub fn get_objectives(&self) -> Vec<f64> {
+        self.objectives.clone()
+    }
+
+    /// Returns true if this individual Pareto-dominates the other: it is at least as
+    /// good in every objective and better in at least one.
+    pub fn dominates(&self, other: &Individual<G>) -> bool {
+        if self.objectives.len() != other.objectives.len() {
+            panic!("cant compare {} objectives to {}", self.objectives.len(), other.objectives.len());
+        }
+        let mut better = false;
+        for (a, b) in self.objectives.iter().zip(other.objectives.iter()) {
+            if a < b {
+                return false;
+            }
+            if a > b {
+                better = true;
+            }
+        }
+        better
+    }
+}
+
+/// Dominance with constraints: a feasible individual dominates every infeasible one and
+/// of two infeasible individuals the one with the smaller violation dominates.
+fn constrained_dominates<G: Gene>(a: &Individual<G>, b: &Individual<G>, constrained: bool) -> bool {
+    if constrained {
+        match (a.is_feasible(), b.is_feasible()) {
+            (true, false) => return true,
+            (false, true) => return false,
+            (false, false) => return a.violation < b.violation,
+            (true, true) => {}
+        }
+    }
+    a.dominates(b)
+}
+
+/// Splits individuals into non-dominated fronts with the fast non-dominated sort of
+/// NSGA-II. The first front is the Pareto front, every following front is dominated
+/// only by individuals of earlier fronts.
+///
+/// # Returns
+/// The indices of the individuals of every front, best front first.
+pub fn non_dominated_fronts<G: Gene>(individuals: &[Individual<G>]) -> Vec<Vec<usize>> {
+    fronts(individuals, false)
+}
+
+fn fronts<G: Gene>(individuals: &[Individual<G>], constrained: bool) -> Vec<Vec<usize>> {
+    let count = individuals.len();
+    let mut dominated_by_count = vec![0usize; count];
+    let mut dominates: Vec<Vec<usize>> = vec![vec![]; count];
+    for i in 0..count {
+        for j in i + 1..count {
+            if constrained_dominates(&individuals[i], &individuals[j], constrained) {
+                dominates[i].push(j);
+                dominated_by_count[j] += 1;
+            } else if constrained_dominates(&individuals[j], &individuals[i], constrained) {
+                dominates[j].push(i);
+                dominated_by_count[i] += 1;
+            }
+        }
+    }
+    let mut fronts = vec![];
+    let mut current: Vec<usize> = (0..count).filter(|&i| dominated_by_count[i] == 0).collect();
+    while !current.is_empty() {
+        let mut next = vec![];
+        for &i in &current {
+            for &j in &dominates[i] {
+                dominated_by_count[j] -= 1;
+                if dominated_by_count[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        next.sort();
+        fronts.push(current);
+        current = next;
+    }
+    fronts
+}
+
+/// Computes the crowding distance of every individual of a front: the sum over all
+/// objectives of the normalized distance between its two neighbours. The extremes of
+/// every objective get an infinite distance so they are always kept.
+///
+/// # Returns
+/// The distances, in the order of `front`.
+pub fn crowding_distances<G: Gene>(individuals: &[Individual<G>], front: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; front.len()];
+    if front.len() < 3 {
+        return vec![f64::INFINITY; front.len()];
+    }
+    let objective_count = individuals[front[0]].objectives.len();
+    for objective in 0..objective_count {
+        let value = |position: usize| individuals[front[position]].objectives[objective];
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|&a, &b| value(a).total_cmp(&value(b)));
+        let (min, max) = (value(order[0]), value(order[front.len() - 1]));
+        distances[order[0]] = f64::INFINITY;
+        distances[order[front.len() - 1]] = f64::INFINITY;
+        if max == min {
+            continue;
+        }
+        for k in 1..front.len() - 1 {
+            distances[order[k]] += (value(order[k + 1]) - value(order[k - 1])) / (max - min);
+        }
+    }
+    distances
+}
+
+impl<G: Gene> Population<G> {
+    /// Enables or disables NSGA-II style multi-objective mode. Instead of by fitness the
+    /// population is then ranked by non-dominated front and, within a front, by crowding
+    /// distance, so selection prefers better fronts and spreads out along them. Parents
+    /// survive into the next generation, which keeps the front found so far.
+    /// Set each individual's objectives with `Individual::set_objectives` when evaluating
+    /// it; the fitness is only used by fitness-proportional selection and `get_best_ever`.
+    /// With feasibility-first enabled feasible individuals dominate infeasible ones.
+    pub fn set_multi_objective(&mut self, enabled: bool) {
+        self.multi_objective = enabled;
+    }
+
+    pub fn is_multi_objective(&self) -> bool {
+        self.multi_objective
+    }
+
+    /// Returns the individuals no other individual of the population dominates.
+    pub fn pareto_front(&self) -> Vec<Individual<G>> {
+        match fronts(&self.individuals, self.feasibility_first).first() {
+            Some(front) => front.iter().map(|&i| self.individuals[i].clone()).collect(),
+            None => vec![],
+        }
+    }
+
+    /// Returns the indices of the individuals ordered by front, best first, and by
+    /// descending crowding distance within a front.
+    pub(crate) fn pareto_order(&self) -> Vec<usize> {
+        let mut order = vec![];
+        for front in fronts(&self.individuals, self.feasibility_first) {
+            let distances = crowding_distances(&self.individuals, &front);
+            let mut ranked: Vec<(usize, f64)> = front.into_iter().zip(distances).collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+            order.extend(ranked.into_iter().map(|(i, _)| i));
+        }
+        order
+    }
+
+    /// Sorts the individuals by front, best first, and by descending crowding distance
+    /// within a front.
+    pub(crate) fn sort_by_pareto_rank(&mut self) {
+        let order = self.pareto_order();
+        let mut individuals: Vec<Option<Individual<G>>> = std::mem::take(&mut self.individuals).into_iter().map(Some).collect();
+        self.individuals = order.into_iter().map(|i| individuals[i].take().unwrap()).collect();
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn individuals(objectives: &[[f64; 2]]) -> Vec<Individual> {
+        objectives
+            .iter()
+            .map(|objectives| {
+                let mut individual = Individual::from_genes(vec![false]);
+                individual.set_objectives(objectives.to_vec());
+                individual
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_hand_built_set_into_fronts() {
+        let individuals = individuals(&[[5.0, 0.0], [2.0, 2.0], [4.0, 1.0], [0.0, 0.0], [3.0, 3.0], [2.0, 1.0], [1.0, 4.0]]);
+        assert_eq!(non_dominated_fronts(&individuals), vec![vec![0, 2, 4, 6], vec![1], vec![5], vec![3]]);
+    }
+
+    #[test]
+    fn crowding_distance_of_hand_built_front() {
+        let individuals = individuals(&[[5.0, 0.0], [4.0, 1.0], [3.0, 3.0], [1.0, 4.0]]);
+        let distances = crowding_distances(&individuals, &[0, 1, 2, 3]);
+        assert_eq!(distances, vec![f64::INFINITY, 1.25, 1.5, f64::INFINITY]);
+        assert_eq!(crowding_distances(&individuals, &[1, 2]), vec![f64::INFINITY; 2]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::thread;
+
+use rand::Rng;
+
+use super::{Individual, Population};
+
+/// Settings for a parallel multi-start run.
+#[derive(Debug, Clone, Copy)]
+pub struct MultiStartSettings {
+    /// The number of independent runs launched, one thread each.
+    pub runs: usize,
+    /// The maximum number of generations of every run, 1 or more.
+    pub max_generations: u64,
+    /// Stops a run early once reached, 0 to always run `max_generations`.
+    pub goal_fitness: u64,
+    /// How many of the best individuals are kept in the merged hall of fame.
+    pub hall_of_fame_size: usize,
+    /// Whether to do one more run seeded with the merged hall of fame.
+    pub final_run: bool,
+}
+
+/// The outcome of a multi-start run.
+#[derive(Debug, Clone)]
+pub struct MultiStartResult {
+    /// The best individuals of all runs merged, fittest first.
+    pub hall_of_fame: Vec<Individual>,
+    /// The fittest individual of each independent run, in run order.
+    pub run_bests: Vec<Individual>,
+    /// The fittest individual of the final run, if one was done.
+    pub final_best: Option<Individual>,
+}
+
+/// Launches `settings.runs` independent runs in parallel and merges their results.
+/// Every run gets its own population from `make_population`, which is passed the run
+/// index so that callers can use a different configuration per run. Each population is
+/// reseeded with a seed derived from its own generator and the run index, so runs
+/// explore differently even when `make_population` seeds them all alike, and a seeded
+/// multi-start is still reproducible.
+///
+/// # Arguments
+/// * `make_population` - Creates the starting population of the run with the given index.
+/// * `fitness` - The fitness function shared by all runs.
+/// * `settings` - The run settings.
+///
+/// # Returns
+/// The merged hall of fame, the best of every run and the result of the optional final run.
+pub fn multi_start<P, F>(make_population: P, fitness: F, settings: MultiStartSettings) -> MultiStartResult
+where
+    P: Fn(usize) -> Population + Sync,
+    F: Fn(&Individual) -> u64 + Sync,
+{
+    if settings.runs < 1 {
+        panic!("run count must be 1 or more");
+    }
+    if settings.hall_of_fame_size < 1 {
+        panic!("hall of fame size must be 1 or more");
+    }
+    if settings.max_generations < 1 {
+        panic!("max generations must be 1 or more");
+    }
+
+    let run_halls: Vec<Vec<Individual>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..settings.runs)
+            .map(|run| {
+                let make_population = &make_population;
+                let fitness = &fitness;
+                scope.spawn(move || run_population(run_start(make_population, run), fitness, &settings))
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+
+    let run_bests: Vec<Individual> = run_halls.iter().map(|hall| hall[0].clone()).collect();
+    let mut hall_of_fame = vec![];
+    for hall in &run_halls {
+        merge_into_hall(&mut hall_of_fame, hall, settings.hall_of_fame_size);
+    }
+
+    let mut final_best = None;
+    if settings.final_run {
+        let mut population = run_start(&make_population, settings.runs);
+        for (i, elite) in hall_of_fame.iter().take(population.population_size).enumerate() {
+            population.individuals[i] = elite.clone();
+        }
+        let hall = run_population(population, &fitness, &settings);
+        final_best = Some(hall[0].clone());
+        merge_into_hall(&mut hall_of_fame, &hall, settings.hall_of_fame_size);
+    }
+
+    MultiStartResult {
+        hall_of_fame,
+        run_bests,
+        final_best,
+    }
+}
+
+/// Creates the starting population of a run and gives it a seed of its own.
+fn run_start<P: Fn(usize) -> Population>(make_population: &P, run: usize) -> Population {
+    let mut population = make_population(run);
+    // an odd multiplier keeps the seeds of all runs apart even if the generators start alike
+    let seed = population.rng.random::<u64>() ^ (run as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    population.reseed(seed);
+    population
+}
+
+/// Evolves a single population and returns its hall of fame, fittest first.
+fn run_population<F: Fn(&Individual) -> u64>(
+    mut population: Population,
+    fitness: &F,
+    settings: &MultiStartSettings,
+) -> Vec<Individual> {
+    let mut hall = vec![];
+    for generation in 0..settings.max_generations {
+        population.evaluate(fitness);
+        merge_into_hall(&mut hall, &population.individuals, settings.hall_of_fame_size);
+
+        let reached_goal = settings.goal_fitness != 0 && hall[0].fitness >= settings.goal_fitness;
+        if reached_goal || generation + 1 == settings.max_generations {
+            break;
+        }
+        population.next_generation();
+    }
+    hall
+}
+
+/// Adds candidates to a hall of fame, fittest first, skipping genomes already in it,
+/// and keeps only the `size` fittest. Only the admitted candidates are cloned.
+fn merge_into_hall<'a>(hall: &mut Vec<Individual>, candidates: impl IntoIterator<Item = &'a Individual>, size: usize) {
+    for candidate in candidates {
+        let admitted = hall.len() < size || hall.last().is_some_and(|worst| candidate > worst);
+        if admitted && !hall.iter().any(|member| member.genes == candidate.genes) {
+            let position = hall.partition_point(|member| member >= candidate);
+            hall.insert(position, candidate.clone());
+            hall.truncate(size);
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::super::{init_population_seeded, CrossoverType};
+    use super::*;
+
+    fn settings(max_generations: u64) -> MultiStartSettings {
+        MultiStartSettings {
+            runs: 3,
+            max_generations,
+            goal_fitness: 0,
+            hall_of_fame_size: 5,
+            final_run: false,
+        }
+    }
+
+    #[test]
+    fn every_generation_is_evaluated_once() {
+        let calls = AtomicU64::new(0);
+        let fitness = |individual: &Individual| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+        };
+        let result = multi_start(|run| init_population_seeded(16, 10, 2, 5, false, CrossoverType::Bit, run as u64), fitness, settings(4));
+        assert_eq!(calls.load(Ordering::Relaxed), 3 * 4 * 10);
+        assert_eq!(result.run_bests.len(), 3);
+        assert_eq!(result.hall_of_fame.len(), 5);
+        assert!(result.hall_of_fame.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    #[should_panic(expected = "max generations must be 1 or more")]
+    fn zero_generations_are_rejected() {
+        multi_start(|run| init_population_seeded(16, 10, 2, 5, false, CrossoverType::Bit, run as u64), |_: &Individual| 0, settings(0));
+    }
+}
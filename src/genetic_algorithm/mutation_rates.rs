@@ -0,0 +1,52 @@
+use super::{gene::Gene, Population};
+
+impl<G: Gene> Population<G> {
+    /// Gives every gene its own mutation probability in place of the population's,
+    /// so parts of the chromosome that are known to be sensitive can mutate less.
+    /// Only the built-in mutation uses the rates, variation operators keep using the
+    /// mutation probability. A mutation schedule has no effect while rates are set.
+    ///
+    /// # Arguments
+    /// * `rates` - The probability (0.0-1.0) of mutating each gene, one per gene.
+    pub fn set_gene_mutation_rates(&mut self, rates: Vec<f64>) {
+        let gene_length = self.individuals[0].gene_length;
+        if rates.len() != gene_length {
+            panic!("got {} mutation rates for a gene length of {}", rates.len(), gene_length);
+        }
+        for &rate in &rates {
+            check_rate(rate);
+        }
+        self.gene_mutation_rates = Some(rates);
+    }
+
+    /// Changes the mutation probability of a single gene, e.g. between generations.
+    /// The other genes keep the mutation probability if no rates were set before.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the gene, must be within the gene length.
+    /// * `rate` - The probability (0.0-1.0) of mutating the gene.
+    pub fn set_gene_mutation_rate(&mut self, index: usize, rate: f64) {
+        let gene_length = self.individuals[0].gene_length;
+        if index >= gene_length {
+            panic!("gene index {} is outside the gene length {}", index, gene_length);
+        }
+        check_rate(rate);
+        let probability = self.mutation_probability;
+        self.gene_mutation_rates.get_or_insert_with(|| vec![probability; gene_length])[index] = rate;
+    }
+
+    /// Goes back to mutating every gene with the mutation probability.
+    pub fn clear_gene_mutation_rates(&mut self) {
+        self.gene_mutation_rates = None;
+    }
+
+    pub fn get_gene_mutation_rates(&self) -> Option<&[f64]> {
+        self.gene_mutation_rates.as_deref()
+    }
+}
+
+fn check_rate(rate: f64) {
+    if !(0.0..=1.0).contains(&rate) {
+        panic!("mutation rate must be between 0 and 1");
+    }
+}
@@ -0,0 +1,109 @@
+use super::{gene::Gene, Population};
+
+/// How the mutation probability of a population changes over a run, high early on to
+/// explore and low later to fine tune, or in response to how the run is going.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MutationSchedule {
+    /// Falls linearly from `start` to `end` (0.0-1.0) over `generations`, then stays at `end`.
+    LinearDecay { start: f64, end: f64, generations: u64 },
+    /// Multiplies `start` (0.0-1.0) by `factor` (0.0-1.0) every generation, never falling
+    /// below `min`.
+    ExponentialDecay { start: f64, factor: f64, min: f64 },
+    /// Adapts to the population every generation: raises the probability by `step` when
+    /// the diversity falls below `min_diversity`, lowers it by `step` when the best fitness
+    /// improved, keeping it between `min` and `max` (0.0-1.0). The diversity is the mean
+    /// fraction (0.0-1.0) of loci at which the individuals differ from the fittest one.
+    Adaptive { min: f64, max: f64, step: f64, min_diversity: f64 },
+}
+
+impl<G: Gene> Population<G> {
+    /// Changes the mutation probability every generation by the given schedule, replacing
+    /// the fixed mutation probability. Decays count the generations from now on and the
+    /// mutation probability is set to the start of the schedule right away.
+    ///
+    /// # Arguments
+    /// * `schedule` - How the mutation probability changes.
+    pub fn set_mutation_schedule(&mut self, schedule: MutationSchedule) {
+        let valid = |probability: f64| (0.0..=1.0).contains(&probability);
+        self.mutation_probability = match schedule {
+            MutationSchedule::LinearDecay { start, end, .. } => {
+                if !valid(start) || !valid(end) {
+                    panic!("mutation probability must be between 0 and 1");
+                }
+                start
+            }
+            MutationSchedule::ExponentialDecay { start, factor, min } => {
+                if !valid(start) || !valid(min) {
+                    panic!("mutation probability must be between 0 and 1");
+                }
+                if !valid(factor) {
+                    panic!("decay factor must be between 0 and 1");
+                }
+                start.max(min)
+            }
+            MutationSchedule::Adaptive { min, max, step, min_diversity } => {
+                if !valid(min) || !valid(max) || !valid(step) {
+                    panic!("mutation probability must be between 0 and 1");
+                }
+                if min > max {
+                    panic!("minimum mutation probability cant be higher than the maximum");
+                }
+                if !(0.0..=1.0).contains(&min_diversity) {
+                    panic!("minimum diversity must be between 0 and 1");
+                }
+                self.mutation_probability.clamp(min, max)
+            }
+        };
+        self.mutation_schedule = Some(schedule);
+        self.mutation_schedule_start = self.generation;
+    }
+
+    /// Goes back to a fixed mutation probability, the current one.
+    pub fn clear_mutation_schedule(&mut self) {
+        self.mutation_schedule = None;
+    }
+
+    pub fn get_mutation_schedule(&self) -> Option<MutationSchedule> {
+        self.mutation_schedule
+    }
+
+    /// Moves the mutation probability one generation along the schedule, if one is set.
+    /// Called with the evaluated population sorted, before the best fitness is recorded.
+    pub(crate) fn apply_mutation_schedule(&mut self) {
+        let elapsed = self.generation - self.mutation_schedule_start + 1;
+        self.mutation_probability = match self.mutation_schedule {
+            None => return,
+            Some(MutationSchedule::LinearDecay { start, end, generations }) => {
+                let progress = (elapsed as f64 / generations.max(1) as f64).min(1.0);
+                start + (end - start) * progress
+            }
+            Some(MutationSchedule::ExponentialDecay { start, factor, min }) => (start * factor.powf(elapsed as f64)).max(min),
+            Some(MutationSchedule::Adaptive { min, max, step, min_diversity }) => {
+                let best = match self.individuals.first() {
+                    Some(best) => best,
+                    None => return,
+                };
+                let improved = self.best_fitness_seen.is_some_and(|seen| best.fitness > seen);
+                if self.diversity_to_best() < min_diversity {
+                    (self.mutation_probability + step).clamp(min, max)
+                } else if improved {
+                    (self.mutation_probability - step).clamp(min, max)
+                } else {
+                    self.mutation_probability
+                }
+            }
+        };
+    }
+
+    /// Returns the mean fraction (0.0-1.0) of loci at which the individuals differ from
+    /// the first one, the fittest of a sorted population.
+    pub(crate) fn diversity_to_best(&self) -> f64 {
+        let best = &self.individuals[0];
+        if best.gene_length == 0 || self.individuals.len() < 2 {
+            return 0.0;
+        }
+        let distance: usize = self.individuals[1..].iter().map(|individual| best.hamming_distance(individual)).sum();
+        distance as f64 / ((self.individuals.len() - 1) * best.gene_length) as f64
+    }
+}
@@ -0,0 +1,282 @@
+use std::{cmp::Ordering, ops::Range};
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+
+use super::{constraints, gene::Gene, rng::GaRng, Individual, Population};
+
+/// Goldberg's fitness sharing: every individual's fitness is divided by its niche count,
+/// the sum of `1 - (d / radius)^alpha` over all individuals within a Hamming distance
+/// `d < radius` of it, itself included. Crowded optima become less attractive, so the
+/// population can hold several niches instead of collapsing onto one optimum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FitnessSharing {
+    radius: usize,
+    alpha: f64,
+}
+
+impl FitnessSharing {
+    /// Creates a new sharing setting.
+    ///
+    /// # Arguments
+    /// * `radius` - The niche radius in differing genes, 1 or more.
+    /// * `alpha` - The shape of the sharing function, 1.0 is triangular, larger values share less with distant neighbours.
+    pub fn new(radius: usize, alpha: f64) -> FitnessSharing {
+        if radius < 1 {
+            panic!("sharing radius must be 1 or more");
+        }
+        if alpha.is_nan() || alpha <= 0.0 {
+            panic!("sharing alpha must be larger than 0");
+        }
+        FitnessSharing { radius, alpha }
+    }
+
+    pub fn get_radius(&self) -> usize {
+        self.radius
+    }
+
+    pub fn get_alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Returns how much two individuals at the given distance share, 1.0 for identical ones.
+    fn share(&self, distance: usize) -> f64 {
+        if distance >= self.radius {
+            return 0.0;
+        }
+        1.0 - (distance as f64 / self.radius as f64).powf(self.alpha)
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Enables fitness sharing. After every evaluation, and after the constraints, each
+    /// individual's fitness is replaced by its shared fitness, which is also what the
+    /// fittest individual and the statistics report. The fitness cache keeps the raw
+    /// fitness. Costs a distance computation per pair of individuals.
+    ///
+    /// # Arguments
+    /// * `sharing` - The niche radius and shape.
+    pub fn set_fitness_sharing(&mut self, sharing: FitnessSharing) {
+        self.fitness_sharing = Some(sharing);
+    }
+
+    /// Disables fitness sharing, the next evaluation gives raw fitness values again.
+    pub fn clear_fitness_sharing(&mut self) {
+        self.fitness_sharing = None;
+    }
+
+    pub fn get_fitness_sharing(&self) -> Option<FitnessSharing> {
+        self.fitness_sharing
+    }
+
+    /// Returns the niche count of every individual of the range, measured against the
+    /// whole population, 1.0 or more. All 1.0 without fitness sharing.
+    pub fn niche_counts(&self, range: Range<usize>) -> Vec<f64> {
+        let sharing = match self.fitness_sharing {
+            Some(sharing) => sharing,
+            None => return vec![1.0; range.len()],
+        };
+        self.individuals[range]
+            .iter()
+            .map(|individual| {
+                self.individuals
+                    .iter()
+                    .map(|other| sharing.share(individual.hamming_distance(other)))
+                    .sum::<f64>()
+            })
+            .collect()
+    }
+
+    /// Divides the fitness of a range of freshly evaluated individuals by their niche counts.
+    pub(crate) fn apply_fitness_sharing_to(&mut self, range: Range<usize>) {
+        if self.fitness_sharing.is_none() {
+            return;
+        }
+        let counts = self.niche_counts(range.clone());
+        for (individual, count) in self.individuals[range].iter_mut().zip(counts) {
+            individual.fitness = (individual.fitness as f64 / count).round() as u64;
+        }
+    }
+
+    /// Switches `next_generation` to deterministic crowding: the individuals are paired at
+    /// random, every pair breeds two children and each child competes against the parent
+    /// it is most similar to instead of the global worst, replacing it if fitter. Similar
+    /// individuals compete with each other, so several optima keep their share of the
+    /// population. The children are appended after the parents; the competition happens
+    /// as soon as they are evaluated, best with `evaluate_last(population_size, ..)`, and
+    /// leaves `population_size` individuals. The selection type, parent count and elitism
+    /// are not used, the best individual always survives. Cant be combined with
+    /// another replacement mode, e.g. steady-state mode or ALPS.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to use crowding replacement.
+    pub fn set_crowding(&mut self, enabled: bool) {
+        if enabled {
+            self.check_replacement_mode("crowding");
+        }
+        self.crowding = enabled;
+        self.crowding_pending = None;
+    }
+
+    pub fn is_crowding(&self) -> bool {
+        self.crowding
+    }
+
+    /// Returns the number of children waiting for their evaluation to compete against
+    /// their parents, `None` when no crowding step is pending.
+    pub fn get_crowding_pending(&self) -> Option<usize> {
+        self.crowding_pending
+    }
+
+    /// Breeds two children per random pair of individuals and appends them. With an odd
+    /// population the last individual breeds one child with a random partner.
+    pub(crate) fn crowding_step(&mut self) {
+        let count = self.individuals.len();
+        let mut individuals = std::mem::take(&mut self.individuals);
+        individuals.shuffle(&mut self.rng);
+        let mut children = Vec::with_capacity(count);
+        for pair in individuals.chunks(2) {
+            let parents = match pair.len() {
+                2 => [&pair[0], &pair[1]],
+                _ if count > 1 => [&pair[0], &individuals[self.rng.random_range(0..count - 1)]],
+                _ => [&pair[0], &pair[0]],
+            };
+            for _ in 0..pair.len() {
+                let mut rng = GaRng::seed_from_u64(self.rng.random());
+                children.push(self.create_child(&parents, &mut rng, Individual::blank()));
+            }
+        }
+        individuals.append(&mut children);
+        self.individuals = individuals;
+        self.crowding_pending = Some(count);
+    }
+
+    /// Lets every evaluated child of a crowding step compete against its most similar
+    /// parent. Pairs are matched so the summed distances of both contests are smallest.
+    /// A pending step is dropped if the population changed size since.
+    pub(crate) fn resolve_crowding(&mut self) {
+        let count = match self.crowding_pending.take() {
+            Some(count) if self.individuals.len() == 2 * count => count,
+            _ => return,
+        };
+        let children = self.individuals.split_off(count);
+        let mut children = children.into_iter();
+        for start in (0..count).step_by(2) {
+            if start + 1 == count {
+                let child = children.next().unwrap();
+                self.replace_if_fitter(start, child);
+                continue;
+            }
+            let (first, second) = (children.next().unwrap(), children.next().unwrap());
+            let (a, b) = (&self.individuals[start], &self.individuals[start + 1]);
+            let straight = a.hamming_distance(&first) + b.hamming_distance(&second);
+            let crossed = a.hamming_distance(&second) + b.hamming_distance(&first);
+            let (first, second) = if straight <= crossed { (first, second) } else { (second, first) };
+            self.replace_if_fitter(start, first);
+            self.replace_if_fitter(start + 1, second);
+        }
+    }
+
+    /// Replaces the individual at `index` with the challenger if the challenger is fitter,
+    /// by feasibility first if enabled.
+    fn replace_if_fitter(&mut self, index: usize, challenger: Individual<G>) {
+        let incumbent = &self.individuals[index];
+        let fitter = if self.feasibility_first {
+            constraints::compare_feasibility_first(&challenger, incumbent) == Ordering::Greater
+        } else {
+            challenger.fitness > incumbent.fitness
+        };
+        if fitter {
+            self.individuals[index] = challenger;
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{init_population_seeded, CrossoverType};
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+    }
+
+    fn individual(genes: &str, fitness: u64) -> Individual {
+        let mut individual = Individual::from_genes(genes.chars().map(|gene| gene == '1').collect());
+        individual.set_fitness(fitness);
+        individual
+    }
+
+    fn population_of(individuals: Vec<Individual>) -> Population {
+        let mut population = init_population_seeded(individuals[0].gene_length, individuals.len(), 2, 5, false, CrossoverType::Bit, 1);
+        population.individuals = individuals;
+        population
+    }
+
+    #[test]
+    fn niche_counts_sum_the_sharing_of_close_neighbours() {
+        let mut population = population_of(vec![individual("0000", 30), individual("0001", 30), individual("1111", 30)]);
+        assert_eq!(population.niche_counts(0..3), vec![1.0; 3]);
+        population.set_fitness_sharing(FitnessSharing::new(2, 1.0));
+        assert_eq!(population.niche_counts(0..3), vec![1.5, 1.5, 1.0]);
+        assert_eq!(population.niche_counts(1..2), vec![1.5]);
+        population.apply_fitness_sharing_to(1..3);
+        let fitness: Vec<u64> = population.individuals.iter().map(|individual| individual.get_fitness()).collect();
+        assert_eq!(fitness, vec![30, 20, 30]);
+    }
+
+    #[test]
+    fn children_only_replace_the_closer_parent_when_fitter() {
+        let mut population = population_of(vec![
+            individual("000000", 5),
+            individual("111111", 5),
+            individual("110000", 5),
+            // the children of the first pair, each closer to the other parent
+            individual("111110", 9),
+            individual("000001", 1),
+            // the only child of the odd individual
+            individual("110001", 6),
+        ]);
+        population.set_crowding(true);
+        population.crowding_pending = Some(3);
+        population.resolve_crowding();
+        let genes: Vec<Vec<bool>> = population.individuals.iter().map(|individual| individual.get_genes()).collect();
+        assert_eq!(genes, vec![individual("000000", 0).get_genes(), individual("111110", 0).get_genes(), individual("110001", 0).get_genes()]);
+        assert_eq!(population.get_crowding_pending(), None);
+    }
+
+    #[test]
+    fn a_seeded_crowding_generation_keeps_parents_unless_their_closest_child_is_fitter() {
+        let mut population = init_population_seeded(12, 7, 2, 20, false, CrossoverType::Bit, 3);
+        population.set_crowding(true);
+        let mut replaced = 0;
+        for _ in 0..10 {
+            population.evaluate(ones);
+            population.next_generation();
+            assert_eq!(population.individuals.len(), 14);
+            assert_eq!(population.get_crowding_pending(), Some(7));
+            let parents = population.individuals[..7].to_vec();
+            let children = population.individuals[7..].to_vec();
+            population.evaluate_last(7, ones);
+            assert_eq!(population.individuals.len(), 7);
+            for (slot, result) in population.individuals.iter().enumerate() {
+                if result.get_genes() == parents[slot].get_genes() {
+                    continue;
+                }
+                replaced += 1;
+                assert!(result.get_fitness() > parents[slot].get_fitness());
+                let pair = slot - slot % 2;
+                if pair + 1 == 7 {
+                    assert_eq!(result.get_genes(), children[pair].get_genes());
+                    continue;
+                }
+                let (own, other) = if result.get_genes() == children[pair].get_genes() { (pair, pair + 1) } else { (pair + 1, pair) };
+                assert_eq!(result.get_genes(), children[own].get_genes());
+                let partner = &parents[pair + 1 - slot % 2];
+                let matched = parents[slot].hamming_distance(&children[own]) + partner.hamming_distance(&children[other]);
+                let swapped = parents[slot].hamming_distance(&children[other]) + partner.hamming_distance(&children[own]);
+                assert!(matched <= swapped);
+            }
+        }
+        assert!(replaced > 0);
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use super::{gene::Gene, restart::RestartInfo, stats::GenerationStats, Population};
+
+/// Gets called with every evaluated generation, e.g. to log, plot or checkpoint a run
+/// without rewriting the evolution loop. Closures `FnMut(&Population<G>, GenerationStats)`
+/// implement it too.
+pub trait Observer<G: Gene = bool>: Send {
+    /// # Arguments
+    /// * `population` - The evaluated population, sorted best first.
+    /// * `stats` - The fitness statistics of the generation.
+    fn on_generation(&mut self, population: &Population<G>, stats: GenerationStats);
+
+    /// Gets called when a `GaRunner` restarts the population, after the re-randomization
+    /// and before the population is evaluated again. Does nothing by default.
+    ///
+    /// # Arguments
+    /// * `population` - The restarted population, the kept elites first.
+    /// * `restart` - Why and when the population was restarted.
+    fn on_restart(&mut self, _population: &Population<G>, _restart: RestartInfo) {}
+
+    /// Whether `on_generation` needs `GenerationStats::diversity`, which compares every
+    /// pair of individuals and is only computed if some observer asks for it. False by
+    /// default, and for closures.
+    fn needs_diversity(&self) -> bool {
+        false
+    }
+}
+
+impl<G: Gene, F: FnMut(&Population<G>, GenerationStats) + Send> Observer<G> for F {
+    fn on_generation(&mut self, population: &Population<G>, stats: GenerationStats) {
+        self(population, stats)
+    }
+}
+
+/// A shared observer stored on a population.
+#[derive(Clone)]
+pub(crate) struct ObserverHook<G: Gene>(Arc<Mutex<dyn Observer<G>>>);
+
+impl<G: Gene> fmt::Debug for ObserverHook<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ObserverHook")
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Adds an observer that `next_generation` calls with every evaluated generation
+    /// before breeding the next one, single or multi threaded. `GaRunner` also calls it
+    /// with the last generation of a run.
+    pub fn add_observer<O: Observer<G> + 'static>(&mut self, observer: O) {
+        self.observers.push(ObserverHook(Arc::new(Mutex::new(observer))));
+    }
+
+    /// Removes all observers.
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+    }
+
+    /// Calls every observer with the current, evaluated population.
+    pub(crate) fn notify_observers(&mut self) {
+        if self.observers.is_empty() {
+            return;
+        }
+        self.sort_individuals();
+        let diversity = self.observers.iter().any(|ObserverHook(observer)| observer.lock().unwrap().needs_diversity());
+        let stats = self.stats_with(diversity);
+        for ObserverHook(observer) in self.observers.clone() {
+            observer.lock().unwrap().on_generation(self, stats.clone());
+        }
+    }
+
+    /// Calls `Observer::on_restart` of every observer.
+    pub(crate) fn notify_restart(&self, info: RestartInfo) {
+        for ObserverHook(observer) in self.observers.clone() {
+            observer.lock().unwrap().on_restart(self, info);
+        }
+    }
+}
@@ -0,0 +1,517 @@
+use std::{any::Any, borrow::Cow, fmt, ops::{Index, Range}};
+
+use rand::Rng;
+
+use super::{gene::Gene, CrossoverType, Individual, Population};
+
+/// The gene storage of an individual. Genes are kept as a `Vec<G>`, or for `bool` genes
+/// in a packed population 64 to a `u64` word, see `Population::set_packed_storage`.
+pub(crate) enum Genes<G: Gene> {
+    Dense(Vec<G>),
+    Packed(PackedBits),
+}
+
+/// Bit string genes, gene `i` is bit `i % 64` of word `i / 64` and the unused bits of
+/// the last word are always 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PackedBits {
+    length: usize,
+    words: Vec<u64>,
+}
+
+/// Reads a gene of a packed population as a bit.
+fn to_bit<G: Gene>(gene: &G) -> bool {
+    *(gene as &dyn Any).downcast_ref::<bool>().expect("only bool genes can be packed")
+}
+
+/// Reads a bit of a packed genome as a gene.
+fn from_bit<G: Gene>(bit: bool) -> &'static G {
+    let bit: &'static bool = if bit { &true } else { &false };
+    (bit as &dyn Any).downcast_ref::<G>().expect("only bool genes can be packed")
+}
+
+impl<G: Gene> Genes<G> {
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Genes::Dense(genes) => genes.len(),
+            Genes::Packed(bits) => bits.length,
+        }
+    }
+
+    pub(crate) fn is_packed(&self) -> bool {
+        matches!(self, Genes::Packed(_))
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_, G> {
+        Iter { genes: self, range: 0..self.len() }
+    }
+
+    pub(crate) fn set(&mut self, index: usize, value: G) {
+        match self {
+            Genes::Dense(genes) => genes[index] = value,
+            Genes::Packed(bits) => bits.set(index, to_bit(&value)),
+        }
+    }
+
+    pub(crate) fn to_vec(&self) -> Vec<G> {
+        match self {
+            Genes::Dense(genes) => genes.clone(),
+            Genes::Packed(_) => self.iter().cloned().collect(),
+        }
+    }
+
+    /// Returns the genes as a slice, borrowed when dense and unpacked when packed.
+    pub(crate) fn as_slice(&self) -> Cow<'_, [G]> {
+        match self {
+            Genes::Dense(genes) => Cow::Borrowed(genes),
+            Genes::Packed(_) => Cow::Owned(self.to_vec()),
+        }
+    }
+
+    /// Unpacks packed genes and returns them for editing in place. The population packs
+    /// them again at the end of the generation.
+    pub(crate) fn make_dense(&mut self) -> &mut Vec<G> {
+        if let Genes::Packed(_) = self {
+            *self = Genes::Dense(self.to_vec());
+        }
+        match self {
+            Genes::Dense(genes) => genes,
+            Genes::Packed(_) => unreachable!(),
+        }
+    }
+
+    /// Replaces the genes, keeping the storage.
+    pub(crate) fn assign(&mut self, genes: Vec<G>) {
+        *self = match self {
+            Genes::Dense(_) => Genes::Dense(genes),
+            Genes::Packed(_) => Genes::Packed(PackedBits::from_bits(genes.iter().map(to_bit))),
+        };
+    }
+
+    /// Packs dense genes, which must be `bool`s.
+    pub(crate) fn pack(&mut self) {
+        if let Genes::Dense(genes) = self {
+            *self = Genes::Packed(PackedBits::from_bits(genes.iter().map(to_bit)));
+        }
+    }
+
+    pub(crate) fn unpack(&mut self) {
+        self.make_dense();
+    }
+
+    /// Returns the number of loci at which the two genomes differ, counting every locus
+    /// only the longer one has. Packed genomes are compared a word at a time.
+    pub(crate) fn count_differences(&self, other: &Genes<G>) -> usize {
+        match (self, other) {
+            (Genes::Packed(a), Genes::Packed(b)) => a.count_differences(b),
+            _ => self.len().abs_diff(other.len()) + self.iter().zip(other.iter()).filter(|(a, b)| a != b).count(),
+        }
+    }
+}
+
+impl<G: Gene> Index<usize> for Genes<G> {
+    type Output = G;
+
+    fn index(&self, index: usize) -> &G {
+        match self {
+            Genes::Dense(genes) => &genes[index],
+            Genes::Packed(bits) => from_bit(bits.get(index)),
+        }
+    }
+}
+
+impl<G: Gene> From<Vec<G>> for Genes<G> {
+    fn from(genes: Vec<G>) -> Genes<G> {
+        Genes::Dense(genes)
+    }
+}
+
+impl<G: Gene> FromIterator<G> for Genes<G> {
+    fn from_iter<I: IntoIterator<Item = G>>(genes: I) -> Genes<G> {
+        Genes::Dense(genes.into_iter().collect())
+    }
+}
+
+impl<G: Gene> Clone for Genes<G> {
+    fn clone(&self) -> Genes<G> {
+        match self {
+            Genes::Dense(genes) => Genes::Dense(genes.clone()),
+            Genes::Packed(bits) => Genes::Packed(bits.clone()),
+        }
+    }
+
+    // children are built in the storage of discarded individuals
+    fn clone_from(&mut self, source: &Genes<G>) {
+        match (self, source) {
+            (Genes::Dense(genes), Genes::Dense(other)) => genes.clone_from(other),
+            (Genes::Packed(bits), Genes::Packed(other)) => bits.clone_from(other),
+            (genes, source) => *genes = source.clone(),
+        }
+    }
+}
+
+impl<G: Gene> PartialEq for Genes<G> {
+    fn eq(&self, other: &Genes<G>) -> bool {
+        match (self, other) {
+            (Genes::Dense(a), Genes::Dense(b)) => a == b,
+            (Genes::Packed(a), Genes::Packed(b)) => a == b,
+            _ => self.len() == other.len() && self.iter().eq(other.iter()),
+        }
+    }
+}
+
+impl<G: Gene> fmt::Debug for Genes<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Packed genes are written as a plain sequence of genes and read back dense.
+#[cfg(feature = "serde")]
+impl<G: Gene + serde::Serialize> serde::Serialize for Genes<G> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, G: Gene + serde::Deserialize<'de>> serde::Deserialize<'de> for Genes<G> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Genes<G>, D::Error> {
+        Vec::deserialize(deserializer).map(Genes::Dense)
+    }
+}
+
+pub(crate) struct Iter<'a, G: Gene> {
+    genes: &'a Genes<G>,
+    range: Range<usize>,
+}
+
+impl<'a, G: Gene> Iterator for Iter<'a, G> {
+    type Item = &'a G;
+
+    fn next(&mut self) -> Option<&'a G> {
+        self.range.next().map(|index| &self.genes[index])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<G: Gene> ExactSizeIterator for Iter<'_, G> {}
+
+impl<'a, G: Gene> IntoIterator for &'a Genes<G> {
+    type Item = &'a G;
+    type IntoIter = Iter<'a, G>;
+
+    fn into_iter(self) -> Iter<'a, G> {
+        self.iter()
+    }
+}
+
+impl PackedBits {
+    fn from_bits(bits: impl ExactSizeIterator<Item = bool>) -> PackedBits {
+        let length = bits.len();
+        let mut words = vec![0; length.div_ceil(64)];
+        for (i, bit) in bits.enumerate() {
+            words[i / 64] |= (bit as u64) << (i % 64);
+        }
+        PackedBits { length, words }
+    }
+
+    /// Creates random genes, each `true` with a 50% chance.
+    pub(crate) fn random<R: Rng + ?Sized>(length: usize, rng: &mut R) -> PackedBits {
+        let mut bits = PackedBits { length, words: (0..length.div_ceil(64)).map(|_| rng.random()).collect() };
+        bits.clear_unused_bits();
+        bits
+    }
+
+    fn get(&self, index: usize) -> bool {
+        if index >= self.length {
+            panic!("index out of bounds: the len is {} but the index is {}", self.length, index);
+        }
+        self.words[index / 64] >> (index % 64) & 1 == 1
+    }
+
+    fn set(&mut self, index: usize, value: bool) {
+        if index >= self.length {
+            panic!("index out of bounds: the len is {} but the index is {}", self.length, index);
+        }
+        let bit = 1 << (index % 64);
+        if value {
+            self.words[index / 64] |= bit;
+        } else {
+            self.words[index / 64] &= !bit;
+        }
+    }
+
+    fn count_differences(&self, other: &PackedBits) -> usize {
+        let shorter = self.length.min(other.length);
+        // a locus only the longer genome has differs whatever its value
+        let mut differences = self.length.abs_diff(other.length);
+        for (i, (a, b)) in self.words.iter().zip(&other.words).enumerate() {
+            let mask = u64::MAX >> (64 - (shorter - 64 * i).min(64));
+            differences += ((a ^ b) & mask).count_ones() as usize;
+        }
+        differences
+    }
+
+    /// Copies the genes in `range` from `source`, a word at a time.
+    fn copy_range(&mut self, source: &PackedBits, range: Range<usize>) {
+        let mut index = range.start;
+        while index < range.end {
+            let word = index / 64;
+            let end = range.end.min(word * 64 + 64);
+            let mask = (u64::MAX >> (64 - (end - index))) << (index % 64);
+            self.words[word] = (self.words[word] & !mask) | (source.words[word] & mask);
+            index = end;
+        }
+    }
+
+    /// Takes each gene from `first` or `second` at random, a random mask word at a time.
+    fn mix<R: Rng + ?Sized>(&mut self, first: &PackedBits, second: &PackedBits, rng: &mut R) {
+        for (i, word) in self.words.iter_mut().enumerate() {
+            let mask: u64 = rng.random();
+            *word = (first.words[i] & mask) | (second.words[i] & !mask);
+        }
+    }
+
+    /// Flips every gene independently with the given probability. The gaps between
+    /// flips are drawn from a geometric distribution, so the cost grows with the number
+    /// of flips rather than with the gene length.
+    ///
+    /// # Returns
+    /// The number of flipped genes.
+    fn mutate<R: Rng + ?Sized>(&mut self, probability: f64, rng: &mut R) -> u64 {
+        if probability <= 0.0 {
+            return 0;
+        }
+        if probability >= 1.0 {
+            for word in self.words.iter_mut() {
+                *word = !*word;
+            }
+            self.clear_unused_bits();
+            return self.length as u64;
+        }
+        let mut flips = 0;
+        let mut index = 0;
+        loop {
+            let roll: f64 = rng.random_range(f64::MIN_POSITIVE..1.0);
+            let skip = (roll.ln() / (1.0 - probability).ln()).floor();
+            if skip >= (self.length - index) as f64 {
+                return flips;
+            }
+            index += skip as usize;
+            self.words[index / 64] ^= 1 << (index % 64);
+            flips += 1;
+            index += 1;
+        }
+    }
+
+    /// Sets the bits past the gene length in the last word back to 0.
+    fn clear_unused_bits(&mut self) {
+        if !self.length.is_multiple_of(64) {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1u64 << (self.length % 64)) - 1;
+        }
+    }
+}
+
+impl Individual {
+    /// Returns true if the genes are stored 64 to a word, see `Population::set_packed_storage`.
+    pub fn is_packed(&self) -> bool {
+        self.genes.is_packed()
+    }
+}
+
+impl Population {
+    /// Stores the genes of every individual 64 to a `u64` word instead of one `bool` per
+    /// byte, for gene lengths in the hundreds of thousands. It uses an eighth of the
+    /// memory, and point crossover, two parent bit crossover and mutation work on whole
+    /// words, so a packed population draws different random numbers than a dense one.
+    /// The individuals keep the same getters.
+    ///
+    /// # Arguments
+    /// * `packed` - Whether to pack the genes, false unpacks them again.
+    pub fn set_packed_storage(&mut self, packed: bool) {
+        self.packed_storage = packed;
+        for individual in self.individuals.iter_mut() {
+            if packed {
+                individual.genes.pack();
+            } else {
+                individual.genes.unpack();
+            }
+        }
+    }
+
+    pub fn is_packed_storage(&self) -> bool {
+        self.packed_storage
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Packs the genes the last generation left dense, e.g. those of immigrants and of
+    /// children made by variation operators.
+    pub(crate) fn apply_storage(&mut self) {
+        if self.packed_storage {
+            for individual in self.individuals.iter_mut() {
+                individual.genes.pack();
+            }
+        }
+    }
+
+    /// Creates the genes of a child of packed parents a word at a time, or returns false
+    /// if the crossover needs the gene by gene path.
+    ///
+    /// # Arguments
+    /// * `child` - The child, its genes a packed copy of the first parent.
+    /// * `parents` - The parents of the child.
+    /// * `rng` - The random number generator to draw from.
+    /// * `mutations` - Counts the mutated genes.
+    pub(crate) fn packed_child<R: Rng + ?Sized>(&self, child: &mut Individual<G>, parents: &[&Individual<G>], rng: &mut R, mutations: &mut u64) -> bool {
+        if self.crossover_mask.is_some() || self.self_adaptation.is_some() || self.gene_mutation_rates.is_some() {
+            return false;
+        }
+        let bits: Vec<&PackedBits> = parents
+            .iter()
+            .filter_map(|parent| match &parent.genes {
+                Genes::Packed(bits) => Some(bits),
+                Genes::Dense(_) => None,
+            })
+            .collect();
+        let Genes::Packed(genes) = &mut child.genes else {
+            return false;
+        };
+        if bits.len() != parents.len() {
+            return false;
+        }
+        let length = genes.length;
+        match self.crossover {
+            CrossoverType::SinglePoint | CrossoverType::TwoPoint | CrossoverType::KPoint(_) | CrossoverType::Diagonal => {
+                let count = match self.crossover {
+                    CrossoverType::SinglePoint => 1,
+                    CrossoverType::TwoPoint => 2,
+                    CrossoverType::KPoint(k) => k,
+                    _ => parents.len() - 1,
+                };
+                let cuts = self.choose_cuts(length, count, rng);
+                let mut parent = rng.random_range(0..parents.len());
+                let mut start = 0;
+                for &cut in cuts.iter().chain(std::iter::once(&length)) {
+                    genes.copy_range(bits[parent], start..cut);
+                    start = cut;
+                    parent = match self.crossover {
+                        CrossoverType::Diagonal => (parent + 1) % parents.len(),
+                        _ if parents.len() > 1 => (parent + rng.random_range(1..parents.len())) % parents.len(),
+                        _ => parent,
+                    };
+                }
+            }
+            CrossoverType::Bit if parents.len() <= 2 => {
+                genes.mix(bits[0], bits[bits.len() - 1], rng);
+            }
+            _ => return false,
+        }
+        *mutations += genes.mutate(self.mutation_probability, rng);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::super::{init_population_seeded, rng::GaRng};
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+    }
+
+    fn packed(genes: &[bool]) -> Genes<bool> {
+        let mut genes = Genes::Dense(genes.to_vec());
+        genes.pack();
+        genes
+    }
+
+    #[test]
+    fn packed_genes_read_like_dense_ones() {
+        let dense: Vec<bool> = (0..150).map(|i| i % 3 == 0 || i % 7 == 0).collect();
+        let mut genes = packed(&dense);
+        assert!(genes.is_packed());
+        assert_eq!(genes.len(), 150);
+        assert_eq!(genes.to_vec(), dense);
+        assert_eq!(genes, Genes::Dense(dense.clone()));
+        assert_eq!(format!("{:?}", genes), format!("{:?}", dense));
+        genes.set(149, false);
+        genes.set(64, true);
+        assert!(!genes[149] && genes[64]);
+        genes.unpack();
+        assert!(!genes.is_packed());
+        assert_eq!(genes.len(), 150);
+    }
+
+    #[test]
+    fn packed_differences_count_the_longer_tail() {
+        let a: Vec<bool> = (0..70).map(|i| i % 2 == 0).collect();
+        let b: Vec<bool> = (0..130).map(|i| i % 5 == 0).collect();
+        let dense = Genes::Dense(a.clone()).count_differences(&Genes::Dense(b.clone()));
+        assert_eq!(packed(&a).count_differences(&packed(&b)), dense);
+        assert_eq!(packed(&b).count_differences(&packed(&a)), dense);
+        assert_eq!(packed(&a).count_differences(&packed(&a)), 0);
+    }
+
+    #[test]
+    fn copying_a_range_takes_only_those_genes() {
+        let mut genes = PackedBits::from_bits([false; 200].into_iter());
+        let source = PackedBits::from_bits([true; 200].into_iter());
+        genes.copy_range(&source, 60..131);
+        for i in 0..200 {
+            assert_eq!(genes.get(i), (60..131).contains(&i), "{}", i);
+        }
+    }
+
+    #[test]
+    fn mutation_flips_about_the_expected_number_of_genes() {
+        let mut rng = GaRng::seed_from_u64(3);
+        let mut bits = PackedBits::from_bits([false; 10_000].into_iter());
+        let flips = bits.mutate(0.01, &mut rng);
+        assert_eq!(Genes::<bool>::Packed(bits.clone()).iter().filter(|&&gene| gene).count() as u64, flips);
+        assert!((50..150).contains(&flips), "{}", flips);
+        assert_eq!(bits.mutate(1.0, &mut rng), 10_000);
+        assert_eq!(bits.words.iter().map(|word| word.count_ones() as u64).sum::<u64>(), 10_000 - flips);
+        assert_eq!(bits.words[bits.words.len() - 1] >> (10_000 % 64), 0);
+    }
+
+    #[test]
+    fn packed_populations_evolve_with_the_same_getters() {
+        for crossover in [CrossoverType::Bit, CrossoverType::SinglePoint, CrossoverType::TwoPoint, CrossoverType::Diagonal, CrossoverType::Uniform { parent_bias: 0.5 }] {
+            let mut population = init_population_seeded(200, 30, 10, 1, false, crossover, 5);
+            population.set_packed_storage(true);
+            population.evaluate(ones);
+            let start = population.individuals[0].get_fitness();
+            for _ in 0..40 {
+                population.next_generation();
+                population.evaluate(ones);
+                assert!(population.individuals.iter().all(|individual| individual.is_packed() && individual.get_genes().len() == 200));
+            }
+            assert!(population.individuals[0].get_fitness() > start, "{:?}", crossover);
+            let best = &population.individuals[0];
+            assert_eq!(ones(best), best.get_fitness());
+            assert_eq!(best.get_gene(199), best.get_genes()[199]);
+        }
+    }
+
+    #[test]
+    fn unpacking_keeps_the_genes() {
+        let mut population = init_population_seeded(100, 10, 4, 5, false, CrossoverType::Bit, 8);
+        let genes: Vec<Vec<bool>> = population.individuals.iter().map(|individual| individual.get_genes()).collect();
+        population.set_packed_storage(true);
+        assert!(population.is_packed_storage() && population.individuals[3].is_packed());
+        population.individuals[3].flip_gene(99);
+        population.set_packed_storage(false);
+        assert!(!population.individuals[3].is_packed());
+        assert_eq!(population.individuals[3].get_gene(99), !genes[3][99]);
+        assert_eq!(population.individuals[4].get_genes(), genes[4]);
+    }
+}
@@ -0,0 +1,262 @@
+use rand::{seq::SliceRandom, Rng};
+
+use super::{rng::GaRng, variation::{BuiltInVariation, Variation}, Individual, Population};
+
+/// How permutation parents are recombined. Every child is again an ordering of
+/// `0..gene_length`, each value exactly once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PermutationCrossover {
+    /// Order crossover (OX): a random segment is copied from one parent, the remaining
+    /// values follow in the order they have in the other parent. Keeps relative order,
+    /// e.g. for scheduling.
+    Order,
+    /// Partially mapped crossover (PMX): a random segment is copied from one parent, the
+    /// other positions come from the other parent, conflicts are resolved through the
+    /// mapping between the segments. Keeps absolute positions.
+    PartiallyMapped,
+    /// Cycle crossover (CX): every value keeps the position it has in one of the
+    /// parents, alternating between the parents cycle by cycle.
+    Cycle,
+}
+
+/// How a permutation changes when it mutates, in place of a gene mutation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PermutationMutation {
+    /// Swaps the mutating position with another random one.
+    Swap,
+    /// Reverses the segment between the mutating position and another random one,
+    /// which for a tour changes only two edges.
+    Inversion,
+}
+
+/// Variation operators for `Population<usize>` whose genomes are orderings of
+/// `0..gene_length`, for travelling salesman, scheduling and routing problems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PermutationOperators {
+    crossover: PermutationCrossover,
+    mutation: PermutationMutation,
+}
+
+impl PermutationOperators {
+    /// Creates new operators.
+    ///
+    /// # Arguments
+    /// * `crossover` - How parents are recombined.
+    /// * `mutation` - How a permutation is mutated, each position starts a mutation with the mutation probability.
+    pub fn new(crossover: PermutationCrossover, mutation: PermutationMutation) -> PermutationOperators {
+        PermutationOperators { crossover, mutation }
+    }
+
+    pub fn get_crossover(&self) -> PermutationCrossover {
+        self.crossover
+    }
+
+    pub fn get_mutation(&self) -> PermutationMutation {
+        self.mutation
+    }
+}
+
+impl Variation<usize> for PermutationOperators {
+    fn random_genes(&self, gene_length: usize, rng: &mut GaRng) -> Vec<usize> {
+        let mut genes: Vec<usize> = (0..gene_length).collect();
+        genes.shuffle(rng);
+        genes
+    }
+
+    fn crossover(&self, parents: &[&Individual<usize>], rng: &mut GaRng) -> Vec<usize> {
+        let first = parents[rng.random_range(0..parents.len())].genes.as_slice();
+        let second = parents[rng.random_range(0..parents.len())].genes.as_slice();
+        let length = first.len();
+        let mut start = rng.random_range(0..length);
+        let mut end = rng.random_range(0..length);
+        if start > end {
+            std::mem::swap(&mut start, &mut end);
+        }
+        let end = end + 1;
+        match self.crossover {
+            PermutationCrossover::Order => order_crossover(&first, &second, start, end),
+            PermutationCrossover::PartiallyMapped => partially_mapped_crossover(&first, &second, start, end),
+            PermutationCrossover::Cycle => cycle_crossover(&first, &second),
+        }
+    }
+
+    fn mutate(&self, genes: &mut [usize], mutation_probability: f64, rng: &mut GaRng) -> u64 {
+        let mut mutations = 0;
+        for i in 0..genes.len() {
+            if genes.len() < 2 || !rng.random_bool(mutation_probability) {
+                continue;
+            }
+            let other = (i + rng.random_range(1..genes.len())) % genes.len();
+            match self.mutation {
+                PermutationMutation::Swap => genes.swap(i, other),
+                PermutationMutation::Inversion => genes[i.min(other)..=i.max(other)].reverse(),
+            }
+            mutations += 1;
+        }
+        mutations
+    }
+}
+
+/// Copies `first[start..end]` and fills the rest, starting after the segment, with the
+/// missing values in the order they appear in `second` from the same position on.
+fn order_crossover(first: &[usize], second: &[usize], start: usize, end: usize) -> Vec<usize> {
+    let length = first.len();
+    let mut child = vec![usize::MAX; length];
+    let mut used = vec![false; length];
+    for i in start..end {
+        child[i] = first[i];
+        used[first[i]] = true;
+    }
+    let mut position = end % length;
+    for offset in 0..length {
+        let value = second[(end + offset) % length];
+        if !used[value] {
+            child[position] = value;
+            used[value] = true;
+            position = (position + 1) % length;
+        }
+    }
+    child
+}
+
+/// Copies `first[start..end]` and the rest from `second`, following the mapping between
+/// the two segments for values of `second`'s segment that were pushed out.
+fn partially_mapped_crossover(first: &[usize], second: &[usize], start: usize, end: usize) -> Vec<usize> {
+    let length = first.len();
+    let mut child = vec![usize::MAX; length];
+    let mut position_in_second = vec![0; length];
+    for (i, &value) in second.iter().enumerate() {
+        position_in_second[value] = i;
+    }
+    let mut used = vec![false; length];
+    for i in start..end {
+        child[i] = first[i];
+        used[first[i]] = true;
+    }
+    for (i, &value) in second.iter().enumerate().take(end).skip(start) {
+        if used[value] {
+            continue;
+        }
+        // follow the mapping until a position outside the segment is found
+        let mut position = i;
+        while (start..end).contains(&position) {
+            position = position_in_second[first[position]];
+        }
+        child[position] = value;
+        used[value] = true;
+    }
+    for i in 0..length {
+        if child[i] == usize::MAX {
+            child[i] = second[i];
+        }
+    }
+    child
+}
+
+/// Takes the first cycle from `first`, the next from `second` and so on.
+fn cycle_crossover(first: &[usize], second: &[usize]) -> Vec<usize> {
+    let length = first.len();
+    let mut child = vec![usize::MAX; length];
+    let mut position_in_first = vec![0; length];
+    for (i, &value) in first.iter().enumerate() {
+        position_in_first[value] = i;
+    }
+    let mut from_first = true;
+    for start in 0..length {
+        if child[start] != usize::MAX {
+            continue;
+        }
+        let mut position = start;
+        while child[position] == usize::MAX {
+            child[position] = if from_first { first[position] } else { second[position] };
+            position = position_in_first[second[position]];
+        }
+        from_first = !from_first;
+    }
+    child
+}
+
+impl Individual<usize> {
+    /// Returns true if the genes are an ordering of `0..gene_length`, each value exactly once.
+    pub fn is_permutation(&self) -> bool {
+        let mut seen = vec![false; self.gene_length];
+        for &gene in &self.genes {
+            if gene >= self.gene_length || seen[gene] {
+                return false;
+            }
+            seen[gene] = true;
+        }
+        true
+    }
+}
+
+impl Population<usize> {
+    /// Evolves the population with permutation operators and re-initializes every
+    /// individual with a random permutation, resetting its fitness.
+    ///
+    /// # Arguments
+    /// * `operators` - The operators.
+    pub fn set_permutation_operators(&mut self, operators: PermutationOperators) {
+        self.set_built_in_variation(operators, BuiltInVariation::Permutation(operators));
+        self.reinitialize_individuals();
+    }
+}
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::super::builder::PopulationBuilder;
+    use super::*;
+
+    const CROSSOVERS: [PermutationCrossover; 3] = [
+        PermutationCrossover::Order,
+        PermutationCrossover::PartiallyMapped,
+        PermutationCrossover::Cycle,
+    ];
+    const MUTATIONS: [PermutationMutation; 2] = [PermutationMutation::Swap, PermutationMutation::Inversion];
+
+    #[test]
+    fn every_operator_keeps_children_permutations() {
+        let mut rng = GaRng::seed_from_u64(11);
+        for crossover in CROSSOVERS {
+            for mutation in MUTATIONS {
+                let operators = PermutationOperators::new(crossover, mutation);
+                for _ in 0..200 {
+                    let first = Individual::from_genes(operators.random_genes(20, &mut rng));
+                    let second = Individual::from_genes(operators.random_genes(20, &mut rng));
+                    let mut child = operators.crossover(&[&first, &second], &mut rng);
+                    assert!(Individual::from_genes(child.clone()).is_permutation(), "{:?} gave {:?}", crossover, child);
+                    operators.mutate(&mut child, 0.5, &mut rng);
+                    assert!(Individual::from_genes(child.clone()).is_permutation(), "{:?} gave {:?}", mutation, child);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn evolved_population_stays_permutations() {
+        for crossover in CROSSOVERS {
+            for mutation in MUTATIONS {
+                let mut population = PopulationBuilder::<usize>::new()
+                    .gene_length(12)
+                    .size(30)
+                    .parents(4)
+                    .mutation_probability(0.2)
+                    .seed(5)
+                    .build()
+                    .unwrap();
+                population.set_permutation_operators(PermutationOperators::new(crossover, mutation));
+                for _ in 0..20 {
+                    population.evaluate(|individual| {
+                        individual.get_genes().iter().enumerate().filter(|(i, gene)| i == *gene).count() as u64
+                    });
+                    population.next_generation();
+                    assert!(population.individuals.iter().all(Individual::is_permutation));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::{fs, io, path::Path};
+
+use super::allele_history::AlleleFrequencyHistory;
+
+/// Maps an allele frequency to a colour: loci fixed on `false` are blue,
+/// undecided loci white and loci fixed on `true` red.
+fn frequency_color(frequency: f64) -> [u8; 3] {
+    let frequency = frequency.clamp(0.0, 1.0);
+    if frequency < 0.5 {
+        let shade = (255.0 * frequency * 2.0) as u8;
+        [shade, shade, 255]
+    } else {
+        let shade = (255.0 * (1.0 - frequency) * 2.0) as u8;
+        [255, shade, shade]
+    }
+}
+
+/// Encodes an RGB image as an uncompressed 24-bit BMP file.
+fn encode_bmp(width: usize, height: usize, pixel: impl Fn(usize, usize) -> [u8; 3]) -> Vec<u8> {
+    let row_size = (3 * width).div_ceil(4) * 4;
+    let data_size = row_size * height;
+    let file_size = 54 + data_size;
+    let mut bmp: Vec<u8> = Vec::with_capacity(file_size);
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&(file_size as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]);
+    bmp.extend_from_slice(&54u32.to_le_bytes());
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&(width as i32).to_le_bytes());
+    bmp.extend_from_slice(&(height as i32).to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&24u16.to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]);
+    bmp.extend_from_slice(&(data_size as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 16]);
+    // rows are stored bottom to top, pixels as BGR
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let [r, g, b] = pixel(x, y);
+            bmp.extend_from_slice(&[b, g, r]);
+        }
+        bmp.extend(std::iter::repeat_n(0, row_size - 3 * width));
+    }
+    bmp
+}
+
+impl AlleleFrequencyHistory {
+    /// Renders the recorded history as a heatmap, one column per recorded generation
+    /// (oldest left) and one row per locus (locus 0 on top). Loci fixed on `false` are
+    /// blue, fixed on `true` red and undecided loci white, so it shows at a glance
+    /// which regions of the genome converged and when.
+    ///
+    /// # Arguments
+    /// * `cell_size` - The width and height in pixels of one generation/locus cell.
+    ///
+    /// # Returns
+    /// The heatmap as the bytes of a 24-bit BMP image.
+    pub fn render_heatmap(&self, cell_size: usize) -> Vec<u8> {
+        if cell_size < 1 {
+            panic!("the cell size must be 1 or more");
+        }
+        let rows = self.get_rows();
+        if rows.is_empty() {
+            panic!("cant render a heatmap of an empty history");
+        }
+        let width = rows.len() * cell_size;
+        let height = rows[0].len() * cell_size;
+        encode_bmp(width, height, |x, y| frequency_color(rows[x / cell_size][y / cell_size]))
+    }
+
+    /// Renders the recorded history as a heatmap and writes it to a BMP file.
+    /// See `render_heatmap` for the layout.
+    ///
+    /// # Arguments
+    /// * `path` - The file to write.
+    /// * `cell_size` - The width and height in pixels of one generation/locus cell.
+    pub fn write_heatmap<P: AsRef<Path>>(&self, path: P, cell_size: usize) -> io::Result<()> {
+        fs::write(path, self.render_heatmap(cell_size))
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    cell::Cell,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, OnceLock,
+    },
+    thread,
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+thread_local! {
+    // set by a job whose worker has been replaced, the worker stops after that job
+    static RETIRE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A fixed set of worker threads that run jobs from a shared queue. Multi threaded
+/// populations breed, and run timed evaluations, on one pool shared by the whole process,
+/// instead of spawning new threads every generation.
+pub(crate) struct WorkerPool {
+    sender: Mutex<Sender<Job>>,
+    receiver: Arc<Mutex<Receiver<Job>>>,
+    size: usize,
+    spawned: AtomicUsize,
+}
+
+impl WorkerPool {
+    /// Starts a pool with `size` worker threads.
+    fn new(size: usize) -> WorkerPool {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let pool = WorkerPool {
+            sender: Mutex::new(sender),
+            receiver: Arc::new(Mutex::new(receiver)),
+            size,
+            spawned: AtomicUsize::new(0),
+        };
+        for _ in 0..size {
+            pool.spawn_worker();
+        }
+        pool
+    }
+
+    fn spawn_worker(&self) {
+        let receiver = Arc::clone(&self.receiver);
+        let i = self.spawned.fetch_add(1, Ordering::Relaxed);
+        thread::Builder::new()
+            .name(format!("ga-worker-{}", i))
+            .spawn(move || work(&receiver))
+            .expect("cant spawn worker thread");
+    }
+
+    /// Starts a worker in place of one stuck in a job nobody waits for anymore, e.g. a
+    /// timed-out evaluation, so the pool doesn't run out of workers. The stuck job has
+    /// to call `retire_worker` when it returns, to keep the pool at its size.
+    pub(crate) fn replace_worker(&self) {
+        self.spawn_worker();
+    }
+
+    /// Returns the pool shared by all populations, one worker per CPU, started on first use.
+    pub(crate) fn global() -> &'static WorkerPool {
+        static POOL: OnceLock<WorkerPool> = OnceLock::new();
+        POOL.get_or_init(|| WorkerPool::new(num_cpus::get().max(1)))
+    }
+
+    /// Queues a job. A job that panics doesn't take its worker down; whoever waits for
+    /// its result sees the job's channels closed instead.
+    pub(crate) fn execute<F: FnOnce() + Send + 'static>(&self, job: F) {
+        self.sender.lock().unwrap().send(Box::new(job)).expect("worker pool is gone");
+    }
+
+    pub(crate) fn get_size(&self) -> usize {
+        self.size
+    }
+}
+
+/// Called from a job whose worker was replaced with `WorkerPool::replace_worker`: the
+/// worker stops once the job returns.
+pub(crate) fn retire_worker() {
+    RETIRE.with(|retire| retire.set(true));
+}
+
+/// The loop of a worker thread: runs jobs until the queue is closed or a job retires it.
+fn work(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = match receiver.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+        if RETIRE.with(|retire| retire.get()) {
+            return;
+        }
+    }
+}
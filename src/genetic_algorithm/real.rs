@@ -0,0 +1,206 @@
+use rand::Rng;
+
+use super::{rng::GaRng, variation::{BuiltInVariation, Variation}, Individual, Population};
+
+/// How a real-valued gene changes when it mutates. Both keep the gene inside its bounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RealMutation {
+    /// Adds normally distributed noise with a standard deviation of `sigma` times the
+    /// width of the gene's bounds, e.g. 0.1.
+    Gaussian { sigma: f64 },
+    /// Deb's polynomial mutation. A larger distribution index `eta` (usually 20-100)
+    /// keeps the mutated value closer to the original.
+    Polynomial { eta: f64 },
+}
+
+/// How real-valued parents are recombined. Children are clamped to the bounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RealCrossover {
+    /// A random weighted mean of two parents, the same weight for every gene.
+    Arithmetic,
+    /// BLX-α: every gene is drawn uniformly from the interval spanned by two parents,
+    /// widened by `alpha` times its length on both sides, e.g. 0.5.
+    Blend { alpha: f64 },
+    /// Deb's simulated binary crossover (SBX), the usual choice for real-coded NSGA-II:
+    /// every gene is, with probability 0.5, spread around the two parents' values like a
+    /// single-point crossover of their binary encodings would. A larger distribution
+    /// index `eta` (usually 2-20) keeps children closer to their parents.
+    SimulatedBinary { eta: f64 },
+}
+
+/// Variation operators for `Population<f64>` with a `[min, max]` range per gene, for
+/// continuous optimization without a binary encoding. Genes hold the actual values, so
+/// the fitness function can use them directly.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RealOperators {
+    bounds: Vec<(f64, f64)>,
+    mutation: RealMutation,
+    crossover: RealCrossover,
+}
+
+impl RealOperators {
+    /// Creates new operators.
+    ///
+    /// # Arguments
+    /// * `bounds` - The inclusive `(min, max)` range of every gene, one per gene.
+    /// * `mutation` - How a mutating gene picks its new value.
+    /// * `crossover` - How parents are recombined.
+    pub fn new(bounds: Vec<(f64, f64)>, mutation: RealMutation, crossover: RealCrossover) -> RealOperators {
+        if bounds.is_empty() {
+            panic!("the gene length cannot be less than 1");
+        }
+        if let Some((min, max)) = bounds.iter().find(|(min, max)| min > max || !min.is_finite() || !max.is_finite()) {
+            panic!("invalid gene bounds {}..={}", min, max);
+        }
+        match mutation {
+            RealMutation::Gaussian { sigma } if sigma.is_nan() || sigma <= 0.0 => panic!("gaussian sigma must be larger than 0"),
+            RealMutation::Polynomial { eta } if eta.is_nan() || eta < 0.0 => panic!("polynomial eta cant be negative"),
+            _ => {}
+        }
+        match crossover {
+            RealCrossover::Blend { alpha } if alpha.is_nan() || alpha < 0.0 => panic!("blend alpha cant be negative"),
+            RealCrossover::SimulatedBinary { eta } if eta.is_nan() || eta < 0.0 => panic!("sbx eta cant be negative"),
+            _ => {}
+        }
+        RealOperators {
+            bounds,
+            mutation,
+            crossover,
+        }
+    }
+
+    pub fn get_bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    pub fn get_mutation(&self) -> RealMutation {
+        self.mutation
+    }
+
+    pub fn get_crossover(&self) -> RealCrossover {
+        self.crossover
+    }
+
+    fn clamp(&self, index: usize, value: f64) -> f64 {
+        let (min, max) = self.bounds[index];
+        value.clamp(min, max)
+    }
+}
+
+impl Variation<f64> for RealOperators {
+    fn random_genes(&self, gene_length: usize, rng: &mut GaRng) -> Vec<f64> {
+        if gene_length != self.bounds.len() {
+            panic!("{} gene bounds for a gene length of {}", self.bounds.len(), gene_length);
+        }
+        self.bounds.iter().map(|&(min, max)| if min < max { rng.random_range(min..=max) } else { min }).collect()
+    }
+
+    fn crossover(&self, parents: &[&Individual<f64>], rng: &mut GaRng) -> Vec<f64> {
+        let first = rng.random_range(0..parents.len());
+        let second = if parents.len() > 1 {
+            (first + rng.random_range(1..parents.len())) % parents.len()
+        } else {
+            first
+        };
+        let (a, b) = (&parents[first].genes, &parents[second].genes);
+        match self.crossover {
+            RealCrossover::Arithmetic => {
+                let weight = rng.random_range(0.0..=1.0);
+                (0..a.len()).map(|i| self.clamp(i, weight * a[i] + (1.0 - weight) * b[i])).collect()
+            }
+            RealCrossover::Blend { alpha } => (0..a.len())
+                .map(|i| {
+                    let (low, high) = (a[i].min(b[i]), a[i].max(b[i]));
+                    let spread = alpha * (high - low);
+                    if low == high {
+                        return self.clamp(i, low);
+                    }
+                    self.clamp(i, rng.random_range(low - spread..=high + spread))
+                })
+                .collect(),
+            RealCrossover::SimulatedBinary { eta } => (0..a.len())
+                .map(|i| {
+                    if (a[i] - b[i]).abs() < 1e-14 || !rng.random_bool(0.5) {
+                        return a[i];
+                    }
+                    let (low, high) = (a[i].min(b[i]), a[i].max(b[i]));
+                    let (min, max) = self.bounds[i];
+                    let u: f64 = rng.random_range(0.0..1.0);
+                    // the spread is limited so the child stays within the bounds
+                    let spread_factor = |distance: f64| {
+                        let beta = 1.0 + 2.0 * distance / (high - low);
+                        let alpha = 2.0 - beta.powf(-(eta + 1.0));
+                        if u <= 1.0 / alpha {
+                            (u * alpha).powf(1.0 / (eta + 1.0))
+                        } else {
+                            (1.0 / (2.0 - u * alpha)).powf(1.0 / (eta + 1.0))
+                        }
+                    };
+                    let child = if rng.random_bool(0.5) {
+                        0.5 * ((low + high) - spread_factor(low - min) * (high - low))
+                    } else {
+                        0.5 * ((low + high) + spread_factor(max - high) * (high - low))
+                    };
+                    self.clamp(i, child)
+                })
+                .collect(),
+        }
+    }
+
+    fn mutate(&self, genes: &mut [f64], mutation_probability: f64, rng: &mut GaRng) -> u64 {
+        let mut mutations = 0;
+        for (i, gene) in genes.iter_mut().enumerate() {
+            if !rng.random_bool(mutation_probability) {
+                continue;
+            }
+            let (min, max) = self.bounds[i];
+            let width = max - min;
+            let change = match self.mutation {
+                RealMutation::Gaussian { sigma } => standard_normal(rng) * sigma * width,
+                RealMutation::Polynomial { eta } => {
+                    let u: f64 = rng.random_range(0.0..1.0);
+                    let delta = if u < 0.5 {
+                        (2.0 * u).powf(1.0 / (eta + 1.0)) - 1.0
+                    } else {
+                        1.0 - (2.0 * (1.0 - u)).powf(1.0 / (eta + 1.0))
+                    };
+                    delta * width
+                }
+            };
+            *gene = (*gene + change).clamp(min, max);
+            mutations += 1;
+        }
+        mutations
+    }
+
+    /// Reflects every gene in its bounds, `min + max - x`.
+    fn opposite_genes(&self, genes: &[f64]) -> Option<Vec<f64>> {
+        Some(genes.iter().zip(&self.bounds).map(|(gene, (min, max))| min + max - gene).collect())
+    }
+}
+
+/// Draws from the standard normal distribution with the Box-Muller transform.
+pub(crate) fn standard_normal(rng: &mut GaRng) -> f64 {
+    let u1: f64 = 1.0 - rng.random_range(0.0..1.0);
+    let u2: f64 = rng.random_range(0.0..1.0);
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+impl Population<f64> {
+    /// Evolves the population with real-valued operators and re-initializes every
+    /// individual uniformly within the bounds, resetting its fitness.
+    ///
+    /// # Arguments
+    /// * `operators` - The operators, with one bound per gene.
+    pub fn set_real_operators(&mut self, operators: RealOperators) {
+        let gene_length = self.individuals[0].gene_length;
+        if operators.bounds.len() != gene_length {
+            panic!("{} gene bounds for a gene length of {}", operators.bounds.len(), gene_length);
+        }
+        self.set_built_in_variation(operators.clone(), BuiltInVariation::Real(operators));
+        self.reinitialize_individuals();
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    fmt,
+    sync::{atomic::Ordering, Arc},
+};
+
+use super::{gene::Gene, Individual, Population};
+
+/// Turns an invalid genome into a valid one. A population with a repair set runs every
+/// genome its operators produce through it (initialization, crossover, mutation and
+/// restarts), so validity rules live in one place instead of in every operator.
+/// Closures `Fn(&mut Individual)` implement it too.
+pub trait Repair<G: Gene = bool>: Send + Sync {
+    /// Fixes the individual in place, leaving valid genomes unchanged.
+    fn repair(&self, individual: &mut Individual<G>);
+}
+
+impl<G: Gene, F: Fn(&mut Individual<G>) + Send + Sync> Repair<G> for F {
+    fn repair(&self, individual: &mut Individual<G>) {
+        self(individual)
+    }
+}
+
+/// A shared repair operator stored on a population.
+#[derive(Clone)]
+pub(crate) struct RepairHook<G: Gene>(Arc<dyn Repair<G>>);
+
+impl<G: Gene> fmt::Debug for RepairHook<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RepairHook")
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Sets the repair operator every newly produced genome is passed through and
+    /// repairs the current individuals with it right away.
+    ///
+    /// # Arguments
+    /// * `repair` - The repair operator.
+    pub fn set_repair<R: Repair<G> + 'static>(&mut self, repair: R) {
+        self.repair = Some(RepairHook(Arc::new(repair)));
+        let mut individuals = std::mem::take(&mut self.individuals);
+        for individual in individuals.iter_mut() {
+            self.repair_individual(individual);
+        }
+        self.individuals = individuals;
+    }
+
+    /// Removes the repair operator.
+    pub fn clear_repair(&mut self) {
+        self.repair = None;
+    }
+
+    /// Runs an individual through the repair operator, if one is set.
+    pub(crate) fn repair_individual(&self, individual: &mut Individual<G>) {
+        if let Some(RepairHook(repair)) = &self.repair {
+            let gene_length = individual.gene_length;
+            repair.repair(individual);
+            self.operator_counters.repairs.fetch_add(1, Ordering::Relaxed);
+            if individual.gene_length != gene_length {
+                panic!("the repair operator changed the gene length from {} to {}", gene_length, individual.gene_length);
+            }
+        }
+    }
+}
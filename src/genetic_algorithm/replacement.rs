@@ -0,0 +1,79 @@
+use super::{gene::Gene, Individual, Population};
+
+/// Which individuals make way for the children of a generation, independently of how
+/// the parents are selected, e.g. tournament selection with age-based replacement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReplacementPolicy {
+    /// The default: the selected parents survive and the rest of the population is
+    /// replaced by their children.
+    ParentsSurvive,
+    /// The children replace the whole population, the parents included.
+    Generational,
+    /// The `elites` fittest individuals survive unmodified and the children replace all
+    /// others, see `Population::set_elitism_count`.
+    ElitistGenerational { elites: usize },
+    /// Every step only the `count` worst individuals are replaced, see
+    /// `Population::set_steady_state`.
+    SteadyStateWorst { count: usize },
+    /// Every step the `count` individuals that have survived the most steps are replaced,
+    /// whatever their fitness, so no individual dominates for long. Ties go to the less
+    /// fit. Individual ages count the steps survived, children start at 0.
+    AgeBased { count: usize },
+}
+
+impl<G: Gene> Population<G> {
+    /// Sets the replacement policy, replacing the elitism count and steady-state mode it
+    /// is expressed with. The steady-state policies cant be combined with another
+    /// replacement mode, e.g. crowding.
+    ///
+    /// # Arguments
+    /// * `policy` - Which individuals the children replace.
+    pub fn set_replacement_policy(&mut self, policy: ReplacementPolicy) {
+        match policy {
+            ReplacementPolicy::ParentsSurvive => {
+                self.clear_steady_state();
+                self.clear_elitism_count();
+            }
+            ReplacementPolicy::Generational => {
+                self.clear_steady_state();
+                self.set_elitism_count(0);
+            }
+            ReplacementPolicy::ElitistGenerational { elites } => {
+                self.clear_steady_state();
+                self.set_elitism_count(elites);
+            }
+            ReplacementPolicy::SteadyStateWorst { count } => self.set_steady_state(count),
+            ReplacementPolicy::AgeBased { count } => {
+                self.set_steady_state(count);
+                self.replace_oldest = true;
+            }
+        }
+    }
+
+    /// Returns the replacement policy the elitism count and steady-state mode amount to.
+    pub fn get_replacement_policy(&self) -> ReplacementPolicy {
+        match (self.steady_state, self.elitism_count) {
+            (Some(count), _) if self.replace_oldest => ReplacementPolicy::AgeBased { count },
+            (Some(count), _) => ReplacementPolicy::SteadyStateWorst { count },
+            (None, Some(0)) => ReplacementPolicy::Generational,
+            (None, Some(elites)) => ReplacementPolicy::ElitistGenerational { elites },
+            (None, None) => ReplacementPolicy::ParentsSurvive,
+        }
+    }
+
+    /// Removes the `count` individuals that survived the most steps, the less fit first
+    /// among equally old ones, and ages all others by one step.
+    pub(crate) fn remove_oldest(&mut self, count: usize) -> Vec<Individual<G>> {
+        // the population is sorted, so among equally old ones the later are less fit
+        let mut order: Vec<usize> = (0..self.individuals.len()).collect();
+        order.sort_by(|&a, &b| self.individuals[b].age.cmp(&self.individuals[a].age).then(b.cmp(&a)));
+        let mut removed: Vec<usize> = order.into_iter().take(count).collect();
+        removed.sort_unstable_by(|a, b| b.cmp(a));
+        let spares = removed.into_iter().map(|index| self.individuals.remove(index)).collect();
+        for individual in self.individuals.iter_mut() {
+            individual.age += 1;
+        }
+        spares
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt;
+
+use super::{gene::Gene, Individual, Population};
+
+/// The settings and fittest individuals of a population, borrowed from it. Printing it
+/// gives the same output as `Population::print`.
+#[derive(Debug, Clone)]
+pub struct Report<'a, G: Gene> {
+    population: &'a Population<G>,
+    top: Vec<&'a Individual<G>>,
+}
+
+impl<'a, G: Gene> Report<'a, G> {
+    pub fn get_population(&self) -> &'a Population<G> {
+        self.population
+    }
+
+    /// Returns the reported individuals, fittest first.
+    pub fn get_top(&self) -> &[&'a Individual<G>] {
+        &self.top
+    }
+}
+
+/// One line with the population settings, then one line per reported individual.
+impl<G: Gene> fmt::Display for Report<'_, G>
+where
+    Individual<G>: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.population)?;
+        for individual in &self.top {
+            write!(f, "\n{}", individual)?;
+        }
+        Ok(())
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Reports the top `count` individuals without sorting the population, so it works
+    /// from observers and other code that only has a `&Population`.
+    ///
+    /// # Arguments
+    /// * `count` - The number of individuals to report, at most the population size.
+    pub fn report(&self, count: usize) -> Report<'_, G> {
+        Report {
+            population: self,
+            top: self.top_k(count.min(self.population_size)),
+        }
+    }
+}
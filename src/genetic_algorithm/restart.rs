@@ -0,0 +1,21 @@
+/// Why a `GaRunner` restarted the population.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RestartReason {
+    /// The best fitness didn't improve for the stagnation window.
+    Stagnation,
+    /// The diversity fell below the minimum set with `GaRunner::diversity_collapse`.
+    DiversityCollapse,
+}
+
+/// A restart as reported to `Observer::on_restart`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RestartInfo {
+    pub generation: u64,
+    pub reason: RestartReason,
+    /// The number of restarts of the run so far, this one included.
+    pub restarts: u64,
+    /// The mutation probability the population continues with.
+    pub mutation_probability: f64,
+}
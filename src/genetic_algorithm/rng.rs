@@ -0,0 +1,124 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::{fmt, str::FromStr};
+
+use super::{gene::Gene, Population};
+
+/// The random number generator owned by a population. Unlike `rand::rng()` its complete
+/// state can be captured and restored, see `RngState`.
+pub type GaRng = ChaCha12Rng;
+
+/// The complete state of a `GaRng`. Restoring it continues the exact random sequence the
+/// generator would have produced, so a run resumed from a checkpoint is bit-identical to
+/// an uninterrupted one.
+///
+/// The text form (`Display`/`FromStr`) is the seed, stream and word position in hex,
+/// separated by colons, for storing the state in checkpoint files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RngState {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
+impl RngState {
+    /// Captures the state of a generator.
+    pub fn capture(rng: &GaRng) -> RngState {
+        RngState {
+            seed: rng.get_seed(),
+            stream: rng.get_stream(),
+            word_pos: rng.get_word_pos(),
+        }
+    }
+
+    /// Creates a generator that continues from this state.
+    pub fn restore(&self) -> GaRng {
+        let mut rng = GaRng::from_seed(self.seed);
+        rng.set_stream(self.stream);
+        rng.set_word_pos(self.word_pos);
+        rng
+    }
+}
+
+impl fmt::Display for RngState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.seed {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ":{:x}:{:x}", self.stream, self.word_pos)
+    }
+}
+
+impl FromStr for RngState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<RngState, String> {
+        let parts: Vec<&str> = s.trim().split(':').collect();
+        if parts.len() != 3 || parts[0].len() != 64 || !parts[0].is_ascii() {
+            return Err(format!("invalid rng state '{}'", s));
+        }
+        let mut seed = [0u8; 32];
+        for (i, byte) in seed.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&parts[0][i * 2..i * 2 + 2], 16).map_err(|e| e.to_string())?;
+        }
+        Ok(RngState {
+            seed,
+            stream: u64::from_str_radix(parts[1], 16).map_err(|e| e.to_string())?,
+            word_pos: u128::from_str_radix(parts[2], 16).map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Returns the current state of the population's random number generator.
+    pub fn get_rng_state(&self) -> RngState {
+        RngState::capture(&self.rng)
+    }
+
+    /// Restarts the population's random number generator from a seed, making everything
+    /// that happens from now on reproducible.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = GaRng::seed_from_u64(seed);
+    }
+
+    /// Replaces the state of the population's random number generator, e.g. with one
+    /// saved by `get_rng_state` when the run was checkpointed.
+    pub fn set_rng_state(&mut self, state: RngState) {
+        self.rng = state.restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::super::{init_population_seeded, CrossoverType, Individual};
+    use super::*;
+
+    #[test]
+    fn restored_state_continues_the_sequence() {
+        let mut rng = GaRng::seed_from_u64(3);
+        for _ in 0..5 {
+            rng.random::<u64>();
+        }
+        let state: RngState = RngState::capture(&rng).to_string().parse().unwrap();
+        let mut restored = state.restore();
+        let expected: Vec<u64> = (0..10).map(|_| rng.random()).collect();
+        let continued: Vec<u64> = (0..10).map(|_| restored.random()).collect();
+        assert_eq!(expected, continued);
+    }
+
+    #[test]
+    fn population_resumes_from_its_rng_state() {
+        let mut population = init_population_seeded(32, 20, 4, 5, false, CrossoverType::Bit, 9);
+        population.evaluate(|individual: &Individual| individual.genes.iter().filter(|&&gene| gene).count() as u64);
+        let mut resumed = population.clone();
+        resumed.reseed(0);
+        resumed.set_rng_state(population.get_rng_state());
+        population.next_generation();
+        resumed.next_generation();
+        let genes = |population: &Population| population.individuals.iter().map(|individual| individual.get_genes()).collect::<Vec<_>>();
+        assert_eq!(genes(&population), genes(&resumed));
+    }
+}
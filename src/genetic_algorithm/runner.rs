@@ -0,0 +1,592 @@
+use std::{
+    cell::Cell,
+    convert::Infallible,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+#[cfg(feature = "plotting")]
+use std::{io, mem, path::Path};
+
+#[cfg(feature = "plotting")]
+use super::allele_history::AlleleFrequencyHistory;
+
+use super::{
+    convergence::DiversityPolicy,
+    evaluation::{EvaluationError, FitnessErrorPolicy},
+    events::GaEvent,
+    gene::Gene,
+    restart::{RestartInfo, RestartReason},
+    Individual, Population,
+};
+
+/// Why a run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The best individual reached the target fitness.
+    TargetFitness,
+    /// The generation limit was reached.
+    MaxGenerations,
+    /// The wall-clock limit was reached.
+    TimeLimit,
+    /// The best fitness didn't improve for the stagnation window, or the diversity collapsed.
+    Stagnation,
+}
+
+/// What a `GaRunner` does when the best fitness stagnates, or the diversity collapses
+/// if `GaRunner::diversity_collapse` is set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StagnationAction {
+    /// End the run with `StopReason::Stagnation`.
+    Stop,
+    /// Re-randomize all but the `keep` fittest individuals and continue. With a `boost`
+    /// of `(probability, generations)` the mutation probability is raised to `probability`
+    /// (0.0-1.0) for that many generations, so the kept elites spread out quickly, and
+    /// then goes back to the one from before. A mutation schedule overrides the boost.
+    /// Every restart is reported to the population's observers with `Observer::on_restart`.
+    Restart { keep: usize, boost: Option<(f64, u64)> },
+}
+
+/// Counts the generations since the best fitness last improved by at least `epsilon`,
+/// for runs driven by hand as well as by `GaRunner`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StagnationTracker {
+    epsilon: u64,
+    reference: Option<u64>,
+    count: u64,
+}
+
+impl StagnationTracker {
+    /// # Arguments
+    /// * `epsilon` - The smallest gain that counts as an improvement, 0 counts any gain.
+    pub fn new(epsilon: u64) -> StagnationTracker {
+        StagnationTracker {
+            epsilon,
+            reference: None,
+            count: 0,
+        }
+    }
+
+    /// Records the best fitness of a generation.
+    ///
+    /// # Returns
+    /// The number of generations without improvement, see `get_count`.
+    pub fn update(&mut self, best_fitness: u64) -> u64 {
+        let improved = match self.reference {
+            Some(reference) => best_fitness >= reference.saturating_add(self.epsilon.max(1)),
+            None => true,
+        };
+        if improved {
+            self.reference = Some(best_fitness);
+            self.count = 0;
+        } else {
+            self.count += 1;
+        }
+        self.count
+    }
+
+    /// Returns the number of generations since the last improvement.
+    pub fn get_count(&self) -> u64 {
+        self.count
+    }
+
+    /// Restarts counting, e.g. after a restart of the population.
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.reference = None;
+    }
+}
+
+/// The outcome of `GaRunner::run`.
+#[derive(Debug, Clone)]
+pub struct RunResult<G: Gene = bool> {
+    /// The best individual the population has seen, see `Population::get_best_ever`,
+    /// so a feasible one before any infeasible one under feasibility-first ordering.
+    pub best: Individual<G>,
+    pub stop_reason: StopReason,
+    /// The number of evaluated generations.
+    pub generations: u64,
+    /// The number of fitness function calls, including local search. Individuals whose
+    /// fitness came from the fitness cache aren't counted.
+    pub evaluations: u64,
+    pub elapsed: Duration,
+    /// The best fitness of every evaluated generation.
+    pub best_fitness_history: Vec<u64>,
+    /// The number of times the population was restarted.
+    pub restarts: u64,
+    /// Why the heatmap set with `GaRunner::heatmap` couldn't be written, `None` if it
+    /// was or none was asked for.
+    #[cfg(feature = "plotting")]
+    pub heatmap_error: Option<String>,
+}
+
+// applies a `DiversityPolicy` to a population, true if it injected individuals
+type DiversityHook<G> = Box<dyn Fn(&mut Population<G>) -> bool + Send + Sync>;
+// records a generation's allele frequencies, or writes the heatmap when given no population
+#[cfg(feature = "plotting")]
+type HeatmapHook<G> = Box<dyn FnMut(Option<&Population<G>>) -> io::Result<()> + Send>;
+
+/// Owns the evolution loop: evaluates the population with a fitness function, checks the
+/// termination conditions and breeds the next generation until one of them is met, e.g.
+/// `GaRunner::new(population, fitness).target_fitness(goal).max_generations(10_000).run()`.
+/// A multi threaded population is also evaluated on several threads. A fitness function
+/// that returns a `Result` is run with `try_run` instead.
+pub struct GaRunner<G: Gene, F> {
+    population: Population<G>,
+    fitness: F,
+    target_fitness: Option<u64>,
+    max_generations: Option<u64>,
+    time_limit: Option<Duration>,
+    stagnation_window: Option<u64>,
+    stagnation_action: StagnationAction,
+    stagnation: StagnationTracker,
+    min_diversity: Option<f64>,
+    max_restarts: Option<u64>,
+    // applies a `DiversityPolicy`, set on bit string runners only
+    diversity_policy: Option<DiversityHook<G>>,
+    // records the allele frequencies of a generation, or writes the heatmap at the end of a run
+    #[cfg(feature = "plotting")]
+    heatmap: Option<HeatmapHook<G>>,
+}
+
+impl<G: Gene, F> GaRunner<G, F> {
+    /// Creates a runner without termination conditions, add at least one before running.
+    ///
+    /// # Arguments
+    /// * `population` - The population to evolve.
+    /// * `fitness` - The fitness function, returning the fitness for `run` or a `Result` for `try_run`.
+    pub fn new<R>(population: Population<G>, fitness: F) -> GaRunner<G, F>
+    where
+        F: Fn(&Individual<G>) -> R,
+    {
+        GaRunner {
+            population,
+            fitness,
+            target_fitness: None,
+            max_generations: None,
+            time_limit: None,
+            stagnation_window: None,
+            stagnation_action: StagnationAction::Stop,
+            stagnation: StagnationTracker::new(0),
+            min_diversity: None,
+            max_restarts: None,
+            diversity_policy: None,
+            #[cfg(feature = "plotting")]
+            heatmap: None,
+        }
+    }
+
+    /// Stops once the best individual has at least this fitness.
+    pub fn target_fitness(mut self, fitness: u64) -> GaRunner<G, F> {
+        self.target_fitness = Some(fitness);
+        self
+    }
+
+    /// Stops after evaluating this many generations.
+    pub fn max_generations(mut self, generations: u64) -> GaRunner<G, F> {
+        self.max_generations = Some(generations);
+        self
+    }
+
+    /// Stops once the run has taken this long, checked once per generation.
+    pub fn time_limit(mut self, limit: Duration) -> GaRunner<G, F> {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Stops when the best fitness hasn't improved for this many generations, or takes
+    /// the action set with `on_stagnation`.
+    pub fn stagnation_window(mut self, generations: u64) -> GaRunner<G, F> {
+        if generations < 1 {
+            panic!("stagnation window must be 1 or more");
+        }
+        self.stagnation_window = Some(generations);
+        self
+    }
+
+    /// Sets the smallest gain of the best fitness that counts as an improvement, 0 by
+    /// default so any gain counts.
+    pub fn stagnation_epsilon(mut self, epsilon: u64) -> GaRunner<G, F> {
+        self.stagnation = StagnationTracker::new(epsilon);
+        self
+    }
+
+    /// Sets what happens when the stagnation window is reached, stopping by default.
+    pub fn on_stagnation(mut self, action: StagnationAction) -> GaRunner<G, F> {
+        if let StagnationAction::Restart {
+            boost: Some((probability, _)),
+            ..
+        } = action
+        {
+            if !(0.0..=1.0).contains(&probability) {
+                panic!("mutation probability must be between 0 and 1");
+            }
+        }
+        self.stagnation_action = action;
+        self
+    }
+
+    /// Also takes the stagnation action when the diversity, the mean fraction (0.0-1.0)
+    /// of loci at which the individuals differ from the fittest one, falls below
+    /// `min_diversity`, however recently the best fitness improved.
+    pub fn diversity_collapse(mut self, min_diversity: f64) -> GaRunner<G, F> {
+        if !(0.0..=1.0).contains(&min_diversity) {
+            panic!("minimum diversity must be between 0 and 1");
+        }
+        self.min_diversity = Some(min_diversity);
+        self
+    }
+
+    /// Restarts at most this many times per run with `StagnationAction::Restart`,
+    /// afterwards the next stagnation stops the run.
+    pub fn max_restarts(mut self, restarts: u64) -> GaRunner<G, F> {
+        self.max_restarts = Some(restarts);
+        self
+    }
+
+    /// Returns the number of generations since the best fitness last improved.
+    pub fn get_stagnation_count(&self) -> u64 {
+        self.stagnation.get_count()
+    }
+
+    /// The evolution loop shared by `run` and `try_run`.
+    ///
+    /// # Arguments
+    /// * `evaluate` - Evaluates the whole population, or only its last `count` individuals if
+    ///   given, and returns the number of fitness function calls it made.
+    ///
+    /// # Returns
+    /// The best individual and statistics of the run, or the first error of `evaluate`.
+    fn run_with<E>(
+        &mut self,
+        evaluate: impl Fn(&mut Population<G>, &F, Option<usize>) -> Result<u64, E>,
+    ) -> Result<RunResult<G>, E> {
+        if self.target_fitness.is_none()
+            && self.max_generations.is_none()
+            && self.time_limit.is_none()
+            && self.stagnation_window.is_none()
+            && self.min_diversity.is_none()
+        {
+            panic!("a run needs at least one termination condition");
+        }
+        let start = Instant::now();
+        let mut best_fitness_history = vec![];
+        let mut evaluations = 0;
+        let mut generations = 0;
+        // in steady-state, crowding, differential evolution and evolution strategy mode only
+        // the children of a step need evaluating, unless the population is new or restarted
+        let mut fresh = true;
+        let mut restarts = 0;
+        // the generations left with a boosted mutation probability, and the one to restore
+        let mut boost_remaining = 0;
+        let mut unboosted_probability = self.population.get_mutation_probability();
+        loop {
+            let pending = self
+                .population
+                .get_crowding_pending()
+                .or(self.population.get_de_pending())
+                .or(self.population.get_es_pending());
+            evaluations += match self.population.get_steady_state().or(pending) {
+                Some(count) if !fresh => evaluate(&mut self.population, &self.fitness, Some(count))?,
+                _ => evaluate(&mut self.population, &self.fitness, None)?,
+            };
+            fresh = false;
+            generations += 1;
+            #[cfg(feature = "plotting")]
+            if let Some(heatmap) = &mut self.heatmap {
+                // recording only fails on a changed gene length, which panics instead
+                let _ = heatmap(Some(&self.population));
+            }
+            let fittest = self.population.read_fittest();
+            best_fitness_history.push(fittest.fitness);
+            let stagnant = self.stagnation.update(fittest.fitness);
+            // read_fittest recorded it, by the population's own ordering
+            let best_fitness = self.population.get_best_ever().map_or(0, |best| best.fitness);
+
+            let stop_reason = if self.target_fitness.is_some_and(|target| best_fitness >= target) {
+                Some(StopReason::TargetFitness)
+            } else if self.max_generations.is_some_and(|max| generations >= max) {
+                Some(StopReason::MaxGenerations)
+            } else if self.time_limit.is_some_and(|limit| start.elapsed() >= limit) {
+                Some(StopReason::TimeLimit)
+            } else if let Some(reason) = self.check_stagnation(stagnant) {
+                match self.stagnation_action {
+                    StagnationAction::Restart { keep, boost } if self.max_restarts.is_none_or(|max| restarts < max) => {
+                        self.population.notify_observers();
+                        self.population.restart(keep);
+                        self.stagnation.reset();
+                        restarts += 1;
+                        if let Some((probability, generations)) = boost {
+                            if boost_remaining == 0 {
+                                unboosted_probability = self.population.get_mutation_probability();
+                            }
+                            self.population.set_mutation_probability(probability);
+                            boost_remaining = generations;
+                        }
+                        self.notify_restart(reason, restarts);
+                        fresh = true;
+                        continue;
+                    }
+                    _ => Some(StopReason::Stagnation),
+                }
+            } else {
+                None
+            };
+            if let Some(stop_reason) = stop_reason {
+                if boost_remaining > 0 {
+                    self.population.set_mutation_probability(unboosted_probability);
+                }
+                self.population.notify_observers();
+                #[cfg(feature = "plotting")]
+                let heatmap_error = match &mut self.heatmap {
+                    Some(heatmap) => heatmap(None).err().map(|error| error.to_string()),
+                    None => None,
+                };
+                return Ok(RunResult {
+                    best: self.population.get_best_ever().cloned().unwrap(),
+                    stop_reason,
+                    generations,
+                    evaluations,
+                    elapsed: start.elapsed(),
+                    best_fitness_history,
+                    restarts,
+                    #[cfg(feature = "plotting")]
+                    heatmap_error,
+                });
+            }
+            if self.diversity_policy.as_ref().is_some_and(|apply| apply(&mut self.population)) {
+                // the injected individuals are evaluated before breeding goes on
+                fresh = true;
+                continue;
+            }
+            self.population.next_generation();
+            if boost_remaining > 0 {
+                boost_remaining -= 1;
+                if boost_remaining == 0 {
+                    self.population.set_mutation_probability(unboosted_probability);
+                }
+            }
+        }
+    }
+
+    /// Returns why the evaluated population counts as stagnant, if it does.
+    ///
+    /// # Arguments
+    /// * `stagnant` - The generations since the best fitness last improved.
+    fn check_stagnation(&self, stagnant: u64) -> Option<RestartReason> {
+        if self.stagnation_window.is_some_and(|window| stagnant >= window) {
+            return Some(RestartReason::Stagnation);
+        }
+        if self.min_diversity.is_some_and(|min| self.population.diversity_to_best() < min) {
+            return Some(RestartReason::DiversityCollapse);
+        }
+        None
+    }
+
+    /// Tells the population's observers about a restart.
+    fn notify_restart(&self, reason: RestartReason, restarts: u64) {
+        self.population.notify_restart(RestartInfo {
+            generation: self.population.get_generation(),
+            reason,
+            restarts,
+            mutation_probability: self.population.get_mutation_probability(),
+        });
+    }
+
+    pub fn get_population(&self) -> &Population<G> {
+        &self.population
+    }
+
+    /// Gives back the population, e.g. to continue evolving it by hand.
+    pub fn into_population(self) -> Population<G> {
+        self.population
+    }
+}
+
+impl<F> GaRunner<bool, F> {
+    /// Checks the population's entropy every generation that doesn't end the run and
+    /// injects diversity with the policy's response when it falls below the threshold.
+    /// The affected individuals are evaluated again before the next generation is bred.
+    pub fn diversity_policy(mut self, policy: DiversityPolicy) -> GaRunner<bool, F> {
+        self.diversity_policy = Some(Box::new(move |population: &mut Population| policy.apply(population)));
+        self
+    }
+
+    /// Records the allele frequencies of every evaluated generation and writes them as a
+    /// heatmap to a BMP file when the run stops, see `AlleleFrequencyHistory::render_heatmap`.
+    /// A failed write is reported in `RunResult::heatmap_error`.
+    ///
+    /// # Arguments
+    /// * `path` - The file to write, overwritten by every run.
+    /// * `cell_size` - The width and height in pixels of one generation/locus cell.
+    #[cfg(feature = "plotting")]
+    pub fn heatmap<P: AsRef<Path>>(mut self, path: P, cell_size: usize) -> GaRunner<bool, F> {
+        if cell_size < 1 {
+            panic!("the cell size must be 1 or more");
+        }
+        let path = path.as_ref().to_path_buf();
+        let mut history = AlleleFrequencyHistory::new(1);
+        self.heatmap = Some(Box::new(move |population: Option<&Population>| {
+            if let Some(population) = population {
+                history.record(population);
+                return Ok(());
+            }
+            let history = mem::replace(&mut history, AlleleFrequencyHistory::new(1));
+            history.write_heatmap(&path, cell_size)
+        }));
+        self
+    }
+}
+
+impl<G: Gene, F: Fn(&Individual<G>) -> u64 + Sync> GaRunner<G, F> {
+    /// Evolves the population until a termination condition is met. The population is
+    /// left evaluated, as it was when the run stopped.
+    ///
+    /// # Returns
+    /// The best individual and statistics of the run.
+    pub fn run(&mut self) -> RunResult<G> {
+        let result = self.run_with(|population, fitness, count| {
+            let calls = AtomicU64::new(0);
+            let counted = |individual: &Individual<G>| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                fitness(individual)
+            };
+            match count {
+                Some(count) => population.evaluate_last(count, counted),
+                None if population.multi_threaded => population.par_evaluate(counted),
+                None => population.evaluate(counted),
+            }
+            Ok::<u64, Infallible>(calls.into_inner())
+        });
+        match result {
+            Ok(result) => result,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl<G: Gene, E, F: Fn(&Individual<G>) -> Result<u64, E>> GaRunner<G, F> {
+    /// Evolves the population like `run` with a fitness function that can fail, see
+    /// `Population::try_evaluate`. The population is evaluated on the calling thread,
+    /// multi threaded or not.
+    ///
+    /// # Arguments
+    /// * `policy` - Whether an error ends the run or is penalized.
+    ///
+    /// # Returns
+    /// The best individual and statistics of the run, or the first error with
+    /// `FitnessErrorPolicy::Abort`. The population is left as it was when the error occurred.
+    pub fn try_run(&mut self, policy: FitnessErrorPolicy) -> Result<RunResult<G>, EvaluationError<E>> {
+        self.run_with(|population, fitness, count| {
+            let calls = Cell::new(0);
+            let counted = |individual: &Individual<G>| {
+                calls.set(calls.get() + 1);
+                fitness(individual)
+            };
+            match count {
+                Some(count) => population.try_evaluate_last(count, counted, policy)?,
+                None => population.try_evaluate(counted, policy)?,
+            };
+            Ok(calls.get())
+        })
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Re-randomizes all but the `keep` fittest individuals and resets their fitness,
+    /// the population has to be evaluated again afterwards.
+    pub fn restart(&mut self, keep: usize) {
+        self.sort_individuals();
+        self.record_best();
+        for i in keep.min(self.individuals.len())..self.individuals.len() {
+            let mut individual = self.individuals[i].clone();
+            self.randomize_individual(&mut individual);
+            individual.fitness = 0;
+            individual.violation = 0.0;
+            self.repair_individual(&mut individual);
+            self.individuals[i] = individual;
+        }
+        self.emit(GaEvent::Restart {
+            generation: self.generation,
+        });
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{init_population_seeded, CrossoverType};
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+    }
+
+    fn population(seed: u64) -> Population {
+        init_population_seeded(24, 20, 4, 5, false, CrossoverType::Bit, seed)
+    }
+
+    #[test]
+    fn stops_at_the_target_fitness() {
+        let result = GaRunner::new(population(1), ones).target_fitness(24).max_generations(2000).run();
+        assert_eq!(result.stop_reason, StopReason::TargetFitness);
+        assert_eq!(result.best.get_fitness(), 24);
+        assert!(result.generations < 2000);
+        assert_eq!(*result.best_fitness_history.last().unwrap(), 24);
+    }
+
+    #[test]
+    fn stops_after_the_generation_limit() {
+        let result = GaRunner::new(population(2), ones).target_fitness(100).max_generations(7).run();
+        assert_eq!(result.stop_reason, StopReason::MaxGenerations);
+        assert_eq!(result.generations, 7);
+        assert_eq!(result.best_fitness_history.len(), 7);
+        assert_eq!(result.evaluations, 7 * 20);
+    }
+
+    #[test]
+    fn stops_at_the_time_limit() {
+        let result = GaRunner::new(population(3), ones).target_fitness(100).time_limit(Duration::ZERO).run();
+        assert_eq!(result.stop_reason, StopReason::TimeLimit);
+        assert_eq!(result.generations, 1);
+    }
+
+    #[test]
+    fn stops_when_the_best_fitness_stagnates() {
+        let result = GaRunner::new(population(4), |_: &Individual| 5).stagnation_window(3).max_generations(100).run();
+        assert_eq!(result.stop_reason, StopReason::Stagnation);
+        // the first generation sets the reference, the next three don't improve on it
+        assert_eq!(result.generations, 4);
+        assert_eq!(result.restarts, 0);
+    }
+
+    #[test]
+    fn restarts_on_stagnation_until_the_limit() {
+        let mut runner = GaRunner::new(population(5), |_: &Individual| 5)
+            .stagnation_window(2)
+            .on_stagnation(StagnationAction::Restart { keep: 2, boost: None })
+            .max_restarts(2)
+            .max_generations(100);
+        let result = runner.run();
+        assert_eq!(result.stop_reason, StopReason::Stagnation);
+        assert_eq!(result.restarts, 2);
+        assert_eq!(result.generations, 9);
+    }
+
+    #[test]
+    fn cached_fitness_isnt_counted_as_an_evaluation() {
+        let mut population = population(6);
+        population.set_fitness_cache(1000);
+        let mut runner = GaRunner::new(population, ones).max_generations(10);
+        let result = runner.run();
+        let (hits, misses) = runner.get_population().get_fitness_cache_counts();
+        assert!(hits > 0);
+        assert_eq!(result.evaluations, misses);
+        assert!(result.evaluations < 10 * 20);
+    }
+
+    #[test]
+    fn failing_evaluations_are_counted() {
+        let fitness = |individual: &Individual| if individual.get_gene(0) { Ok(ones(individual)) } else { Err("no") };
+        let result = GaRunner::new(population(7), fitness).max_generations(3).try_run(FitnessErrorPolicy::Penalize(0)).unwrap();
+        assert_eq!(result.stop_reason, StopReason::MaxGenerations);
+        assert_eq!(result.evaluations, 3 * 20);
+    }
+}
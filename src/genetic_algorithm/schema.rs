@@ -0,0 +1,524 @@
+use std::ops::Range;
+
+use super::{
+    decoding::{binary_to_gray, gray_to_binary, max_value, BinaryEncoding, ByteOrder},
+    Individual, Population,
+};
+
+/// A named range of bits inside a genome.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneField {
+    name: String,
+    bits: Range<usize>,
+    range: Option<(f64, f64)>,
+    encoding: BinaryEncoding,
+    byte_order: ByteOrder,
+}
+
+impl GeneField {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_bits(&self) -> Range<usize> {
+        self.bits.clone()
+    }
+
+    pub fn get_bit_count(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Returns the numeric range the field is scaled into, if it has one.
+    pub fn get_range(&self) -> Option<(f64, f64)> {
+        self.range
+    }
+
+    pub fn get_encoding(&self) -> BinaryEncoding {
+        self.encoding
+    }
+
+    pub fn get_byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Reads the field's raw value from an individual.
+    fn read(&self, individual: &Individual) -> u64 {
+        let raw = self.reorder(individual.read_unsigned(self.bits.start, self.bits.len(), BinaryEncoding::Standard));
+        match self.encoding {
+            BinaryEncoding::Standard => raw,
+            BinaryEncoding::Gray => gray_to_binary(raw),
+        }
+    }
+
+    /// Writes a raw value that fits the field into an individual.
+    fn write(&self, individual: &mut Individual, value: u64) {
+        let raw = match self.encoding {
+            BinaryEncoding::Standard => value,
+            BinaryEncoding::Gray => binary_to_gray(value),
+        };
+        individual.write_unsigned(self.bits.start, self.bits.len(), BinaryEncoding::Standard, self.reorder(raw));
+    }
+
+    /// Swaps the bytes of a little endian value between genome order and value order,
+    /// both ways.
+    fn reorder(&self, value: u64) -> u64 {
+        match self.byte_order {
+            ByteOrder::BigEndian => value,
+            ByteOrder::LittleEndian => value.swap_bytes() >> (64 - self.bits.len()),
+        }
+    }
+
+    /// Maps a raw field value into the field's range, unscaled fields return the raw value.
+    fn scale(&self, raw: u64) -> f64 {
+        match self.range {
+            Some((min, max)) => min + (max - min) * raw as f64 / self.max_raw() as f64,
+            None => raw as f64,
+        }
+    }
+
+    /// Maps a value from the field's range back to the nearest raw field value, clamped
+    /// to the values the field can hold.
+    fn unscale(&self, value: f64) -> u64 {
+        match self.range {
+            Some((min, max)) => {
+                let clamped = value.clamp(min, max);
+                ((clamped - min) / (max - min) * self.max_raw() as f64).round() as u64
+            }
+            None => value.round().clamp(0.0, self.max_raw() as f64) as u64,
+        }
+    }
+
+    fn max_raw(&self) -> u64 {
+        max_value(self.bits.len())
+    }
+}
+
+/// One fixed-width field of a genome for `Individual::decode_fields`. Fields are laid out
+/// back to back from the first gene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    /// An unsigned integer of `bits` genes (1-32).
+    U32 { bits: usize },
+    /// A fixed-point value of `bits` genes (1-64) spread evenly over `[min, max]`.
+    F64 { bits: usize, min: f64, max: f64 },
+}
+
+impl Field {
+    /// Returns the number of genes the field takes.
+    pub fn get_bits(&self) -> usize {
+        match *self {
+            Field::U32 { bits } | Field::F64 { bits, .. } => bits,
+        }
+    }
+}
+
+/// The decoded values of every schema field of one individual,
+/// scaled into their ranges where the field has one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phenotype {
+    values: Vec<(String, f64)>,
+}
+
+impl Phenotype {
+    /// Returns the decoded value of the named field.
+    pub fn get(&self, name: &str) -> f64 {
+        match self.values.iter().find(|(field, _)| field == name) {
+            Some((_, value)) => *value,
+            None => panic!("the phenotype has no field named {}", name),
+        }
+    }
+
+    pub fn get_values(&self) -> &[(String, f64)] {
+        &self.values
+    }
+}
+
+/// Implemented by user parameter structs that can be built from a decoded `Phenotype`,
+/// so fitness code can work on ready-to-use values via `GenomeSchema::decode_into`.
+pub trait FromPhenotype {
+    fn from_phenotype(phenotype: &Phenotype) -> Self;
+}
+
+/// Describes the layout of a genome as a set of named, non-overlapping bit fields,
+/// e.g. `speed` in bits `0..8` and `flags` in bits `8..12`.
+/// Field values are read and written most significant bit first, the same way
+/// `Individual::get_genes_as_decimal_bytes` reads a byte, in plain binary and with the
+/// most significant byte first unless `encoding` or `byte_order` say otherwise, e.g.
+/// `GenomeSchema::new().scaled_field("x", 0..16, -5.0, 5.0).encoding(BinaryEncoding::Gray)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenomeSchema {
+    fields: Vec<GeneField>,
+}
+
+impl GenomeSchema {
+    /// Creates an empty schema.
+    pub fn new() -> GenomeSchema {
+        GenomeSchema { fields: vec![] }
+    }
+
+    /// Adds a named field to the schema.
+    ///
+    /// # Arguments
+    /// * `name` - The unique name of the field.
+    /// * `bits` - The gene indices the field occupies, at most 64 bits.
+    ///
+    /// # Returns
+    /// The schema with the field added.
+    pub fn field(mut self, name: &str, bits: Range<usize>) -> GenomeSchema {
+        if bits.is_empty() {
+            panic!("field {} must contain at least one bit", name);
+        }
+        if bits.len() > 64 {
+            panic!("field {} cant be longer than 64 bits", name);
+        }
+        if self.get_field(name).is_some() {
+            panic!("field {} is defined twice", name);
+        }
+        if let Some(other) = self.fields.iter().find(|other| other.bits.start < bits.end && bits.start < other.bits.end) {
+            panic!("field {} overlaps field {}", name, other.name);
+        }
+        self.fields.push(GeneField {
+            name: name.to_string(),
+            bits,
+            range: None,
+            encoding: BinaryEncoding::Standard,
+            byte_order: ByteOrder::BigEndian,
+        });
+        self
+    }
+
+    /// Sets how the field added last encodes its value, plain binary by default.
+    ///
+    /// # Returns
+    /// The schema with the field changed.
+    pub fn encoding(mut self, encoding: BinaryEncoding) -> GenomeSchema {
+        match self.fields.last_mut() {
+            Some(field) => field.encoding = encoding,
+            None => panic!("add a field before setting its encoding"),
+        }
+        self
+    }
+
+    /// Sets the order of the bytes of the field added last, most significant byte first
+    /// by default, to match an externally defined binary layout. A little endian field
+    /// must be a whole number of bytes long.
+    ///
+    /// # Returns
+    /// The schema with the field changed.
+    pub fn byte_order(mut self, byte_order: ByteOrder) -> GenomeSchema {
+        let field = match self.fields.last_mut() {
+            Some(field) => field,
+            None => panic!("add a field before setting its byte order"),
+        };
+        if byte_order == ByteOrder::LittleEndian && !field.bits.len().is_multiple_of(8) {
+            panic!("little endian field {} must be a multiple of 8 bits", field.name);
+        }
+        field.byte_order = byte_order;
+        self
+    }
+
+    /// Adds a named field whose raw value is scaled linearly into `[min, max]` when decoded,
+    /// e.g. 8 bits where 0 decodes to -1.0 and 255 to 1.0.
+    ///
+    /// # Arguments
+    /// * `name` - The unique name of the field.
+    /// * `bits` - The gene indices the field occupies, at most 64 bits.
+    /// * `min` - The value all zero bits decode to.
+    /// * `max` - The value all one bits decode to.
+    ///
+    /// # Returns
+    /// The schema with the field added.
+    pub fn scaled_field(self, name: &str, bits: Range<usize>, min: f64, max: f64) -> GenomeSchema {
+        if min >= max {
+            panic!("field {} needs a min smaller than its max", name);
+        }
+        let mut schema = self.field(name, bits);
+        schema.fields.last_mut().unwrap().range = Some((min, max));
+        schema
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<&GeneField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    pub fn get_fields(&self) -> &[GeneField] {
+        &self.fields
+    }
+
+    /// Returns the smallest gene length that holds every field.
+    pub fn get_gene_length(&self) -> usize {
+        self.fields.iter().map(|field| field.bits.end).max().unwrap_or(0)
+    }
+
+    /// Returns the gene indices where one field ends and another begins,
+    /// the only places crossover can cut without corrupting a field value.
+    pub fn get_crossover_points(&self) -> Vec<usize> {
+        let mut points: Vec<usize> = self
+            .fields
+            .iter()
+            .flat_map(|field| [field.bits.start, field.bits.end])
+            .filter(|&point| point < self.get_gene_length())
+            .collect();
+        points.sort();
+        points.dedup();
+        points
+    }
+
+    /// Decodes every field of an individual, scaling fields that have a range.
+    ///
+    /// # Arguments
+    /// * `individual` - The individual to decode.
+    pub fn decode(&self, individual: &Individual) -> Phenotype {
+        Phenotype {
+            values: self
+                .fields
+                .iter()
+                .map(|field| (field.name.clone(), individual.get_scaled_field(self, &field.name)))
+                .collect(),
+        }
+    }
+
+    /// Decodes an individual straight into a user parameter struct.
+    ///
+    /// # Arguments
+    /// * `individual` - The individual to decode.
+    pub fn decode_into<T: FromPhenotype>(&self, individual: &Individual) -> T {
+        T::from_phenotype(&self.decode(individual))
+    }
+
+    /// Builds a schema from consecutive fields starting at the first gene, naming each
+    /// field after its index, e.g. `"0"` for the first one.
+    ///
+    /// # Arguments
+    /// * `fields` - The fields, back to back from the first gene.
+    /// * `encoding` - Whether the fields are plain binary or Gray coded.
+    pub fn from_fields(fields: &[Field], encoding: BinaryEncoding) -> GenomeSchema {
+        let mut schema = GenomeSchema::new();
+        let mut start = 0;
+        for (index, field) in fields.iter().enumerate() {
+            let bits = start..start + field.get_bits();
+            schema = match *field {
+                Field::U32 { bits: width } if !(1..=32).contains(&width) => panic!("an integer field must be between 1 and 32 bits"),
+                Field::U32 { .. } => schema.field(&index.to_string(), bits),
+                Field::F64 { min, max, .. } => schema.scaled_field(&index.to_string(), bits, min, max),
+            }
+            .encoding(encoding);
+            start += field.get_bits();
+        }
+        schema
+    }
+
+    fn expect_field(&self, name: &str) -> &GeneField {
+        match self.get_field(name) {
+            Some(field) => field,
+            None => panic!("the schema has no field named {}", name),
+        }
+    }
+}
+
+impl Individual {
+    /// Reads a schema field as an unsigned integer, decoded with the field's encoding and byte order.
+    ///
+    /// # Arguments
+    /// * `schema` - The schema describing the genome layout.
+    /// * `name` - The name of the field.
+    pub fn get_field(&self, schema: &GenomeSchema, name: &str) -> u64 {
+        let field = schema.expect_field(name);
+        if field.bits.end > self.gene_length {
+            panic!("field {} is outside the gene length {}", name, self.gene_length);
+        }
+        field.read(self)
+    }
+
+    /// Writes an unsigned integer into a schema field.
+    ///
+    /// # Arguments
+    /// * `schema` - The schema describing the genome layout.
+    /// * `name` - The name of the field.
+    /// * `value` - The new value, must fit in the field's bit count.
+    pub fn set_field(&mut self, schema: &GenomeSchema, name: &str, value: u64) {
+        let field = schema.expect_field(name);
+        if field.bits.end > self.gene_length {
+            panic!("field {} is outside the gene length {}", name, self.gene_length);
+        }
+        if value > field.max_raw() {
+            panic!("value {} does not fit in the {} bits of field {}", value, field.bits.len(), name);
+        }
+        field.write(self, value);
+    }
+
+    /// Reads a schema field scaled into its range, unscaled fields return the raw value.
+    ///
+    /// # Arguments
+    /// * `schema` - The schema describing the genome layout.
+    /// * `name` - The name of the field.
+    pub fn get_scaled_field(&self, schema: &GenomeSchema, name: &str) -> f64 {
+        schema.expect_field(name).scale(self.get_field(schema, name))
+    }
+
+    /// Writes a value into a schema field, choosing the raw value closest to it.
+    /// Values outside the field's range, or what an unscaled field can hold, are clamped.
+    ///
+    /// # Arguments
+    /// * `schema` - The schema describing the genome layout.
+    /// * `name` - The name of the field.
+    /// * `value` - The new value.
+    pub fn set_scaled_field(&mut self, schema: &GenomeSchema, name: &str, value: f64) {
+        let raw = schema.expect_field(name).unscale(value);
+        self.set_field(schema, name, raw);
+    }
+
+    /// Decodes consecutive plain binary fields starting at the first gene, e.g.
+    /// `decode_fields(&[Field::F64 { bits: 16, min: -5.0, max: 5.0 }, Field::U32 { bits: 4 }])`.
+    /// Genes after the last field are ignored.
+    ///
+    /// # Returns
+    /// The value of every field, integers converted to `f64`.
+    pub fn decode_fields(&self, fields: &[Field]) -> Vec<f64> {
+        self.decode_fields_with(fields, BinaryEncoding::Standard)
+    }
+
+    /// Decodes consecutive fields like `decode_fields`, in the given encoding.
+    pub fn decode_fields_with(&self, fields: &[Field], encoding: BinaryEncoding) -> Vec<f64> {
+        let schema = self.fields_schema(fields, encoding);
+        schema.decode(self).values.into_iter().map(|(_, value)| value).collect()
+    }
+
+    /// Writes values into consecutive fields, the inverse of `decode_fields_with`, e.g. to
+    /// seed known solutions. Fixed-point values are rounded to the nearest representable
+    /// one and clamped, integers must fit their field.
+    ///
+    /// # Arguments
+    /// * `fields` - The fields, starting at the first gene.
+    /// * `values` - One value per field.
+    /// * `encoding` - Whether the fields are plain binary or Gray coded.
+    pub fn encode_fields(&mut self, fields: &[Field], values: &[f64], encoding: BinaryEncoding) {
+        let schema = self.fields_schema(fields, encoding);
+        if values.len() != fields.len() {
+            panic!("got {} values for {} fields", values.len(), fields.len());
+        }
+        for (index, (field, &value)) in fields.iter().zip(values).enumerate() {
+            let name = index.to_string();
+            match *field {
+                Field::U32 { .. } => {
+                    if value < 0.0 || value.fract() != 0.0 {
+                        panic!("integer field value {} must be a whole number of 0 or more", value);
+                    }
+                    self.set_field(&schema, &name, value as u64);
+                }
+                Field::F64 { .. } => self.set_scaled_field(&schema, &name, value),
+            }
+        }
+    }
+
+    /// Builds the schema for a field list, checking that the fields fit in the genome.
+    fn fields_schema(&self, fields: &[Field], encoding: BinaryEncoding) -> GenomeSchema {
+        let schema = GenomeSchema::from_fields(fields, encoding);
+        if schema.get_gene_length() > self.gene_length {
+            panic!("fields of {} bits dont fit in the gene length {}", schema.get_gene_length(), self.gene_length);
+        }
+        schema
+    }
+}
+
+impl Population {
+    /// Makes the population's operators aware of a genome schema:
+    /// crossover is restricted to the boundaries between fields so that
+    /// recombination never splits a field value.
+    ///
+    /// # Arguments
+    /// * `schema` - The schema describing the genome layout.
+    pub fn apply_schema(&mut self, schema: &GenomeSchema) {
+        let gene_length = self.individuals[0].gene_length;
+        if schema.get_gene_length() > gene_length {
+            panic!("the schema needs {} genes but individuals only have {}", schema.get_gene_length(), gene_length);
+        }
+        self.set_crossover_points(&schema.get_crossover_points());
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> GenomeSchema {
+        GenomeSchema::new()
+            .field("count", 0..16)
+            .byte_order(ByteOrder::LittleEndian)
+            .field("step", 16..28)
+            .encoding(BinaryEncoding::Gray)
+            .scaled_field("weight", 28..36, -1.0, 1.0)
+            .field("word", 36..60)
+            .encoding(BinaryEncoding::Gray)
+            .byte_order(ByteOrder::LittleEndian)
+    }
+
+    #[test]
+    fn fields_round_trip() {
+        let schema = schema();
+        let mut individual = Individual::from_genes(vec![false; schema.get_gene_length()]);
+        for value in [0, 1, 0x1234, 0xFFFF] {
+            individual.set_field(&schema, "count", value);
+            assert_eq!(individual.get_field(&schema, "count"), value);
+        }
+        for value in 0..1 << 12 {
+            individual.set_field(&schema, "step", value);
+            assert_eq!(individual.get_field(&schema, "step"), value);
+        }
+        for value in [0, 0x12_3456, 0xFF_FFFF] {
+            individual.set_field(&schema, "word", value);
+            assert_eq!(individual.get_field(&schema, "word"), value);
+        }
+        for value in [-1.0, -0.5, 0.0, 0.25, 1.0] {
+            individual.set_scaled_field(&schema, "weight", value);
+            assert!((individual.get_scaled_field(&schema, "weight") - value).abs() <= 1.0 / 255.0);
+        }
+        individual.set_scaled_field(&schema, "weight", 7.0);
+        assert_eq!(individual.get_scaled_field(&schema, "weight"), 1.0);
+        individual.set_scaled_field(&schema, "count", 1e9);
+        assert_eq!(individual.get_field(&schema, "count"), 0xFFFF);
+    }
+
+    #[test]
+    fn little_endian_field_stores_low_byte_first() {
+        let schema = schema();
+        let mut individual = Individual::from_genes(vec![false; schema.get_gene_length()]);
+        individual.set_field(&schema, "count", 0x1234);
+        assert_eq!(individual.read_unsigned(0, 8, BinaryEncoding::Standard), 0x34);
+        assert_eq!(individual.read_unsigned(8, 8, BinaryEncoding::Standard), 0x12);
+    }
+
+    #[test]
+    fn decode_scales_every_field() {
+        let schema = schema();
+        let mut individual = Individual::from_genes(vec![false; schema.get_gene_length()]);
+        individual.set_field(&schema, "step", 100);
+        individual.set_field(&schema, "weight", 255);
+        let phenotype = schema.decode(&individual);
+        assert_eq!(phenotype.get("count"), 0.0);
+        assert_eq!(phenotype.get("step"), 100.0);
+        assert_eq!(phenotype.get("weight"), 1.0);
+    }
+
+    #[test]
+    fn field_lists_decode_like_fixed_points() {
+        let fields = [Field::F64 { bits: 16, min: -5.0, max: 5.0 }, Field::U32 { bits: 4 }, Field::F64 { bits: 8, min: 0.0, max: 1.0 }];
+        for encoding in [BinaryEncoding::Standard, BinaryEncoding::Gray] {
+            let mut individual = Individual::from_genes(vec![false; 30]);
+            individual.encode_fields(&fields, &[1.25, 9.0, 0.5], encoding);
+            let values = individual.decode_fields_with(&fields, encoding);
+            assert_eq!(values[0], individual.decode_fixed_point(0, 16, -5.0, 5.0, encoding));
+            assert!((values[0] - 1.25).abs() <= 10.0 / 65535.0);
+            assert_eq!(values[1], 9.0);
+            assert_eq!(individual.read_unsigned(16, 4, encoding), 9);
+            assert!((values[2] - 0.5).abs() <= 1.0 / 255.0);
+        }
+        let individual = Individual::from_genes(vec![true; 20]);
+        assert_eq!(individual.decode_fields(&fields[..2]), vec![5.0, 15.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "dont fit in the gene length")]
+    fn field_lists_must_fit_the_genome() {
+        Individual::from_genes(vec![false; 10]).decode_fields(&[Field::U32 { bits: 8 }, Field::U32 { bits: 4 }]);
+    }
+}
@@ -0,0 +1,426 @@
+use rand::Rng;
+use std::{fmt, sync::Arc};
+
+use super::{gene::Gene, rng::GaRng, Individual, Population, SelectionType};
+
+/// One step of a `SelectionPipeline`. A stage receives the population sorted best first
+/// and the indices of the candidates left by the previous stages, in ascending order,
+/// and returns the indices it passes on. A smaller index always means a better individual.
+/// Closures with the signature of `apply` implement it too.
+pub trait SelectionStage<G: Gene = bool>: Send + Sync {
+    /// # Arguments
+    /// * `individuals` - The whole population, sorted best first.
+    /// * `candidates` - The indices left by the previous stages.
+    /// * `count` - How many parents the pipeline has to select.
+    /// * `rng` - The random number generator of the population.
+    fn apply(&self, individuals: &[Individual<G>], candidates: Vec<usize>, count: usize, rng: &mut GaRng) -> Vec<usize>;
+}
+
+impl<G: Gene, F> SelectionStage<G> for F
+where
+    F: Fn(&[Individual<G>], Vec<usize>, usize, &mut GaRng) -> Vec<usize> + Send + Sync,
+{
+    fn apply(&self, individuals: &[Individual<G>], candidates: Vec<usize>, count: usize, rng: &mut GaRng) -> Vec<usize> {
+        self(individuals, candidates, count, rng)
+    }
+}
+
+/// Removes candidates whose genome equals that of a better candidate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dedup;
+
+impl<G: Gene> SelectionStage<G> for Dedup {
+    fn apply(&self, individuals: &[Individual<G>], candidates: Vec<usize>, _count: usize, _rng: &mut GaRng) -> Vec<usize> {
+        let mut kept: Vec<usize> = vec![];
+        for candidate in candidates {
+            if !kept.iter().any(|&other| individuals[other].genes == individuals[candidate].genes) {
+                kept.push(candidate);
+            }
+        }
+        kept
+    }
+}
+
+/// Removes candidates that violate a constraint, see `Individual::set_violation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeasibleOnly;
+
+impl<G: Gene> SelectionStage<G> for FeasibleOnly {
+    fn apply(&self, individuals: &[Individual<G>], candidates: Vec<usize>, _count: usize, _rng: &mut GaRng) -> Vec<usize> {
+        candidates.into_iter().filter(|&candidate| individuals[candidate].is_feasible()).collect()
+    }
+}
+
+/// Keeps the best `count` candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Truncation;
+
+impl<G: Gene> SelectionStage<G> for Truncation {
+    fn apply(&self, _individuals: &[Individual<G>], mut candidates: Vec<usize>, count: usize, _rng: &mut GaRng) -> Vec<usize> {
+        candidates.truncate(count);
+        candidates
+    }
+}
+
+/// Picks `count` distinct candidates, each the best of `size` randomly drawn candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl<G: Gene> SelectionStage<G> for Tournament {
+    fn apply(&self, _individuals: &[Individual<G>], mut candidates: Vec<usize>, count: usize, rng: &mut GaRng) -> Vec<usize> {
+        if self.size < 1 {
+            panic!("tournament size must be 1 or more");
+        }
+        let mut winners = vec![];
+        while winners.len() < count && !candidates.is_empty() {
+            let mut best = rng.random_range(0..candidates.len());
+            for _ in 1..self.size {
+                best = best.min(rng.random_range(0..candidates.len()));
+            }
+            winners.push(candidates.remove(best));
+        }
+        winners.sort();
+        winners
+    }
+}
+
+/// Picks `count` distinct candidates with linear ranking: the best candidate is
+/// `pressure` (1.0-2.0) times as likely to be drawn as the average one and the
+/// probability falls linearly with the rank, whatever the fitness values are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rank {
+    pub pressure: f64,
+}
+
+impl<G: Gene> SelectionStage<G> for Rank {
+    fn apply(&self, _individuals: &[Individual<G>], mut candidates: Vec<usize>, count: usize, rng: &mut GaRng) -> Vec<usize> {
+        if !(1.0..=2.0).contains(&self.pressure) {
+            panic!("rank selection pressure must be between 1 and 2");
+        }
+        let n = candidates.len();
+        // weight of the candidate at rank i (0 is the best) out of n
+        let weight = |i: usize| {
+            if n < 2 {
+                return 1.0;
+            }
+            (2.0 - self.pressure) + 2.0 * (self.pressure - 1.0) * (n - 1 - i) as f64 / (n - 1) as f64
+        };
+        let mut weights: Vec<f64> = (0..n).map(weight).collect();
+        let mut winners = vec![];
+        while winners.len() < count && !candidates.is_empty() {
+            let total: f64 = weights.iter().sum();
+            let mut roll = rng.random_range(0.0..1.0) * total;
+            let mut pick = 0;
+            for (i, &weight) in weights.iter().enumerate() {
+                pick = i;
+                if roll < weight {
+                    break;
+                }
+                roll -= weight;
+            }
+            weights.remove(pick);
+            winners.push(candidates.remove(pick));
+        }
+        winners.sort();
+        winners
+    }
+}
+
+/// Picks `count` candidates proportionally to their fitness with stochastic universal
+/// sampling: `count` evenly spaced pointers from a single random offset over the
+/// cumulative fitness. A candidate can be picked more than once. If every candidate has
+/// a fitness of 0 they are all equally likely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StochasticUniversal;
+
+impl<G: Gene> SelectionStage<G> for StochasticUniversal {
+    fn apply(&self, individuals: &[Individual<G>], candidates: Vec<usize>, count: usize, rng: &mut GaRng) -> Vec<usize> {
+        if candidates.is_empty() || count == 0 {
+            return vec![];
+        }
+        let mut weights: Vec<f64> = candidates.iter().map(|&index| individuals[index].fitness as f64).collect();
+        if weights.iter().all(|&weight| weight == 0.0) {
+            weights = vec![1.0; candidates.len()];
+        }
+        let spacing = weights.iter().sum::<f64>() / count as f64;
+        let mut pointer = rng.random_range(0.0..spacing);
+        let mut cumulative = weights[0];
+        let mut current = 0;
+        let mut picks = vec![];
+        while picks.len() < count {
+            while pointer >= cumulative && current + 1 < candidates.len() {
+                current += 1;
+                cumulative += weights[current];
+            }
+            picks.push(candidates[current]);
+            pointer += spacing;
+        }
+        picks
+    }
+}
+
+/// Makes sure the best `count` individuals of the whole population are selected,
+/// replacing the worst candidates if needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EliteTopUp {
+    pub count: usize,
+}
+
+impl<G: Gene> SelectionStage<G> for EliteTopUp {
+    fn apply(&self, individuals: &[Individual<G>], mut candidates: Vec<usize>, count: usize, _rng: &mut GaRng) -> Vec<usize> {
+        let elites = self.count.min(individuals.len()).min(count);
+        for elite in 0..elites {
+            if !candidates.contains(&elite) {
+                if candidates.len() >= count {
+                    // drop the worst candidate that isn't an elite
+                    if let Some(position) = candidates.iter().rposition(|&candidate| candidate >= elites) {
+                        candidates.remove(position);
+                    }
+                }
+                candidates.push(elite);
+            }
+        }
+        candidates.sort();
+        candidates
+    }
+}
+
+/// A selection policy assembled from small stages that run in order, e.g.
+/// `SelectionPipeline::new().stage(Dedup).stage(FeasibleOnly).stage(Tournament { size: 3 }).stage(EliteTopUp { count: 1 })`.
+/// At most `count` of the candidates left after the last stage become the parents; if
+/// the stages leave nobody the best individual is used. Repeated picks, e.g. of
+/// `StochasticUniversal`, are kept, so a fitter individual can parent several children.
+#[derive(Clone)]
+pub struct SelectionPipeline<G: Gene = bool> {
+    stages: Vec<Arc<dyn SelectionStage<G>>>,
+}
+
+impl<G: Gene> Default for SelectionPipeline<G> {
+    fn default() -> SelectionPipeline<G> {
+        SelectionPipeline { stages: vec![] }
+    }
+}
+
+impl<G: Gene> fmt::Debug for SelectionPipeline<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SelectionPipeline({} stages)", self.stages.len())
+    }
+}
+
+impl<G: Gene> SelectionPipeline<G> {
+    pub fn new() -> SelectionPipeline<G> {
+        SelectionPipeline::default()
+    }
+
+    /// Appends a stage to the pipeline.
+    pub fn stage<S: SelectionStage<G> + 'static>(mut self, stage: S) -> SelectionPipeline<G> {
+        self.stages.push(Arc::new(stage));
+        self
+    }
+
+    /// Runs the pipeline.
+    ///
+    /// # Arguments
+    /// * `individuals` - The population, sorted best first.
+    /// * `count` - The number of parents to select.
+    /// * `rng` - The random number generator handed to the stages.
+    ///
+    /// # Returns
+    /// The indices of the selected individuals, best first, at least one and at most `count`.
+    /// An individual picked several times appears several times.
+    pub fn select(&self, individuals: &[Individual<G>], count: usize, rng: &mut GaRng) -> Vec<usize> {
+        self.select_from(individuals, (0..individuals.len()).collect(), count, rng)
+    }
+
+    /// Runs the stages on a subset of the population, e.g. one age layer.
+    pub(crate) fn select_from(
+        &self,
+        individuals: &[Individual<G>],
+        mut candidates: Vec<usize>,
+        count: usize,
+        rng: &mut GaRng,
+    ) -> Vec<usize> {
+        let fallback = candidates.first().copied().unwrap_or(0);
+        for stage in &self.stages {
+            candidates = stage.apply(individuals, candidates, count, rng);
+            if let Some(&index) = candidates.iter().find(|&&index| index >= individuals.len()) {
+                panic!("selection stage returned index {} of a population of {}", index, individuals.len());
+            }
+        }
+        candidates.sort();
+        candidates.truncate(count);
+        if candidates.is_empty() {
+            candidates.push(fallback);
+        }
+        candidates
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Sets how the parents of the next generation are chosen, truncation by default.
+    /// A selection pipeline, if set, takes precedence.
+    pub fn set_selection_type(&mut self, selection_type: SelectionType) {
+        match selection_type {
+            SelectionType::Tournament { size } if size < 1 => panic!("tournament size must be 1 or more"),
+            SelectionType::Rank { pressure } if !(1.0..=2.0).contains(&pressure) => {
+                panic!("rank selection pressure must be between 1 and 2")
+            }
+            _ => {}
+        }
+        self.selection_type = selection_type;
+    }
+
+    pub fn get_selection_type(&self) -> SelectionType {
+        self.selection_type
+    }
+
+    /// Keeps the `count` fittest individuals unmodified in the next generation, separately
+    /// from the parents: the parents only breed and the rest of the next generation are
+    /// their children. Without an elitism count the parents themselves survive.
+    pub fn set_elitism_count(&mut self, count: usize) {
+        if count > self.population_size {
+            panic!("elitism count cant be larger than population");
+        }
+        self.elitism_count = Some(count);
+    }
+
+    /// Goes back to the parents surviving into the next generation.
+    pub fn clear_elitism_count(&mut self) {
+        self.elitism_count = None;
+    }
+
+    pub fn get_elitism_count(&self) -> Option<usize> {
+        self.elitism_count
+    }
+
+    /// Selects the parents of the next generation with a pipeline instead of the
+    /// selection type. The pipeline picks up to `parent_count` parents.
+    pub fn set_selection(&mut self, pipeline: SelectionPipeline<G>) {
+        self.selection = Some(pipeline);
+    }
+
+    /// Goes back to choosing parents by the selection type.
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    /// Chooses the parents of the next generation from the sorted population, by the
+    /// selection pipeline if set and by the selection type otherwise.
+    ///
+    /// # Returns
+    /// The indices of the parents, an individual picked several times appears several times.
+    pub(crate) fn select_parent_indices(&mut self) -> Vec<usize> {
+        self.select_parent_indices_from((0..self.individuals.len()).collect(), self.parent_count)
+    }
+
+    /// Chooses `count` parents among the given candidates, indices into the sorted
+    /// population in ascending order.
+    pub(crate) fn select_parent_indices_from(&mut self, candidates: Vec<usize>, count: usize) -> Vec<usize> {
+        let stage: Box<dyn SelectionStage<G>> = match self.selection_type {
+            SelectionType::Truncation => Box::new(Truncation),
+            SelectionType::Tournament { size } => Box::new(Tournament { size }),
+            SelectionType::Rank { pressure } => Box::new(Rank { pressure }),
+            SelectionType::StochasticUniversal => Box::new(StochasticUniversal),
+        };
+        match &self.selection {
+            Some(pipeline) => pipeline.select_from(&self.individuals, candidates, count, &mut self.rng),
+            None => stage.apply(&self.individuals, candidates, count, &mut self.rng),
+        }
+    }
+
+    /// Reduces the sorted population to the parents of the next generation. Selected
+    /// individuals are moved rather than cloned, only one picked several times is copied.
+    ///
+    /// # Returns
+    /// The individuals that weren't selected, whose gene storage can be reused.
+    pub(crate) fn select_parents(&mut self) -> Vec<Individual<G>> {
+        let selected = self.select_parent_indices();
+        let mut picks = vec![0usize; self.individuals.len()];
+        for &index in &selected {
+            picks[index] += 1;
+        }
+        let mut individuals: Vec<Option<Individual<G>>> = std::mem::take(&mut self.individuals).into_iter().map(Some).collect();
+        self.individuals = selected
+            .into_iter()
+            .map(|index| {
+                picks[index] -= 1;
+                match picks[index] {
+                    0 => individuals[index].take().unwrap(),
+                    _ => individuals[index].clone().unwrap(),
+                }
+            })
+            .collect();
+        individuals.into_iter().flatten().collect()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::super::{init_population_seeded, CrossoverType};
+    use super::*;
+
+    /// Individuals sorted best first with the genome given by the bits of an id, fitness and violation.
+    fn individuals(specs: &[(u8, u64, f64)]) -> Vec<Individual> {
+        specs
+            .iter()
+            .map(|&(id, fitness, violation)| {
+                let mut individual = Individual::from_genes((0..4).map(|bit| id >> bit & 1 == 1).collect());
+                individual.set_fitness(fitness);
+                individual.set_violation(violation);
+                individual
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pipeline_keeps_repeated_picks() {
+        let individuals = individuals(&[(1, 100, 0.0), (2, 0, 0.0), (3, 0, 0.0), (4, 0, 0.0)]);
+        let pipeline = SelectionPipeline::new().stage(StochasticUniversal);
+        let selected = pipeline.select(&individuals, 3, &mut GaRng::seed_from_u64(1));
+        assert_eq!(selected, vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn pipeline_runs_stages_in_order() {
+        // 1 repeats the genome of 0 and 2 is infeasible
+        let individuals = individuals(&[(1, 9, 0.0), (1, 8, 0.0), (2, 7, 1.0), (3, 6, 0.0), (4, 5, 0.0)]);
+        let pipeline = SelectionPipeline::new().stage(Dedup).stage(FeasibleOnly).stage(Truncation);
+        assert_eq!(pipeline.select(&individuals, 2, &mut GaRng::seed_from_u64(2)), vec![0, 3]);
+    }
+
+    #[test]
+    fn elite_top_up_adds_the_best() {
+        let individuals = individuals(&[(1, 9, 1.0), (2, 8, 0.0), (3, 7, 0.0), (4, 6, 0.0)]);
+        let pipeline = SelectionPipeline::new().stage(FeasibleOnly).stage(Truncation).stage(EliteTopUp { count: 1 });
+        assert_eq!(pipeline.select(&individuals, 2, &mut GaRng::seed_from_u64(3)), vec![0, 1]);
+    }
+
+    #[test]
+    fn empty_pipeline_result_falls_back_to_the_best() {
+        let individuals = individuals(&[(1, 9, 1.0), (2, 8, 1.0)]);
+        let pipeline = SelectionPipeline::new().stage(FeasibleOnly);
+        assert_eq!(pipeline.select(&individuals, 2, &mut GaRng::seed_from_u64(4)), vec![0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "selection stage returned index 5 of a population of 2")]
+    fn out_of_range_stage_result_panics() {
+        let individuals = individuals(&[(1, 9, 0.0), (2, 8, 0.0)]);
+        let stage = |_: &[Individual], _: Vec<usize>, _: usize, _: &mut GaRng| vec![5];
+        SelectionPipeline::new().stage(stage).select(&individuals, 2, &mut GaRng::seed_from_u64(5));
+    }
+
+    #[test]
+    fn repeated_pipeline_picks_become_repeated_parents() {
+        let mut population = init_population_seeded(8, 6, 3, 5, false, CrossoverType::Bit, 6);
+        population.evaluate(|_: &Individual| 1);
+        population.individuals[0].set_fitness(1000);
+        population.set_selection(SelectionPipeline::new().stage(StochasticUniversal));
+        assert_eq!(population.select_parent_indices(), vec![0, 0, 0]);
+        population.select_parents();
+        assert_eq!(population.individuals.len(), 3);
+    }
+}
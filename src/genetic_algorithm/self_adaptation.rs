@@ -0,0 +1,157 @@
+use rand::Rng;
+
+use super::{gene::Gene, real::standard_normal, rng::GaRng, Individual, Population};
+
+/// Evolution strategies style self-adaptive mutation: every individual carries its own
+/// mutation probabilities, its strategy, which are inherited and mutated along with the
+/// genes. Individuals whose rates suit the current stage of the run produce better
+/// children, so the population tunes its own mutation strength.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfAdaptation {
+    learning_rate: f64,
+    min: f64,
+    max: f64,
+    per_gene: bool,
+}
+
+impl SelfAdaptation {
+    /// Creates a self-adaptation with one mutation probability per individual.
+    ///
+    /// # Arguments
+    /// * `learning_rate` - How strongly the rates are mutated, e.g. 0.2. Rates change by a log-normal factor.
+    /// * `min` - The smallest rate (0.0-1.0, above 0), so a rate can't get stuck at 0.
+    /// * `max` - The largest rate (0.0-1.0).
+    pub fn new(learning_rate: f64, min: f64, max: f64) -> SelfAdaptation {
+        if learning_rate.is_nan() || learning_rate < 0.0 {
+            panic!("learning rate cant be negative");
+        }
+        if !(min > 0.0 && min <= max && max <= 1.0) {
+            panic!("rate bounds must satisfy 0 < min <= max <= 1");
+        }
+        SelfAdaptation {
+            learning_rate,
+            min,
+            max,
+            per_gene: false,
+        }
+    }
+
+    /// Gives every gene its own mutation probability instead of one per individual.
+    /// A child inherits each rate from the parent it inherits the gene from.
+    pub fn per_gene(mut self, per_gene: bool) -> SelfAdaptation {
+        self.per_gene = per_gene;
+        self
+    }
+
+    pub fn get_learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    /// Returns the smallest and the largest rate.
+    pub fn get_bounds(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+
+    pub fn is_per_gene(&self) -> bool {
+        self.per_gene
+    }
+
+    /// Mutates an inherited rate with the log-normal rule for probabilities, which keeps
+    /// the rate between 0 and 1 and makes halving and doubling the odds equally likely.
+    ///
+    /// # Arguments
+    /// * `rate` - The rate of the parent, `None` if it carries none.
+    /// * `initial` - The rate used when the parent carries none.
+    /// * `rng` - The random number generator to draw from.
+    pub(crate) fn mutate_rate(&self, rate: Option<f64>, initial: f64, rng: &mut GaRng) -> f64 {
+        let rate = rate.unwrap_or(initial).clamp(self.min, self.max);
+        if rate >= 1.0 {
+            return rate;
+        }
+        let odds = (1.0 - rate) / rate * (-self.learning_rate * standard_normal(rng)).exp();
+        (1.0 / (1.0 + odds)).clamp(self.min, self.max)
+    }
+
+    /// Returns the strategy of a new individual: the given rate for every gene or once.
+    fn initial_strategy(&self, rate: f64, gene_length: usize) -> Vec<f64> {
+        vec![rate.clamp(self.min, self.max); if self.per_gene { gene_length } else { 1 }]
+    }
+}
+
+impl<G: Gene> Individual<G> {
+    /// Returns the mutation probabilities the individual carries under self-adaptation,
+    /// one or one per gene, or its step sizes under an evolution strategy, one per gene.
+    /// Empty otherwise.
+    pub fn get_strategy(&self) -> Vec<f64> {
+        self.strategy.clone()
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Enables self-adaptive mutation. Every individual starts with the current mutation
+    /// probability as its rate; per-gene mutation rates and a mutation schedule have no
+    /// effect while it is enabled. Variation operators mutate a child with the mean of
+    /// its rates.
+    ///
+    /// # Arguments
+    /// * `adaptation` - How the rates are carried and mutated.
+    pub fn set_self_adaptation(&mut self, adaptation: SelfAdaptation) {
+        if self.evolution_strategy.is_some() {
+            panic!("evolution strategies adapt their own step sizes and cant be combined with self-adaptation");
+        }
+        self.self_adaptation = Some(adaptation);
+        for individual in self.individuals.iter_mut() {
+            individual.strategy = adaptation.initial_strategy(self.mutation_probability, individual.gene_length);
+        }
+    }
+
+    /// Goes back to mutating with the population's mutation probability and drops the
+    /// strategies of all individuals.
+    pub fn clear_self_adaptation(&mut self) {
+        self.self_adaptation = None;
+        for individual in self.individuals.iter_mut() {
+            individual.strategy.clear();
+        }
+    }
+
+    pub fn get_self_adaptation(&self) -> Option<SelfAdaptation> {
+        self.self_adaptation
+    }
+
+    /// Returns the mean of the mutation rates all individuals carry, `None` without
+    /// self-adaptation.
+    pub fn mean_strategy_rate(&self) -> Option<f64> {
+        self.self_adaptation?;
+        let rates: Vec<f64> = self.individuals.iter().flat_map(|individual| individual.strategy.iter().copied()).collect();
+        if rates.is_empty() {
+            return None;
+        }
+        Some(rates.iter().sum::<f64>() / rates.len() as f64)
+    }
+
+    /// Gives a new random individual the initial strategy, if self-adaptation is enabled.
+    pub(crate) fn initialize_strategy(&self, individual: &mut Individual<G>) {
+        individual.strategy = match &self.self_adaptation {
+            Some(adaptation) => adaptation.initial_strategy(self.mutation_probability, individual.gene_length),
+            None => vec![],
+        };
+    }
+
+    /// Sets the strategy of a child bred by variation operators from a random parent's
+    /// and returns the probability to mutate it with.
+    pub(crate) fn inherit_strategy(&self, child: &mut Individual<G>, parents: &[&Individual<G>], rng: &mut GaRng) -> f64 {
+        let adaptation = match &self.self_adaptation {
+            Some(adaptation) => adaptation,
+            None => return self.mutation_probability,
+        };
+        let parent = &parents[rng.random_range(0..parents.len())];
+        let length = if adaptation.per_gene { child.gene_length } else { 1 };
+        child.strategy.clear();
+        for i in 0..length {
+            let rate = adaptation.mutate_rate(parent.strategy.get(i).copied(), self.mutation_probability, rng);
+            child.strategy.push(rate);
+        }
+        child.strategy.iter().sum::<f64>() / length as f64
+    }
+}
@@ -0,0 +1,227 @@
+use rand::{Rng, SeedableRng};
+
+use super::{gene::Gene, rng::GaRng, Individual};
+
+/// How the temperature of a simulated annealing run falls. The temperature is in
+/// fitness units: a move that loses `delta` fitness is accepted with probability
+/// `exp(-delta / temperature)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CoolingSchedule {
+    /// Multiplies the temperature by `factor` (0.0-1.0) every iteration.
+    Geometric { initial: f64, factor: f64 },
+    /// Falls linearly from `initial` to `end` over the iterations of the run.
+    Linear { initial: f64, end: f64 },
+    /// `initial / ln(iteration + 2)`, slow but with convergence guarantees in theory.
+    Logarithmic { initial: f64 },
+}
+
+impl CoolingSchedule {
+    /// Returns the temperature at an iteration of a run of `iterations` iterations.
+    pub fn temperature(&self, iteration: u64, iterations: u64) -> f64 {
+        match *self {
+            CoolingSchedule::Geometric { initial, factor } => initial * factor.powf(iteration as f64),
+            CoolingSchedule::Linear { initial, end } => {
+                let progress = iteration as f64 / iterations.max(1) as f64;
+                initial + (end - initial) * progress.min(1.0)
+            }
+            CoolingSchedule::Logarithmic { initial } => initial / ((iteration + 2) as f64).ln(),
+        }
+    }
+}
+
+/// The outcome of a simulated annealing run.
+#[derive(Debug, Clone)]
+pub struct AnnealingResult<G: Gene = bool> {
+    /// The fittest individual visited, with its fitness.
+    pub best: Individual<G>,
+    /// The individual the run ended on, with its fitness.
+    pub last: Individual<G>,
+    /// The number of fitness evaluations, the starting individual included.
+    pub evaluations: u64,
+    /// The number of moves accepted.
+    pub accepted: u64,
+}
+
+/// Simulated annealing on the same `Individual` encoding as the genetic algorithm, to
+/// compare both on one problem or to refine the individuals of a population. Fitness is
+/// maximized, like everywhere in this crate.
+#[derive(Debug, Clone)]
+pub struct SimulatedAnnealing {
+    schedule: CoolingSchedule,
+    iterations: u64,
+    rng: GaRng,
+}
+
+impl SimulatedAnnealing {
+    /// Creates an annealer with a random seed.
+    ///
+    /// # Arguments
+    /// * `schedule` - How the temperature falls.
+    /// * `iterations` - The number of moves tried per run.
+    pub fn new(schedule: CoolingSchedule, iterations: u64) -> SimulatedAnnealing {
+        SimulatedAnnealing::with_rng(schedule, iterations, GaRng::from_rng(&mut rand::rng()))
+    }
+
+    /// Creates an annealer whose runs can be reproduced.
+    ///
+    /// # Arguments
+    /// * `schedule` - How the temperature falls.
+    /// * `iterations` - The number of moves tried per run.
+    /// * `seed` - The seed of the random number generator.
+    pub fn seeded(schedule: CoolingSchedule, iterations: u64, seed: u64) -> SimulatedAnnealing {
+        SimulatedAnnealing::with_rng(schedule, iterations, GaRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(schedule: CoolingSchedule, iterations: u64, rng: GaRng) -> SimulatedAnnealing {
+        let valid = match schedule {
+            CoolingSchedule::Geometric { initial, factor } => initial > 0.0 && factor > 0.0 && factor <= 1.0,
+            CoolingSchedule::Linear { initial, end } => initial > 0.0 && end >= 0.0,
+            CoolingSchedule::Logarithmic { initial } => initial > 0.0,
+        };
+        if !valid {
+            panic!("invalid cooling schedule {:?}", schedule);
+        }
+        SimulatedAnnealing { schedule, iterations, rng }
+    }
+
+    pub fn get_schedule(&self) -> CoolingSchedule {
+        self.schedule
+    }
+
+    pub fn get_iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Anneals from the given individual, every move mutating one random gene with
+    /// `Gene::mutate`, e.g. flipping a bit.
+    ///
+    /// # Arguments
+    /// * `initial` - The starting point with at least one gene, its fitness is evaluated again.
+    /// * `fitness` - Returns the fitness of an individual.
+    pub fn run<G: Gene, F: Fn(&Individual<G>) -> u64>(&mut self, initial: Individual<G>, fitness: F) -> AnnealingResult<G> {
+        if initial.gene_length < 1 {
+            panic!("the gene length cannot be less than 1");
+        }
+        self.run_with(initial, fitness, |individual, rng| {
+            let index = rng.random_range(0..individual.gene_length);
+            let mutated = individual.genes[index].mutate(rng);
+            individual.genes.set(index, mutated);
+        })
+    }
+
+    /// Anneals from the given individual with a custom move, e.g. a swap for
+    /// permutations.
+    ///
+    /// # Arguments
+    /// * `initial` - The starting point, its fitness is evaluated again.
+    /// * `fitness` - Returns the fitness of an individual.
+    /// * `neighbor` - Changes a copy of the current individual into a neighbor.
+    pub fn run_with<G, F, N>(&mut self, initial: Individual<G>, fitness: F, mut neighbor: N) -> AnnealingResult<G>
+    where
+        G: Gene,
+        F: Fn(&Individual<G>) -> u64,
+        N: FnMut(&mut Individual<G>, &mut GaRng),
+    {
+        let mut current = initial;
+        current.fitness = fitness(&current);
+        let mut best = current.clone();
+        let mut candidate = current.clone();
+        let mut accepted = 0;
+        for iteration in 0..self.iterations {
+            candidate.genes.clone_from(&current.genes);
+            neighbor(&mut candidate, &mut self.rng);
+            if candidate.genes.len() != candidate.gene_length {
+                panic!("the neighbor move changed the gene length to {}", candidate.genes.len());
+            }
+            candidate.fitness = fitness(&candidate);
+            let accept = if candidate.fitness >= current.fitness {
+                true
+            } else {
+                let temperature = self.schedule.temperature(iteration, self.iterations);
+                let loss = (current.fitness - candidate.fitness) as f64;
+                temperature > 0.0 && self.rng.random_bool((-loss / temperature).exp())
+            };
+            if accept {
+                std::mem::swap(&mut current, &mut candidate);
+                accepted += 1;
+                if current.fitness > best.fitness {
+                    best.clone_from(&current);
+                }
+            }
+        }
+        AnnealingResult {
+            best,
+            last: current,
+            evaluations: self.iterations + 1,
+            accepted,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ones(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| gene).count() as u64
+    }
+
+    fn zeros(individual: &Individual) -> u64 {
+        individual.get_genes().iter().filter(|&&gene| !gene).count() as u64
+    }
+
+    #[test]
+    fn schedules_cool_as_documented() {
+        let geometric = CoolingSchedule::Geometric { initial: 100.0, factor: 0.5 };
+        assert_eq!(geometric.temperature(0, 10), 100.0);
+        assert_eq!(geometric.temperature(3, 10), 12.5);
+        let linear = CoolingSchedule::Linear { initial: 10.0, end: 2.0 };
+        assert_eq!(linear.temperature(0, 4), 10.0);
+        assert_eq!(linear.temperature(2, 4), 6.0);
+        assert_eq!(linear.temperature(4, 4), 2.0);
+        assert_eq!(linear.temperature(9, 4), 2.0);
+        let logarithmic = CoolingSchedule::Logarithmic { initial: 3.0 };
+        assert_eq!(logarithmic.temperature(0, 10), 3.0 / 2f64.ln());
+        assert!(logarithmic.temperature(100, 10) < logarithmic.temperature(10, 10));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid cooling schedule")]
+    fn a_factor_above_one_is_rejected() {
+        SimulatedAnnealing::new(CoolingSchedule::Geometric { initial: 1.0, factor: 1.5 }, 10);
+    }
+
+    #[test]
+    fn a_cold_run_never_accepts_a_worse_move() {
+        let mut annealer = SimulatedAnnealing::seeded(CoolingSchedule::Geometric { initial: 1e-9, factor: 0.9 }, 50, 1);
+        let result = annealer.run(Individual::from_genes(vec![false; 16]), zeros);
+        // every move from the optimum loses one zero
+        assert_eq!(result.accepted, 0);
+        assert_eq!(result.last.get_genes(), vec![false; 16]);
+        assert_eq!(result.best.get_fitness(), 16);
+        assert_eq!(result.evaluations, 51);
+    }
+
+    #[test]
+    fn a_hot_run_accepts_worse_moves() {
+        let mut annealer = SimulatedAnnealing::seeded(CoolingSchedule::Linear { initial: 1e9, end: 1e9 }, 50, 2);
+        let result = annealer.run(Individual::from_genes(vec![false; 16]), zeros);
+        assert!(result.accepted > 40);
+        assert!(result.last.get_fitness() < 16);
+        assert_eq!(result.best.get_fitness(), 16);
+    }
+
+    #[test]
+    fn annealing_solves_one_max() {
+        let mut annealer = SimulatedAnnealing::seeded(CoolingSchedule::Geometric { initial: 2.0, factor: 0.99 }, 2000, 3);
+        let result = annealer.run(Individual::from_genes(vec![false; 32]), ones);
+        assert_eq!(result.best.get_fitness(), 32);
+        assert!(result.best.get_fitness() >= result.last.get_fitness());
+    }
+
+    #[test]
+    #[should_panic(expected = "the gene length cannot be less than 1")]
+    fn an_empty_genome_is_rejected() {
+        SimulatedAnnealing::seeded(CoolingSchedule::Logarithmic { initial: 1.0 }, 10, 4).run(Individual::blank(), ones);
+    }
+}
@@ -0,0 +1,171 @@
+use rand::Rng;
+use std::collections::BTreeSet;
+
+use super::{rng::GaRng, Individual};
+
+/// A genome of `length` loci that stores only the loci differing from a default value,
+/// for very long and mostly uniform chromosomes such as large feature-selection masks.
+/// Memory use and crossover cost grow with the number of non-default loci instead of the
+/// gene length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGenome {
+    length: usize,
+    default: bool,
+    exceptions: BTreeSet<usize>,
+    fitness: u64,
+}
+
+impl SparseGenome {
+    /// Creates a genome with every locus set to the default value.
+    pub fn new(length: usize, default: bool) -> SparseGenome {
+        if length < 1 {
+            panic!("the gene length cannot be less than 1");
+        }
+        SparseGenome {
+            length,
+            default,
+            exceptions: BTreeSet::new(),
+            fitness: 0,
+        }
+    }
+
+    /// Converts a dense individual, keeping its fitness.
+    pub fn from_individual(individual: &Individual, default: bool) -> SparseGenome {
+        SparseGenome {
+            length: individual.gene_length,
+            default,
+            exceptions: (0..individual.gene_length).filter(|&i| individual.genes[i] != default).collect(),
+            fitness: individual.fitness,
+        }
+    }
+
+    /// Converts to a dense individual, keeping the fitness.
+    pub fn to_individual(&self) -> Individual {
+        let mut genes = vec![self.default; self.length];
+        for &index in &self.exceptions {
+            genes[index] = !self.default;
+        }
+        let mut individual = Individual::from_genes(genes);
+        individual.set_fitness(self.fitness);
+        individual
+    }
+
+    pub fn get_gene_length(&self) -> usize {
+        self.length
+    }
+
+    pub fn get_default(&self) -> bool {
+        self.default
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.check_index(index);
+        self.exceptions.contains(&index) != self.default
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        self.check_index(index);
+        if value == self.default {
+            self.exceptions.remove(&index);
+        } else {
+            self.exceptions.insert(index);
+        }
+    }
+
+    pub fn flip(&mut self, index: usize) {
+        let value = self.get(index);
+        self.set(index, !value);
+    }
+
+    /// Returns the indices of the loci that differ from the default, in ascending order,
+    /// e.g. the selected features of a selection mask with default `false`.
+    pub fn non_default(&self) -> impl Iterator<Item = usize> + '_ {
+        self.exceptions.iter().copied()
+    }
+
+    /// Returns the number of loci that differ from the default.
+    pub fn count_non_default(&self) -> usize {
+        self.exceptions.len()
+    }
+
+    pub fn set_fitness(&mut self, fitness: u64) {
+        self.fitness = fitness;
+    }
+
+    pub fn get_fitness(&self) -> u64 {
+        self.fitness
+    }
+
+    /// Flips every locus with the given probability. Instead of rolling every locus the
+    /// distance to the next flip is drawn from a geometric distribution, so the cost grows
+    /// with the number of flips rather than the gene length. The probability is a
+    /// fraction (0.0-1.0) because a whole percent is far too coarse for millions of loci.
+    ///
+    /// # Returns
+    /// The number of flipped loci.
+    pub fn mutate(&mut self, probability: f64, rng: &mut GaRng) -> usize {
+        if !(0.0..=1.0).contains(&probability) {
+            panic!("mutation probability must be between 0 and 1");
+        }
+        if probability == 0.0 {
+            return 0;
+        }
+        let mut flips = 0;
+        let mut index = 0;
+        loop {
+            if probability < 1.0 {
+                let roll: f64 = rng.random_range(f64::MIN_POSITIVE..1.0);
+                let skip = (roll.ln() / (1.0 - probability).ln()).floor();
+                if skip >= (self.length - index) as f64 {
+                    break;
+                }
+                index += skip as usize;
+            }
+            if index >= self.length {
+                break;
+            }
+            self.flip(index);
+            flips += 1;
+            index += 1;
+        }
+        flips
+    }
+
+    /// Creates a child by uniform crossover, each locus comes from a random parent.
+    /// Loci that are default in both parents stay default without being visited.
+    pub fn uniform_crossover(&self, other: &SparseGenome, rng: &mut GaRng) -> SparseGenome {
+        self.check_compatible(other);
+        let mut child = SparseGenome::new(self.length, self.default);
+        for &index in self.exceptions.union(&other.exceptions) {
+            let parent = if rng.random_bool(0.5) { self } else { other };
+            if parent.exceptions.contains(&index) {
+                child.exceptions.insert(index);
+            }
+        }
+        child
+    }
+
+    /// Creates a child with the loci before `point` from this genome and the rest from `other`.
+    pub fn single_point_crossover(&self, other: &SparseGenome, point: usize) -> SparseGenome {
+        self.check_compatible(other);
+        if point > self.length {
+            panic!("crossover point {} is outside the gene length {}", point, self.length);
+        }
+        let mut child = SparseGenome::new(self.length, self.default);
+        child.exceptions.extend(self.exceptions.range(..point));
+        child.exceptions.extend(other.exceptions.range(point..));
+        child
+    }
+
+    fn check_index(&self, index: usize) {
+        if index >= self.length {
+            panic!("gene index {} is outside the gene length {}", index, self.length);
+        }
+    }
+
+    fn check_compatible(&self, other: &SparseGenome) {
+        if self.length != other.length || self.default != other.default {
+            panic!("sparse genomes must have the same gene length and default to be recombined");
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::ops::Range;
+
+use super::{gene::Gene, Population};
+
+/// NEAT-style speciation settings. Every individual joins the first species whose
+/// representative it differs from in fewer than `threshold` genes, or founds a new one.
+/// Fitness is shared within a species, each member's fitness is divided by the species
+/// size, so a large species can't take over the population. Species younger than
+/// `protected_generations` don't share, which gives a new niche time to improve before
+/// it competes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Speciation {
+    threshold: usize,
+    protected_generations: u64,
+}
+
+impl Speciation {
+    /// Creates new speciation settings.
+    ///
+    /// # Arguments
+    /// * `threshold` - The compatibility threshold in differing genes, 1 or more.
+    /// * `protected_generations` - How many generations a new species keeps its unshared fitness.
+    pub fn new(threshold: usize, protected_generations: u64) -> Speciation {
+        if threshold < 1 {
+            panic!("compatibility threshold must be 1 or more");
+        }
+        Speciation {
+            threshold,
+            protected_generations,
+        }
+    }
+
+    pub fn get_threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn get_protected_generations(&self) -> u64 {
+        self.protected_generations
+    }
+}
+
+/// A cluster of similar individuals, kept from one generation to the next.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Species<G: Gene> {
+    pub(crate) id: u64,
+    pub(crate) representative: Vec<G>,
+    pub(crate) born: u64,
+    pub(crate) size: usize,
+    pub(crate) best_fitness: u64,
+}
+
+impl<G: Gene> Species<G> {
+    /// Returns the id of the species, unique within its population.
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the genes new individuals are compared to, those of the fittest member at
+    /// the last speciation.
+    pub fn get_representative(&self) -> &[G] {
+        &self.representative
+    }
+
+    /// Returns the generation the species was founded in.
+    pub fn get_born(&self) -> u64 {
+        self.born
+    }
+
+    /// Returns the number of members at the last speciation.
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the best fitness of a member at the last speciation, before sharing.
+    pub fn get_best_fitness(&self) -> u64 {
+        self.best_fitness
+    }
+}
+
+/// The summary of one species in `GenerationStats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeciesStats {
+    pub id: u64,
+    pub size: usize,
+    pub best_fitness: u64,
+    /// The number of generations since the species was founded.
+    pub age: u64,
+}
+
+impl<G: Gene> Population<G> {
+    /// Enables speciation. After every evaluation, and after the constraints and fitness
+    /// sharing, the individuals are assigned to species and their fitness is shared within
+    /// their species. Existing species are kept when the settings change.
+    ///
+    /// # Arguments
+    /// * `speciation` - The compatibility threshold and the protection of new species.
+    pub fn set_speciation(&mut self, speciation: Speciation) {
+        self.speciation = Some(speciation);
+    }
+
+    /// Disables speciation and forgets all species.
+    pub fn clear_speciation(&mut self) {
+        self.speciation = None;
+        self.species.clear();
+    }
+
+    pub fn get_speciation(&self) -> Option<Speciation> {
+        self.speciation
+    }
+
+    /// Returns the species of the last speciation, oldest first.
+    pub fn get_species(&self) -> &[Species<G>] {
+        &self.species
+    }
+
+    /// Returns the summary of every species, oldest first, empty without speciation.
+    pub fn species_stats(&self) -> Vec<SpeciesStats> {
+        self
+            .species
+            .iter()
+            .map(|species| SpeciesStats {
+                id: species.id,
+                size: species.size,
+                best_fitness: species.best_fitness,
+                age: self.generation.saturating_sub(species.born),
+            })
+            .collect()
+    }
+
+    /// Assigns the whole population to species, founding new ones and dropping empty
+    /// ones, and shares the fitness of a range of freshly evaluated individuals.
+    pub(crate) fn apply_speciation_to(&mut self, range: Range<usize>) {
+        let speciation = match self.speciation {
+            Some(speciation) => speciation,
+            None => return,
+        };
+        let mut members: Vec<Vec<usize>> = vec![vec![]; self.species.len()];
+        for (index, individual) in self.individuals.iter().enumerate() {
+            let compatible = self.species.iter().position(|species| {
+                let extra = species.representative.len().abs_diff(individual.genes.len());
+                let distance = species.representative.iter().zip(&individual.genes).filter(|(a, b)| a != b).count() + extra;
+                distance < speciation.threshold
+            });
+            match compatible {
+                Some(species) => members[species].push(index),
+                None => {
+                    self.species.push(Species {
+                        id: self.next_species_id,
+                        representative: individual.genes.to_vec(),
+                        born: self.generation,
+                        size: 0,
+                        best_fitness: 0,
+                    });
+                    self.next_species_id += 1;
+                    members.push(vec![index]);
+                }
+            }
+        }
+
+        for (species, members) in self.species.iter_mut().zip(&members) {
+            species.size = members.len();
+            let fittest = members.iter().copied().max_by_key(|&index| self.individuals[index].fitness);
+            if let Some(fittest) = fittest {
+                species.best_fitness = self.individuals[fittest].fitness;
+                species.representative = self.individuals[fittest].genes.to_vec();
+            }
+            if self.generation.saturating_sub(species.born) < speciation.protected_generations {
+                continue;
+            }
+            for &index in members.iter().filter(|index| range.contains(index)) {
+                let individual = &mut self.individuals[index];
+                individual.fitness = (individual.fitness as f64 / members.len() as f64).round() as u64;
+            }
+        }
+        self.species.retain(|species| species.size > 0);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::super::{init_population_seeded, CrossoverType, Individual};
+    use super::*;
+
+    fn population_of(genomes: &[(&str, u64)]) -> Population {
+        let mut population = init_population_seeded(genomes[0].0.len(), genomes.len(), 2, 5, false, CrossoverType::Bit, 1);
+        population.individuals = genomes
+            .iter()
+            .map(|(genes, fitness)| {
+                let mut individual = Individual::from_genes(genes.chars().map(|gene| gene == '1').collect());
+                individual.set_fitness(*fitness);
+                individual
+            })
+            .collect();
+        population
+    }
+
+    fn fitness(population: &Population) -> Vec<u64> {
+        population.individuals.iter().map(|individual| individual.get_fitness()).collect()
+    }
+
+    #[test]
+    fn individuals_join_the_first_compatible_species() {
+        let mut population = population_of(&[("00000000", 10), ("00000001", 20), ("11111111", 30), ("11111110", 40)]);
+        population.set_speciation(Speciation::new(2, 0));
+        population.apply_speciation_to(0..4);
+        let species = population.get_species();
+        assert_eq!(species.len(), 2);
+        assert_eq!((species[0].get_id(), species[0].get_size(), species[0].get_best_fitness()), (0, 2, 20));
+        assert_eq!((species[1].get_id(), species[1].get_size(), species[1].get_best_fitness()), (1, 2, 40));
+        // the representative becomes the fittest member
+        assert_eq!(species[1].get_representative(), population.individuals[3].get_genes().as_slice());
+        assert_eq!(fitness(&population), vec![5, 10, 15, 20]);
+    }
+
+    #[test]
+    fn only_the_evaluated_range_is_shared() {
+        let mut population = population_of(&[("0000", 10), ("0000", 20), ("1111", 30), ("1111", 40)]);
+        population.set_speciation(Speciation::new(1, 0));
+        population.apply_speciation_to(2..4);
+        assert_eq!(fitness(&population), vec![10, 20, 15, 20]);
+    }
+
+    #[test]
+    fn new_species_are_protected_from_sharing() {
+        let mut population = population_of(&[("0000", 10), ("0001", 20), ("1111", 30)]);
+        population.set_speciation(Speciation::new(2, 3));
+        population.apply_speciation_to(0..3);
+        assert_eq!(fitness(&population), vec![10, 20, 30]);
+        population.generation = 3;
+        population.individuals[2] = Individual::from_genes(vec![false, true, true, false]);
+        population.individuals[2].set_fitness(30);
+        population.apply_speciation_to(0..3);
+        // the first species is old enough to share now, the one founded in generation 3 isnt
+        assert_eq!(fitness(&population), vec![5, 10, 30]);
+        let stats = population.species_stats();
+        assert_eq!(stats.iter().map(|species| (species.id, species.age)).collect::<Vec<_>>(), vec![(0, 3), (2, 0)]);
+    }
+}
@@ -0,0 +1,74 @@
+use super::{gene::Gene, speciation::SpeciesStats, Population};
+
+/// Fitness and diversity statistics of one evaluated generation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub best_fitness: u64,
+    pub worst_fitness: u64,
+    pub mean_fitness: f64,
+    /// The middle fitness, the mean of the two middle ones for an even population size.
+    pub median_fitness: f64,
+    /// The population standard deviation of the fitness.
+    pub fitness_std_dev: f64,
+    /// The average Hamming distance between all pairs of individuals, falling towards 0
+    /// as the population converges. `None` in the statistics given to observers unless
+    /// one of them asks for it with `Observer::needs_diversity`.
+    pub diversity: Option<f64>,
+    /// The fitness cache hits since the cache was set, 0 without a fitness cache.
+    pub cache_hits: u64,
+    /// The fitness cache misses since the cache was set, 0 without a fitness cache.
+    pub cache_misses: u64,
+    /// Every species with its size and best fitness, oldest first, empty without speciation.
+    pub species: Vec<SpeciesStats>,
+}
+
+impl<G: Gene> Population<G> {
+    /// Computes the fitness and diversity statistics of the current, evaluated population.
+    /// The diversity compares every pair of individuals, so it costs O(n² · gene length).
+    pub fn stats(&self) -> GenerationStats {
+        self.stats_with(true)
+    }
+
+    /// Computes the statistics, the diversity only if `diversity` is set.
+    pub(crate) fn stats_with(&self, diversity: bool) -> GenerationStats {
+        let mut fitness: Vec<u64> = self.individuals.iter().map(|individual| individual.fitness).collect();
+        fitness.sort();
+        let count = fitness.len().max(1) as f64;
+        let mean_fitness = fitness.iter().map(|&fitness| fitness as f64).sum::<f64>() / count;
+        let variance = fitness.iter().map(|&fitness| (fitness as f64 - mean_fitness).powi(2)).sum::<f64>() / count;
+        let median_fitness = match fitness.len() {
+            0 => 0.0,
+            n if n % 2 == 0 => (fitness[n / 2 - 1] as f64 + fitness[n / 2] as f64) / 2.0,
+            n => fitness[n / 2] as f64,
+        };
+
+        let (cache_hits, cache_misses) = self.get_fitness_cache_counts();
+        GenerationStats {
+            generation: self.generation,
+            best_fitness: fitness.last().copied().unwrap_or(0),
+            worst_fitness: fitness.first().copied().unwrap_or(0),
+            mean_fitness,
+            median_fitness,
+            fitness_std_dev: variance.sqrt(),
+            diversity: if diversity { Some(self.pairwise_diversity()) } else { None },
+            cache_hits,
+            cache_misses,
+            species: self.species_stats(),
+        }
+    }
+
+    /// Returns the average Hamming distance between all pairs of individuals.
+    fn pairwise_diversity(&self) -> f64 {
+        let mut distance_sum = 0;
+        let mut pairs = 0;
+        for (i, a) in self.individuals.iter().enumerate() {
+            for b in &self.individuals[i + 1..] {
+                distance_sum += a.hamming_distance(b);
+                pairs += 1;
+            }
+        }
+        if pairs > 0 { distance_sum as f64 / pairs as f64 } else { 0.0 }
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use super::{
+    metrics::{MetricsCollector, RunMetrics},
+    Population,
+};
+
+/// How many generations of history the status endpoint keeps.
+const HISTORY_LENGTH: usize = 100;
+
+/// How long a client may take to send its request line, so a silent one can't block
+/// the server.
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The longest request line read, in bytes.
+const MAX_REQUEST_LINE: u64 = 8192;
+
+const STATUS_PAGE: &str = "<!DOCTYPE html>
+<html>
+<head><title>genetic algorithm status</title></head>
+<body style=\"font-family: monospace\">
+<h1>genetic algorithm status</h1>
+<pre id=\"status\">loading...</pre>
+<script>
+async function refresh() {
+    const response = await fetch('/status');
+    const status = await response.json();
+    document.getElementById('status').textContent = JSON.stringify(status, null, 2);
+}
+refresh();
+setInterval(refresh, 1000);
+</script>
+</body>
+</html>";
+
+/// The state shared between the run and the server thread.
+#[derive(Debug, Default)]
+struct RunStatus {
+    history: VecDeque<RunMetrics>,
+}
+
+impl RunStatus {
+    fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .history
+            .iter()
+            .map(|status| {
+                format!(
+                    "{{\"generation\":{},\"best_fitness\":{},\"mean_fitness\":{},\"diversity\":{},\"evaluations_per_second\":{},\"timeouts_total\":{}}}",
+                    status.generation,
+                    status.best_fitness,
+                    json_number(status.mean_fitness),
+                    json_number(status.diversity),
+                    json_number(status.evaluations_per_second),
+                    status.timeouts_total
+                )
+            })
+            .collect();
+        let current = entries.last().cloned().unwrap_or_else(|| "null".to_string());
+        format!("{{\"current\":{},\"history\":[{}]}}", current, entries.join(","))
+    }
+}
+
+/// Formats a number for JSON, which has no NaN or infinity, as `null` if it isn't finite.
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+/// A tiny HTTP server that shows the progress of a run, so long headless runs can be
+/// checked from a browser. `GET /status` returns the current generation, best and mean
+/// fitness, diversity and recent history as JSON, `GET /` a page that displays it and
+/// `GET /metrics` the latest metrics in the Prometheus text format.
+///
+/// The server runs on its own thread until it is dropped.
+pub struct StatusServer {
+    status: Arc<Mutex<RunStatus>>,
+    collector: Mutex<MetricsCollector>,
+    address: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl StatusServer {
+    /// Starts serving on the given address, e.g. `"127.0.0.1:8080"`.
+    /// Use port 0 to let the operating system pick a free port, see `get_address`.
+    pub fn start<A: ToSocketAddrs>(address: A) -> io::Result<StatusServer> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let status = Arc::new(Mutex::new(RunStatus::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let status = Arc::clone(&status);
+            let stopped = Arc::clone(&stopped);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        // a misbehaving client must not take the server down
+                        let _ = handle_connection(stream, &status);
+                    }
+                }
+            })
+        };
+        Ok(StatusServer {
+            status,
+            collector: Mutex::new(MetricsCollector::new()),
+            address,
+            stopped,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn get_address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Records the state of an evaluated population, call once per generation.
+    /// Every individual is counted as one fitness evaluation.
+    pub fn update(&self, population: &Population) {
+        let metrics = self
+            .collector
+            .lock()
+            .unwrap()
+            .observe(population, population.individuals.len() as u64);
+        let mut status = self.status.lock().unwrap();
+        if status.history.len() == HISTORY_LENGTH {
+            status.history.pop_front();
+        }
+        status.history.push_back(metrics);
+    }
+}
+
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // wake up the blocking accept so the thread sees the stop flag
+        let _ = TcpStream::connect(self.address);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, status: &Mutex<RunStatus>) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new((&stream).take(MAX_REQUEST_LINE)).read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let (code, content_type, body) = match path {
+        "/" | "/index.html" => ("200 OK", "text/html", STATUS_PAGE.to_string()),
+        "/status" => ("200 OK", "application/json", status.lock().unwrap().to_json()),
+        "/metrics" => {
+            let metrics = status.lock().unwrap().history.back().map(|metrics| metrics.to_prometheus());
+            ("200 OK", "text/plain; version=0.0.4", metrics.unwrap_or_default())
+        }
+        _ => ("404 Not Found", "text/plain", "not found".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(generation: u64, mean_fitness: f64, evaluations_per_second: f64) -> RunMetrics {
+        RunMetrics {
+            generation,
+            best_fitness: 7,
+            mean_fitness,
+            diversity: 0.5,
+            generation_seconds: 0.0,
+            evaluations_per_second,
+            evaluations_total: 10,
+            timeouts_total: 1,
+        }
+    }
+
+    #[test]
+    fn empty_status_has_no_current_entry() {
+        assert_eq!(RunStatus::default().to_json(), "{\"current\":null,\"history\":[]}");
+    }
+
+    #[test]
+    fn status_json_lists_the_history() {
+        let mut status = RunStatus::default();
+        status.history.push_back(metrics(1, 2.5, 100.0));
+        status.history.push_back(metrics(2, 3.0, 50.0));
+        let second = "{\"generation\":2,\"best_fitness\":7,\"mean_fitness\":3,\"diversity\":0.5,\"evaluations_per_second\":50,\"timeouts_total\":1}";
+        assert_eq!(
+            status.to_json(),
+            format!(
+                "{{\"current\":{},\"history\":[{},{}]}}",
+                second,
+                "{\"generation\":1,\"best_fitness\":7,\"mean_fitness\":2.5,\"diversity\":0.5,\"evaluations_per_second\":100,\"timeouts_total\":1}",
+                second
+            )
+        );
+    }
+
+    #[test]
+    fn non_finite_values_are_null() {
+        let mut status = RunStatus::default();
+        status.history.push_back(metrics(0, f64::NAN, f64::INFINITY));
+        let json = status.to_json();
+        assert!(json.contains("\"mean_fitness\":null"));
+        assert!(json.contains("\"evaluations_per_second\":null"));
+        assert!(!json.contains("NaN") && !json.contains("inf"));
+    }
+}
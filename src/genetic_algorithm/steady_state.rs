@@ -0,0 +1,53 @@
+use super::{gene::Gene, Population};
+
+impl<G: Gene> Population<G> {
+    /// Switches `next_generation` to steady-state evolution: each step only replaces the
+    /// `replacement_count` worst individuals with children of the selected parents and
+    /// keeps all others with their fitness. Evaluate the children alone with
+    /// `evaluate_last(replacement_count, ..)`, so no evaluated individual is thrown away.
+    /// Every step counts as a generation. Elitism is implied, the best always survive.
+    ///
+    /// # Arguments
+    /// * `replacement_count` - The number of children per step, 1 or more and at most the population size.
+    pub fn set_steady_state(&mut self, replacement_count: usize) {
+        self.check_replacement_mode("steady-state mode");
+        if replacement_count < 1 || replacement_count > self.population_size {
+            panic!("steady-state replacement count must be between 1 and the population size");
+        }
+        self.steady_state = Some(replacement_count);
+        self.replace_oldest = false;
+    }
+
+    /// Goes back to replacing the whole population every generation.
+    pub fn clear_steady_state(&mut self) {
+        self.steady_state = None;
+        self.replace_oldest = false;
+    }
+
+    /// Returns the number of individuals replaced per steady-state step, `None` in
+    /// generational mode.
+    pub fn get_steady_state(&self) -> Option<usize> {
+        self.steady_state
+    }
+
+    /// Replaces the worst individuals of the sorted population, or the oldest with
+    /// age-based replacement, with children of parents selected from the whole
+    /// population. The children are appended at the end.
+    pub(crate) fn steady_state_step(&mut self, replacement_count: usize) {
+        let count = replacement_count.min(self.individuals.len());
+        let parents = self.select_parent_indices().into_iter().map(|index| self.individuals[index].clone()).collect();
+        // the replaced individuals are recycled into the children
+        let spares = if self.replace_oldest {
+            self.remove_oldest(count)
+        } else {
+            self.individuals.split_off(self.individuals.len() - count)
+        };
+        let (_, mut children) = self.breed_children(parents, count, spares);
+        if self.replace_oldest {
+            for child in children.iter_mut() {
+                child.age = 0;
+            }
+        }
+        self.individuals.append(&mut children);
+    }
+}
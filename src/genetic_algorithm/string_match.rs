@@ -0,0 +1,178 @@
+use super::{
+    decoding::{BitOrder, ByteOrder},
+    Individual,
+};
+
+/// The closeness score of one byte, 255 for an exact match.
+const BYTE_SCORE: u64 = 255;
+/// Codepoints are encoded as 4 big-endian bytes (UTF-32).
+const CODEPOINT_SCORE: u64 = 4 * BYTE_SCORE;
+
+/// The unit a target string is compared in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextUnit {
+    /// Every UTF-8 byte is a unit of 8 genes. Non-ASCII characters are split over several
+    /// bytes, so a partially matching genome can decode to invalid UTF-8.
+    Byte,
+    /// Every Unicode scalar value is a unit of 32 genes (UTF-32), so every character of the
+    /// target counts once no matter how many UTF-8 bytes it needs.
+    Codepoint,
+    /// Like `Codepoint`, but the score is weighted per user-perceived character: a base
+    /// character together with its combining marks, variation selectors and zero width
+    /// joiner sequences counts as much as any single character. This is an approximation
+    /// of Unicode grapheme clusters that covers accents and emoji sequences.
+    Grapheme,
+}
+
+/// A target string for string matching problems that knows how to size genomes
+/// for it and how to score an individual against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringTarget {
+    target: String,
+    unit: TextUnit,
+    /// The codepoints of the target grouped per scored unit, bytes for `TextUnit::Byte`.
+    units: Vec<Vec<u32>>,
+}
+
+impl StringTarget {
+    /// Creates a target from a string, compared in the given unit.
+    ///
+    /// # Arguments
+    /// * `target` - The string to match, must not be empty.
+    /// * `unit` - Whether bytes, codepoints or graphemes are compared.
+    pub fn new(target: &str, unit: TextUnit) -> StringTarget {
+        if target.is_empty() {
+            panic!("the target string cannot be empty");
+        }
+        let units = match unit {
+            TextUnit::Byte => target.bytes().map(|byte| vec![byte as u32]).collect(),
+            TextUnit::Codepoint => target.chars().map(|c| vec![c as u32]).collect(),
+            TextUnit::Grapheme => split_graphemes(target),
+        };
+        StringTarget {
+            target: target.to_string(),
+            unit,
+            units,
+        }
+    }
+
+    pub fn get_target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn get_unit(&self) -> TextUnit {
+        self.unit
+    }
+
+    /// Returns the number of scored units of the target.
+    pub fn get_unit_count(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Returns the gene length individuals need to represent the target.
+    pub fn get_gene_length(&self) -> usize {
+        match self.unit {
+            TextUnit::Byte => self.units.len() * 8,
+            _ => self.codepoint_count() * 32,
+        }
+    }
+
+    /// Returns the fitness of a perfect match.
+    pub fn get_goal_fitness(&self) -> u64 {
+        match self.unit {
+            TextUnit::Byte => self.units.len() as u64 * BYTE_SCORE,
+            _ => self.units.len() as u64 * CODEPOINT_SCORE,
+        }
+    }
+
+    /// Scores how close an individual is to the target, higher is closer and
+    /// `get_goal_fitness` is an exact match.
+    ///
+    /// # Arguments
+    /// * `individual` - An individual with `get_gene_length` genes.
+    pub fn fitness(&self, individual: &Individual) -> u64 {
+        if individual.gene_length != self.get_gene_length() {
+            panic!("gene length {} does not match the target's {}", individual.gene_length, self.get_gene_length());
+        }
+        if self.unit == TextUnit::Byte {
+            let bytes = individual.get_genes_as_decimal_bytes();
+            return self
+                .units
+                .iter()
+                .zip(bytes.iter())
+                .map(|(unit, &byte)| BYTE_SCORE - (unit[0] as i64 - byte as i64).unsigned_abs())
+                .sum();
+        }
+
+        let words = individual.get_genes_as_words(4, BitOrder::MsbFirst, ByteOrder::BigEndian);
+        let mut words = words.iter();
+        let mut fitness = 0;
+        for unit in self.units.iter() {
+            let score: u64 = unit
+                .iter()
+                .map(|&target| codepoint_closeness(target, *words.next().unwrap() as u32))
+                .sum();
+            // every grapheme is worth one codepoint no matter how many it is made of
+            fitness += score / unit.len() as u64;
+        }
+        fitness
+    }
+
+    /// Decodes an individual to text, invalid sequences become `U+FFFD`.
+    ///
+    /// # Arguments
+    /// * `individual` - An individual with `get_gene_length` genes.
+    pub fn decode(&self, individual: &Individual) -> String {
+        match self.unit {
+            TextUnit::Byte => String::from_utf8_lossy(&individual.get_genes_as_decimal_bytes()).into_owned(),
+            _ => individual
+                .get_genes_as_words(4, BitOrder::MsbFirst, ByteOrder::BigEndian)
+                .iter()
+                .map(|&word| char::from_u32(word as u32).unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect(),
+        }
+    }
+
+    fn codepoint_count(&self) -> usize {
+        self.units.iter().map(|unit| unit.len()).sum()
+    }
+}
+
+/// Scores two codepoints byte by byte on their UTF-32 encoding.
+fn codepoint_closeness(target: u32, guess: u32) -> u64 {
+    target
+        .to_be_bytes()
+        .iter()
+        .zip(guess.to_be_bytes().iter())
+        .map(|(&a, &b)| BYTE_SCORE - (a as i64 - b as i64).unsigned_abs())
+        .sum()
+}
+
+/// Returns true for codepoints that attach to the previous character.
+fn is_extending(c: char) -> bool {
+    matches!(c as u32,
+        0x0300..=0x036F // combining diacritical marks
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200C..=0x200D // zero width non-joiner and joiner
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F // variation selectors
+        | 0xFE20..=0xFE2F
+        | 0x1F3FB..=0x1F3FF // emoji skin tone modifiers
+        | 0xE0020..=0xE007F // tag characters
+    )
+}
+
+/// Splits a string into approximate grapheme clusters of codepoints.
+fn split_graphemes(text: &str) -> Vec<Vec<u32>> {
+    let mut graphemes: Vec<Vec<u32>> = vec![];
+    let mut after_joiner = false;
+    for c in text.chars() {
+        match graphemes.last_mut() {
+            Some(last) if is_extending(c) || after_joiner => last.push(c as u32),
+            _ => graphemes.push(vec![c as u32]),
+        }
+        after_joiner = c == '\u{200D}';
+    }
+    graphemes
+}
@@ -0,0 +1,219 @@
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use std::fmt;
+
+use super::{decoding::BinaryEncoding, rng::GaRng, Individual};
+
+/// A user-defined operator to validate. Every operator receives the random number
+/// generator it must draw from, so it can be checked for determinism.
+pub enum Operator<'a> {
+    /// Creates one child from the given parents.
+    Crossover(&'a dyn Fn(&[Individual], &mut GaRng) -> Individual),
+    /// Mutates an individual in place.
+    Mutation(&'a dyn Fn(&mut Individual, &mut GaRng)),
+    /// Picks parents from a population, returning their indices.
+    Selection(&'a dyn Fn(&[Individual], &mut GaRng) -> Vec<usize>),
+}
+
+/// The genome layout the test individuals are built with and the operator must keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenomeLayout {
+    /// Any bit string.
+    Bits,
+    /// Groups of `bits` that each hold a value of at most `max`.
+    Bounded { bits: usize, max: u64 },
+    /// Groups of `bits` that together hold a permutation of `0..gene_length / bits`.
+    Permutation { bits: usize },
+}
+
+/// Settings of an operator validation run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationSettings {
+    pub gene_length: usize,
+    pub layout: GenomeLayout,
+    /// The number of parents handed to a crossover and individuals handed to a selection.
+    pub population_size: usize,
+    /// The number of random test cases.
+    pub trials: usize,
+    /// The seed of the first test case, each test case uses its own seed.
+    pub seed: u64,
+}
+
+/// An invariant a well-behaved operator keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invariant {
+    GeneLength,
+    Bounds,
+    Permutation,
+    /// The same seed must give the same result.
+    Determinism,
+    /// Selected indices must point into the population.
+    SelectionIndex,
+}
+
+/// An invariant an operator broke in one test case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub trial: usize,
+    pub invariant: Invariant,
+    pub detail: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "trial {}: {:?} violated, {}", self.trial, self.invariant, self.detail)
+    }
+}
+
+/// The result of `validate_operator`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub trials: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// Returns true if no invariant was broken.
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Stress-tests a user-defined operator on random individuals of the given layout and
+/// reports every broken invariant: gene length preserved, values within bounds,
+/// permutations still permutations, selected indices in range and identical results
+/// for identical seeds.
+///
+/// # Arguments
+/// * `operator` - The operator to test.
+/// * `settings` - The test settings.
+///
+/// # Returns
+/// A report of all violations found.
+pub fn validate_operator(operator: &Operator, settings: &ValidationSettings) -> ValidationReport {
+    if settings.gene_length < 1 {
+        panic!("the gene length cannot be less than 1");
+    }
+    if settings.population_size < 1 {
+        panic!("population size must be 1 or more");
+    }
+    match settings.layout {
+        GenomeLayout::Bits => {}
+        GenomeLayout::Bounded { bits, .. } | GenomeLayout::Permutation { bits } => {
+            if !(1..=64).contains(&bits) || !settings.gene_length.is_multiple_of(bits) {
+                panic!("the gene length must be a multiple of the group size");
+            }
+        }
+    }
+    if let GenomeLayout::Permutation { bits } = settings.layout {
+        if bits < 64 && (settings.gene_length / bits) as u64 > 1 << bits {
+            panic!("a permutation of {} values does not fit in {} bits", settings.gene_length / bits, bits);
+        }
+    }
+    let mut violations = vec![];
+    for trial in 0..settings.trials {
+        let seed = settings.seed.wrapping_add(trial as u64);
+        let individuals = test_individuals(settings, seed);
+        let mut violate = |invariant: Invariant, detail: String| {
+            violations.push(Violation { trial, invariant, detail });
+        };
+        match operator {
+            Operator::Crossover(crossover) => {
+                let child = crossover(&individuals, &mut GaRng::seed_from_u64(seed));
+                let again = crossover(&individuals, &mut GaRng::seed_from_u64(seed));
+                check_genome(&child, settings, &mut violate);
+                if child.genes != again.genes {
+                    violate(Invariant::Determinism, "the same seed gave different children".to_string());
+                }
+            }
+            Operator::Mutation(mutation) => {
+                let mut mutant = individuals[0].clone();
+                let mut again = individuals[0].clone();
+                mutation(&mut mutant, &mut GaRng::seed_from_u64(seed));
+                mutation(&mut again, &mut GaRng::seed_from_u64(seed));
+                check_genome(&mutant, settings, &mut violate);
+                if mutant.genes != again.genes {
+                    violate(Invariant::Determinism, "the same seed gave different mutants".to_string());
+                }
+            }
+            Operator::Selection(selection) => {
+                let selected = selection(&individuals, &mut GaRng::seed_from_u64(seed));
+                let again = selection(&individuals, &mut GaRng::seed_from_u64(seed));
+                if let Some(index) = selected.iter().find(|&&index| index >= individuals.len()) {
+                    violate(
+                        Invariant::SelectionIndex,
+                        format!("selected index {} of a population of {}", index, individuals.len()),
+                    );
+                }
+                if selected != again {
+                    violate(Invariant::Determinism, "the same seed gave different selections".to_string());
+                }
+            }
+        }
+    }
+    ValidationReport {
+        trials: settings.trials,
+        violations,
+    }
+}
+
+/// Creates random individuals with random fitness that follow the layout.
+fn test_individuals(settings: &ValidationSettings, seed: u64) -> Vec<Individual> {
+    let mut rng = GaRng::seed_from_u64(seed);
+    (0..settings.population_size)
+        .map(|_| {
+            let genes = (0..settings.gene_length).map(|_| rng.random_bool(0.5)).collect();
+            let mut individual = Individual::from_genes(genes);
+            match settings.layout {
+                GenomeLayout::Bits => {}
+                GenomeLayout::Bounded { bits, max } => {
+                    for start in (0..settings.gene_length).step_by(bits) {
+                        let value = rng.random_range(0..=max.min(u64::MAX >> (64 - bits)));
+                        individual.write_unsigned(start, bits, BinaryEncoding::Standard, value);
+                    }
+                }
+                GenomeLayout::Permutation { bits } => {
+                    let mut order: Vec<u64> = (0..(settings.gene_length / bits) as u64).collect();
+                    order.shuffle(&mut rng);
+                    for (i, value) in order.into_iter().enumerate() {
+                        individual.write_unsigned(i * bits, bits, BinaryEncoding::Standard, value);
+                    }
+                }
+            }
+            individual.set_fitness(rng.random_range(0..1000));
+            individual
+        })
+        .collect()
+}
+
+fn check_genome(individual: &Individual, settings: &ValidationSettings, violate: &mut impl FnMut(Invariant, String)) {
+    if individual.genes.len() != settings.gene_length || individual.gene_length != settings.gene_length {
+        violate(
+            Invariant::GeneLength,
+            format!("expected {} genes, got {}", settings.gene_length, individual.genes.len()),
+        );
+        return;
+    }
+    match settings.layout {
+        GenomeLayout::Bits => {}
+        GenomeLayout::Bounded { bits, max } => {
+            for start in (0..settings.gene_length).step_by(bits) {
+                let value = individual.read_unsigned(start, bits, BinaryEncoding::Standard);
+                if value > max {
+                    violate(Invariant::Bounds, format!("value {} at gene {} exceeds {}", value, start, max));
+                }
+            }
+        }
+        GenomeLayout::Permutation { bits } => {
+            let count = settings.gene_length / bits;
+            let mut seen = vec![false; count];
+            for start in (0..settings.gene_length).step_by(bits) {
+                let value = individual.read_unsigned(start, bits, BinaryEncoding::Standard) as usize;
+                if value >= count || seen[value] {
+                    violate(Invariant::Permutation, format!("value {} at gene {} breaks the permutation", value, start));
+                    return;
+                }
+                seen[value] = true;
+            }
+        }
+    }
+}
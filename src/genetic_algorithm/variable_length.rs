@@ -0,0 +1,255 @@
+use rand::Rng;
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+use super::{gene::Gene, rng::GaRng, Individual, Population};
+
+/// Variable-length chromosome settings, for genomes of unknown size such as rule sets or
+/// network topologies. Genes come in blocks, e.g. one rule each, and lengths are always
+/// a whole number of blocks. Crossover cuts each parent at its own block boundary and
+/// splices the head of one to the tail of the other (cut and splice), so a child can be
+/// longer or shorter than both; mutation can also insert a random block or delete one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariableLength {
+    min: usize,
+    max: usize,
+    block: usize,
+    insertion: f64,
+    deletion: f64,
+}
+
+impl VariableLength {
+    /// Creates settings with single-gene blocks and a probability of 0.1 for both an
+    /// insertion and a deletion per child.
+    ///
+    /// # Arguments
+    /// * `min` - The smallest gene length, 1 or more.
+    /// * `max` - The largest gene length.
+    pub fn new(min: usize, max: usize) -> VariableLength {
+        if min < 1 || min > max {
+            panic!("gene length bounds must satisfy 1 <= min <= max");
+        }
+        VariableLength {
+            min,
+            max,
+            block: 1,
+            insertion: 0.1,
+            deletion: 0.1,
+        }
+    }
+
+    /// Sets the number of genes per block, the bounds have to be multiples of it.
+    pub fn block_size(mut self, block: usize) -> VariableLength {
+        if block < 1 || !self.min.is_multiple_of(block) || !self.max.is_multiple_of(block) {
+            panic!("the gene length bounds must be multiples of the block size");
+        }
+        self.block = block;
+        self
+    }
+
+    /// Sets the probability (0.0-1.0) that a child gets a random block inserted.
+    pub fn insertion(mut self, probability: f64) -> VariableLength {
+        if !(0.0..=1.0).contains(&probability) {
+            panic!("insertion probability must be between 0 and 1");
+        }
+        self.insertion = probability;
+        self
+    }
+
+    /// Sets the probability (0.0-1.0) that a child gets a random block deleted.
+    pub fn deletion(mut self, probability: f64) -> VariableLength {
+        if !(0.0..=1.0).contains(&probability) {
+            panic!("deletion probability must be between 0 and 1");
+        }
+        self.deletion = probability;
+        self
+    }
+
+    /// Returns the smallest and the largest gene length.
+    pub fn get_bounds(&self) -> (usize, usize) {
+        (self.min, self.max)
+    }
+
+    pub fn get_block_size(&self) -> usize {
+        self.block
+    }
+
+    pub fn get_insertion(&self) -> f64 {
+        self.insertion
+    }
+
+    pub fn get_deletion(&self) -> f64 {
+        self.deletion
+    }
+
+    /// Returns true if a gene length is within the bounds and a whole number of blocks.
+    pub fn allows(&self, gene_length: usize) -> bool {
+        (self.min..=self.max).contains(&gene_length) && gene_length.is_multiple_of(self.block)
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Lets the gene length of children grow and shrink within the bounds. Breeding uses
+    /// cut and splice crossover and insertion and deletion mutation instead of the
+    /// crossover type; crossover points and per-gene mutation rates are not used. Every
+    /// individual has to fit the settings already, the initial population keeps its gene
+    /// length until children change it. Cant be combined with variation operators, which
+    /// breed children of their parents' length.
+    ///
+    /// # Arguments
+    /// * `variable` - The length bounds, block size and insertion and deletion probabilities.
+    pub fn set_variable_length(&mut self, variable: VariableLength) {
+        if self.variation.is_some() {
+            panic!("variable-length chromosomes cant be combined with variation operators");
+        }
+        if let Some(individual) = self.individuals.iter().find(|individual| !variable.allows(individual.gene_length)) {
+            panic!("gene length {} doesnt fit the variable-length settings", individual.gene_length);
+        }
+        self.variable_length = Some(variable);
+    }
+
+    /// Goes back to fixed-length breeding, which needs all individuals to have the same
+    /// gene length again.
+    pub fn clear_variable_length(&mut self) {
+        self.variable_length = None;
+    }
+
+    pub fn get_variable_length(&self) -> Option<VariableLength> {
+        self.variable_length
+    }
+
+    /// Returns the smallest, the mean and the largest gene length of the population.
+    pub fn gene_length_range(&self) -> (usize, f64, usize) {
+        let lengths = self.individuals.iter().map(|individual| individual.gene_length);
+        let min = lengths.clone().min().unwrap_or(0);
+        let max = lengths.clone().max().unwrap_or(0);
+        let mean = lengths.sum::<usize>() as f64 / self.individuals.len().max(1) as f64;
+        (min, mean, max)
+    }
+
+    /// Breeds a child with cut and splice crossover, then mutates its genes and inserts or
+    /// deletes a block.
+    ///
+    /// # Arguments
+    /// * `parents` - The parent individuals.
+    /// * `rng` - The random number generator to draw from.
+    /// * `individual` - The child with its genes still to set.
+    /// * `variable` - The variable-length settings.
+    pub(crate) fn splice_child(
+        &self,
+        parents: &[&Individual<G>],
+        rng: &mut GaRng,
+        mut individual: Individual<G>,
+        variable: VariableLength,
+    ) -> Individual<G> {
+        let block = variable.block;
+        let first = rng.random_range(0..parents.len());
+        let second = if parents.len() > 1 { (first + rng.random_range(1..parents.len())) % parents.len() } else { first };
+        let (head, tail) = (parents[first].genes.as_slice(), parents[second].genes.as_slice());
+        // cut and splice works on dense genes, a packed population packs them again
+        let genes = individual.genes.make_dense();
+        genes.clear();
+        for _ in 0..10 {
+            let head_cut = block * rng.random_range(0..=head.len() / block);
+            let tail_cut = block * rng.random_range(0..=tail.len() / block);
+            if variable.allows(head_cut + tail.len() - tail_cut) {
+                genes.extend_from_slice(&head[..head_cut]);
+                genes.extend_from_slice(&tail[tail_cut..]);
+                break;
+            }
+        }
+        if genes.is_empty() {
+            genes.extend_from_slice(&head);
+        }
+        let mut mutations = 0;
+        if rng.random_bool(variable.insertion) && genes.len() + block <= variable.max {
+            let at = block * rng.random_range(0..=genes.len() / block);
+            let inserted: Vec<G> = (0..block).map(|_| G::random(rng)).collect();
+            genes.splice(at..at, inserted);
+            mutations += 1;
+        }
+        if rng.random_bool(variable.deletion) && genes.len() >= variable.min + block {
+            let at = block * rng.random_range(0..genes.len() / block);
+            genes.drain(at..at + block);
+            mutations += 1;
+        }
+        individual.gene_length = individual.genes.len();
+        let probability = self.inherit_strategy(&mut individual, parents, rng);
+        for i in 0..individual.gene_length {
+            if individual.mutate_at_index(i, probability, rng) {
+                mutations += 1;
+            }
+        }
+        self.operator_counters.offspring.fetch_add(1, AtomicOrdering::Relaxed);
+        self.operator_counters.mutations.fetch_add(mutations, AtomicOrdering::Relaxed);
+        self.repair_individual(&mut individual);
+        individual
+    }
+}
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::super::{init_population_seeded, CrossoverType};
+    use super::*;
+
+    #[test]
+    fn spliced_children_are_whole_blocks_within_the_bounds() {
+        let mut population = init_population_seeded(6, 4, 2, 0, false, CrossoverType::Bit, 1);
+        population.set_mutation_probability(0.0);
+        let variable = VariableLength::new(3, 12).block_size(3).insertion(0.0).deletion(0.0);
+        let short = Individual::from_genes(vec![true; 6]);
+        let long = Individual::from_genes(vec![false; 12]);
+        let mut rng = GaRng::seed_from_u64(2);
+        for _ in 0..200 {
+            let child = population.splice_child(&[&short, &long], &mut rng, Individual::blank(), variable);
+            assert!(variable.allows(child.gene_length));
+            assert_eq!(child.get_genes().len(), child.gene_length);
+            // a head of one parent followed by a tail of the other, both cut at block boundaries
+            let genes = child.get_genes();
+            let head = genes.iter().take_while(|&&gene| gene == genes[0]).count();
+            assert!(genes[head..].iter().all(|&gene| gene != genes[0]));
+            assert_eq!(head % 3, 0);
+        }
+    }
+
+    #[test]
+    fn insertion_and_deletion_keep_the_length_in_bounds() {
+        let population = init_population_seeded(6, 4, 2, 5, false, CrossoverType::Bit, 3);
+        let mut rng = GaRng::seed_from_u64(4);
+        // a block is always inserted below the largest length, or deleted above the smallest one
+        for (insertion, deletion, never) in [(1.0, 0.0, 3), (0.0, 1.0, 9)] {
+            let variable = VariableLength::new(3, 9).block_size(3).insertion(insertion).deletion(deletion);
+            let mut lengths = vec![];
+            for length in [3, 6, 9] {
+                let parent = Individual::from_genes(vec![true; length]);
+                for _ in 0..100 {
+                    let child = population.splice_child(&[&parent, &parent], &mut rng, Individual::blank(), variable);
+                    assert!(variable.allows(child.gene_length), "length {}", child.gene_length);
+                    lengths.push(child.gene_length);
+                }
+            }
+            assert!(!lengths.contains(&never));
+        }
+    }
+
+    #[test]
+    fn generations_grow_and_shrink_within_the_bounds() {
+        let mut population = init_population_seeded(8, 20, 4, 5, false, CrossoverType::Bit, 5);
+        population.set_variable_length(VariableLength::new(4, 16).block_size(4).insertion(0.5).deletion(0.2));
+        for _ in 0..20 {
+            population.evaluate(|individual| individual.get_genes().iter().filter(|&&gene| gene).count() as u64);
+            population.next_generation();
+            assert!(population.individuals.iter().all(|individual| individual.gene_length % 4 == 0 && (4..=16).contains(&individual.gene_length)));
+        }
+        let (min, _, max) = population.gene_length_range();
+        assert!(max > 8);
+        assert!(min >= 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be multiples of the block size")]
+    fn bounds_must_be_whole_blocks() {
+        VariableLength::new(3, 10).block_size(3);
+    }
+}
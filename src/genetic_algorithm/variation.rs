@@ -0,0 +1,174 @@
+use rand::Rng;
+use std::{fmt, sync::Arc};
+
+use super::{
+    allele::AlleleOperators, alphabet::SymbolOperators, gene::Gene, integer::IntegerOperators, permutation::PermutationOperators,
+    real::RealOperators, rng::GaRng, Individual, Population,
+};
+
+/// Gene-type specific initialization, crossover and mutation that replace the built-in
+/// operators of a population, for genomes whose genes aren't independent of each other
+/// or need more context than the gene itself, e.g. per-gene bounds.
+pub trait Variation<G: Gene = bool>: Send + Sync {
+    /// Returns the genes of a new random individual. Defaults to `Gene::random` for every gene.
+    fn random_genes(&self, gene_length: usize, rng: &mut GaRng) -> Vec<G> {
+        (0..gene_length).map(|_| G::random(rng)).collect()
+    }
+
+    /// Returns the genes of a child of the given parents, as many as the parents have.
+    fn crossover(&self, parents: &[&Individual<G>], rng: &mut GaRng) -> Vec<G>;
+
+    /// Mutates the genes of a child in place. Defaults to `Gene::mutate` on every gene
+    /// with the mutation probability.
+    ///
+    /// # Arguments
+    /// * `genes` - The genes of the child.
+    /// * `mutation_probability` - The mutation probability (0.0-1.0) of the population.
+    /// * `rng` - The random number generator to draw from.
+    ///
+    /// # Returns
+    /// The number of mutated genes.
+    fn mutate(&self, genes: &mut [G], mutation_probability: f64, rng: &mut GaRng) -> u64 {
+        let mut mutations = 0;
+        for gene in genes.iter_mut() {
+            if rng.random_bool(mutation_probability) {
+                *gene = gene.mutate(rng);
+                mutations += 1;
+            }
+        }
+        mutations
+    }
+
+    /// Returns the genes of the opposite of a genome for opposition-based initialization,
+    /// or `None` if the operators have no notion of an opposite, the default.
+    fn opposite_genes(&self, _genes: &[G]) -> Option<Vec<G>> {
+        None
+    }
+}
+
+/// A shared variation operator stored on a population, with its settings if it is one
+/// of the built-in operator sets.
+#[derive(Clone)]
+pub(crate) struct VariationHook<G: Gene>(pub(crate) Arc<dyn Variation<G>>, pub(crate) Option<BuiltInVariation>);
+
+impl<G: Gene> fmt::Debug for VariationHook<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "VariationHook({:?})", self.1)
+    }
+}
+
+/// The operator sets that come with the crate. Unlike other variation operators they
+/// are saved with the population, e.g. in a checkpoint.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+pub(crate) enum BuiltInVariation {
+    Real(RealOperators),
+    Integer(IntegerOperators),
+    Allele(AlleleOperators),
+    Symbol(SymbolOperators),
+    Permutation(PermutationOperators),
+}
+
+#[cfg(feature = "serde")]
+impl BuiltInVariation {
+    /// Returns the operators as a hook for genes of type `G`, `None` if they are for
+    /// another gene type.
+    fn into_hook<G: Gene>(self) -> Option<VariationHook<G>> {
+        let operators: Box<dyn std::any::Any> = match self.clone() {
+            BuiltInVariation::Real(operators) => Box::new(Arc::new(operators) as Arc<dyn Variation<f64>>),
+            BuiltInVariation::Integer(operators) => Box::new(Arc::new(operators) as Arc<dyn Variation<i64>>),
+            BuiltInVariation::Allele(operators) => Box::new(Arc::new(operators) as Arc<dyn Variation<usize>>),
+            BuiltInVariation::Symbol(operators) => Box::new(Arc::new(operators) as Arc<dyn Variation<char>>),
+            BuiltInVariation::Permutation(operators) => Box::new(Arc::new(operators) as Arc<dyn Variation<usize>>),
+        };
+        let operators = operators.downcast::<Arc<dyn Variation<G>>>().ok()?;
+        Some(VariationHook(*operators, Some(self)))
+    }
+}
+
+/// Saves the settings of built-in operators, other operators can't be saved.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_hook<G: Gene, S: serde::Serializer>(hook: &Option<VariationHook<G>>, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    hook.as_ref().and_then(|VariationHook(_, built_in)| built_in.as_ref()).serialize(serializer)
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_hook<'de, G: Gene, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<VariationHook<G>>, D::Error> {
+    use serde::{de::Error, Deserialize};
+    match Option::<BuiltInVariation>::deserialize(deserializer)? {
+        Some(built_in) => built_in.into_hook().map(Some).ok_or_else(|| D::Error::custom("variation operators for another gene type")),
+        None => Ok(None),
+    }
+}
+
+impl<G: Gene> Population<G> {
+    /// Breeds and initializes individuals with the given operators instead of the
+    /// crossover type and `Gene::mutate`. The crossover points set with
+    /// `set_crossover_points` are not used while operators are set. Existing individuals
+    /// are kept, re-randomize them with `restart(0)` if they don't fit the operators.
+    pub fn set_variation<V: Variation<G> + 'static>(&mut self, variation: V) {
+        if self.variable_length.is_some() {
+            panic!("variable-length chromosomes cant be combined with variation operators");
+        }
+        self.variation = Some(VariationHook(Arc::new(variation), None));
+    }
+
+    /// Sets one of the built-in operator sets, which are saved with the population.
+    pub(crate) fn set_built_in_variation<V: Variation<G> + 'static>(&mut self, variation: V, built_in: BuiltInVariation) {
+        self.set_variation(variation);
+        if let Some(hook) = self.variation.as_mut() {
+            hook.1 = Some(built_in);
+        }
+    }
+
+    /// Returns true if variation operators are set that can't be saved with the population.
+    #[cfg(feature = "serde")]
+    pub(crate) fn has_custom_variation(&self) -> bool {
+        self.variation.as_ref().is_some_and(|VariationHook(_, built_in)| built_in.is_none())
+    }
+
+    /// Goes back to the built-in crossover and mutation.
+    pub fn clear_variation(&mut self) {
+        self.variation = None;
+    }
+
+    /// Gives an individual random genes, from the variation operators if set.
+    pub(crate) fn randomize_individual(&mut self, individual: &mut Individual<G>) {
+        match &self.variation {
+            Some(VariationHook(variation, _)) => {
+                individual.genes.assign(variation.random_genes(individual.gene_length, &mut self.rng));
+                if individual.genes.len() != individual.gene_length {
+                    panic!("the variation operators made {} random genes instead of {}", individual.genes.len(), individual.gene_length);
+                }
+            }
+            None => individual.randomize(&mut self.rng),
+        }
+        individual.age = 0;
+        self.initialize_strategy(individual);
+    }
+
+    /// Returns a random, repaired individual with the template's gene length and a fitness of 0.
+    pub(crate) fn random_individual(&mut self, template: &Individual<G>) -> Individual<G> {
+        let mut individual = template.clone();
+        self.randomize_individual(&mut individual);
+        individual.fitness = 0;
+        individual.violation = 0.0;
+        individual.objectives.clear();
+        self.repair_individual(&mut individual);
+        individual
+    }
+
+    /// Gives every individual random genes and resets its fitness.
+    pub(crate) fn reinitialize_individuals(&mut self) {
+        let mut individuals = std::mem::take(&mut self.individuals);
+        for individual in individuals.iter_mut() {
+            self.randomize_individual(individual);
+            individual.fitness = 0;
+            individual.violation = 0.0;
+            self.repair_individual(individual);
+        }
+        self.individuals = individuals;
+    }
+}
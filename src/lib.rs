@@ -0,0 +1,6 @@
+/*
+The genetic algorithm as a library, main.rs shows its usage
+*/
+mod genetic_algorithm;
+
+pub use genetic_algorithm::*;